@@ -0,0 +1,4 @@
+-- Keep the live bonding curve progress next to the (optionally) monotonic one
+ALTER TABLE tokens ADD COLUMN bonding_curve_progress_raw DECIMAL(5, 2) DEFAULT 0;
+
+UPDATE tokens SET bonding_curve_progress_raw = bonding_curve_progress;
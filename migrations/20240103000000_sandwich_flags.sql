@@ -0,0 +1,6 @@
+-- Sandwich detection results, recorded on the victim trade
+ALTER TABLE trades ADD COLUMN sandwiched BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE trades ADD COLUMN sandwich_attacker TEXT;
+ALTER TABLE trades ADD COLUMN sandwich_profit NUMERIC(20,0);
+
+CREATE INDEX idx_trades_sandwiched ON trades (token_mint) WHERE sandwiched;
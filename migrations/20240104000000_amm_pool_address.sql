@@ -0,0 +1,2 @@
+-- Pool a graduated token's liquidity migrated to
+ALTER TABLE tokens ADD COLUMN amm_pool_address TEXT;
@@ -0,0 +1,3 @@
+-- SOL/USD rate each trade's price_usd was converted with
+ALTER TABLE trades ADD COLUMN sol_price_usd NUMERIC(20, 8);
+ALTER TABLE trades ADD COLUMN sol_price_at TIMESTAMPTZ;
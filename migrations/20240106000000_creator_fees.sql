@@ -0,0 +1,3 @@
+-- Creator fee terms per token (from TradeEvents) and the creator's cut per trade
+ALTER TABLE tokens ADD COLUMN creator_fee_bps INTEGER;
+ALTER TABLE trades ADD COLUMN creator_fee_sol NUMERIC(20,0) NOT NULL DEFAULT 0;
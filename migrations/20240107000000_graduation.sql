@@ -0,0 +1,5 @@
+-- Exactly-once record of the transaction at which each curve completed
+ALTER TABLE tokens ADD COLUMN graduated_at TIMESTAMPTZ;
+ALTER TABLE tokens ADD COLUMN graduation_slot BIGINT;
+ALTER TABLE tokens ADD COLUMN graduation_signature TEXT;
+ALTER TABLE tokens ADD COLUMN graduation_market_cap_usd DECIMAL(20, 2);
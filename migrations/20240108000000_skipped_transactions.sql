@@ -0,0 +1,10 @@
+-- Sampled transactions that fetched fine but yielded no trade or creation
+CREATE TABLE skipped_transactions (
+    signature TEXT PRIMARY KEY,
+    slot BIGINT NOT NULL,
+    reason TEXT NOT NULL, -- 'no_balance_change' | 'unknown_instruction'
+    raw JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_skipped_transactions_reason ON skipped_transactions (reason, created_at DESC);
@@ -0,0 +1,5 @@
+-- Unclamped market cap, and the slot of the trade that last updated the token
+ALTER TABLE tokens ADD COLUMN market_cap_usd_raw DECIMAL(20, 2) DEFAULT 0;
+ALTER TABLE tokens ADD COLUMN last_trade_slot BIGINT;
+
+UPDATE tokens SET market_cap_usd_raw = market_cap_usd;
@@ -0,0 +1,11 @@
+-- Curve completions moving liquidity off pump.fun, one row per transaction
+CREATE TABLE migrations (
+    signature TEXT PRIMARY KEY,
+    mint_address TEXT NOT NULL,
+    pool_address TEXT, -- NULL for legacy `withdraw` migrations (pool created later)
+    slot BIGINT NOT NULL,
+    migrated_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_migrations_mint ON migrations (mint_address);
@@ -0,0 +1,8 @@
+-- Aggregate of dust holders evicted from token_holders once a token exceeds
+-- the configured holder cap, so supply totals stay correct
+CREATE TABLE token_holder_tails (
+    token_mint TEXT PRIMARY KEY,
+    holder_count BIGINT NOT NULL DEFAULT 0,
+    balance NUMERIC(20,0) NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
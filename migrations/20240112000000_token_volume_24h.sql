@@ -0,0 +1,33 @@
+-- Hourly per-token volume, maintained incrementally so 24h volume reads don't
+-- scan `trades` (TimescaleDB only; without it the app queries `trades` directly).
+--
+-- Refresh lag: a bucket is materialized once it is more than an hour old,
+-- on a 15 minute schedule, so the newest 1-1.25 hours are never in the
+-- aggregate. get_24h_volume and get_top_tokens_by_volume read that uncovered
+-- window from `trades` live.
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb') THEN
+        CREATE MATERIALIZED VIEW token_volume_24h
+        WITH (timescaledb.continuous, timescaledb.materialized_only = true) AS
+        SELECT
+            token_mint,
+            time_bucket(INTERVAL '1 hour', timestamp) AS bucket,
+            SUM(sol_amount) AS volume_sol,
+            COUNT(*) AS trade_count
+        FROM trades
+        WHERE track_volume
+        GROUP BY token_mint, bucket
+        WITH NO DATA;
+
+        CREATE INDEX idx_token_volume_24h_mint_bucket ON token_volume_24h (token_mint, bucket DESC);
+        CREATE INDEX idx_token_volume_24h_bucket ON token_volume_24h (bucket DESC);
+
+        PERFORM add_continuous_aggregate_policy(
+            'token_volume_24h',
+            start_offset => INTERVAL '3 days',
+            end_offset => INTERVAL '1 hour',
+            schedule_interval => INTERVAL '15 minutes'
+        );
+    END IF;
+END $$;
@@ -0,0 +1,7 @@
+-- Several trades can share a signature (bundles, routers): key each trade by
+-- the instruction that made it. Inner index -1 = a top-level instruction.
+ALTER TABLE trades ADD COLUMN instruction_index INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE trades ADD COLUMN inner_instruction_index INTEGER NOT NULL DEFAULT -1;
+
+ALTER TABLE trades DROP CONSTRAINT trades_pkey;
+ALTER TABLE trades ADD PRIMARY KEY (timestamp, signature, instruction_index, inner_instruction_index);
@@ -0,0 +1,3 @@
+-- Optional snapshot of the accounts a trade touched (STORE_TRADE_ACCOUNTS),
+-- so parser fixes can be re-applied without re-fetching from chain
+ALTER TABLE trades ADD COLUMN accounts JSONB;
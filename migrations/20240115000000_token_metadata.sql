@@ -0,0 +1,8 @@
+-- Off-chain metadata resolved from each token's URI
+ALTER TABLE tokens ADD COLUMN image TEXT;
+ALTER TABLE tokens ADD COLUMN description TEXT;
+ALTER TABLE tokens ADD COLUMN twitter TEXT;
+ALTER TABLE tokens ADD COLUMN telegram TEXT;
+ALTER TABLE tokens ADD COLUMN website TEXT;
+-- Set when the fetch is claimed, so each URI is only requested once
+ALTER TABLE tokens ADD COLUMN metadata_fetched_at TIMESTAMPTZ;
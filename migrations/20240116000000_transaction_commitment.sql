@@ -0,0 +1,11 @@
+-- Commitment level the audit row was fetched at, so a finalized re-fetch can
+-- replace data recorded at confirmed. Existing rows are assumed confirmed.
+ALTER TABLE transactions ADD COLUMN commitment TEXT NOT NULL DEFAULT 'confirmed';
+
+CREATE OR REPLACE FUNCTION commitment_rank(commitment TEXT) RETURNS INTEGER
+LANGUAGE SQL IMMUTABLE AS $$
+    SELECT array_position(ARRAY['processed', 'confirmed', 'finalized'], commitment)
+$$;
+
+-- Replacement looks rows up by signature alone (block time may have changed)
+CREATE INDEX IF NOT EXISTS idx_transactions_signature ON transactions (signature);
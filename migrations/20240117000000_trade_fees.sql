@@ -0,0 +1,4 @@
+-- Network fee and compute units of the transaction a trade came from.
+-- Nullable: rows stored before this weren't recorded with them.
+ALTER TABLE trades ADD COLUMN fee_lamports BIGINT;
+ALTER TABLE trades ADD COLUMN compute_units BIGINT;
@@ -0,0 +1,3 @@
+-- Priority fee the trade's transaction bid, in micro-lamports per compute
+-- unit (ComputeBudget `SetComputeUnitPrice`). NULL when none was set.
+ALTER TABLE trades ADD COLUMN compute_unit_price BIGINT;
@@ -0,0 +1,16 @@
+-- When the token first and last traded (transfers excluded), for recency
+-- rankings that created_at can't give
+ALTER TABLE tokens ADD COLUMN first_trade_at TIMESTAMPTZ;
+ALTER TABLE tokens ADD COLUMN last_trade_at TIMESTAMPTZ;
+
+UPDATE tokens t
+SET first_trade_at = s.first_trade_at, last_trade_at = s.last_trade_at
+FROM (
+    SELECT token_mint, MIN(timestamp) AS first_trade_at, MAX(timestamp) AS last_trade_at
+    FROM trades
+    WHERE track_volume
+    GROUP BY token_mint
+) s
+WHERE t.mint_address = s.token_mint;
+
+CREATE INDEX idx_tokens_last_trade_at ON tokens (last_trade_at DESC) WHERE last_trade_at IS NOT NULL;
@@ -0,0 +1,8 @@
+-- Launch sniping: the slot the token was created in, how many wallets other
+-- than the creator bought in that same slot, and whether enough did to call
+-- the launch bundled
+ALTER TABLE tokens ADD COLUMN creation_slot BIGINT;
+ALTER TABLE tokens ADD COLUMN sniper_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE tokens ADD COLUMN bundled BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX idx_tokens_bundled ON tokens (created_at DESC) WHERE bundled;
@@ -0,0 +1,45 @@
+-- USD value of each trade's SOL leg at the SOL/USD rate it was priced with
+-- (sol_amount is in lamports). NULL when the trade had no USD price; USD
+-- volume leaves those out and reports how many it skipped.
+ALTER TABLE trades ADD COLUMN usd_amount NUMERIC;
+
+-- Rows from before sol_price_usd was recorded still carry the rate implicitly
+-- as price_usd / price_sol
+UPDATE trades
+SET usd_amount = sol_amount / 1000000000
+    * COALESCE(sol_price_usd, price_usd / NULLIF(price_sol, 0))
+WHERE COALESCE(sol_price_usd, price_usd / NULLIF(price_sol, 0)) IS NOT NULL;
+
+-- Rebuild the hourly volume aggregate with USD volume alongside SOL. It
+-- starts empty; until the policy has materialized buckets, 24h volume reads
+-- scan `trades` for the whole window.
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb') THEN
+        DROP MATERIALIZED VIEW IF EXISTS token_volume_24h;
+
+        CREATE MATERIALIZED VIEW token_volume_24h
+        WITH (timescaledb.continuous, timescaledb.materialized_only = true) AS
+        SELECT
+            token_mint,
+            time_bucket(INTERVAL '1 hour', timestamp) AS bucket,
+            SUM(sol_amount) AS volume_sol,
+            SUM(usd_amount) AS volume_usd,
+            COUNT(*) AS trade_count,
+            COUNT(usd_amount) AS priced_count
+        FROM trades
+        WHERE track_volume
+        GROUP BY token_mint, bucket
+        WITH NO DATA;
+
+        CREATE INDEX idx_token_volume_24h_mint_bucket ON token_volume_24h (token_mint, bucket DESC);
+        CREATE INDEX idx_token_volume_24h_bucket ON token_volume_24h (bucket DESC);
+
+        PERFORM add_continuous_aggregate_policy(
+            'token_volume_24h',
+            start_offset => INTERVAL '3 days',
+            end_offset => INTERVAL '1 hour',
+            schedule_interval => INTERVAL '15 minutes'
+        );
+    END IF;
+END $$;
@@ -0,0 +1,41 @@
+-- One-minute USD price candles per token, maintained incrementally so charts
+-- don't scan `trades` (TimescaleDB only; without it get_ohlcv buckets
+-- `trades` directly). get_ohlcv rolls these up to 5m/15m/1h/1d on read.
+--
+-- Real-time aggregation is on, so minutes the policy hasn't materialized yet
+-- are computed from `trades` at query time. Trades arriving late for an
+-- already materialized minute are picked up by the next policy run if they
+-- fall within its 3 day window; backfill refreshes the range it wrote itself.
+--
+-- open/close follow the same order as the raw query (slot, then position in
+-- the transaction), packed into one sortable number for first()/last().
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb') THEN
+        CREATE MATERIALIZED VIEW token_candles_1m
+        WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+        SELECT
+            token_mint,
+            time_bucket(INTERVAL '1 minute', timestamp) AS bucket,
+            first(price_usd, slot::numeric * 1000000 + instruction_index * 1000 + inner_instruction_index + 1) AS open,
+            MAX(price_usd) AS high,
+            MIN(price_usd) AS low,
+            last(price_usd, slot::numeric * 1000000 + instruction_index * 1000 + inner_instruction_index + 1) AS close,
+            SUM(sol_amount) AS volume_sol,
+            COUNT(*) AS trade_count
+        FROM trades
+        WHERE track_volume
+        AND price_usd IS NOT NULL
+        GROUP BY token_mint, bucket
+        WITH NO DATA;
+
+        CREATE INDEX idx_token_candles_1m_mint_bucket ON token_candles_1m (token_mint, bucket);
+
+        PERFORM add_continuous_aggregate_policy(
+            'token_candles_1m',
+            start_offset => INTERVAL '3 days',
+            end_offset => INTERVAL '2 minutes',
+            schedule_interval => INTERVAL '1 minute'
+        );
+    END IF;
+END $$;
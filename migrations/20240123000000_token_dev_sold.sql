@@ -0,0 +1,13 @@
+-- Whether the creator has sold or moved any of their tokens, a common rug
+-- signal. Kept up to date from `trades` by the worker.
+ALTER TABLE tokens ADD COLUMN dev_sold BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE tokens t
+SET dev_sold = TRUE
+WHERE t.creator_wallet IS NOT NULL
+AND EXISTS (
+    SELECT 1 FROM trades tr
+    WHERE tr.token_mint = t.mint_address
+    AND tr.user_wallet = t.creator_wallet
+    AND NOT tr.is_buy
+);
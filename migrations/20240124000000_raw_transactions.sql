@@ -0,0 +1,12 @@
+-- Transactions as fetched (STORE_RAW_TX=true), so they can be reprocessed
+-- without the RPC. The worker deletes rows older than RAW_TX_RETENTION_DAYS.
+CREATE TABLE raw_transactions (
+    signature TEXT PRIMARY KEY,
+    slot BIGINT NOT NULL,
+    commitment TEXT NOT NULL,
+    data BYTEA NOT NULL, -- gzip-compressed getTransaction result (jsonParsed)
+    stored_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_raw_transactions_slot ON raw_transactions (slot, signature);
+CREATE INDEX idx_raw_transactions_stored_at ON raw_transactions (stored_at);
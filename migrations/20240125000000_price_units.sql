@@ -0,0 +1,58 @@
+-- Prices were stored in lamports per raw token unit and market caps in
+-- lamports x USD/SOL. Rescale them to SOL (or USD) per whole token (6
+-- decimals) and real USD, and widen price_usd so sub-cent prices keep their
+-- significant digits.
+--
+-- With TimescaleDB the one-minute candles are rebuilt empty. Materialize the
+-- history older than the policy's 3 day window once afterwards (outside a
+-- transaction):
+--   CALL refresh_continuous_aggregate('token_candles_1m', NULL, now() - INTERVAL '3 days');
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb') THEN
+        DROP MATERIALIZED VIEW IF EXISTS token_candles_1m;
+    END IF;
+END $$;
+
+ALTER TABLE trades ALTER COLUMN price_usd TYPE DECIMAL(30, 18);
+
+UPDATE trades
+SET price_sol = price_sol / 1000,
+    price_usd = price_usd / 1000
+WHERE price_sol IS NOT NULL OR price_usd IS NOT NULL;
+
+UPDATE tokens
+SET market_cap_usd = market_cap_usd / 1000000000,
+    market_cap_usd_raw = market_cap_usd_raw / 1000000000,
+    graduation_market_cap_usd = graduation_market_cap_usd / 1000000000;
+
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb') THEN
+        CREATE MATERIALIZED VIEW token_candles_1m
+        WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+        SELECT
+            token_mint,
+            time_bucket(INTERVAL '1 minute', timestamp) AS bucket,
+            first(price_usd, slot::numeric * 1000000 + instruction_index * 1000 + inner_instruction_index + 1) AS open,
+            MAX(price_usd) AS high,
+            MIN(price_usd) AS low,
+            last(price_usd, slot::numeric * 1000000 + instruction_index * 1000 + inner_instruction_index + 1) AS close,
+            SUM(sol_amount) AS volume_sol,
+            COUNT(*) AS trade_count
+        FROM trades
+        WHERE track_volume
+        AND price_usd IS NOT NULL
+        GROUP BY token_mint, bucket
+        WITH NO DATA;
+
+        CREATE INDEX idx_token_candles_1m_mint_bucket ON token_candles_1m (token_mint, bucket);
+
+        PERFORM add_continuous_aggregate_policy(
+            'token_candles_1m',
+            start_offset => INTERVAL '3 days',
+            end_offset => INTERVAL '2 minutes',
+            schedule_interval => INTERVAL '1 minute'
+        );
+    END IF;
+END $$;
@@ -0,0 +1,13 @@
+-- Make trades and transactions hypertables wherever TimescaleDB is installed.
+-- The initial schema's CREATE EXTENSION and create_hypertable calls fail on
+-- plain Postgres and psql moves on, leaving plain tables; this converts them
+-- if the extension is added later and is a no-op otherwise.
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb') THEN
+        PERFORM create_hypertable('trades', 'timestamp',
+            if_not_exists => TRUE, migrate_data => TRUE);
+        PERFORM create_hypertable('transactions', 'block_time',
+            if_not_exists => TRUE, migrate_data => TRUE);
+    END IF;
+END $$;
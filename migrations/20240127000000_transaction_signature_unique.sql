@@ -0,0 +1,29 @@
+-- One audit row per signature. On a hypertable every unique index must
+-- include the partition column (block_time), and a re-fetch can move a
+-- transaction's block time, so there the writer's advisory lock is the only
+-- guard; on plain Postgres the index backs it up.
+DO $$
+DECLARE
+    is_hypertable BOOLEAN := FALSE;
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb') THEN
+        EXECUTE $q$
+            SELECT EXISTS (
+                SELECT 1 FROM timescaledb_information.hypertables
+                WHERE hypertable_name = 'transactions'
+            )
+        $q$ INTO is_hypertable;
+    END IF;
+
+    IF NOT is_hypertable THEN
+        -- Keep the highest-commitment copy of anything written twice
+        DELETE FROM transactions t
+        USING transactions other
+        WHERE t.signature = other.signature
+          AND (commitment_rank(t.commitment), t.block_time)
+              < (commitment_rank(other.commitment), other.block_time);
+
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_transactions_signature_unique
+            ON transactions (signature);
+    END IF;
+END $$;
@@ -0,0 +1,6 @@
+-- Position, within its slot, of the trade that last set a holder's balance,
+-- so a trade replayed out of order can't overwrite a later one from the same
+-- slot. Rows written before this sort first within their slot.
+ALTER TABLE token_holders
+    ADD COLUMN last_updated_instruction_index INTEGER NOT NULL DEFAULT -1,
+    ADD COLUMN last_updated_inner_instruction_index INTEGER NOT NULL DEFAULT -1;
@@ -0,0 +1,7 @@
+-- `Token::bonding_curve_progress_raw` isn't optional, so a NULL here fails
+-- every read of the row. Backfill from the clamped value and keep it set.
+UPDATE tokens
+SET bonding_curve_progress_raw = COALESCE(bonding_curve_progress, 0)
+WHERE bonding_curve_progress_raw IS NULL;
+
+ALTER TABLE tokens ALTER COLUMN bonding_curve_progress_raw SET NOT NULL;
@@ -0,0 +1,3 @@
+pub mod price_impact;
+pub mod sandwich;
+pub mod snipers;
@@ -0,0 +1,83 @@
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+use crate::error::Result;
+use crate::models::queries::get_token;
+use crate::pricing::price_from_reserves;
+
+/// Effect of a hypothetical trade on a bonding curve
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceImpact {
+    /// Tokens received (buy) or needed (sell), raw units
+    pub token_amount: Decimal,
+    /// Curve price before and after the trade (SOL per whole token)
+    pub price_before: Decimal,
+    pub price_after: Decimal,
+    /// Signed change from `price_before` to `price_after`, in percent
+    pub price_change_pct: Decimal,
+}
+
+/// Estimate a trade against pump.fun's constant-product curve
+/// (`virtual_sol * virtual_token = k`), the inverse of the reserve math the
+/// parser records.
+///
+/// `sol_in` is the lamports spent on a buy, or the lamports to receive on a
+/// sell. Protocol and creator fees are not included. `None` when the curve is
+/// empty or a sell would take out all of its SOL.
+pub fn estimate_price_impact(
+    virtual_sol: Decimal,
+    virtual_token: Decimal,
+    sol_in: Decimal,
+    is_buy: bool,
+) -> Option<PriceImpact> {
+    if virtual_sol <= Decimal::ZERO || virtual_token <= Decimal::ZERO || sol_in < Decimal::ZERO {
+        return None;
+    }
+
+    let new_sol = if is_buy {
+        virtual_sol.checked_add(sol_in)?
+    } else {
+        virtual_sol - sol_in
+    };
+    if new_sol <= Decimal::ZERO {
+        return None;
+    }
+
+    // Keep k constant: the token side moves by whatever the SOL side forces
+    let new_token = virtual_sol
+        .checked_mul(virtual_token)?
+        .checked_div(new_sol)?;
+    let token_amount = (virtual_token - new_token).abs().floor();
+
+    let price_before = price_from_reserves(virtual_sol, virtual_token).ok()?;
+    let price_after = price_from_reserves(new_sol, new_token).ok()?;
+    let price_change_pct =
+        (price_after - price_before).checked_div(price_before)? * Decimal::ONE_HUNDRED;
+
+    Some(PriceImpact {
+        token_amount,
+        price_before,
+        price_after,
+        price_change_pct,
+    })
+}
+
+/// `estimate_price_impact` against a token's last stored reserves.
+/// `None` for unknown tokens and impossible trades.
+pub async fn estimate_token_price_impact(
+    pool: &PgPool,
+    mint_address: &str,
+    sol_in: Decimal,
+    is_buy: bool,
+) -> Result<Option<PriceImpact>> {
+    let Some(token) = get_token(pool, mint_address).await? else {
+        return Ok(None);
+    };
+
+    Ok(estimate_price_impact(
+        token.virtual_sol_reserves,
+        token.virtual_token_reserves,
+        sol_in,
+        is_buy,
+    ))
+}
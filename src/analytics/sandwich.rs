@@ -0,0 +1,83 @@
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+use crate::error::Result;
+use crate::models::queries::{get_trades_in_slot, mark_sandwiched};
+use crate::models::{Trade, TradeCursor};
+
+/// An attacker buy and sell bracketing another wallet's buy
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sandwich {
+    /// Primary key of the victim trade: a transaction can hold several trades
+    pub victim: TradeCursor,
+    pub front_run_signature: String,
+    pub back_run_signature: String,
+    pub attacker_wallet: String,
+    /// SOL received on the back-run minus SOL spent on the front-run (lamports)
+    pub profit: Decimal,
+}
+
+/// Find sandwiches among one token's trades in a single slot.
+///
+/// Trades are walked in intra-slot order, `(slot, instruction_index,
+/// inner_instruction_index)`; trades sharing that key keep the order they are
+/// given in. A victim buy is sandwiched when the same other wallet bought
+/// before it (front-run) and sold after it (back-run).
+pub fn find_sandwiches(trades: &[Trade]) -> Vec<Sandwich> {
+    let mut swaps: Vec<&Trade> = trades.iter().filter(|t| t.track_volume).collect();
+    swaps.sort_by_key(|t| (t.slot, t.instruction_index, t.inner_instruction_index));
+    let mut found = Vec::new();
+
+    for (position, victim) in swaps.iter().enumerate() {
+        if !victim.is_buy {
+            continue;
+        }
+        let (before, after) = (&swaps[..position], &swaps[position + 1..]);
+        let bracket = before
+            .iter()
+            .filter(|f| f.is_buy && f.slot == victim.slot && f.user_wallet != victim.user_wallet)
+            .find_map(|front| {
+                after
+                    .iter()
+                    .find(|b| {
+                        !b.is_buy && b.slot == victim.slot && b.user_wallet == front.user_wallet
+                    })
+                    .map(|back| (front, back))
+            });
+
+        if let Some((front, back)) = bracket {
+            found.push(Sandwich {
+                victim: TradeCursor::from(*victim),
+                front_run_signature: front.signature.clone(),
+                back_run_signature: back.signature.clone(),
+                attacker_wallet: front.user_wallet.clone(),
+                profit: back.sol_amount - front.sol_amount,
+            });
+        }
+    }
+
+    found
+}
+
+/// Re-run detection for a token's slot and flag any victims.
+/// Safe to call after every trade insert; flagging is idempotent.
+pub async fn detect_sandwiches_in_slot(
+    pool: &PgPool,
+    mint_address: &str,
+    slot: i64,
+) -> Result<Vec<Sandwich>> {
+    let trades = get_trades_in_slot(pool, mint_address, slot).await?;
+    let sandwiches = find_sandwiches(&trades);
+
+    for sandwich in &sandwiches {
+        mark_sandwiched(
+            pool,
+            &sandwich.victim,
+            &sandwich.attacker_wallet,
+            sandwich.profit,
+        )
+        .await?;
+    }
+
+    Ok(sandwiches)
+}
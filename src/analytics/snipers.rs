@@ -0,0 +1,19 @@
+use sqlx::PgPool;
+
+use crate::error::Result;
+use crate::models::queries::update_sniper_count;
+
+/// Snipers in the creation slot from which a launch counts as bundled: one
+/// fast bot is normal, several wallets landing with the create usually means
+/// the creator bought through them in a bundle
+pub const BUNDLED_MIN_SNIPERS: i32 = 3;
+
+/// Recount a token's snipers, the wallets other than its creator that bought
+/// in the slot it was created in, and update its `bundled` flag.
+///
+/// The creation and the trades in its slot can be indexed in any order, so
+/// this runs after either one and always counts from the stored trades.
+/// Returns the count, or `None` while the creation slot isn't known.
+pub async fn refresh_snipers(pool: &PgPool, mint_address: &str) -> Result<Option<i32>> {
+    update_sniper_count(pool, mint_address, BUNDLED_MIN_SNIPERS).await
+}
@@ -0,0 +1,446 @@
+use anyhow::{Context, Result};
+use axum::{
+    Json, Router,
+    extract::{Path, Query, Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::db::{DbPools, detect_capabilities};
+use crate::error::IndexerError;
+use crate::models::queries::{
+    get_24h_volume_stats, get_avg_fee, get_dev_holdings, get_holder_count, get_holder_stats,
+    get_recently_active_tokens, get_snipers, get_token, get_token_version, get_top_holders,
+    get_top_tokens_by_volume, get_trades_paginated, get_wallet_pnl, search_tokens,
+};
+use crate::models::{
+    HolderStats, Sniper, Token, TokenHolder, TokenVolume, TradeCursor, TradePage, WalletPnl,
+};
+use crate::pricing::{price_from_reserves, raw_to_tokens};
+use crate::redis::redis_cleint::RedisClient;
+use crate::redis::token_card::{TokenCard, read_token_card};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+/// Sent with conditional responses: shared caches may serve them briefly,
+/// then revalidate with `If-None-Match`
+const CACHE_CONTROL: &str = "public, max-age=5";
+
+#[derive(Clone)]
+struct ApiState {
+    pools: DbPools,
+    /// Token cards; `None` when Redis is unreachable at startup
+    redis: Option<RedisClient>,
+    feed_filter: FeedFilter,
+}
+
+/// Hides dead tokens from the feeds (`/tokens/active`, `/tokens/top`): no
+/// trade for `dead_after` and a market cap under `min_market_cap_usd`. They
+/// stay in the database and are still served by mint.
+#[derive(Debug, Clone, Copy)]
+pub struct FeedFilter {
+    dead_after: Option<chrono::Duration>,
+    min_market_cap_usd: Decimal,
+}
+
+impl FeedFilter {
+    /// Read from `FEED_DEAD_AFTER_HOURS` (default 24, 0 = off) and
+    /// `FEED_MIN_MARKET_CAP_USD` (default 5000)
+    pub fn from_env() -> Self {
+        let hours = std::env::var("FEED_DEAD_AFTER_HOURS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(24);
+        let min_market_cap_usd = std::env::var("FEED_MIN_MARKET_CAP_USD")
+            .ok()
+            .and_then(|v| v.parse::<Decimal>().ok())
+            .unwrap_or(Decimal::from(5000));
+        Self {
+            dead_after: (hours > 0).then(|| chrono::Duration::hours(hours)),
+            min_market_cap_usd,
+        }
+    }
+
+    /// Tokens untraded since before this are candidates to hide
+    fn dead_before(self) -> Option<DateTime<Utc>> {
+        self.dead_after.map(|after| Utc::now() - after)
+    }
+}
+
+/// Any failure behind a request, rendered as `{"error": ...}`
+enum ApiError {
+    NotFound,
+    BadRequest(String),
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Internal(e)
+    }
+}
+
+impl From<IndexerError> for ApiError {
+    fn from(e: IndexerError) -> Self {
+        Self::Internal(e.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            Self::NotFound => (StatusCode::NOT_FOUND, "not found".to_string()),
+            Self::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            Self::Internal(e) => {
+                error!("API error: {:#}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal error".to_string(),
+                )
+            }
+        };
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<Json<T>, ApiError>;
+
+#[derive(Debug, Deserialize)]
+struct LimitParams {
+    limit: Option<i64>,
+}
+
+impl LimitParams {
+    fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TradesParams {
+    limit: Option<i64>,
+    /// `next_cursor` of the previous page
+    before: Option<TradeCursor>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchParams {
+    q: String,
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopParams {
+    by: Option<String>,
+    limit: Option<i64>,
+}
+
+/// Serve the read-only query API on `API_ADDR` (default `0.0.0.0:3000`)
+/// until `shutdown`. Reads go to the replica pool when one is configured.
+pub async fn run_api(shutdown: CancellationToken) -> Result<()> {
+    let addr = std::env::var("API_ADDR").unwrap_or_else(|_| "0.0.0.0:3000".to_string());
+    let redis_url =
+        std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+
+    let pools = DbPools::from_env().await?;
+    detect_capabilities(pools.reader()).await?;
+    let redis = match RedisClient::new(&redis_url).await {
+        Ok(redis) => Some(redis),
+        Err(e) => {
+            warn!("Redis unavailable, token cards come from the DB: {}", e);
+            None
+        }
+    };
+
+    let app = router(pools, redis);
+
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("Failed to bind {}", addr))?;
+    info!("API listening on {}", addr);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { shutdown.cancelled().await })
+        .await
+        .context("API server failed")
+}
+
+/// The API's routes over `pools`, with token cards read from `redis` when
+/// given
+pub fn router(pools: DbPools, redis: Option<RedisClient>) -> Router {
+    let state = ApiState {
+        pools,
+        redis,
+        feed_filter: FeedFilter::from_env(),
+    };
+
+    // Versioned by the token row, so these answer `If-None-Match` with a 304
+    let conditional = Router::new()
+        .route("/tokens/{mint}", get(token))
+        .route_layer(middleware::from_fn_with_state(state.clone(), token_etag));
+
+    Router::new()
+        .route("/tokens/top", get(top_tokens))
+        .route("/tokens/search", get(search))
+        .route("/tokens/active", get(active_tokens))
+        .route("/tokens/{mint}/card", get(token_card))
+        .route("/tokens/{mint}/trades", get(token_trades))
+        .route("/tokens/{mint}/holders", get(token_holders))
+        .route("/tokens/{mint}/holders/stats", get(holder_stats))
+        .route("/tokens/{mint}/fees", get(token_fees))
+        .route("/tokens/{mint}/snipers", get(token_snipers))
+        .route("/tokens/{mint}/wallets/{wallet}/pnl", get(wallet_pnl))
+        .merge(conditional)
+        .with_state(state)
+}
+
+/// Tag a token's responses with an `ETag` from `get_token_version`, and
+/// answer a request whose `If-None-Match` already holds it with `304 Not
+/// Modified` before running the handler
+async fn token_etag(
+    State(state): State<ApiState>,
+    Path(mint): Path<String>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let version = match get_token_version(state.pools.reader(), &mint).await {
+        Ok(Some(version)) => version,
+        // Unknown tokens 404 in the handler; errors there too
+        Ok(None) | Err(_) => return next.run(request).await,
+    };
+    let etag = format!("\"{}\"", version.timestamp_micros());
+
+    if if_none_match(request.headers(), &etag) {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        set_cache_headers(response.headers_mut(), &etag);
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+    if response.status() == StatusCode::OK {
+        set_cache_headers(response.headers_mut(), &etag);
+    }
+    response
+}
+
+/// Whether `If-None-Match` lists `etag` (or is `*`); weak tags compare equal
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+fn set_cache_headers(headers: &mut HeaderMap, etag: &str) {
+    if let Ok(etag) = HeaderValue::from_str(etag) {
+        headers.insert(header::ETAG, etag);
+    }
+    headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(CACHE_CONTROL),
+    );
+}
+
+/// The token row plus what's derived from it for the detail view
+#[derive(Debug, Serialize)]
+struct TokenDetail {
+    #[serde(flatten)]
+    token: Token,
+    /// Creator's current share of the supply, in percent; `null` when the
+    /// creator isn't known
+    dev_holdings_pct: Option<Decimal>,
+}
+
+/// `GET /tokens/{mint}`
+async fn token(State(state): State<ApiState>, Path(mint): Path<String>) -> ApiResult<TokenDetail> {
+    let pool = state.pools.reader();
+    let token = get_token(pool, &mint).await?.ok_or(ApiError::NotFound)?;
+    let dev_holdings_pct = get_dev_holdings(pool, &mint).await?;
+    Ok(Json(TokenDetail {
+        token,
+        dev_holdings_pct,
+    }))
+}
+
+/// `GET /tokens/{mint}/card`: the worker's Redis card, or the same fields
+/// computed from the database when there is none
+async fn token_card(
+    State(mut state): State<ApiState>,
+    Path(mint): Path<String>,
+) -> ApiResult<TokenCard> {
+    if let Some(redis) = state.redis.as_mut() {
+        match read_token_card(redis, &mint).await {
+            Ok(Some(card)) => return Ok(Json(card)),
+            Ok(None) => {}
+            Err(e) => warn!("Token card read failed for {}: {}", mint, e),
+        }
+    }
+
+    let pool = state.pools.reader();
+    let token = get_token(pool, &mint).await?.ok_or(ApiError::NotFound)?;
+    let price_sol = price_from_reserves(token.virtual_sol_reserves, token.virtual_token_reserves)
+        .map_err(anyhow::Error::from)?;
+    // Market cap is spot price x supply at the SOL price of the last trade
+    let price_usd = token
+        .market_cap_usd
+        .checked_div(raw_to_tokens(token.token_total_supply))
+        .unwrap_or_default();
+    let volume = get_24h_volume_stats(pool, &mint).await?;
+
+    Ok(Json(TokenCard {
+        mint_address: token.mint_address.clone(),
+        price_sol,
+        price_usd,
+        market_cap_usd: token.market_cap_usd,
+        bonding_curve_progress: token.bonding_curve_progress,
+        volume_24h_sol: volume.volume_sol,
+        volume_24h_usd: volume.volume_usd,
+        holder_count: get_holder_count(pool, &mint).await?,
+        last_trade_slot: token.last_trade_slot.unwrap_or_default(),
+        last_trade_at: token
+            .last_trade_at
+            .or(token.updated_at)
+            .unwrap_or(token.created_at),
+    }))
+}
+
+/// `GET /tokens/{mint}/trades?limit=&before=&from=&to=`: newest first, one
+/// page at a time; pass the returned `next_cursor` as `before` for the next
+async fn token_trades(
+    State(state): State<ApiState>,
+    Path(mint): Path<String>,
+    Query(params): Query<TradesParams>,
+) -> ApiResult<TradePage> {
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let page = get_trades_paginated(
+        state.pools.reader(),
+        &mint,
+        params.before.as_ref(),
+        params.from,
+        params.to,
+        limit,
+    )
+    .await?;
+    Ok(Json(page))
+}
+
+/// `GET /tokens/{mint}/holders?limit=`: largest balances first
+async fn token_holders(
+    State(state): State<ApiState>,
+    Path(mint): Path<String>,
+    Query(params): Query<LimitParams>,
+) -> ApiResult<Vec<TokenHolder>> {
+    let holders = get_top_holders(state.pools.reader(), &mint, params.limit()).await?;
+    Ok(Json(holders))
+}
+
+/// `GET /tokens/{mint}/fees`: average network fee per trading transaction
+async fn token_fees(
+    State(state): State<ApiState>,
+    Path(mint): Path<String>,
+) -> ApiResult<serde_json::Value> {
+    let avg_fee = get_avg_fee(state.pools.reader(), &mint).await?;
+    Ok(Json(json!({ "avg_fee_lamports": avg_fee })))
+}
+
+/// `GET /tokens/{mint}/snipers`: wallets besides the creator that bought in
+/// the creation slot
+async fn token_snipers(
+    State(state): State<ApiState>,
+    Path(mint): Path<String>,
+) -> ApiResult<Vec<Sniper>> {
+    let snipers = get_snipers(state.pools.reader(), &mint).await?;
+    Ok(Json(snipers))
+}
+
+/// `GET /tokens/{mint}/holders/stats`: holder count and concentration
+async fn holder_stats(
+    State(state): State<ApiState>,
+    Path(mint): Path<String>,
+) -> ApiResult<HolderStats> {
+    get_holder_stats(state.pools.reader(), &mint)
+        .await?
+        .map(Json)
+        .ok_or(ApiError::NotFound)
+}
+
+/// `GET /tokens/{mint}/wallets/{wallet}/pnl`: the wallet's profit and loss
+/// on the token
+async fn wallet_pnl(
+    State(state): State<ApiState>,
+    Path((mint, wallet)): Path<(String, String)>,
+) -> ApiResult<WalletPnl> {
+    let pnl = get_wallet_pnl(state.pools.reader(), &mint, &wallet).await?;
+    Ok(Json(pnl))
+}
+
+/// `GET /tokens/search?q=&limit=`: tokens by name or symbol, most traded first
+async fn search(
+    State(state): State<ApiState>,
+    Query(params): Query<SearchParams>,
+) -> ApiResult<Vec<Token>> {
+    let query = params.q.trim();
+    if query.is_empty() {
+        return Err(ApiError::BadRequest("empty search query".to_string()));
+    }
+
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let tokens = search_tokens(state.pools.reader(), query, limit).await?;
+    Ok(Json(tokens))
+}
+
+/// `GET /tokens/active?limit=`: most recently traded first
+async fn active_tokens(
+    State(state): State<ApiState>,
+    Query(params): Query<LimitParams>,
+) -> ApiResult<Vec<Token>> {
+    let filter = state.feed_filter;
+    let tokens = get_recently_active_tokens(
+        state.pools.reader(),
+        params.limit(),
+        filter.dead_before(),
+        filter.min_market_cap_usd,
+    )
+    .await?;
+    Ok(Json(tokens))
+}
+
+/// `GET /tokens/top?by=volume&limit=`: 24h SOL volume leaders
+async fn top_tokens(
+    State(state): State<ApiState>,
+    Query(params): Query<TopParams>,
+) -> ApiResult<Vec<TokenVolume>> {
+    match params.by.as_deref() {
+        None | Some("volume") => {}
+        Some(other) => {
+            return Err(ApiError::BadRequest(format!(
+                "unsupported ranking '{}', expected 'volume'",
+                other
+            )));
+        }
+    }
+
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let filter = state.feed_filter;
+    let tokens = get_top_tokens_by_volume(
+        state.pools.reader(),
+        limit,
+        filter.dead_before(),
+        filter.min_market_cap_usd,
+    )
+    .await?;
+    Ok(Json(tokens))
+}
@@ -0,0 +1,54 @@
+use indexer::db::get_db_pool;
+use indexer::helius::fetcher::{RpcPool, backfill_range};
+use indexer::helius::http::HttpConfig;
+use indexer::metrics::push_metrics;
+use std::sync::Arc;
+
+const USAGE: &str = "Usage: backfill [--before <SIG>] [--until <SIG>] [--limit <N>]";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+    indexer::logging::init();
+
+    let mut before = None;
+    let mut until = None;
+    let mut limit = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args.next();
+        match (flag.as_str(), value) {
+            ("--before", Some(sig)) => before = Some(sig),
+            ("--until", Some(sig)) => until = Some(sig),
+            ("--limit", Some(n)) if n.parse::<usize>().is_ok() => limit = n.parse().ok(),
+            _ => {
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    println!("📼 Starting Pump.fun Indexer - Backfill");
+    println!("========================================\n");
+
+    let rpc = RpcPool::from_env().expect("RPC_URL or HELIUS_API_KEY missing");
+    let pool = get_db_pool().await?;
+    let client = HttpConfig::from_env().build()?;
+
+    let result = backfill_range(
+        &pool,
+        &client,
+        Arc::new(rpc),
+        before.as_deref(),
+        until.as_deref(),
+        limit,
+    )
+    .await;
+    if let Err(e) = push_metrics("backfill").await {
+        eprintln!("⚠️ {:#}", e);
+    }
+    result?;
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use indexer::db::get_db_pool;
+use indexer::models::Trade;
+use indexer::models::queries::{batch_insert_trades, delete_trades_for_mint, insert_trade};
+use rust_decimal::Decimal;
+
+const DEFAULT_COUNT: usize = 5_000;
+/// Synthetic trades go under this mint and are deleted afterwards
+const BENCH_MINT: &str = "bench-inserts-mint";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+    indexer::logging::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let count = match args.as_slice() {
+        [] => DEFAULT_COUNT,
+        [count] => count.parse()?,
+        _ => {
+            eprintln!("Usage: bench-inserts [COUNT]");
+            std::process::exit(2);
+        }
+    };
+
+    println!("⏱️  Benchmarking trade inserts ({} trades per run)", count);
+    println!("==============================================\n");
+
+    let pool = get_db_pool().await?;
+    delete_trades_for_mint(&pool, BENCH_MINT).await?;
+
+    let trades = synthetic_trades("single", count);
+    let started = Instant::now();
+    for trade in &trades {
+        insert_trade(&pool, trade).await?;
+    }
+    report("insert_trade, one per trade", count, started.elapsed());
+
+    let trades = synthetic_trades("batch", count);
+    let started = Instant::now();
+    batch_insert_trades(&pool, &trades).await?;
+    report("batch_insert_trades (UNNEST)", count, started.elapsed());
+
+    let deleted = delete_trades_for_mint(&pool, BENCH_MINT).await?;
+    println!("\n🧹 Deleted {} synthetic trades", deleted);
+    Ok(())
+}
+
+fn report(label: &str, count: usize, elapsed: Duration) {
+    println!(
+        "{:<32} {:>8.1} ms  {:>10.0} trades/s",
+        label,
+        elapsed.as_secs_f64() * 1000.0,
+        count as f64 / elapsed.as_secs_f64()
+    );
+}
+
+fn synthetic_trades(run: &str, count: usize) -> Vec<Trade> {
+    let timestamp = Utc::now();
+    (0..count)
+        .map(|i| Trade {
+            signature: format!("bench-{}-{}", run, i),
+            token_mint: BENCH_MINT.to_string(),
+            sol_amount: Decimal::from(100_000_000),
+            token_amount: Decimal::from(3_000_000_000_000u64),
+            is_buy: i % 2 == 0,
+            user_wallet: format!("bench-wallet-{}", i % 100),
+            timestamp,
+            virtual_sol_reserves: Decimal::from(30_000_000_000u64),
+            virtual_token_reserves: Decimal::from(1_073_000_000_000_000u64),
+            price_sol: Some(Decimal::new(33, 6)),
+            price_usd: None,
+            sol_price_usd: None,
+            sol_price_at: None,
+            usd_amount: None,
+            creator_fee_sol: Decimal::ZERO,
+            track_volume: true,
+            ix_name: "buy".to_string(),
+            slot: i as i64,
+            instruction_index: 0,
+            inner_instruction_index: -1,
+            accounts: None,
+            fee_lamports: Some(5_000),
+            compute_units: None,
+            compute_unit_price: None,
+        })
+        .collect()
+}
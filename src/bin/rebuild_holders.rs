@@ -0,0 +1,32 @@
+use indexer::db::get_db_pool;
+use indexer::metrics::push_metrics;
+use indexer::models::queries::rebuild_token_holders;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+    indexer::logging::init();
+
+    let mints: Vec<String> = std::env::args().skip(1).collect();
+    if mints.is_empty() {
+        eprintln!("Usage: rebuild-holders <MINT> [MINT...]");
+        std::process::exit(2);
+    }
+
+    println!("🛠️  Rebuilding token holders from stored trades");
+    println!("==============================================\n");
+
+    let pool = get_db_pool().await?;
+
+    for mint in &mints {
+        match rebuild_token_holders(&pool, mint).await {
+            Ok(count) => println!("✅ {}: {} holders rebuilt", mint, count),
+            Err(e) => eprintln!("❌ {}: rebuild failed: {}", mint, e),
+        }
+    }
+    if let Err(e) = push_metrics("rebuild-holders").await {
+        eprintln!("⚠️ {:#}", e);
+    }
+
+    Ok(())
+}
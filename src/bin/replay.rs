@@ -0,0 +1,65 @@
+use indexer::helius::fetcher::{replay_dead_letters, replay_raw};
+use indexer::metrics::push_metrics;
+
+const USAGE: &str =
+    "Usage: replay [--raw [--from-slot <SLOT>] [--to-slot <SLOT>] [--sol-price <USD>]]";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+    indexer::logging::init();
+
+    let mut raw = false;
+    let mut from_slot = None;
+    let mut to_slot = None;
+    let mut sol_price = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--raw" => raw = true,
+            "--from-slot" => from_slot = Some(args.next().ok_or(USAGE)?.parse::<u64>()?),
+            "--to-slot" => to_slot = Some(args.next().ok_or(USAGE)?.parse::<u64>()?),
+            "--sol-price" => sol_price = Some(args.next().ok_or(USAGE)?.parse::<f64>()?),
+            _ => {
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+    if !raw && (from_slot.is_some() || to_slot.is_some() || sol_price.is_some()) {
+        eprintln!("{}", USAGE);
+        std::process::exit(2);
+    }
+
+    if raw {
+        println!("🗄️ Starting Pump.fun Indexer - Stored Transaction Replay");
+        println!("=========================================================\n");
+
+        let result = replay_raw(from_slot, to_slot, sol_price).await;
+        if let Err(e) = push_metrics("replay-raw").await {
+            eprintln!("⚠️ {:#}", e);
+        }
+        let (_, failed) = result?;
+        if failed > 0 {
+            eprintln!("⚠️ {} transactions failed", failed);
+        }
+        return Ok(());
+    }
+
+    println!("🪦 Starting Pump.fun Indexer - Dead-Letter Replay");
+    println!("==================================================\n");
+
+    let result = replay_dead_letters().await;
+    if let Err(e) = push_metrics("replay-dead-letters").await {
+        eprintln!("⚠️ {:#}", e);
+    }
+    let (replayed, succeeded) = result?;
+    if succeeded < replayed {
+        eprintln!(
+            "⚠️ {} transactions failed again and are back in the queue",
+            replayed - succeeded
+        );
+    }
+
+    Ok(())
+}
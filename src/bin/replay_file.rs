@@ -0,0 +1,47 @@
+use indexer::helius::fetcher::replay_file;
+use indexer::metrics::push_metrics;
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: replay-file <FILE.jsonl> [--dry-run] [--sol-price <USD>]";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+    indexer::logging::init();
+
+    let mut path = None;
+    let mut dry_run = false;
+    let mut sol_price = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--sol-price" => sol_price = Some(args.next().ok_or(USAGE)?.parse::<f64>()?),
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(PathBuf::from(arg)),
+            _ => {
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+    let Some(path) = path else {
+        eprintln!("{}", USAGE);
+        std::process::exit(2);
+    };
+
+    if !dry_run {
+        println!("📼 Starting Pump.fun Indexer - File Replay");
+        println!("===========================================\n");
+    }
+
+    let result = replay_file(&path, dry_run, sol_price).await;
+    if let Err(e) = push_metrics("replay-file").await {
+        eprintln!("⚠️ {:#}", e);
+    }
+    let (_, failed) = result?;
+    if failed > 0 {
+        eprintln!("⚠️ {} transactions failed", failed);
+    }
+
+    Ok(())
+}
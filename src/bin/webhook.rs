@@ -0,0 +1,24 @@
+use indexer::helius;
+use indexer::tasks::{TaskManager, shutdown_signal};
+use std::time::Duration;
+
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+    indexer::logging::init();
+    println!("🪝 Starting Pump.fun Indexer - Webhook Receiver");
+    println!("================================================\n");
+
+    let mut tasks = TaskManager::new();
+    tasks.spawn("webhook", helius::webhook::run_webhook);
+
+    tokio::select! {
+        _ = shutdown_signal() => println!("🛑 Shutting down..."),
+        _ = tasks.any_finished() => {}
+    }
+
+    tasks.shutdown(SHUTDOWN_TIMEOUT).await;
+    Ok(())
+}
@@ -1,12 +1,46 @@
 use indexer::helius::fetcher;
+use indexer::metrics::push_metrics;
+use indexer::tasks::{TaskManager, shutdown_signal};
+use std::time::Duration;
+
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenvy::dotenv().ok();
-    
-    println!("🎧 Starting Pump.fun Indexer - Worker");
-    println!("======================================\n");
-    
-    fetcher::run_worker().await?;
+    indexer::logging::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [] => {
+            println!("🎧 Starting Pump.fun Indexer - Worker");
+            println!("======================================\n");
+
+            let mut tasks = TaskManager::new();
+            tasks.spawn("worker", fetcher::run_worker);
+
+            tokio::select! {
+                _ = shutdown_signal() => println!("🛑 Shutting down..."),
+                _ = tasks.any_finished() => {}
+            }
+
+            tasks.shutdown(SHUTDOWN_TIMEOUT).await;
+        }
+        [mode, from_id, to_id] if mode == "replay" => {
+            println!("⏪ Starting Pump.fun Indexer - Stream Replay");
+            println!("=============================================\n");
+
+            let result = fetcher::replay_stream(from_id, to_id).await;
+            if let Err(e) = push_metrics("replay-stream").await {
+                eprintln!("⚠️ {:#}", e);
+            }
+            result?;
+        }
+        _ => {
+            eprintln!("Usage: worker [replay <FROM_ID> <TO_ID>]");
+            std::process::exit(2);
+        }
+    }
+
     Ok(())
 }
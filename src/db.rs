@@ -2,18 +2,130 @@ use anyhow::{Context, Result};
 use redis::Client;
 use sqlx::postgres::{PgPool, PgPoolOptions};
 use std::env;
+use std::sync::OnceLock;
 use std::time::Duration;
 
+/// Optional database features detected at startup
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DbCapabilities {
+    /// TimescaleDB extension installed (`time_bucket`, hypertables, continuous aggregates)
+    pub timescaledb: bool,
+}
+
+static CAPABILITIES: OnceLock<DbCapabilities> = OnceLock::new();
+
+/// Sizing and timeouts for each Postgres pool. Every process opens its own
+/// pools, so `max_connections` times the number of workers, API and webhook
+/// processes (twice over with a replica) has to fit in the server's
+/// `max_connections`, less its reserved slots.
+#[derive(Debug, Clone, Copy)]
+pub struct DbPoolConfig {
+    pub max_connections: u32,
+    /// How long a query waits for a free connection before failing
+    pub acquire_timeout: Duration,
+    /// Idle connections above the minimum are closed after this long;
+    /// `None` keeps them
+    pub idle_timeout: Option<Duration>,
+}
+
+impl Default for DbPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            acquire_timeout: Duration::from_secs(10),
+            idle_timeout: Some(Duration::from_secs(300)),
+        }
+    }
+}
+
+impl DbPoolConfig {
+    /// Read from `DB_MAX_CONNECTIONS`, `DB_ACQUIRE_TIMEOUT` and
+    /// `DB_IDLE_TIMEOUT` (seconds, 0 never closes idle connections),
+    /// keeping the defaults for unset or invalid values
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            env::var(name).ok()?.parse().ok()
+        }
+
+        let defaults = Self::default();
+        Self {
+            max_connections: var("DB_MAX_CONNECTIONS")
+                .filter(|n| *n > 0)
+                .unwrap_or(defaults.max_connections),
+            acquire_timeout: var("DB_ACQUIRE_TIMEOUT")
+                .map_or(defaults.acquire_timeout, Duration::from_secs),
+            idle_timeout: var::<u64>("DB_IDLE_TIMEOUT").map_or(defaults.idle_timeout, |secs| {
+                (secs > 0).then(|| Duration::from_secs(secs))
+            }),
+        }
+    }
+
+    /// Connect a pool to `url`. Connections are pinged before being handed
+    /// out, so one the server or a proxy dropped isn't given to a query.
+    pub async fn connect(self, url: &str) -> Result<PgPool, sqlx::Error> {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(self.idle_timeout)
+            .test_before_acquire(true)
+            .connect(url)
+            .await
+    }
+}
+
 /// Creates a connection pool to the Postgres database
 pub async fn get_db_pool() -> Result<PgPool> {
     let database_url = env::var("DATABASE_URL").context("DATABASE_URL must be set in .env")?;
 
-    PgPoolOptions::new()
+    DbPoolConfig::from_env()
         .connect(&database_url)
         .await
         .context("Failed to connect to Postgres")
 }
 
+/// Primary pool for writes plus an optional read-replica pool for query-heavy
+/// readers such as the API. Without a replica both point at the primary.
+#[derive(Debug, Clone)]
+pub struct DbPools {
+    primary: PgPool,
+    read: PgPool,
+}
+
+impl DbPools {
+    /// Connects to `DATABASE_URL`, and to `DATABASE_READ_URL` when it is set
+    pub async fn from_env() -> Result<Self> {
+        let primary = get_db_pool().await?;
+
+        let read = match env::var("DATABASE_READ_URL") {
+            Ok(read_url) if !read_url.is_empty() => DbPoolConfig::from_env()
+                .connect(&read_url)
+                .await
+                .context("Failed to connect to Postgres read replica")?,
+            _ => primary.clone(),
+        };
+
+        Ok(Self { primary, read })
+    }
+
+    /// Reads and writes both on `pool`
+    pub fn single(pool: PgPool) -> Self {
+        Self {
+            primary: pool.clone(),
+            read: pool,
+        }
+    }
+
+    /// Pool for `insert_*` / `upsert_*` and anything that must see its own writes
+    pub fn writer(&self) -> &PgPool {
+        &self.primary
+    }
+
+    /// Pool for `get_*` queries; may lag the primary slightly
+    pub fn reader(&self) -> &PgPool {
+        &self.read
+    }
+}
+
 /// Creates a Redis client instance.
 pub fn get_redis_client() -> Result<Client> {
     let redis_url = env::var("REDIS_URL").context("REDIS_URL must be set in .env")?;
@@ -30,3 +142,43 @@ pub async fn get_redis_conn(client: &Client) -> Result<redis::aio::MultiplexedCo
         .await
         .context("Failed to get async Redis connection")
 }
+
+/// Probe the database for optional extensions and remember the result for
+/// the lifetime of the process. Call once after the pool is created.
+pub async fn detect_capabilities(pool: &PgPool) -> Result<DbCapabilities> {
+    let timescaledb: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb')",
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to detect database capabilities")?;
+
+    Ok(*CAPABILITIES.get_or_init(|| DbCapabilities { timescaledb }))
+}
+
+/// Capabilities recorded by `detect_capabilities`, or plain Postgres if never probed
+pub fn capabilities() -> DbCapabilities {
+    CAPABILITIES.get().copied().unwrap_or_default()
+}
+
+/// SQL expression that buckets `column` into fixed windows of `interval_secs_param`
+/// seconds (a bound `BIGINT` placeholder such as `$2`).
+///
+/// Uses `time_bucket` when TimescaleDB is available and falls back to flooring the
+/// epoch otherwise. Both align buckets to the Unix epoch, so results are identical
+/// for any interval that divides a day.
+pub fn time_bucket_sql(column: &str, interval_secs_param: &str) -> String {
+    if capabilities().timescaledb {
+        format!(
+            "time_bucket(make_interval(secs => {p}), {c})",
+            p = interval_secs_param,
+            c = column
+        )
+    } else {
+        format!(
+            "to_timestamp(floor(extract(epoch FROM {c}) / {p}) * {p})",
+            p = interval_secs_param,
+            c = column
+        )
+    }
+}
@@ -1,9 +1,19 @@
-use Error::Error;
+use thiserror::Error;
 
+use crate::pricing::PricingError;
+
+/// Errors from the indexing pipeline (fetch, parse, store). The task entry
+/// points and binaries wrap these in `anyhow`; below them, callers match on
+/// the variant, e.g. to retry a transient database error instead of giving up
+/// on the transaction.
 #[derive(Error, Debug)]
 pub enum IndexerError {
-    #[error("Database error: {0}")]
-    DbError(#[from] sqlx::Error),
+    #[error("Database error: {context}: {source}")]
+    DbError {
+        context: &'static str,
+        #[source]
+        source: sqlx::Error,
+    },
 
     #[error("Redis error: {0}")]
     RedisError(#[from] redis::RedisError),
@@ -16,4 +26,62 @@ pub enum IndexerError {
 
     #[error("Blockchain Parse error: {0}")]
     ParseError(String),
+
+    #[error("Pricing error: {0}")]
+    PricingError(#[from] PricingError),
+
+    #[error("SOL price unavailable: {0}")]
+    PriceFeedError(String),
+}
+
+pub type Result<T, E = IndexerError> = std::result::Result<T, E>;
+
+impl From<sqlx::Error> for IndexerError {
+    fn from(source: sqlx::Error) -> Self {
+        Self::DbError {
+            context: "query failed",
+            source,
+        }
+    }
+}
+
+/// Transport and HTTP errors from the RPC. The request URL is dropped: it
+/// can carry an API key.
+impl From<reqwest::Error> for IndexerError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::HeliusError(e.without_url().to_string())
+    }
+}
+
+impl IndexerError {
+    /// Whether trying again later could succeed: connection trouble with the
+    /// database, Redis or the RPC, as opposed to data that will fail the
+    /// same way every time
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::DbError { source, .. } => matches!(
+                source,
+                sqlx::Error::Io(_)
+                    | sqlx::Error::Tls(_)
+                    | sqlx::Error::PoolTimedOut
+                    | sqlx::Error::PoolClosed
+                    | sqlx::Error::WorkerCrashed
+            ),
+            Self::RedisError(e) => e.is_io_error() || e.is_timeout() || e.is_connection_dropped(),
+            Self::HeliusError(_) | Self::PriceFeedError(_) => true,
+            Self::SerdeError(_) | Self::ParseError(_) | Self::PricingError(_) => false,
+        }
+    }
+}
+
+/// `.context(...)` for database results, like anyhow's but keeping the
+/// error typed
+pub trait DbContext<T> {
+    fn context(self, context: &'static str) -> Result<T>;
+}
+
+impl<T> DbContext<T> for std::result::Result<T, sqlx::Error> {
+    fn context(self, context: &'static str) -> Result<T> {
+        self.map_err(|source| IndexerError::DbError { context, source })
+    }
 }
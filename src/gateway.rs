@@ -0,0 +1,285 @@
+use anyhow::{Context, Result};
+use axum::{
+    Router,
+    extract::{
+        State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::Response,
+    routing::get,
+};
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use crate::db::DbPools;
+use crate::models::queries::get_recent_trades;
+use crate::models::{Trade, TradeCursor};
+use crate::redis::redis_cleint::RedisClient;
+use crate::redis::trade_feed::{TOKEN_TRADES_PATTERN, mint_from_channel};
+
+/// Trades sent, newest first, when a client subscribes to a mint
+const SNAPSHOT_LIMIT: i64 = 50;
+/// Trade messages a client may fall behind the feed before it is dropped
+const CLIENT_BUFFER: usize = 1024;
+/// Longest a single send may block before the client counts as stalled
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_SUBSCRIPTIONS: usize = 100;
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+/// One trade from the Redis feed, already rendered as the frame clients get
+struct FeedMessage {
+    mint: String,
+    /// The trade's primary key; `None` if the payload didn't parse as a
+    /// `Trade`, which is then never taken for a duplicate
+    key: Option<TradeCursor>,
+    frame: String,
+}
+
+/// A client's subscribed mints, each with the keys of the trades its snapshot
+/// already sent. The feed is subscribed before a snapshot is read, so a trade
+/// stored while it loads can arrive both ways; the live copy is dropped.
+#[derive(Debug, Default)]
+pub struct Subscriptions {
+    mints: HashMap<String, HashSet<TradeCursor>>,
+}
+
+impl Subscriptions {
+    pub fn len(&self) -> usize {
+        self.mints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mints.is_empty()
+    }
+
+    pub fn contains(&self, mint: &str) -> bool {
+        self.mints.contains_key(mint)
+    }
+
+    /// Subscribe to `mint` (again), remembering what `snapshot` sent
+    pub fn insert(&mut self, mint: &str, snapshot: &[Trade]) {
+        let sent = snapshot.iter().map(TradeCursor::from).collect();
+        self.mints.insert(mint.to_string(), sent);
+    }
+
+    pub fn remove(&mut self, mint: &str) {
+        self.mints.remove(mint);
+    }
+
+    /// Whether a live trade of `mint` should be sent: subscribed, and not
+    /// already in the snapshot
+    pub fn admits(&self, mint: &str, key: Option<&TradeCursor>) -> bool {
+        self.mints
+            .get(mint)
+            .is_some_and(|sent| key.is_none_or(|key| !sent.contains(key)))
+    }
+}
+
+#[derive(Clone)]
+struct GatewayState {
+    pools: DbPools,
+    feed: broadcast::Sender<Arc<FeedMessage>>,
+    shutdown: CancellationToken,
+}
+
+/// Control messages a client sends
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe { mint: String },
+    Unsubscribe { mint: String },
+}
+
+/// Stream newly indexed trades to browsers over WebSocket (`GATEWAY_ADDR`,
+/// default `0.0.0.0:3001`, path `/ws`) until `shutdown`.
+///
+/// Clients send `{"type":"subscribe","mint":...}` and get a `snapshot` of
+/// the mint's recent trades followed by a `trade` message per new trade, read
+/// from the worker's `token:{mint}:trades` channels. Live trades the snapshot
+/// already held are not sent again. Clients that can't keep up are
+/// disconnected rather than buffered without bound.
+pub async fn run_gateway(shutdown: CancellationToken) -> Result<()> {
+    let addr = std::env::var("GATEWAY_ADDR").unwrap_or_else(|_| "0.0.0.0:3001".to_string());
+    let redis_url =
+        std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+
+    let pools = DbPools::from_env().await?;
+    let redis = RedisClient::new(&redis_url).await?;
+    let (feed, _) = broadcast::channel(CLIENT_BUFFER);
+    tokio::spawn(run_feed(redis, feed.clone(), shutdown.clone()));
+
+    let app = Router::new()
+        .route("/ws", get(upgrade))
+        .with_state(GatewayState {
+            pools,
+            feed,
+            shutdown: shutdown.clone(),
+        });
+
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("Failed to bind {}", addr))?;
+    info!("Gateway listening on {}", addr);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { shutdown.cancelled().await })
+        .await
+        .context("Gateway server failed")
+}
+
+/// Fan every token trade published to Redis out to the connected clients,
+/// resubscribing whenever the pub/sub connection drops
+async fn run_feed(
+    redis: RedisClient,
+    feed: broadcast::Sender<Arc<FeedMessage>>,
+    shutdown: CancellationToken,
+) {
+    while !shutdown.is_cancelled() {
+        match redis.psubscribe(TOKEN_TRADES_PATTERN).await {
+            Ok(stream) => {
+                tokio::pin!(stream);
+                loop {
+                    tokio::select! {
+                        _ = shutdown.cancelled() => return,
+                        message = stream.next() => match message {
+                            Some((channel, payload)) => forward(&feed, &channel, &payload),
+                            None => break,
+                        },
+                    }
+                }
+                warn!("Trade feed subscription ended, resubscribing");
+            }
+            Err(e) => error!(error = %e, "Trade feed subscription failed"),
+        }
+
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = tokio::time::sleep(RESUBSCRIBE_DELAY) => {}
+        }
+    }
+}
+
+fn forward(feed: &broadcast::Sender<Arc<FeedMessage>>, channel: &str, payload: &str) {
+    let Some(mint) = mint_from_channel(channel) else {
+        return;
+    };
+    let trade: Value = match serde_json::from_str(payload) {
+        Ok(trade) => trade,
+        Err(e) => {
+            warn!(channel, error = %e, "Dropping malformed trade message");
+            return;
+        }
+    };
+
+    let key = serde_json::from_value::<Trade>(trade.clone())
+        .ok()
+        .map(|trade| TradeCursor::from(&trade));
+    let frame = json!({ "type": "trade", "mint": mint, "trade": trade }).to_string();
+    // Fails only when no client is connected
+    let _ = feed.send(Arc::new(FeedMessage {
+        mint: mint.to_string(),
+        key,
+        frame,
+    }));
+}
+
+/// `GET /ws`
+async fn upgrade(ws: WebSocketUpgrade, State(state): State<GatewayState>) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(socket, state))
+}
+
+async fn handle_socket(socket: WebSocket, state: GatewayState) {
+    let (mut sender, mut receiver) = socket.split();
+    // Subscribed before any snapshot is read, so trades stored while it loads
+    // are queued here rather than lost
+    let mut feed = state.feed.subscribe();
+    let mut mints = Subscriptions::default();
+
+    loop {
+        tokio::select! {
+            _ = state.shutdown.cancelled() => {
+                let _ = send(&mut sender, Message::Close(None)).await;
+                break;
+            }
+            incoming = receiver.next() => {
+                let text = match incoming {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    // Pings are answered by axum
+                    Some(Ok(_)) => continue,
+                };
+                let reply = match serde_json::from_str::<ClientMessage>(text.as_str()) {
+                    Ok(ClientMessage::Subscribe { mint }) => {
+                        subscribe(&state, &mut mints, mint).await
+                    }
+                    Ok(ClientMessage::Unsubscribe { mint }) => {
+                        mints.remove(&mint);
+                        json!({ "type": "unsubscribed", "mint": mint })
+                    }
+                    Err(e) => error_frame(format!("invalid message: {}", e)),
+                };
+                if !send(&mut sender, Message::Text(reply.to_string().into())).await {
+                    break;
+                }
+            }
+            message = feed.recv() => match message {
+                Ok(message) => {
+                    if mints.admits(&message.mint, message.key.as_ref())
+                        && !send(&mut sender, Message::Text(message.frame.clone().into())).await
+                    {
+                        debug!("Dropping stalled gateway client");
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    info!(skipped, "Dropping slow gateway client");
+                    let frame = error_frame(format!("slow consumer: {} trades dropped", skipped));
+                    let _ = send(&mut sender, Message::Text(frame.to_string().into())).await;
+                    break;
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+/// Add `mint` to the client's subscriptions and build its snapshot reply
+async fn subscribe(state: &GatewayState, mints: &mut Subscriptions, mint: String) -> Value {
+    if !mints.contains(&mint) && mints.len() >= MAX_SUBSCRIPTIONS {
+        return error_frame(format!("at most {} subscriptions", MAX_SUBSCRIPTIONS));
+    }
+
+    match get_recent_trades(state.pools.reader(), &mint, SNAPSHOT_LIMIT).await {
+        Ok(trades) => {
+            mints.insert(&mint, &trades);
+            json!({ "type": "snapshot", "mint": mint, "trades": trades })
+        }
+        Err(e) => {
+            error!(mint = %mint, error = %e, "Snapshot query failed");
+            // Still subscribed; live trades follow
+            mints.insert(&mint, &[]);
+            json!({ "type": "snapshot", "mint": mint, "trades": [], "error": "snapshot unavailable" })
+        }
+    }
+}
+
+fn error_frame(message: String) -> Value {
+    json!({ "type": "error", "message": message })
+}
+
+/// Send one frame; `false` if the client is gone or stalled past
+/// [`SEND_TIMEOUT`]
+async fn send(sender: &mut SplitSink<WebSocket, Message>, message: Message) -> bool {
+    matches!(
+        tokio::time::timeout(SEND_TIMEOUT, sender.send(message)).await,
+        Ok(Ok(()))
+    )
+}
@@ -0,0 +1,130 @@
+use anyhow::{Context, Result};
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde_json::{Map, Value, json};
+use sqlx::PgPool;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use crate::helius::fetcher::RpcPool;
+use crate::redis::redis_cleint::RedisClient;
+
+/// Longest a single component check may take before it counts as down
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A dependency a process can't work without
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    Postgres,
+    Redis,
+    Rpc,
+}
+
+impl Component {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Postgres => "postgres",
+            Self::Redis => "redis",
+            Self::Rpc => "rpc",
+        }
+    }
+}
+
+/// What `GET /health` checks. Each component is registered once its
+/// connection is up; until then it reports `starting` and the probe fails.
+pub struct Health {
+    components: Vec<Component>,
+    db: OnceLock<PgPool>,
+    redis: OnceLock<RedisClient>,
+    rpc: OnceLock<(reqwest::Client, Arc<RpcPool>)>,
+}
+
+impl Health {
+    pub fn new(components: &[Component]) -> Arc<Self> {
+        Arc::new(Self {
+            components: components.to_vec(),
+            db: OnceLock::new(),
+            redis: OnceLock::new(),
+            rpc: OnceLock::new(),
+        })
+    }
+
+    pub fn set_db(&self, pool: PgPool) {
+        let _ = self.db.set(pool);
+    }
+
+    pub fn set_redis(&self, redis: RedisClient) {
+        let _ = self.redis.set(redis);
+    }
+
+    /// Checked against whichever endpoint `rpc` would use now
+    pub fn set_rpc(&self, client: reqwest::Client, rpc: Arc<RpcPool>) {
+        let _ = self.rpc.set((client, rpc));
+    }
+
+    async fn check(&self, component: Component) -> Result<()> {
+        match component {
+            Component::Postgres => {
+                let pool = self.db.get().context("starting")?;
+                sqlx::query("SELECT 1").execute(pool).await?;
+            }
+            Component::Redis => {
+                let mut redis = self.redis.get().context("starting")?.clone();
+                redis.ping().await?;
+            }
+            Component::Rpc => {
+                let (client, rpc) = self.rpc.get().context("starting")?;
+                // The URL carries the API key; keep it out of the response
+                let response: Value = client
+                    .post(rpc.url()?)
+                    .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "getHealth" }))
+                    .send()
+                    .await
+                    .map_err(|e| e.without_url())?
+                    .json()
+                    .await
+                    .map_err(|e| e.without_url())?;
+                if response.get("result").and_then(|r| r.as_str()) != Some("ok") {
+                    let error = response.get("error").unwrap_or(&response);
+                    anyhow::bail!("{}", error);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `GET /health`: 200 when every component checks out, 503 otherwise, with
+/// each component's status in the body
+pub async fn health_handler(State(health): State<Arc<Health>>) -> impl IntoResponse {
+    let checks = health.components.iter().map(|&component| {
+        let health = &health;
+        async move {
+            let status = match tokio::time::timeout(CHECK_TIMEOUT, health.check(component)).await {
+                Ok(Ok(())) => "ok".to_string(),
+                Ok(Err(e)) => format!("{:#}", e),
+                Err(_) => format!("timed out after {:?}", CHECK_TIMEOUT),
+            };
+            (component.name(), status)
+        }
+    });
+    let results = futures_util::future::join_all(checks).await;
+
+    let healthy = results.iter().all(|(_, status)| status == "ok");
+    let components: Map<String, Value> = results
+        .into_iter()
+        .map(|(name, status)| (name.to_string(), Value::String(status)))
+        .collect();
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(json!({
+            "status": if healthy { "ok" } else { "unavailable" },
+            "components": components,
+        })),
+    )
+}
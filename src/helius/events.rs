@@ -0,0 +1,284 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use borsh::BorshDeserialize;
+
+use crate::models::helius_model::TransactionMeta;
+
+/// Prefix Anchor uses when an event is emitted with `emit!`
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+const PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+/// PDA pump.fun signs its event self-CPIs with (`seeds = [b"__event_authority"]`)
+const EVENT_AUTHORITY: &str = "Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1";
+
+/// Tag Anchor puts before the event in `emit_cpi!` self-invocations
+/// (`sha256("anchor:event")[..8]`, little-endian)
+const EVENT_IX_TAG: [u8; 8] = [228, 69, 165, 46, 81, 203, 154, 29];
+
+/// `sha256("event:CreateEvent")[..8]`
+const CREATE_EVENT_DISCRIMINATOR: [u8; 8] = [27, 114, 169, 77, 222, 235, 99, 118];
+
+/// `sha256("event:TradeEvent")[..8]`
+const TRADE_EVENT_DISCRIMINATOR: [u8; 8] = [189, 219, 127, 211, 78, 230, 97, 238];
+
+/// Fields present in every version of pump.fun's `CreateEvent`
+#[derive(BorshDeserialize)]
+struct CreateEventHeader {
+    name: String,
+    symbol: String,
+    uri: String,
+    mint: [u8; 32],
+    bonding_curve: [u8; 32],
+    user: [u8; 32],
+}
+
+/// Fields appended to `CreateEvent` in later program versions
+#[derive(BorshDeserialize)]
+struct CreateEventTail {
+    creator: [u8; 32],
+    timestamp: i64,
+    virtual_token_reserves: u64,
+    virtual_sol_reserves: u64,
+    real_token_reserves: u64,
+    token_total_supply: u64,
+}
+
+/// Initial bonding curve state carried by newer `CreateEvent`s
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitialCurveState {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub token_total_supply: u64,
+}
+
+/// Decoded pump.fun `CreateEvent`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateEvent {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub mint: String,
+    pub bonding_curve: String,
+    pub user: String,
+    pub creator: Option<String>,
+    pub timestamp: Option<i64>,
+    pub initial_state: Option<InitialCurveState>,
+}
+
+/// Fields present in every version of pump.fun's `TradeEvent`
+#[derive(BorshDeserialize)]
+struct TradeEventHeader {
+    mint: [u8; 32],
+    sol_amount: u64,
+    token_amount: u64,
+    is_buy: bool,
+    user: [u8; 32],
+    timestamp: i64,
+    virtual_sol_reserves: u64,
+    virtual_token_reserves: u64,
+    real_sol_reserves: u64,
+    real_token_reserves: u64,
+}
+
+/// Fee fields appended to `TradeEvent` once protocol and creator fees existed
+#[derive(BorshDeserialize)]
+struct TradeEventFeeTail {
+    fee_recipient: [u8; 32],
+    fee_basis_points: u64,
+    fee: u64,
+    creator: [u8; 32],
+    creator_fee_basis_points: u64,
+    creator_fee: u64,
+}
+
+/// Fees charged on a trade, as reported by newer `TradeEvent`s (lamports)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeFees {
+    pub fee_recipient: String,
+    pub fee_basis_points: u64,
+    pub fee: u64,
+    pub creator: String,
+    pub creator_fee_basis_points: u64,
+    pub creator_fee: u64,
+}
+
+/// Decoded pump.fun `TradeEvent`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeEvent {
+    pub mint: String,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub is_buy: bool,
+    pub user: String,
+    pub timestamp: i64,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub fees: Option<TradeFees>,
+}
+
+/// Decode every `CreateEvent` the transaction emitted
+pub fn decode_create_events(meta: &TransactionMeta) -> Vec<CreateEvent> {
+    event_payloads(meta)
+        .filter_map(|data| decode_create_event_data(&data))
+        .collect()
+}
+
+/// Decode one event payload as a `CreateEvent`, if it is one
+pub fn decode_create_event_data(data: &[u8]) -> Option<CreateEvent> {
+    let mut payload = data.strip_prefix(CREATE_EVENT_DISCRIMINATOR.as_slice())?;
+
+    let header = CreateEventHeader::deserialize(&mut payload).ok()?;
+    // Older program versions stop after `user`
+    let tail = CreateEventTail::deserialize(&mut payload).ok();
+
+    Some(CreateEvent {
+        name: header.name,
+        symbol: header.symbol,
+        uri: header.uri,
+        mint: encode_pubkey(&header.mint),
+        bonding_curve: encode_pubkey(&header.bonding_curve),
+        user: encode_pubkey(&header.user),
+        creator: tail.as_ref().map(|t| encode_pubkey(&t.creator)),
+        timestamp: tail.as_ref().map(|t| t.timestamp),
+        initial_state: tail.map(|t| InitialCurveState {
+            virtual_token_reserves: t.virtual_token_reserves,
+            virtual_sol_reserves: t.virtual_sol_reserves,
+            real_token_reserves: t.real_token_reserves,
+            token_total_supply: t.token_total_supply,
+        }),
+    })
+}
+
+/// Decode every `TradeEvent` the transaction emitted
+pub fn decode_trade_events(meta: &TransactionMeta) -> Vec<TradeEvent> {
+    event_payloads(meta)
+        .filter_map(|data| decode_trade_event_data(&data))
+        .collect()
+}
+
+/// The transaction's first `TradeEvent`, if any
+pub fn decode_trade_event(meta: &TransactionMeta) -> Option<TradeEvent> {
+    event_payloads(meta).find_map(|data| decode_trade_event_data(&data))
+}
+
+/// Decode one event payload as a `TradeEvent`, if it is one
+pub fn decode_trade_event_data(data: &[u8]) -> Option<TradeEvent> {
+    let mut payload = data.strip_prefix(TRADE_EVENT_DISCRIMINATOR.as_slice())?;
+
+    let header = TradeEventHeader::deserialize(&mut payload).ok()?;
+    // Older program versions stop after the reserves
+    let fees = TradeEventFeeTail::deserialize(&mut payload).ok();
+
+    Some(TradeEvent {
+        mint: encode_pubkey(&header.mint),
+        sol_amount: header.sol_amount,
+        token_amount: header.token_amount,
+        is_buy: header.is_buy,
+        user: encode_pubkey(&header.user),
+        timestamp: header.timestamp,
+        virtual_sol_reserves: header.virtual_sol_reserves,
+        virtual_token_reserves: header.virtual_token_reserves,
+        real_sol_reserves: header.real_sol_reserves,
+        real_token_reserves: header.real_token_reserves,
+        fees: fees.map(|f| TradeFees {
+            fee_recipient: encode_pubkey(&f.fee_recipient),
+            fee_basis_points: f.fee_basis_points,
+            fee: f.fee,
+            creator: encode_pubkey(&f.creator),
+            creator_fee_basis_points: f.creator_fee_basis_points,
+            creator_fee: f.creator_fee,
+        }),
+    })
+}
+
+/// The `TradeEvent` for `user`'s trade of `mint`, if the program logged one
+pub fn find_trade_event(meta: &TransactionMeta, mint: &str, user: &str) -> Option<TradeEvent> {
+    decode_trade_events(meta)
+        .into_iter()
+        .find(|event| event.mint == mint && event.user == user)
+}
+
+/// Every event payload (discriminator + Borsh data) in the transaction:
+/// `emit_cpi!` self-invocations first, then `emit!` log lines. A log line
+/// repeating a self-CPI's payload is the same event and is skipped, once per
+/// self-CPI, so identical events (the same trade twice) still count twice.
+fn event_payloads(meta: &TransactionMeta) -> impl Iterator<Item = Vec<u8>> {
+    let cpi: Vec<Vec<u8>> = event_cpi_data(meta).collect();
+    let mut unmatched = cpi.clone();
+    let logged: Vec<Vec<u8>> = program_data(meta)
+        .filter(
+            |payload| match unmatched.iter().position(|p| p == payload) {
+                Some(index) => {
+                    unmatched.swap_remove(index);
+                    false
+                }
+                None => true,
+            },
+        )
+        .collect();
+    cpi.into_iter().chain(logged)
+}
+
+/// Payloads of pump.fun's event self-CPIs: inner instructions to its own
+/// program whose only account is its event authority (the PDA that must
+/// sign them, so no other program can forge one), carrying the Anchor event
+/// tag
+fn event_cpi_data(meta: &TransactionMeta) -> impl Iterator<Item = Vec<u8>> + '_ {
+    meta.inner_instructions
+        .iter()
+        .flatten()
+        .flat_map(|wrapper| wrapper.instructions.iter())
+        .filter(|ix| ix.program_id == PUMP_FUN_PROGRAM_ID)
+        .filter(|ix| ix.accounts.as_deref().is_some_and(is_event_authority))
+        .filter_map(|ix| bs58::decode(ix.data.as_ref()?).into_vec().ok())
+        .filter_map(|data| Some(event_cpi_payload(&data)?.to_vec()))
+}
+
+/// Whether an instruction's accounts are exactly pump.fun's event authority,
+/// as in its event self-CPIs
+pub fn is_event_authority(accounts: &[String]) -> bool {
+    accounts == [EVENT_AUTHORITY]
+}
+
+/// The event payload carried by a pump.fun event self-CPI's decoded data;
+/// `None` for any other instruction
+pub fn event_cpi_payload(data: &[u8]) -> Option<&[u8]> {
+    data.strip_prefix(EVENT_IX_TAG.as_slice())
+}
+
+/// Base64-decoded payloads of the `Program data:` log lines pump.fun wrote
+/// itself: those directly inside one of its invocations. Any program can log
+/// `Program data:`, so a line from another frame (a router's, say) could
+/// carry a forged event.
+fn program_data(meta: &TransactionMeta) -> impl Iterator<Item = Vec<u8>> + '_ {
+    // Programs currently executing, innermost last
+    let mut frames: Vec<&str> = Vec::new();
+    meta.log_messages.iter().flatten().filter_map(move |log| {
+        if let Some(encoded) = log.strip_prefix(PROGRAM_DATA_PREFIX) {
+            return (frames.last() == Some(&PUMP_FUN_PROGRAM_ID))
+                .then(|| BASE64.decode(encoded.trim()).ok())
+                .flatten();
+        }
+        // `Program <id> invoke [n]` opens a frame, `Program <id> success` or
+        // `Program <id> failed: ...` closes it. `Program log:` and the like
+        // are a program's own output.
+        let (program, status) = log.strip_prefix("Program ")?.split_once(' ')?;
+        if program.ends_with(':') {
+            return None;
+        }
+        if status.starts_with("invoke [") {
+            frames.push(program);
+        } else if status == "success" || status.starts_with("failed") {
+            frames.pop();
+        }
+        None
+    })
+}
+
+fn encode_pubkey(bytes: &[u8; 32]) -> String {
+    bs58::encode(bytes).into_string()
+}
@@ -1,184 +1,2421 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 use futures_util::StreamExt;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::FromPrimitive;
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tokio::time::{Duration, Instant};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::{Mutex, RwLock, Semaphore};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::{Instrument, debug, error, info, info_span, warn};
 
-use crate::db::get_db_pool;
-use crate::helius::parser::{parse_pump_fun_transaction, parse_token_creation};
+use crate::analytics::sandwich::detect_sandwiches_in_slot;
+use crate::analytics::snipers::refresh_snipers;
+use crate::db::{detect_capabilities, get_db_pool};
+use crate::error::IndexerError;
+use crate::health::{Component, Health};
+use crate::helius::events::find_trade_event;
+use crate::helius::global_config::fetch_global_config;
+use crate::helius::http::HttpConfig;
+use crate::helius::latency::LatencyTracker;
+use crate::helius::metadata::fetch_token_metadata;
+use crate::helius::parser::{
+    ParseOptions, ParseOutcome, derive_bonding_curve, find_create_args, is_valid_pubkey, parse_all,
+    parse_enhanced_transaction, parse_migration, parse_token_creation, parse_transaction,
+    post_token_balance,
+};
+use crate::helius::price::{PriceProviders, stream_pyth_sol_usd};
+use crate::helius::retry::RetryPolicy;
+use crate::metrics;
 use crate::models::queries::{
-    get_token, get_token_holder, insert_trade, upsert_token, upsert_token_holder,
+    batch_insert_trades, claim_token_metadata_fetch, debit_holder_tail, delete_trade,
+    evict_dust_holders, get_24h_volume_stats, get_holder_count, get_raw_transactions, get_token,
+    get_token_holder, get_trade_signatures_since_slot, insert_migration, insert_raw_transaction,
+    insert_skipped_transaction, insert_trade, insert_transaction, prune_raw_transactions,
+    record_graduation, refresh_candles, set_amm_pool_address, set_token_metadata, update_dev_sold,
+    upsert_token, upsert_token_holder,
+};
+use crate::models::{
+    Commitment, Token, TokenHolder, Trade, Transaction,
+    helius_model::{EnhancedTransaction, TransactionMeta, TransactionResult},
+};
+use crate::pricing::{
+    CurveParams, LAMPORTS_PER_SOL, market_cap_from_reserves, price_from_reserves,
+    progress_from_reserves, sol_to_usd,
 };
-use crate::models::{Token, TokenHolder, helius_model::TransactionResult};
 use crate::redis::redis_cleint::RedisClient;
+use crate::redis::token_card::{TokenCard, write_token_card};
+use crate::redis::trade_feed::publish_trade;
 
-const REDIS_CHANNEL: &str = "solana:transactions";
-const SOL_USD_FEED_ID: &str = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+const REDIS_STREAM: &str = "solana:transactions:stream";
+const REPLAY_PAGE_SIZE: usize = 500;
+/// Sorted set of signatures to fetch again later, scored by due time
+const RETRY_QUEUE: &str = "solana:retry";
+/// Requeues before a not-yet-indexed signature is given up on
+const MAX_REQUEUE_ATTEMPTS: u32 = 5;
+/// Delay before requeue attempt `n` is this x 2^(n-1): 5s, 10s, ... 80s
+const REQUEUE_BASE_DELAY: Duration = Duration::from_secs(5);
+const RETRY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Consumer group the workers share `REDIS_STREAM` through
+const DEFAULT_WORKER_GROUP: &str = "workers";
+/// Entries unacked this long (their worker died or couldn't park them) are
+/// taken over by whichever worker checks next
+const CLAIM_MIN_IDLE_MS: u64 = 60_000;
+const CLAIM_POLL_INTERVAL: Duration = Duration::from_secs(30);
+const RETRY_BATCH_SIZE: usize = 100;
+/// Trades buffered before the worker flushes them in one transaction
+const DEFAULT_TRADE_BATCH_SIZE: usize = 100;
+/// Longest a buffered trade waits for its batch to fill
+const DEFAULT_TRADE_BATCH_WAIT: Duration = Duration::from_millis(250);
+/// List of transactions that failed to fetch or process, newest first
+const DEAD_LETTER_QUEUE: &str = "solana:deadletter";
+const DEAD_LETTER_MAX_LEN: usize = 10_000;
+const PUMP_FUN_PROGRAM: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+/// Highest slot the workers have processed a transaction from
+const LAST_SLOT_KEY: &str = "indexer:last_slot";
+/// Solana's target slot time, for turning a slot gap into a duration
+const SLOT_DURATION: Duration = Duration::from_millis(400);
+/// A restart gap longer than this is reported as possible data loss; the
+/// stream backlog covers anything shorter
+const STALE_CONNECTION_GAP: Duration = Duration::from_secs(60);
+/// How often trades indexed at `confirmed` are checked against the chain
+const FORK_RECONCILE_INTERVAL: Duration = Duration::from_secs(30);
+/// Trades are only checked once this many slots old, so a lagging RPC node
+/// that hasn't seen a fresh transaction yet can't get it deleted
+const FORK_RECONCILE_MIN_AGE_SLOTS: u64 = 32;
+const DEFAULT_FORK_RECONCILE_SLOTS: u64 = 150;
+/// getSignatureStatuses maximum
+const SIGNATURE_STATUSES_BATCH: usize = 256;
+const SIGNATURES_PAGE_SIZE: usize = 1000; // getSignaturesForAddress maximum
+const BACKFILL_PROGRESS_EVERY: usize = 100;
+/// Days stored raw transactions are kept by default
+const DEFAULT_RAW_TX_RETENTION_DAYS: u64 = 30;
+const RAW_TX_PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+/// Metadata fetches in flight at once; past this, new ones are left for a
+/// later trade of the same token
+const MAX_METADATA_FETCHES: usize = 16;
+static METADATA_FETCHES: Semaphore = Semaphore::const_new(MAX_METADATA_FETCHES);
+/// Rows deleted per statement while pruning, so no single delete runs long
+const RAW_TX_PRUNE_BATCH: i64 = 10_000;
 const PRICE_CACHE_TTL_SECS: u64 = 30; // Cache price for 30 seconds
+const PRICE_STREAM_BACKOFF_INITIAL: Duration = Duration::from_secs(1);
+const PRICE_STREAM_BACKOFF_MAX: Duration = Duration::from_secs(60);
+/// A stream that lasted this long was healthy; reset the backoff
+const PRICE_STREAM_STABLE: Duration = Duration::from_secs(30);
+
+/// A SOL/USD price and when it was fetched
+#[derive(Clone)]
+pub struct PriceCache {
+    pub price: f64,
+    pub fetched_at: DateTime<Utc>,
+    updated_at: Instant,
+}
+
+/// Cached SOL price plus a refresh lock, so concurrent cache misses share a
+/// single round of provider requests instead of each firing their own
+#[derive(Default)]
+pub struct SharedPriceCache {
+    cached: RwLock<Option<PriceCache>>,
+    refresh: Mutex<()>,
+}
+
+impl SharedPriceCache {
+    async fn fresh(&self) -> Option<PriceCache> {
+        self.cached
+            .read()
+            .await
+            .as_ref()
+            .filter(|p| p.updated_at.elapsed().as_secs() < PRICE_CACHE_TTL_SECS)
+            .cloned()
+    }
+
+    /// The last price cached, however old
+    async fn last(&self) -> Option<PriceCache> {
+        self.cached.read().await.clone()
+    }
+
+    async fn store(&self, price: f64) -> PriceCache {
+        let fresh = PriceCache {
+            price,
+            fetched_at: Utc::now(),
+            updated_at: Instant::now(),
+        };
+        *self.cached.write().await = Some(fresh.clone());
+        fresh
+    }
+}
+
+/// Keep `cache` current from Pyth's price stream so the hot path never waits
+/// on the network. Reconnects with backoff until `shutdown`; while the stream
+/// is down the cache goes stale and `get_sol_price` falls back to HTTP.
+fn spawn_price_updater(
+    tasks: &TaskTracker,
+    cache: Arc<SharedPriceCache>,
+    shutdown: CancellationToken,
+) {
+    tasks.spawn(async move {
+        let mut backoff = PRICE_STREAM_BACKOFF_INITIAL;
+        while !shutdown.is_cancelled() {
+            let connected_at = Instant::now();
+            // Updates come in faster than they're needed; write at most one
+            // a second
+            let (tx, mut rx) = tokio::sync::watch::channel(None);
+            let stream = stream_pyth_sol_usd(
+                |price| {
+                    tx.send_replace(Some(price));
+                },
+                &shutdown,
+            );
+            tokio::pin!(stream);
+            let mut tick = tokio::time::interval(Duration::from_secs(1));
+
+            let result = loop {
+                tokio::select! {
+                    result = &mut stream => break result,
+                    _ = tick.tick() => {
+                        let latest = match rx.has_changed() {
+                            Ok(true) => *rx.borrow_and_update(),
+                            _ => None,
+                        };
+                        if let Some(price) = latest {
+                            cache.store(price).await;
+                        }
+                    }
+                }
+            };
+
+            match result {
+                Ok(()) => break,
+                Err(e) => {
+                    if connected_at.elapsed() >= PRICE_STREAM_STABLE {
+                        backoff = PRICE_STREAM_BACKOFF_INITIAL;
+                    }
+                    warn!(
+                        "Pyth price stream dropped ({}), reconnecting in {:?}",
+                        e, backoff
+                    );
+                    tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        _ = tokio::time::sleep(backoff) => {}
+                    }
+                    backoff = (backoff * 2).min(PRICE_STREAM_BACKOFF_MAX);
+                }
+            }
+        }
+    });
+}
+
+/// How `bonding_curve_progress` follows the curve. The live value is always
+/// kept in `bonding_curve_progress_raw`, and `complete` latches either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgressMode {
+    /// Mirror current reserves, so progress drops on sells
+    #[default]
+    Instantaneous,
+    /// Never decrease: progress is the highest value seen so far
+    HighWater,
+}
+
+impl ProgressMode {
+    /// Read from `BONDING_PROGRESS_MODE` (`instantaneous` | `high_water`)
+    pub fn from_env() -> Self {
+        match std::env::var("BONDING_PROGRESS_MODE").as_deref() {
+            Ok("high_water") => Self::HighWater,
+            _ => Self::Instantaneous,
+        }
+    }
+
+    /// Record a freshly computed progress value on the token
+    pub fn apply(self, token: &mut Token, raw_progress: Decimal) {
+        token.bonding_curve_progress_raw = raw_progress;
+        token.bonding_curve_progress = match self {
+            Self::Instantaneous => raw_progress,
+            Self::HighWater => token.bonding_curve_progress.max(raw_progress),
+        };
+        if raw_progress >= Decimal::from(100) {
+            token.complete = true;
+        }
+    }
+}
+
+/// Guards `market_cap_usd` against one-trade spikes: within a single slot, a
+/// value more than `factor` times above or below the previous one is treated
+/// as transient and the previous value kept. The computed value always goes
+/// to `market_cap_usd_raw`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarketCapClamp {
+    factor: Option<Decimal>,
+}
+
+impl MarketCapClamp {
+    /// Read from `MARKET_CAP_CLAMP_FACTOR` (e.g. `10`); unset or <= 1 disables it
+    pub fn from_env() -> Self {
+        let factor = std::env::var("MARKET_CAP_CLAMP_FACTOR")
+            .ok()
+            .and_then(|v| v.parse::<Decimal>().ok())
+            .filter(|f| *f > Decimal::ONE);
+        Self { factor }
+    }
+
+    /// Record a freshly computed market cap for a trade in `slot`.
+    /// Returns whether the update was clamped.
+    pub fn apply(self, token: &mut Token, raw_market_cap: Decimal, slot: i64) -> bool {
+        token.market_cap_usd_raw = raw_market_cap;
+
+        let previous = token.market_cap_usd;
+        let clamped = match self.factor {
+            Some(factor) if token.last_trade_slot == Some(slot) && previous > Decimal::ZERO => {
+                raw_market_cap > previous * factor || raw_market_cap * factor < previous
+            }
+            _ => false,
+        };
+
+        if !clamped {
+            token.market_cap_usd = raw_market_cap;
+        }
+        clamped
+    }
+}
+
+/// Bounds `token_holders` rows per token. Past `max_holders` (plus 10% slack,
+/// so eviction runs in batches) the smallest holders are folded into the
+/// token's `token_holder_tails` aggregate; holders above `min_balance` are
+/// always kept.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HolderCap {
+    max_holders: Option<i64>,
+    min_balance: Decimal,
+}
+
+impl HolderCap {
+    /// Default significance threshold: 10,000 tokens at pump.fun's 6 decimals
+    const DEFAULT_MIN_BALANCE: i64 = 10_000_000_000;
+
+    /// Read from `HOLDER_CAP` (unset or 0 = off) and `HOLDER_CAP_MIN_BALANCE`
+    /// (raw token units)
+    pub fn from_env() -> Self {
+        let max_holders = std::env::var("HOLDER_CAP")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|cap| *cap > 0);
+        let min_balance = std::env::var("HOLDER_CAP_MIN_BALANCE")
+            .ok()
+            .and_then(|v| v.parse::<Decimal>().ok())
+            .unwrap_or(Decimal::from(Self::DEFAULT_MIN_BALANCE));
+        Self {
+            max_holders,
+            min_balance,
+        }
+    }
+
+    pub fn enabled(self) -> bool {
+        self.max_holders.is_some()
+    }
+
+    /// Evict dust holders of `mint` if it is over the cap
+    pub async fn enforce(self, pool: &PgPool, mint: &str) -> Result<(), IndexerError> {
+        let Some(cap) = self.max_holders else {
+            return Ok(());
+        };
+
+        let evicted = evict_dust_holders(pool, mint, cap, cap + cap / 10, self.min_balance).await?;
+        if evicted > 0 {
+            info!("Evicted {} dust holders of {}", evicted, mint);
+        }
+        Ok(())
+    }
+}
+
+/// The mints to index (`WATCHLIST`); empty means every mint. Trades,
+/// migrations and audit rows of other mints are skipped, and so are their
+/// creations unless `include_creations` is set.
+#[derive(Debug, Clone, Default)]
+pub struct Watchlist {
+    mints: HashSet<String>,
+    include_creations: bool,
+}
+
+impl Watchlist {
+    /// Read from `WATCHLIST` (comma-separated mints; unset or empty = all)
+    /// and `WATCHLIST_INCLUDE_CREATIONS` (`true` stores every new token)
+    pub fn from_env() -> Self {
+        let mints: HashSet<String> = std::env::var("WATCHLIST")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|mint| !mint.is_empty())
+            .map(str::to_string)
+            .collect();
+        let include_creations = std::env::var("WATCHLIST_INCLUDE_CREATIONS")
+            .is_ok_and(|v| v.eq_ignore_ascii_case("true") || v == "1");
+        if !mints.is_empty() {
+            info!("Indexing {} watched mint(s) only", mints.len());
+        }
+        Self {
+            mints,
+            include_creations,
+        }
+    }
+
+    pub fn includes(&self, mint: &str) -> bool {
+        self.mints.is_empty() || self.mints.contains(mint)
+    }
+
+    /// Whether a newly created `mint` is stored
+    pub fn includes_creation(&self, mint: &str) -> bool {
+        self.include_creations || self.includes(mint)
+    }
+
+    /// Whether `tx` references a watched mint, as an account or in its token
+    /// balances
+    pub fn touches(&self, tx: &TransactionResult) -> bool {
+        if self.mints.is_empty() {
+            return true;
+        }
+        let mut balances = tx.meta.iter().flat_map(|meta| {
+            let pre = meta.pre_token_balances.iter().flatten();
+            pre.chain(meta.post_token_balances.iter().flatten())
+        });
+        tx.transaction
+            .message
+            .account_keys
+            .iter()
+            .any(|key| self.mints.contains(&key.pubkey))
+            || balances.any(|balance| self.mints.contains(&balance.mint))
+    }
+}
+
+/// Whether the worker is keeping up with the chain or draining a backlog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessingMode {
+    /// Full processing
+    RealTime,
+    /// Behind by more than the enter threshold: optional enrichment
+    /// (sandwich detection, metadata resolution, skip archiving) is skipped
+    CatchUp,
+}
+
+static CATCHING_UP: AtomicBool = AtomicBool::new(false);
+static LAST_LAG_MS: AtomicU64 = AtomicU64::new(0);
+
+/// The worker's current mode (`RealTime` until lag is first observed; always
+/// `RealTime` outside the live worker)
+pub fn processing_mode() -> ProcessingMode {
+    if CATCHING_UP.load(Ordering::Relaxed) {
+        ProcessingMode::CatchUp
+    } else {
+        ProcessingMode::RealTime
+    }
+}
+
+/// How long the most recently read stream entry waited in the stream
+pub fn processing_lag() -> Duration {
+    Duration::from_millis(LAST_LAG_MS.load(Ordering::Relaxed))
+}
+
+/// How long stream entry `id` has waited at `now_ms`: entry IDs start with
+/// the millisecond they were added (`<ms>-<seq>`). `None` for a malformed ID.
+pub fn stream_entry_lag(id: &str, now_ms: i64) -> Option<Duration> {
+    let added_ms = id.split_once('-')?.0.parse::<i64>().ok()?;
+    Some(Duration::from_millis(
+        now_ms.saturating_sub(added_ms).max(0) as u64,
+    ))
+}
+
+/// Switches between `RealTime` and `CatchUp` on observed stream lag, with
+/// hysteresis: catch-up starts above `enter_lag` and ends below `exit_lag`
+#[derive(Debug, Clone, Copy)]
+pub struct CatchUpSwitch {
+    enter_lag: Option<Duration>,
+    exit_lag: Duration,
+}
+
+impl CatchUpSwitch {
+    /// Catch up above `enter_lag` until back under `exit_lag`
+    pub fn new(enter_lag: Duration, exit_lag: Duration) -> Self {
+        Self {
+            enter_lag: Some(enter_lag),
+            exit_lag: exit_lag.min(enter_lag),
+        }
+    }
+
+    /// Read from `CATCH_UP_ENTER_LAG_SECS` (default 60, 0 = never) and
+    /// `CATCH_UP_EXIT_LAG_SECS` (default 10)
+    pub fn from_env() -> Self {
+        let secs = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(default)
+        };
+        let enter = secs("CATCH_UP_ENTER_LAG_SECS", 60);
+        let exit = secs("CATCH_UP_EXIT_LAG_SECS", 10).min(enter);
+        Self {
+            enter_lag: (enter > 0).then(|| Duration::from_secs(enter)),
+            exit_lag: Duration::from_secs(exit),
+        }
+    }
+
+    /// Record the lag of a stream entry about to be processed and return the
+    /// mode to process it in
+    pub fn observe(self, lag: Duration) -> ProcessingMode {
+        LAST_LAG_MS.store(lag.as_millis() as u64, Ordering::Relaxed);
+        let Some(enter_lag) = self.enter_lag else {
+            return ProcessingMode::RealTime;
+        };
+
+        let was_catching_up = CATCHING_UP.load(Ordering::Relaxed);
+        let catching_up = if was_catching_up {
+            lag >= self.exit_lag
+        } else {
+            lag > enter_lag
+        };
+
+        if catching_up != was_catching_up {
+            CATCHING_UP.store(catching_up, Ordering::Relaxed);
+            if catching_up {
+                info!(
+                    "{}s behind: catch-up mode, skipping enrichment",
+                    lag.as_secs()
+                );
+            } else {
+                info!("Caught up ({}s behind): real-time mode", lag.as_secs());
+            }
+        }
+        processing_mode()
+    }
+}
+
+/// Samples transactions that fetched fine but parsed to nothing into
+/// `skipped_transactions`, so new instruction shapes can be found and handled
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SkipArchive {
+    sample_rate: f64,
+}
+
+impl SkipArchive {
+    /// Read from `SKIPPED_TX_SAMPLE_RATE` (0.0 - 1.0), defaulting to 0 (off)
+    pub fn from_env() -> Self {
+        let sample_rate = std::env::var("SKIPPED_TX_SAMPLE_RATE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0)
+            .clamp(0.0, 1.0);
+        Self { sample_rate }
+    }
+
+    /// Sample by signature, so reprocessing makes the same choice on any
+    /// build. A signature is already uniformly random bytes, so its first 8
+    /// serve as the hash; anything that isn't one falls back to FNV-1a.
+    fn sampled(&self, signature: &str) -> bool {
+        let hash = bs58::decode(signature)
+            .into_vec()
+            .ok()
+            .and_then(|bytes| Some(u64::from_le_bytes(bytes.get(..8)?.try_into().ok()?)))
+            .unwrap_or_else(|| {
+                signature
+                    .bytes()
+                    .fold(0xcbf2_9ce4_8422_2325, |hash: u64, byte| {
+                        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+                    })
+            });
+        (hash % 10_000) < (self.sample_rate * 10_000.0) as u64
+    }
+
+    async fn archive(&self, pool: &PgPool, tx: &TransactionResult, signature: &str, reason: &str) {
+        if !self.sampled(signature) {
+            return;
+        }
+        let raw = match serde_json::to_value(tx) {
+            Ok(raw) => raw,
+            Err(e) => {
+                warn!("Could not serialize skipped {}: {}", signature, e);
+                return;
+            }
+        };
+        if let Err(e) =
+            insert_skipped_transaction(pool, signature, tx.slot as i64, reason, &raw).await
+        {
+            warn!("Could not archive skipped {}: {}", signature, e);
+        }
+    }
+}
+
+/// Keeps fetched transactions, gzip-compressed, in `raw_transactions` so they
+/// can be reprocessed (`replay_raw`) without the RPC
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawTxStore {
+    enabled: bool,
+    /// How long rows are kept; `None` keeps them forever
+    retention: Option<chrono::Duration>,
+}
+
+impl RawTxStore {
+    /// Read from `STORE_RAW_TX` (`true` to enable) and
+    /// `RAW_TX_RETENTION_DAYS` (default 30, 0 = keep forever)
+    pub fn from_env() -> Self {
+        let days = std::env::var("RAW_TX_RETENTION_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_RAW_TX_RETENTION_DAYS);
+        Self {
+            enabled: std::env::var("STORE_RAW_TX").as_deref() == Ok("true"),
+            retention: (days > 0).then(|| chrono::Duration::days(days as i64)),
+        }
+    }
+
+    /// Best effort: a transaction that can't be stored is still processed
+    async fn save(&self, pool: &PgPool, tx: &TransactionResult, commitment: Commitment) {
+        if !self.enabled {
+            return;
+        }
+        let signature = tx
+            .transaction
+            .signatures
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default();
+        let data = match compress_transaction(tx) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Could not compress raw {}: {:#}", signature, e);
+                return;
+            }
+        };
+        if let Err(e) =
+            insert_raw_transaction(pool, signature, tx.slot as i64, commitment, &data).await
+        {
+            warn!("Could not store raw {}: {}", signature, e);
+        }
+    }
+}
+
+fn compress_transaction(tx: &TransactionResult) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, tx)?;
+    Ok(encoder.finish()?)
+}
+
+fn decompress_transaction(data: &[u8]) -> Result<TransactionResult> {
+    Ok(serde_json::from_reader(GzDecoder::new(data))?)
+}
+
+/// Hourly, delete stored raw transactions older than the retention, until
+/// `shutdown`
+fn spawn_raw_tx_pruner(
+    tasks: &TaskTracker,
+    pool: PgPool,
+    retention: chrono::Duration,
+    shutdown: CancellationToken,
+) {
+    tasks.spawn(async move {
+        let mut tick = tokio::time::interval(RAW_TX_PRUNE_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tick.tick() => {}
+            }
+            let cutoff = Utc::now() - retention;
+            let mut pruned = 0;
+            loop {
+                match prune_raw_transactions(&pool, cutoff, RAW_TX_PRUNE_BATCH).await {
+                    Ok(0) => break,
+                    Ok(n) => pruned += n,
+                    Err(e) => {
+                        warn!("Raw transaction pruning failed: {}", e);
+                        break;
+                    }
+                }
+            }
+            if pruned > 0 {
+                info!("Pruned {} raw transaction(s)", pruned);
+            }
+        }
+    });
+}
+
+/// Read from `FETCH_COMMITMENT` (`confirmed` | `finalized`, the default and
+/// the RPC's own default)
+fn fetch_commitment_from_env() -> Commitment {
+    match std::env::var("FETCH_COMMITMENT").as_deref() {
+        Ok("confirmed") => Commitment::Confirmed,
+        _ => Commitment::Finalized,
+    }
+}
+
+/// The worker's buffer of parsed trades, stored together through
+/// `batch_insert_trades` once `max_trades` have collected or `max_wait` has
+/// passed, whichever comes first. Stream entries are acked only after their
+/// trades are stored, so a crash leaves them pending for redelivery.
+struct TradeBatch {
+    max_trades: usize,
+    max_wait: Duration,
+    pending: Mutex<PendingTrades>,
+}
+
+#[derive(Default)]
+struct PendingTrades {
+    transactions: Vec<PendingTransaction>,
+    trade_count: usize,
+    /// Stream entries handled since the last flush
+    acks: Vec<String>,
+}
+
+/// A transaction's parsed trades, with what their token and holder updates need
+struct PendingTransaction {
+    tx: TransactionResult,
+    program_id: Option<String>,
+    commitment: Commitment,
+    trades: Vec<Trade>,
+    sol_price: PriceCache,
+    enrich: bool,
+    /// Its `transaction` span, so the deferred logs still carry the signature
+    span: tracing::Span,
+}
+
+impl TradeBatch {
+    /// Read from `TRADE_BATCH_SIZE` (0 or 1 stores each trade as it is
+    /// parsed) and `TRADE_BATCH_WAIT_MS`
+    fn from_env() -> Option<Self> {
+        let max_trades = std::env::var("TRADE_BATCH_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_TRADE_BATCH_SIZE);
+        let max_wait = std::env::var("TRADE_BATCH_WAIT_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|ms| *ms > 0)
+            .map_or(DEFAULT_TRADE_BATCH_WAIT, Duration::from_millis);
+        (max_trades > 1).then(|| Self {
+            max_trades,
+            max_wait,
+            pending: Mutex::new(PendingTrades::default()),
+        })
+    }
+
+    async fn push(&self, transaction: PendingTransaction) {
+        let mut pending = self.pending.lock().await;
+        pending.trade_count += transaction.trades.len();
+        pending.transactions.push(transaction);
+    }
+
+    async fn defer_ack(&self, id: &str) {
+        self.pending.lock().await.acks.push(id.to_string());
+    }
+
+    async fn is_full(&self) -> bool {
+        self.pending.lock().await.trade_count >= self.max_trades
+    }
+
+    async fn take(&self) -> PendingTrades {
+        std::mem::take(&mut *self.pending.lock().await)
+    }
+}
+
+/// Everything needed to turn a stream payload into database rows
+struct WorkerContext {
+    /// JSON-RPC endpoints transactions and slots are fetched from
+    rpc: Arc<RpcPool>,
+    db_pool: PgPool,
+    http_client: reqwest::Client,
+    price_cache: Arc<SharedPriceCache>,
+    /// SOL/USD sources, tried in order on a cache miss
+    price_providers: PriceProviders,
+    parse_options: ParseOptions,
+    progress_mode: ProgressMode,
+    skip_archive: SkipArchive,
+    raw_tx: RawTxStore,
+    market_cap_clamp: MarketCapClamp,
+    holder_cap: HolderCap,
+    watchlist: Watchlist,
+    /// Switches to catch-up on stream lag; `None` (everything but the live
+    /// worker, whose backlog is historical by design) always runs in real time
+    catch_up: Option<CatchUpSwitch>,
+    /// Shared with the metrics endpoint's percentile gauges
+    rpc_latency: Arc<LatencyTracker>,
+    rpc_retry: RetryPolicy,
+    /// Where token cards are kept; `None` leaves them alone (backfill)
+    token_cards: Option<RedisClient>,
+    /// Where new trades are published for realtime clients; `None` (backfill)
+    /// doesn't publish
+    trade_feed: Option<RedisClient>,
+    /// Buffers trades for batched inserts; `None` stores each as it is parsed
+    /// (everything but the live worker)
+    trade_batch: Option<TradeBatch>,
+    /// Commitment signatures from the stream are fetched at
+    fetch_commitment: Commitment,
+    /// Consumer group on `REDIS_STREAM` (`WORKER_GROUP`)
+    worker_group: String,
+    /// Background tasks (price stream, metadata fetches, ...), waited for
+    /// before the worker returns
+    background: TaskTracker,
+}
+
+impl WorkerContext {
+    async fn init() -> Result<Self> {
+        let rpc = RpcPool::from_env().expect("RPC_URL or HELIUS_API_KEY missing");
+
+        // Initialize database pool
+        let db_pool = get_db_pool().await?;
+        info!("Database connected");
+
+        let mut ctx =
+            Self::with_pool(Arc::new(rpc), db_pool, HttpConfig::from_env().build()?).await?;
+        ctx.load_global_config().await;
+        Ok(ctx)
+    }
+
+    /// Take the curve's starting reserves and supply from pump.fun's global
+    /// config account, under any `CURVE_*` set in the environment. Keeps the
+    /// configured values if the account can't be read (e.g. devnet without
+    /// the program).
+    async fn load_global_config(&mut self) {
+        match fetch_global_config(&self.http_client, &self.rpc).await {
+            Ok(global) => {
+                info!(
+                    fee_recipient = %global.fee_recipient,
+                    fee_basis_points = global.fee_basis_points,
+                    initial_virtual_sol = global.initial_virtual_sol_reserves,
+                    initial_virtual_tokens = global.initial_virtual_token_reserves,
+                    "Loaded pump.fun global config"
+                );
+                self.parse_options.curve = CurveParams::from_env_over(global.curve_params());
+            }
+            Err(e) => warn!(
+                "Could not read pump.fun global config, using configured curve: {}",
+                e
+            ),
+        }
+    }
+
+    /// Context around an existing pool and HTTP client, knobs read from env
+    async fn with_pool(
+        rpc: Arc<RpcPool>,
+        db_pool: PgPool,
+        http_client: reqwest::Client,
+    ) -> Result<Self> {
+        let capabilities = detect_capabilities(&db_pool).await?;
+        if !capabilities.timescaledb {
+            info!("TimescaleDB not available, using plain SQL fallbacks");
+        }
+
+        Ok(Self {
+            rpc,
+            db_pool,
+            price_providers: PriceProviders::from_env(&http_client),
+            http_client,
+            // Initialize price cache with shared state
+            price_cache: Arc::new(SharedPriceCache::default()),
+            parse_options: ParseOptions::from_env(),
+            progress_mode: ProgressMode::from_env(),
+            skip_archive: SkipArchive::from_env(),
+            raw_tx: RawTxStore::from_env(),
+            market_cap_clamp: MarketCapClamp::from_env(),
+            holder_cap: HolderCap::from_env(),
+            watchlist: Watchlist::from_env(),
+            catch_up: None,
+            rpc_latency: metrics::register_rpc_latency(Arc::new(LatencyTracker::default())),
+            rpc_retry: RetryPolicy::from_env(),
+            token_cards: None,
+            trade_feed: None,
+            trade_batch: None,
+            fetch_commitment: fetch_commitment_from_env(),
+            worker_group: std::env::var("WORKER_GROUP")
+                .unwrap_or_else(|_| DEFAULT_WORKER_GROUP.to_string()),
+            background: TaskTracker::new(),
+        })
+    }
+
+    /// `fetch_full_transaction` through the context's RPC pool, keeping the
+    /// result in `raw_transactions` when that is on
+    async fn fetch_transaction(
+        &self,
+        signature: &str,
+        commitment: Commitment,
+    ) -> Result<TransactionResult, FetchError> {
+        let tx = fetch_full_transaction(
+            &self.http_client,
+            &self.rpc,
+            &self.rpc_latency,
+            &self.rpc_retry,
+            signature,
+            commitment,
+        )
+        .await?;
+        self.raw_tx.save(&self.db_pool, &tx, commitment).await;
+        Ok(tx)
+    }
+
+    /// Handle one queued signature. Only `Outcome::Failed` leaves it without
+    /// a home: processed, or parked in the retry or dead-letter queue.
+    async fn handle_payload(&self, redis: &mut RedisClient, payload: &str) -> Outcome {
+        // Parse the mini-info (Signature) from Redis
+        let Ok(info) = serde_json::from_str::<Value>(payload) else {
+            return Outcome::Processed;
+        };
+        let Some(signature) = info.get("signature").and_then(|s| s.as_str()) else {
+            return Outcome::Processed;
+        };
+
+        // Everything logged while handling it carries the signature
+        self.handle_signature(redis, &info, signature)
+            .instrument(info_span!("transaction", signature = %signature))
+            .await
+    }
+
+    async fn handle_signature(
+        &self,
+        redis: &mut RedisClient,
+        info: &Value,
+        signature: &str,
+    ) -> Outcome {
+        // Which subscription reported it, for dispatching to a parser
+        let program_id = info.get("program_id").and_then(|p| p.as_str());
+
+        // Webhook deliveries carry the whole transaction already
+        if let Some(tx) = info
+            .get("transaction")
+            .and_then(|tx| serde_json::from_value::<TransactionResult>(tx.clone()).ok())
+        {
+            // Helius delivers webhooks at confirmed
+            self.raw_tx
+                .save(&self.db_pool, &tx, Commitment::Confirmed)
+                .await;
+            return self
+                .process_or_dead_letter(redis, &tx, program_id, Commitment::Confirmed)
+                .await;
+        }
+
+        info!("Fetching details for: {}", signature);
+
+        // Fetch full data from RPC
+        match self
+            .fetch_transaction(signature, self.fetch_commitment)
+            .await
+        {
+            Ok(tx) => {
+                // Parse and save to Database
+                self.process_or_dead_letter(redis, &tx, program_id, self.fetch_commitment)
+                    .await
+            }
+            Err(FetchError::NotYetIndexed) => {
+                let attempt = info.get("attempt").and_then(|a| a.as_u64()).unwrap_or(0);
+                match requeue_signature(redis, signature, program_id, attempt as u32 + 1).await {
+                    // Past the retry limit it's given up on, not lost
+                    Ok(_) => Outcome::Parked,
+                    Err(e) => {
+                        error!("Requeue failed for {}: {}", signature, e);
+                        Outcome::Failed
+                    }
+                }
+            }
+            Err(FetchError::CircuitOpen { retry_in }) => {
+                // Not the transaction's fault, so it doesn't use up an attempt
+                let attempt = info.get("attempt").and_then(|a| a.as_u64()).unwrap_or(0);
+                debug!("RPC circuit open, deferring {}", signature);
+                match schedule_retry(redis, signature, program_id, attempt as u32, retry_in).await {
+                    Ok(()) => Outcome::Parked,
+                    Err(e) => {
+                        error!("Requeue failed for {}: {}", signature, e);
+                        Outcome::Failed
+                    }
+                }
+            }
+            Err(e) => {
+                metrics::RPC_ERRORS.inc();
+                warn!("Fetch error for {}: {}", signature, e);
+                let entry = DeadLetter::new(signature, program_id, &e, None);
+                dead_letter(redis, &entry).await
+            }
+        }
+    }
+
+    /// `process_and_save`, parking the transaction in `DEAD_LETTER_QUEUE` if
+    /// it fails for good. A transient failure (database or RPC unreachable)
+    /// is left where it was queued, to be redelivered once it recovers.
+    async fn process_or_dead_letter(
+        &self,
+        redis: &mut RedisClient,
+        tx: &TransactionResult,
+        program_id: Option<&str>,
+        commitment: Commitment,
+    ) -> Outcome {
+        match process_and_save(self, tx, program_id, commitment).await {
+            Ok(()) => {
+                if let Err(e) = set_last_slot(redis, tx.slot).await {
+                    warn!("Failed to record last slot {}: {}", tx.slot, e);
+                }
+                Outcome::Processed
+            }
+            Err(e) if e.is_transient() => {
+                warn!("Transient error, leaving for redelivery: {:#}", e);
+                Outcome::Failed
+            }
+            Err(e) => {
+                let signature = tx
+                    .transaction
+                    .signatures
+                    .first()
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                warn!("DB Error for {}: {:#}", signature, e);
+                let entry = DeadLetter::new(signature, program_id, &e, Some((tx, commitment)));
+                dead_letter(redis, &entry).await
+            }
+        }
+    }
+}
+
+/// What became of a queued signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    /// Stored (or nothing to store)
+    Processed,
+    /// Moved to the retry or dead-letter queue
+    Parked,
+    /// Neither; it should stay where it was queued
+    Failed,
+}
+
+/// A transaction that failed to fetch or process, kept in
+/// `DEAD_LETTER_QUEUE` for `replay_dead_letters`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub signature: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_id: Option<String>,
+    pub error: String,
+    /// The transaction as fetched, and at what commitment; `None` when
+    /// fetching it is what failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<Commitment>,
+    pub failed_at: DateTime<Utc>,
+}
+
+impl DeadLetter {
+    fn new(
+        signature: &str,
+        program_id: Option<&str>,
+        error: &dyn std::fmt::Display,
+        tx: Option<(&TransactionResult, Commitment)>,
+    ) -> Self {
+        Self {
+            signature: signature.to_string(),
+            program_id: program_id.map(str::to_string),
+            error: format!("{:#}", error),
+            transaction: tx.and_then(|(tx, _)| serde_json::to_value(tx).ok()),
+            commitment: tx.map(|(_, commitment)| commitment),
+            failed_at: Utc::now(),
+        }
+    }
+}
+
+async fn dead_letter(redis: &mut RedisClient, entry: &DeadLetter) -> Outcome {
+    match redis
+        .push_capped(DEAD_LETTER_QUEUE, entry, DEAD_LETTER_MAX_LEN)
+        .await
+    {
+        Ok(()) => {
+            info!("Dead-lettered {}", entry.signature);
+            Outcome::Parked
+        }
+        Err(e) => {
+            error!("Dead-letter push failed for {}: {}", entry.signature, e);
+            Outcome::Failed
+        }
+    }
+}
+
+/// Reprocess everything in `DEAD_LETTER_QUEUE`, oldest first, e.g. after a
+/// parser fix. Entries with a stored transaction are processed from it, the
+/// rest are fetched again. Whatever fails again goes back on the queue, and
+/// entries added meanwhile wait for the next run.
+/// Returns `(replayed, succeeded)`.
+pub async fn replay_dead_letters() -> Result<(usize, usize)> {
+    let mut ctx = WorkerContext::init().await?;
+    let mut redis = RedisClient::new(&redis_url()).await?;
+    ctx.token_cards = Some(redis.clone());
+    ctx.trade_feed = Some(redis.clone());
+
+    let pending = redis.list_len(DEAD_LETTER_QUEUE).await?;
+    info!("Replaying {} dead-lettered transactions", pending);
+
+    let mut succeeded = 0;
+    for _ in 0..pending {
+        let Some(payload) = redis.pop_oldest(DEAD_LETTER_QUEUE).await? else {
+            break;
+        };
+        let entry = match serde_json::from_str::<DeadLetter>(&payload) {
+            Ok(entry) => entry,
+            Err(e) => {
+                warn!("Dropping malformed dead letter ({}): {}", e, payload);
+                continue;
+            }
+        };
+        info!("Replaying {} (failed: {})", entry.signature, entry.error);
+
+        let program_id = entry.program_id.as_deref();
+        let stored = entry
+            .transaction
+            .and_then(|tx| serde_json::from_value::<TransactionResult>(tx).ok());
+        let commitment = entry.commitment.unwrap_or(ctx.fetch_commitment);
+        let outcome = match stored {
+            Some(tx) => {
+                ctx.process_or_dead_letter(&mut redis, &tx, program_id, commitment)
+                    .await
+            }
+            None => match ctx
+                .fetch_transaction(&entry.signature, ctx.fetch_commitment)
+                .await
+            {
+                Ok(tx) => {
+                    ctx.process_or_dead_letter(&mut redis, &tx, program_id, ctx.fetch_commitment)
+                        .await
+                }
+                Err(e) => {
+                    warn!("Fetch error for {}: {}", entry.signature, e);
+                    let entry = DeadLetter::new(&entry.signature, program_id, &e, None);
+                    dead_letter(&mut redis, &entry).await
+                }
+            },
+        };
+        if outcome == Outcome::Processed {
+            succeeded += 1;
+        }
+    }
+
+    info!("Replayed {}, {} succeeded", pending, succeeded);
+    Ok((pending, succeeded))
+}
+
+/// Run captured transactions from a JSONL file (one `TransactionResult` per
+/// line) through `process_and_save` against `DATABASE_URL`, with no RPC or
+/// Redis. With `dry_run` nothing is stored: each transaction's
+/// `ParsedTransaction` is printed as a JSON line instead. `sol_price` pins
+/// SOL/USD for reproducible runs; otherwise the live sources are asked.
+/// Returns `(processed, failed)`.
+pub async fn replay_file(
+    path: &Path,
+    dry_run: bool,
+    sol_price: Option<f64>,
+) -> Result<(usize, usize)> {
+    let http_client = HttpConfig::from_env().build()?;
+    // Without a database there's no worker context to price through
+    let providers = match sol_price {
+        Some(price) => PriceProviders::fixed(price),
+        None => PriceProviders::from_env(&http_client),
+    };
+    let ctx = if dry_run {
+        None
+    } else {
+        // Only used for RPC fetches, which this path never makes
+        let rpc = Arc::new(RpcPool::default());
+        let db_pool = get_db_pool().await?;
+        info!("Database connected");
+        let mut ctx = WorkerContext::with_pool(rpc, db_pool, http_client).await?;
+        if let Some(price) = sol_price {
+            ctx.price_providers = PriceProviders::fixed(price);
+        }
+        Some(ctx)
+    };
+    let options = ParseOptions::from_env();
+
+    let file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut lines = BufReader::new(file).lines();
+    let (mut processed, mut failed) = (0, 0);
+    let mut line_number = 0;
+
+    while let Some(line) = lines.next_line().await? {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let tx = match serde_json::from_str::<TransactionResult>(&line) {
+            Ok(tx) => tx,
+            Err(e) => {
+                warn!("Line {}: not a transaction: {}", line_number, e);
+                failed += 1;
+                continue;
+            }
+        };
+        let signature = tx
+            .transaction
+            .signatures
+            .first()
+            .cloned()
+            .unwrap_or_default();
+
+        let result = match &ctx {
+            Some(ctx) => {
+                process_and_save(ctx, &tx, None, Commitment::Finalized)
+                    .instrument(info_span!("transaction", signature = %signature))
+                    .await
+            }
+            None => {
+                let price = providers.get_sol_usd().await?.0;
+                let parsed = parse_all(&tx, None, price, options);
+                println!("{}", serde_json::to_string(&parsed)?);
+                Ok(())
+            }
+        };
+        match result {
+            Ok(()) => processed += 1,
+            Err(e) => {
+                warn!("Line {} ({}): {:#}", line_number, signature, e);
+                failed += 1;
+            }
+        }
+    }
+
+    info!("Replayed {} transactions, {} failed", processed, failed);
+    Ok((processed, failed))
+}
+
+/// Reprocess the transactions stored in `raw_transactions` (`STORE_RAW_TX`)
+/// between `from_slot` and `to_slot` inclusive (default: all of them), oldest
+/// first, through `process_and_save` with no RPC or Redis, e.g. after a
+/// parser fix. Rows already in the database are deduplicated on insert as
+/// usual. `sol_price` pins SOL/USD as in `replay_file`.
+/// Returns `(processed, failed)`.
+pub async fn replay_raw(
+    from_slot: Option<u64>,
+    to_slot: Option<u64>,
+    sol_price: Option<f64>,
+) -> Result<(usize, usize)> {
+    // Only used for RPC fetches, which this path never makes
+    let rpc = Arc::new(RpcPool::default());
+    let db_pool = get_db_pool().await?;
+    info!("Database connected");
+    let mut ctx = WorkerContext::with_pool(rpc, db_pool, HttpConfig::from_env().build()?).await?;
+    if let Some(price) = sol_price {
+        ctx.price_providers = PriceProviders::fixed(price);
+    }
+
+    let from_slot = from_slot.map_or(0, |slot| slot as i64);
+    let to_slot = to_slot.map_or(i64::MAX, |slot| slot as i64);
+    let (mut processed, mut failed) = (0, 0);
+    let mut after: Option<(i64, String)> = None;
+
+    loop {
+        let page = get_raw_transactions(
+            &ctx.db_pool,
+            from_slot,
+            to_slot,
+            after.as_ref().map(|(slot, sig)| (*slot, sig.as_str())),
+            REPLAY_PAGE_SIZE as i64,
+        )
+        .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after = Some((last.slot, last.signature.clone()));
+
+        for raw in &page {
+            let result = match decompress_transaction(&raw.data) {
+                Ok(tx) => process_and_save(&ctx, &tx, None, raw.commitment)
+                    .instrument(info_span!("transaction", signature = %raw.signature))
+                    .await
+                    .map_err(anyhow::Error::from),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(()) => processed += 1,
+                Err(e) => {
+                    warn!("{} (slot {}): {:#}", raw.signature, raw.slot, e);
+                    failed += 1;
+                }
+            }
+        }
+        info!("Replayed up to slot {}", last.slot);
+    }
+
+    info!(
+        "Replayed {} stored transactions, {} failed",
+        processed, failed
+    );
+    Ok((processed, failed))
+}
+
+/// A signature waiting in `RETRY_QUEUE`
+#[derive(Debug, Serialize, Deserialize)]
+struct RetryEntry {
+    signature: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    program_id: Option<String>,
+    attempt: u32,
+}
+
+/// Schedule `signature` to be fetched again, with a delay doubling per
+/// `attempt` (1-based). Past `MAX_REQUEUE_ATTEMPTS` it is logged and dropped.
+/// Returns whether it was requeued.
+pub async fn requeue_signature(
+    redis: &mut RedisClient,
+    signature: &str,
+    program_id: Option<&str>,
+    attempt: u32,
+) -> Result<bool> {
+    if attempt > MAX_REQUEUE_ATTEMPTS {
+        error!(
+            "Giving up on {}: still not indexed after {} requeues",
+            signature, MAX_REQUEUE_ATTEMPTS
+        );
+        return Ok(false);
+    }
+
+    let delay = REQUEUE_BASE_DELAY * 2u32.pow(attempt - 1);
+    schedule_retry(redis, signature, program_id, attempt, delay).await?;
+
+    info!(
+        "{} not indexed yet, retrying in {}s (attempt {}/{})",
+        signature,
+        delay.as_secs(),
+        attempt,
+        MAX_REQUEUE_ATTEMPTS
+    );
+    Ok(true)
+}
+
+/// Put `signature` in `RETRY_QUEUE`, due after `delay`
+async fn schedule_retry(
+    redis: &mut RedisClient,
+    signature: &str,
+    program_id: Option<&str>,
+    attempt: u32,
+    delay: Duration,
+) -> Result<()> {
+    let due_at = Utc::now() + delay;
+    let entry = RetryEntry {
+        signature: signature.to_string(),
+        program_id: program_id.map(str::to_string),
+        attempt,
+    };
+    redis
+        .schedule(RETRY_QUEUE, &entry, due_at.timestamp_millis())
+        .await
+}
+
+/// Highest slot processed so far, `None` before the first transaction
+pub async fn get_last_slot(redis: &mut RedisClient) -> Result<Option<u64>> {
+    redis.get(LAST_SLOT_KEY).await
+}
+
+/// Record that `slot` was processed; never moves the mark backwards
+pub async fn set_last_slot(redis: &mut RedisClient, slot: u64) -> Result<()> {
+    redis.set_max(LAST_SLOT_KEY, slot as i64).await
+}
+
+/// The chain's current slot at `commitment`
+async fn fetch_current_slot(
+    client: &reqwest::Client,
+    rpc: &RpcPool,
+    commitment: Commitment,
+) -> Result<u64, IndexerError> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getSlot",
+        "params": [{ "commitment": commitment.as_str() }]
+    });
+    let body: Value = client
+        .post(rpc.url()?)
+        .json(&request)
+        .send()
+        .await?
+        .json()
+        .await?;
+    body.get("result")
+        .and_then(|slot| slot.as_u64())
+        .ok_or_else(|| IndexerError::HeliusError(format!("getSlot failed: {}", body)))
+}
+
+/// Compare the last processed slot with the chain's and log what may have
+/// been missed while no worker was running
+async fn report_slot_gap(ctx: &WorkerContext, redis: &mut RedisClient) {
+    let last_slot = match get_last_slot(redis).await {
+        Ok(Some(slot)) => slot,
+        Ok(None) => {
+            info!("No last processed slot recorded yet");
+            return;
+        }
+        Err(e) => {
+            warn!("Could not read last processed slot: {}", e);
+            return;
+        }
+    };
+    let current_slot =
+        match fetch_current_slot(&ctx.http_client, &ctx.rpc, ctx.fetch_commitment).await {
+            Ok(slot) => slot,
+            Err(e) => {
+                warn!("Could not fetch current slot: {}", e);
+                return;
+            }
+        };
+
+    let gap = current_slot.saturating_sub(last_slot);
+    let approx = SLOT_DURATION * gap.min(u32::MAX as u64) as u32;
+    if approx >= STALE_CONNECTION_GAP {
+        warn!(
+            "Last processed slot {}, chain is at {}: {} slots (~{}s) may be missing. \
+             Backfill them with `backfill --until <last indexed signature>`",
+            last_slot,
+            current_slot,
+            gap,
+            approx.as_secs()
+        );
+    } else {
+        info!(
+            "Resuming from slot {} ({} behind the chain)",
+            last_slot, gap
+        );
+    }
+}
+
+/// Periodically delete trades from transactions an abandoned fork took with
+/// it. Checks trades between `FORK_RECONCILE_MIN_AGE_SLOTS` and
+/// `FORK_RECONCILE_SLOTS` (default 150) slots old, until `shutdown`.
+fn spawn_fork_reconciler(ctx: &WorkerContext, shutdown: CancellationToken) {
+    let window = std::env::var("FORK_RECONCILE_SLOTS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_FORK_RECONCILE_SLOTS)
+        .max(FORK_RECONCILE_MIN_AGE_SLOTS);
+    let pool = ctx.db_pool.clone();
+    let client = ctx.http_client.clone();
+    let rpc = ctx.rpc.clone();
+
+    ctx.background.spawn(async move {
+        let mut tick = tokio::time::interval(FORK_RECONCILE_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tick.tick() => {}
+            }
+            match reconcile_forks(&pool, &client, &rpc, window).await {
+                Ok(0) => {}
+                Ok(n) => info!("Rolled back {} orphaned transaction(s)", n),
+                Err(e) => warn!("Fork reconciliation failed: {}", e),
+            }
+        }
+    });
+}
+
+/// One reconciliation pass: every recently indexed transaction the chain no
+/// longer has, or now reports as failed, has its trades deleted. Returns the
+/// number of transactions rolled back.
+async fn reconcile_forks(
+    pool: &PgPool,
+    client: &reqwest::Client,
+    rpc: &RpcPool,
+    window: u64,
+) -> Result<usize, IndexerError> {
+    let current_slot = fetch_current_slot(client, rpc, Commitment::Confirmed).await?;
+    let min_slot = current_slot.saturating_sub(window);
+    let max_slot = current_slot.saturating_sub(FORK_RECONCILE_MIN_AGE_SLOTS);
+
+    let candidates: Vec<_> = get_trade_signatures_since_slot(pool, min_slot as i64)
+        .await?
+        .into_iter()
+        .filter(|(_, _, slot)| *slot as u64 <= max_slot)
+        .collect();
+
+    let mut rolled_back = 0;
+    for batch in candidates.chunks(SIGNATURE_STATUSES_BATCH) {
+        let signatures: Vec<&str> = batch.iter().map(|(sig, _, _)| sig.as_str()).collect();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getSignatureStatuses",
+            "params": [signatures, { "searchTransactionHistory": true }]
+        });
+        let body: Value = client
+            .post(rpc.url()?)
+            .json(&request)
+            .send()
+            .await?
+            .json()
+            .await?;
+        let statuses = body
+            .pointer("/result/value")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| {
+                IndexerError::HeliusError(format!("getSignatureStatuses failed: {}", body))
+            })?;
+
+        for ((signature, timestamp, _), status) in batch.iter().zip(statuses) {
+            // `null`: no node knows the transaction any more
+            let orphaned = status.is_null();
+            let failed = status.get("err").is_some_and(|err| !err.is_null());
+            if !orphaned && !failed {
+                continue;
+            }
+
+            let deleted = delete_trade(pool, signature, *timestamp).await?;
+            info!(
+                "{} {}: deleted {} trade(s)",
+                signature,
+                if orphaned {
+                    "dropped with its fork"
+                } else {
+                    "failed on the canonical chain"
+                },
+                deleted
+            );
+            rolled_back += 1;
+        }
+    }
+
+    Ok(rolled_back)
+}
+
+/// Consecutive retryable failures (429, 5xx, timeouts) before an RPC
+/// endpoint is benched in favour of the next one
+const RPC_FAILURE_THRESHOLD: u32 = 3;
+const RPC_BENCH_BASE: Duration = Duration::from_secs(5);
+const RPC_BENCH_MAX: Duration = Duration::from_secs(300);
+
+/// The configured JSON-RPC endpoints and their health.
+///
+/// Requests go to the first endpoint that isn't benched, so the list is in
+/// order of preference and the rest are fallbacks. An endpoint failing
+/// [`RPC_FAILURE_THRESHOLD`] times in a row is benched for 5s, doubling each
+/// time it trips again (up to 5 min); once the bench expires it is back in
+/// rotation, and a success resets it. With every endpoint benched, the one
+/// due back soonest is used.
+///
+/// The pool as a whole sits behind a [`CircuitBreaker`], which stops
+/// `getTransaction` calls altogether during an outage.
+#[derive(Default)]
+pub struct RpcPool {
+    endpoints: Vec<RpcEndpoint>,
+    breaker: CircuitBreaker,
+}
+
+struct RpcEndpoint {
+    url: String,
+    health: std::sync::Mutex<EndpointHealth>,
+}
+
+#[derive(Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    /// Benches since the last success; sets the length of the next one
+    trips: u32,
+    benched_until: Option<Instant>,
+}
+
+impl RpcPool {
+    pub fn new(urls: impl IntoIterator<Item = String>) -> Self {
+        let endpoints = urls
+            .into_iter()
+            .map(|url| RpcEndpoint {
+                url,
+                health: Default::default(),
+            })
+            .collect();
+        Self {
+            endpoints,
+            breaker: CircuitBreaker::from_env(),
+        }
+    }
+
+    /// `RPC_URL` (comma-separated, in order of preference), else Helius
+    /// mainnet for `HELIUS_API_KEY`. `None` when neither is set.
+    pub fn from_env() -> Option<Self> {
+        let urls: Vec<String> = endpoint_from_env("RPC_URL", "https")?
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .collect();
+        (!urls.is_empty()).then(|| Self::new(urls))
+    }
+
+    /// The endpoint to use now, with its index for reporting the outcome
+    pub fn pick(&self) -> Result<(usize, &str), IndexerError> {
+        let now = Instant::now();
+        let mut soonest: Option<(usize, Instant)> = None;
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            match endpoint.health().benched_until {
+                Some(until) if until > now => {
+                    if soonest.is_none_or(|(_, back)| until < back) {
+                        soonest = Some((index, until));
+                    }
+                }
+                _ => return Ok((index, &endpoint.url)),
+            }
+        }
+        let (index, _) = soonest
+            .ok_or_else(|| IndexerError::HeliusError("No RPC endpoint configured".to_string()))?;
+        Ok((index, &self.endpoints[index].url))
+    }
+
+    /// The endpoint to use now
+    pub fn url(&self) -> Result<&str, IndexerError> {
+        self.pick().map(|(_, url)| url)
+    }
+
+    /// Whether a request may be sent now; when the circuit is open, how long
+    /// until it may be tried again
+    pub fn admit(&self) -> Result<(), Duration> {
+        self.breaker.admit()
+    }
+
+    pub fn record_success(&self, index: usize) {
+        *self.endpoints[index].health() = EndpointHealth::default();
+        self.breaker.record_success();
+    }
+
+    /// Count a retryable failure, benching the endpoint once they add up
+    pub fn record_failure(&self, index: usize) {
+        self.breaker.record_failure();
+        let endpoint = &self.endpoints[index];
+        let mut health = endpoint.health();
+        health.consecutive_failures += 1;
+        if health.consecutive_failures < RPC_FAILURE_THRESHOLD {
+            return;
+        }
+
+        let bench = RPC_BENCH_BASE
+            .saturating_mul(2u32.saturating_pow(health.trips))
+            .min(RPC_BENCH_MAX);
+        health.consecutive_failures = 0;
+        health.trips += 1;
+        health.benched_until = Some(Instant::now() + bench);
+        metrics::RPC_ENDPOINTS_BENCHED.inc();
+        // The query string can carry an API key
+        let host = endpoint.url.split('?').next().unwrap_or_default();
+        warn!(
+            "RPC endpoint {} benched for {:?} after {} failures",
+            host, bench, RPC_FAILURE_THRESHOLD
+        );
+    }
+}
+
+/// Consecutive failed RPC requests, on any endpoint, that open the circuit
+const DEFAULT_BREAKER_THRESHOLD: u32 = 20;
+const DEFAULT_BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Whether `getTransaction` calls are going out (see [`CircuitBreaker`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    /// Calls fail fast until the cooldown ends
+    Open,
+    /// Cooldown over; a single probe request decides whether to close
+    HalfOpen,
+}
+
+static CIRCUIT_STATE: AtomicU8 = AtomicU8::new(CircuitState::Closed as u8);
+
+/// The RPC circuit's current state, for the metrics endpoint
+pub fn rpc_circuit_state() -> CircuitState {
+    match CIRCUIT_STATE.load(Ordering::Relaxed) {
+        1 => CircuitState::Open,
+        2 => CircuitState::HalfOpen,
+        _ => CircuitState::Closed,
+    }
+}
+
+/// Stops RPC calls during an upstream outage instead of spending quota on
+/// requests that will fail.
+///
+/// `threshold` failures in a row (429, 5xx, timeouts, on whichever
+/// endpoint) open the circuit: for `cooldown`, callers are refused without
+/// a request being sent. After that one probe is let through; its success
+/// closes the circuit and its failure opens it for another cooldown. A probe
+/// that never reports back is replaced once the cooldown passes again.
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: std::sync::Mutex<BreakerState>,
+}
+
+#[derive(Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    /// Set while open or half-open: when the next probe may go out
+    retry_at: Option<Instant>,
+    half_open: bool,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(DEFAULT_BREAKER_THRESHOLD, DEFAULT_BREAKER_COOLDOWN)
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            state: Default::default(),
+        }
+    }
+
+    /// Read from `RPC_BREAKER_THRESHOLD` and `RPC_BREAKER_COOLDOWN_SECS`,
+    /// keeping the defaults (20 failures, 30s) for unset or invalid values
+    pub fn from_env() -> Self {
+        let threshold = std::env::var("RPC_BREAKER_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_BREAKER_THRESHOLD);
+        let cooldown = std::env::var("RPC_BREAKER_COOLDOWN_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map_or(DEFAULT_BREAKER_COOLDOWN, Duration::from_secs);
+        Self::new(threshold, cooldown)
+    }
+
+    /// `Ok` if a request may go out now, else the time left until it may
+    pub fn admit(&self) -> Result<(), Duration> {
+        let mut state = self.state();
+        let Some(retry_at) = state.retry_at else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        if retry_at > now {
+            return Err(retry_at - now);
+        }
+
+        // This caller is the probe; the rest keep failing fast meanwhile
+        state.half_open = true;
+        state.retry_at = Some(now + self.cooldown);
+        CIRCUIT_STATE.store(CircuitState::HalfOpen as u8, Ordering::Relaxed);
+        info!("RPC circuit half-open, probing");
+        Ok(())
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state();
+        if state.retry_at.is_some() {
+            info!("RPC circuit closed");
+        }
+        *state = BreakerState::default();
+        CIRCUIT_STATE.store(CircuitState::Closed as u8, Ordering::Relaxed);
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state();
+        state.consecutive_failures += 1;
+        // Once open, stragglers admitted before it tripped don't extend it
+        let trips = state.half_open
+            || (state.retry_at.is_none() && state.consecutive_failures >= self.threshold);
+        if !trips {
+            return;
+        }
+
+        state.half_open = false;
+        state.retry_at = Some(Instant::now() + self.cooldown);
+        CIRCUIT_STATE.store(CircuitState::Open as u8, Ordering::Relaxed);
+        metrics::RPC_CIRCUIT_OPENED.inc();
+        warn!(
+            "RPC circuit open for {:?} after {} consecutive failures",
+            self.cooldown, state.consecutive_failures
+        );
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        // A few counters, valid even if a holder panicked
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl RpcEndpoint {
+    fn health(&self) -> std::sync::MutexGuard<'_, EndpointHealth> {
+        // The state is a few counters, valid even if a holder panicked
+        self.health
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The URL in `var`, else the Helius mainnet endpoint over `scheme`
+/// (`https`/`wss`) for `HELIUS_API_KEY`
+pub(crate) fn endpoint_from_env(var: &str, scheme: &str) -> Option<String> {
+    std::env::var(var)
+        .ok()
+        .filter(|url| !url.is_empty())
+        .or_else(|| {
+            let api_key = std::env::var("HELIUS_API_KEY").ok()?;
+            Some(format!(
+                "{}://mainnet.helius-rpc.com/?api-key={}",
+                scheme, api_key
+            ))
+        })
+}
+
+fn redis_url() -> String {
+    std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string())
+}
+
+/// This worker's consumer name: `WORKER_NAME`, else `HOSTNAME`, else one
+/// from the pid. A stable name lets a restarted worker resume its own
+/// unacked entries right away instead of waiting for them to go stale.
+fn worker_consumer_name() -> String {
+    std::env::var("WORKER_NAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| format!("worker-{}", std::process::id()))
+}
+
+/// Handle a consumer-group entry and ack it unless it failed without being
+/// parked anywhere; then it stays pending for `claim_stale`
+async fn handle_entry(ctx: &WorkerContext, redis: &mut RedisClient, id: &str, payload: &str) {
+    if ctx.handle_payload(redis, payload).await == Outcome::Failed {
+        return;
+    }
+    // Its trades may still be buffered; acked with them
+    if let Some(batch) = &ctx.trade_batch {
+        batch.defer_ack(id).await;
+        flush_if_full(ctx, redis).await;
+        return;
+    }
+    if let Err(e) = redis.ack(REDIS_STREAM, &ctx.worker_group, id).await {
+        error!("Ack failed for {}: {}", id, e);
+    }
+}
+
+async fn flush_if_full(ctx: &WorkerContext, redis: &mut RedisClient) {
+    if let Some(batch) = &ctx.trade_batch
+        && batch.is_full().await
+    {
+        flush_trades(ctx, redis).await;
+    }
+}
+
+/// Store the buffered trades in one transaction, apply the new ones to their
+/// tokens and holders, then ack the stream entries handled meanwhile. If the
+/// insert fails nothing is acked, so the entries are reclaimed and retried.
+async fn flush_trades(ctx: &WorkerContext, redis: &mut RedisClient) {
+    let Some(batch) = &ctx.trade_batch else {
+        return;
+    };
+    let PendingTrades {
+        transactions,
+        trade_count,
+        acks,
+    } = batch.take().await;
+
+    if trade_count > 0 {
+        let trades: Vec<Trade> = transactions
+            .iter()
+            .flat_map(|pending| pending.trades.iter().cloned())
+            .collect();
+        let inserted = match batch_insert_trades(&ctx.db_pool, &trades).await {
+            Ok(inserted) => inserted,
+            Err(e) => {
+                error!(
+                    "Trade batch of {} failed, leaving {} entries for redelivery: {:#}",
+                    trade_count,
+                    acks.len(),
+                    e
+                );
+                return;
+            }
+        };
+        debug!("Stored batch of {} trades", trade_count);
+
+        let mut inserted = inserted.into_iter();
+        let mut cards = CardRefreshes::default();
+        for pending in &transactions {
+            let new: Vec<bool> = inserted.by_ref().take(pending.trades.len()).collect();
+            apply_pending(ctx, redis, pending, &new, &mut cards)
+                .instrument(pending.span.clone())
+                .await;
+        }
+        cards.write(ctx).await;
+    }
+
+    for id in acks {
+        if let Err(e) = redis.ack(REDIS_STREAM, &ctx.worker_group, &id).await {
+            error!("Ack failed for {}: {}", id, e);
+        }
+    }
+}
+
+/// The stored half of `save_trade` for a flushed transaction's trades,
+/// dead-lettering it like `process_or_dead_letter` if that fails
+async fn apply_pending(
+    ctx: &WorkerContext,
+    redis: &mut RedisClient,
+    pending: &PendingTransaction,
+    inserted: &[bool],
+    cards: &mut CardRefreshes,
+) {
+    for (trade, new) in pending.trades.iter().zip(inserted) {
+        if !new {
+            info!("Trade already indexed: {}", trade.signature);
+            continue;
+        }
+        if let Err(e) = apply_trade(
+            ctx,
+            Some(&pending.tx),
+            trade,
+            &pending.sol_price,
+            pending.enrich,
+            cards,
+        )
+        .await
+        {
+            warn!("DB Error for {}: {:#}", trade.signature, e);
+            let entry = DeadLetter::new(
+                &trade.signature,
+                pending.program_id.as_deref(),
+                &e,
+                Some((&pending.tx, pending.commitment)),
+            );
+            dead_letter(redis, &entry).await;
+            return;
+        }
+    }
+}
+
+pub async fn run_worker(shutdown: CancellationToken) -> Result<()> {
+    // Up before any connection, so the readiness probe fails until they are
+    let health = Health::new(&[Component::Postgres, Component::Redis, Component::Rpc]);
+    let background = TaskTracker::new();
+    metrics::spawn_metrics_server(&background, shutdown.clone(), health.clone()).await?;
+
+    let mut ctx = WorkerContext::init().await?;
+    ctx.background = background;
+    health.set_db(ctx.db_pool.clone());
+    health.set_rpc(ctx.http_client.clone(), ctx.rpc.clone());
+    let redis = RedisClient::new(&redis_url()).await?;
+    health.set_redis(redis.clone());
+    let mut retry_redis = redis.clone();
+    report_slot_gap(&ctx, &mut retry_redis).await;
+    ctx.token_cards = Some(redis.clone());
+    ctx.trade_feed = Some(redis.clone());
+    ctx.trade_batch = TradeBatch::from_env();
+    ctx.catch_up = Some(CatchUpSwitch::from_env());
+    spawn_price_updater(&ctx.background, ctx.price_cache.clone(), shutdown.clone());
+    // Finalized transactions can't be rolled back
+    if ctx.fetch_commitment == Commitment::Confirmed {
+        spawn_fork_reconciler(&ctx, shutdown.clone());
+    }
+    if ctx.raw_tx.enabled
+        && let Some(retention) = ctx.raw_tx.retention
+    {
+        spawn_raw_tx_pruner(
+            &ctx.background,
+            ctx.db_pool.clone(),
+            retention,
+            shutdown.clone(),
+        );
+    }
+
+    let consumer = worker_consumer_name();
+    info!(
+        "Worker {} started. Consuming {} in group {}",
+        consumer, REDIS_STREAM, ctx.worker_group
+    );
+
+    let stream = redis
+        .consume_stream(REDIS_STREAM, &ctx.worker_group, &consumer)
+        .await?;
+    tokio::pin!(stream);
+    let mut retry_poll = tokio::time::interval(RETRY_POLL_INTERVAL);
+    let mut claim_poll = tokio::time::interval(CLAIM_POLL_INTERVAL);
+    let mut flush_poll = tokio::time::interval(
+        ctx.trade_batch
+            .as_ref()
+            .map_or(RETRY_POLL_INTERVAL, |batch| batch.max_wait),
+    );
+
+    // Reactive Loop: Code waits here until the stream has an entry, a requeued
+    // signature comes due or another worker's entry goes stale
+    loop {
+        let (id, payload) = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            entry = stream.next() => match entry {
+                Some(Ok(entry)) => entry,
+                Some(Err(e)) => {
+                    error!("Stream read failed: {:#}", e);
+                    tokio::time::sleep(RETRY_POLL_INTERVAL).await;
+                    continue;
+                }
+                None => break,
+            },
+            _ = claim_poll.tick() => {
+                match retry_redis
+                    .claim_stale(
+                        REDIS_STREAM,
+                        &ctx.worker_group,
+                        &consumer,
+                        CLAIM_MIN_IDLE_MS,
+                        RETRY_BATCH_SIZE,
+                    )
+                    .await
+                {
+                    Ok(claimed) => {
+                        for (id, payload) in claimed {
+                            info!("Claimed stale {}: {}", id, payload);
+                            handle_entry(&ctx, &mut retry_redis, &id, &payload).await;
+                        }
+                    }
+                    Err(e) => error!("Stale entry claim failed: {}", e),
+                }
+                continue;
+            }
+            _ = retry_poll.tick() => {
+                let now = Utc::now().timestamp_millis();
+                match retry_redis.take_due(RETRY_QUEUE, now, RETRY_BATCH_SIZE).await {
+                    Ok(due) => {
+                        for payload in due {
+                            info!("Retrying: {}", payload);
+                            ctx.handle_payload(&mut retry_redis, &payload).await;
+                            flush_if_full(&ctx, &mut retry_redis).await;
+                        }
+                    }
+                    Err(e) => error!("Retry queue read failed: {}", e),
+                }
+                continue;
+            }
+            _ = flush_poll.tick() => {
+                flush_trades(&ctx, &mut retry_redis).await;
+                continue;
+            }
+        };
+        info!("Event Received {}: {}", id, payload);
+        if let Some(catch_up) = ctx.catch_up
+            && let Some(lag) = stream_entry_lag(&id, Utc::now().timestamp_millis())
+        {
+            catch_up.observe(lag);
+        }
+        handle_entry(&ctx, &mut retry_redis, &id, &payload).await;
+    }
+
+    // Store what's buffered so its entries are acked; anything newer stays in
+    // the stream (and the retry queue) for the group
+    flush_trades(&ctx, &mut retry_redis).await;
+    ctx.background.close();
+    ctx.background.wait().await;
+    ctx.db_pool.close().await;
+    info!("Worker stopped");
+    Ok(())
+}
+
+/// Stores Helius enhanced-webhook transactions straight into the database,
+/// with the worker's pricing and token/holder bookkeeping but no RPC fetch
+pub struct EnhancedProcessor {
+    ctx: WorkerContext,
+}
+
+impl EnhancedProcessor {
+    /// Connect to the database, write token cards to `redis`, and keep the
+    /// SOL price warm until `shutdown`
+    pub async fn init(redis: RedisClient, shutdown: CancellationToken) -> Result<Self> {
+        // Only used for RPC fetches, which this path never makes
+        let rpc = Arc::new(RpcPool::default());
+        let db_pool = get_db_pool().await?;
+        info!("Database connected");
+
+        let mut ctx =
+            WorkerContext::with_pool(rpc, db_pool, HttpConfig::from_env().build()?).await?;
+        ctx.token_cards = Some(redis.clone());
+        ctx.trade_feed = Some(redis);
+        spawn_price_updater(&ctx.background, ctx.price_cache.clone(), shutdown);
+        Ok(Self { ctx })
+    }
+
+    /// Wait for the background tasks `init` and `process` started, once the
+    /// shutdown token passed to `init` is cancelled
+    pub async fn finish(&self) {
+        self.ctx.background.close();
+        self.ctx.background.wait().await;
+    }
+
+    /// Store one delivered transaction's created tokens and trades
+    pub async fn process(&self, tx: &EnhancedTransaction) -> Result<(), IndexerError> {
+        self.store(tx)
+            .instrument(info_span!("transaction", signature = %tx.signature))
+            .await
+    }
+
+    async fn store(&self, tx: &EnhancedTransaction) -> Result<(), IndexerError> {
+        let ctx = &self.ctx;
+        info!("Processing enhanced transaction: {}", tx.signature);
+
+        let sol_price = get_sol_price(&ctx.price_providers, &ctx.price_cache).await?;
+        let (mut tokens, mut trades) =
+            parse_enhanced_transaction(tx, sol_price.price, ctx.parse_options);
+        tokens.retain(|token| ctx.watchlist.includes_creation(&token.mint_address));
+        trades.retain(|trade| ctx.watchlist.includes(&trade.token_mint));
+        if tokens.is_empty() && trades.is_empty() && !ctx.watchlist.mints.is_empty() {
+            debug!("No watched mint in {}", tx.signature);
+            return Ok(());
+        }
+
+        if let Some(block_time) = tx.timestamp.and_then(|ts| DateTime::from_timestamp(ts, 0)) {
+            let audit = Transaction {
+                signature: tx.signature.clone(),
+                slot: tx.slot as i64,
+                block_time,
+                signer: tx.fee_payer.clone(),
+                success: tx.transaction_error.is_none(),
+                instruction_count: i32::try_from(tx.instructions.len()).ok(),
+                // Helius delivers webhooks at confirmed
+                commitment: Commitment::Confirmed,
+                created_at: None,
+            };
+            insert_transaction(&ctx.db_pool, &audit).await?;
+        }
 
-#[derive(Clone)]
-struct PriceCache {
-    price: f64,
-    updated_at: Instant,
+        // Pushed as they land, so never behind enough to skip enrichment
+        for token in tokens {
+            save_created_token(ctx, token, sol_price.price, true).await?;
+        }
+        let mut cards = CardRefreshes::default();
+        for mut trade in trades {
+            save_trade(ctx, None, &mut trade, &sol_price, true, &mut cards).await?;
+        }
+        cards.write(ctx).await;
+        Ok(())
+    }
 }
 
-pub async fn run_worker() -> Result<()> {
-    let api_key = std::env::var("HELIUS_API_KEY").expect("HELIUS_API_KEY missing");
-    let redis_url =
-        std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+/// Reprocess stream entries `from_id..=to_id` in order, e.g. after a parser fix.
+/// Reads with `XRANGE`, so it neither needs nor disturbs any live consumer.
+/// Returns the number of entries replayed.
+pub async fn replay_stream(from_id: &str, to_id: &str) -> Result<usize> {
+    let mut ctx = WorkerContext::init().await?;
+    let mut redis = RedisClient::new(&redis_url()).await?;
+    ctx.token_cards = Some(redis.clone());
+    ctx.trade_feed = Some(redis.clone());
 
-    // Initialize database pool
-    let db_pool = get_db_pool().await?;
-    println!("✅ Database connected");
+    info!("Replaying {} from {} to {}", REDIS_STREAM, from_id, to_id);
 
-    let redis = RedisClient::new(&redis_url).await?;
-    let http_client = reqwest::Client::new();
+    let mut start = from_id.to_string();
+    let mut replayed = 0;
 
-    // Initialize price cache with shared state
-    let price_cache = Arc::new(RwLock::new(None::<PriceCache>));
+    loop {
+        let entries = redis
+            .read_range(REDIS_STREAM, &start, to_id, REPLAY_PAGE_SIZE)
+            .await?;
+        let Some((last_id, _)) = entries.last() else {
+            break;
+        };
+        // Next page starts just after the last entry we saw
+        start = format!("({}", last_id);
 
-    println!("🎧 Worker started. Listening on: {}", REDIS_CHANNEL);
+        for (id, payload) in &entries {
+            let Some(payload) = payload else {
+                warn!("Skipping {}: no payload", id);
+                continue;
+            };
+            info!("Replaying {}: {}", id, payload);
+            ctx.handle_payload(&mut redis, payload).await;
+            replayed += 1;
+        }
 
-    // Subscribe to the channel
-    let mut stream = redis.subscribe(REDIS_CHANNEL).await?;
+        // A short page is the end of the range; counted before skipping
+        // malformed entries, so those don't end it early
+        if entries.len() < REPLAY_PAGE_SIZE {
+            break;
+        }
+    }
 
-    // Reactive Loop: Code waits here until Redis sends a message
-    while let Some(payload) = stream.next().await {
-        println!("⚡ Event Received: {}", payload);
+    info!("Replayed {} entries", replayed);
+    Ok(replayed)
+}
 
-        // Parse the mini-info (Signature) from Redis
-        if let Ok(info) = serde_json::from_str::<Value>(&payload) {
-            if let Some(signature) = info.get("signature").and_then(|s| s.as_str()) {
-                println!("🔍 Fetching details for: {}", signature);
-
-                // Fetch full data from RPC
-                match fetch_full_transaction(&http_client, &api_key, signature).await {
-                    Ok(tx) => {
-                        // Parse and save to Database
-                        if let Err(e) =
-                            process_and_save(&db_pool, &http_client, &price_cache, tx).await
-                        {
-                            eprintln!("DB Error: {}", e);
-                        }
-                    }
-                    Err(e) => eprintln!(" Fetch Error for {}: {}", signature, e),
+/// Index pump.fun transactions the live path missed, e.g. while the worker
+/// was down: pages back through `getSignaturesForAddress` from `before_sig`
+/// (or the newest) until `until_sig` (exclusive) or `limit` signatures, then
+/// fetches and stores them oldest first so holder balances accumulate in
+/// order. Stored rows are deduplicated on insert, so re-running is safe.
+///
+/// Trades are priced at the current SOL/USD rate, not the historical one.
+/// Returns the number of transactions processed.
+pub async fn backfill_range(
+    pool: &PgPool,
+    client: &reqwest::Client,
+    rpc: Arc<RpcPool>,
+    before_sig: Option<&str>,
+    until_sig: Option<&str>,
+    limit: Option<usize>,
+) -> Result<usize> {
+    let mut ctx = WorkerContext::with_pool(rpc, pool.clone(), client.clone()).await?;
+    ctx.load_global_config().await;
+
+    // 1. Collect the signatures, newest first as the RPC returns them
+    let mut signatures = Vec::new();
+    let mut before = before_sig.map(str::to_string);
+    loop {
+        let page_size = limit.map_or(SIGNATURES_PAGE_SIZE, |limit| {
+            (limit - signatures.len()).min(SIGNATURES_PAGE_SIZE)
+        });
+        if page_size == 0 {
+            break;
+        }
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getSignaturesForAddress",
+            "params": [
+                PUMP_FUN_PROGRAM,
+                { "limit": page_size, "before": before, "until": until_sig }
+            ]
+        });
+        let body: Value = client
+            .post(ctx.rpc.url()?)
+            .json(&request)
+            .send()
+            .await?
+            .json()
+            .await?;
+        let page = body
+            .get("result")
+            .and_then(|r| r.as_array())
+            .ok_or_else(|| anyhow::anyhow!("getSignaturesForAddress failed: {}", body))?;
+
+        for entry in page {
+            let Some(signature) = entry.get("signature").and_then(|s| s.as_str()) else {
+                continue;
+            };
+            before = Some(signature.to_string());
+            // Failed transactions change nothing
+            if entry.get("err").is_some_and(|err| !err.is_null()) {
+                continue;
+            }
+            signatures.push(signature.to_string());
+        }
+
+        info!("Collected {} signatures", signatures.len());
+        if page.len() < page_size {
+            break;
+        }
+    }
+
+    // 2. Process oldest first, at finalized so the audit rows are authoritative
+    let total = signatures.len();
+    // Block times written, for refreshing the candles they fall in
+    let mut written: Option<(i64, i64)> = None;
+    for (done, signature) in signatures.iter().rev().enumerate() {
+        let fetched = loop {
+            match ctx
+                .fetch_transaction(signature, Commitment::Finalized)
+                .await
+            {
+                // Wait out the outage rather than skip the rest of the range
+                Err(FetchError::CircuitOpen { retry_in }) => tokio::time::sleep(retry_in).await,
+                fetched => break fetched,
+            }
+        };
+        match fetched {
+            Ok(tx) => {
+                if let Err(e) = process_and_save(&ctx, &tx, None, Commitment::Finalized).await {
+                    warn!("DB Error for {}: {}", signature, e);
+                } else if let Some(at) = tx.block_time {
+                    written = Some(written.map_or((at, at), |(lo, hi)| (lo.min(at), hi.max(at))));
                 }
             }
+            Err(e) => {
+                metrics::RPC_ERRORS.inc();
+                warn!("Fetch error for {}: {}", signature, e);
+            }
+        }
+
+        if (done + 1) % BACKFILL_PROGRESS_EVERY == 0 {
+            info!("Backfilled {}/{} (at {})", done + 1, total, signature);
         }
     }
 
-    Ok(())
+    // Older than the refresh policy reaches, these minutes would otherwise
+    // keep their pre-backfill candles
+    if let Some((from, to)) = written
+        && let (Some(from), Some(to)) = (
+            DateTime::from_timestamp(from, 0),
+            DateTime::from_timestamp(to, 0),
+        )
+        && let Err(e) = refresh_candles(pool, from, to).await
+    {
+        warn!("Candle refresh after backfill failed: {:#}", e);
+    }
+
+    info!("Backfilled {} transactions", total);
+    Ok(total)
+}
+
+/// Why `fetch_full_transaction` gave up
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    /// The RPC kept returning `null`: the transaction isn't available yet
+    /// and may be worth trying again later
+    #[error("transaction not indexed yet")]
+    NotYetIndexed,
+    /// The RPC circuit is open; nothing was sent. It may be tried again
+    /// after `retry_in`.
+    #[error("RPC circuit open, retry in {retry_in:?}")]
+    CircuitOpen { retry_in: Duration },
+    /// Anything else: transport errors, bad responses, undecodable data
+    #[error(transparent)]
+    Failed(#[from] IndexerError),
 }
 
 async fn fetch_full_transaction(
     client: &reqwest::Client,
-    api_key: &str,
+    rpc: &RpcPool,
+    latency: &LatencyTracker,
+    retry: &RetryPolicy,
     signature: &str,
-) -> Result<TransactionResult> {
-    // Basic Helius RPC call with retries
-    let rpc_url = format!("https://mainnet.helius-rpc.com/?api-key={}", api_key);
+    commitment: Commitment,
+) -> Result<TransactionResult, FetchError> {
     let request = json!({
         "jsonrpc": "2.0",
         "id": 1,
         "method": "getTransaction",
-        "params": [ signature, { "encoding": "jsonParsed", "maxSupportedTransactionVersion": 0 } ]
+        "params": [ signature, {
+            "encoding": "jsonParsed",
+            "maxSupportedTransactionVersion": 0,
+            "commitment": commitment.as_str()
+        } ]
     });
 
-    // Simple retry logic (3 attempts)
-    for attempt in 1..=3 {
-        let resp = client.post(&rpc_url).json(&request).send().await?;
-        if resp.status() == 429 {
-            tokio::time::sleep(Duration::from_secs(attempt)).await;
-            continue;
+    // Timed from observed RPC latency; retried with backoff on rate limits,
+    // 5xx, transport errors and not-yet-indexed (null) results. Each attempt
+    // goes to the pool's current endpoint, so one that keeps failing is
+    // benched and the retries move on to the next. While the pool's circuit
+    // is open nothing is sent at all.
+    let mut last_error = FetchError::NotYetIndexed;
+    for attempt in 0..retry.max_attempts {
+        if attempt > 0 {
+            tokio::time::sleep(retry.backoff(attempt - 1)).await;
+        }
+        if let Err(retry_in) = rpc.admit() {
+            return Err(FetchError::CircuitOpen { retry_in });
         }
 
-        let body: Value = resp.json().await?;
-        if let Some(result) = body.get("result") {
-            if !result.is_null() {
-                let tx: TransactionResult = serde_json::from_value(result.clone())?;
-                return Ok(tx);
+        let (endpoint, rpc_url) = rpc.pick()?;
+        let started = Instant::now();
+        let resp = match tokio::time::timeout(
+            latency.timeout(),
+            client.post(rpc_url).json(&request).send(),
+        )
+        .await
+        {
+            Ok(Ok(resp)) => resp,
+            Ok(Err(e)) => {
+                rpc.record_failure(endpoint);
+                last_error = IndexerError::from(e).into();
+                continue;
+            }
+            Err(_) => {
+                rpc.record_failure(endpoint);
+                last_error = IndexerError::HeliusError("RPC request timed out".to_string()).into();
+                continue;
             }
+        };
+
+        let status = resp.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            rpc.record_failure(endpoint);
+            last_error = IndexerError::HeliusError(format!("RPC returned {}", status)).into();
+            continue;
+        }
+        if !status.is_success() {
+            return Err(IndexerError::HeliusError(format!("RPC returned {}", status)).into());
         }
 
-        // If result is null (not indexed yet), wait a bit and retry
-        tokio::time::sleep(Duration::from_millis(500 * attempt)).await;
-    }
+        let body: Value = match resp.json().await {
+            Ok(body) => body,
+            Err(e) => {
+                rpc.record_failure(endpoint);
+                last_error = IndexerError::from(e).into();
+                continue;
+            }
+        };
+        rpc.record_success(endpoint);
+        latency.record(started.elapsed());
+        metrics::FETCH_LATENCY.observe(started.elapsed());
 
-    Err(anyhow::anyhow!("Transaction not found after retries"))
-}
+        if let Some(error) = body.get("error") {
+            return Err(IndexerError::HeliusError(format!("RPC error: {}", error)).into());
+        }
 
-/// Fetch current SOL/USD price from Pyth Network with caching
-async fn get_sol_price(
-    client: &reqwest::Client,
-    cache: &Arc<RwLock<Option<PriceCache>>>,
-) -> Result<f64> {
-    // Check cache first
-    {
-        let cached = cache.read().await;
-        if let Some(price_data) = cached.as_ref() {
-            let age = price_data.updated_at.elapsed();
-            if age.as_secs() < PRICE_CACHE_TTL_SECS {
-                return Ok(price_data.price);
+        match body.get("result") {
+            Some(result) if !result.is_null() => {
+                let tx: TransactionResult =
+                    serde_json::from_value(result.clone()).map_err(IndexerError::from)?;
+                return Ok(tx);
             }
+            // Not indexed yet
+            _ => last_error = FetchError::NotYetIndexed,
         }
     }
 
-    // Fetch new price from Pyth Hermes API
-    let url = format!(
-        "https://hermes.pyth.network/v2/updates/price/latest?ids[]={}",
-        SOL_USD_FEED_ID
-    );
-
-    let response = client.get(&url).send().await?;
+    Err(last_error)
+}
 
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("Pyth API error: {}", response.status()));
+/// Current SOL/USD price with caching. The cache is normally kept warm by
+/// `spawn_price_updater`; on a miss (cold start, stream down) providers are
+/// tried in priority order; when all of them fail, the last cached price is used, however
+/// stale, and only a worker that never got a price gives up.
+pub async fn get_sol_price(
+    providers: &PriceProviders,
+    cache: &SharedPriceCache,
+) -> Result<PriceCache, IndexerError> {
+    // Check cache first
+    if let Some(price_data) = cache.fresh().await {
+        return Ok(price_data);
     }
 
-    let data: Value = response.json().await?;
-
-    // Parse Pyth response
-    if let Some(parsed) = data.get("parsed").and_then(|p| p.as_array()) {
-        if let Some(first_price) = parsed.first() {
-            if let Some(price_data) = first_price.get("price") {
-                let price_str = price_data.get("price").and_then(|p| p.as_str());
-                let expo = price_data.get("expo").and_then(|e| e.as_i64());
-
-                if let (Some(price), Some(exp)) = (price_str, expo) {
-                    if let Ok(price_int) = price.parse::<i64>() {
-                        // Calculate actual price: price * 10^expo
-                        let actual_price = (price_int as f64) * 10f64.powi(exp as i32);
-
-                        // Update cache
-                        {
-                            let mut cached = cache.write().await;
-                            *cached = Some(PriceCache {
-                                price: actual_price,
-                                updated_at: Instant::now(),
-                            });
-                        }
+    // Single-flight: one caller refreshes, the rest wait here and then find
+    // the price it cached
+    let _refresh = cache.refresh.lock().await;
+    if let Some(price_data) = cache.fresh().await {
+        return Ok(price_data);
+    }
 
-                        println!("💵 SOL Price: ${:.2}", actual_price);
-                        return Ok(actual_price);
-                    }
-                }
-            }
+    match providers.get_sol_usd().await {
+        Ok((price, source)) => {
+            let fresh = cache.store(price).await;
+            info!("SOL Price: ${:.2} ({})", price, source);
+            Ok(fresh)
         }
+        Err(e) => match cache.last().await {
+            Some(stale) => {
+                warn!(
+                    "All price sources failed ({}), using SOL price ${:.2} from {}s ago",
+                    e,
+                    stale.price,
+                    stale.updated_at.elapsed().as_secs()
+                );
+                Ok(stale)
+            }
+            None => Err(IndexerError::PriceFeedError(format!("{:#}", e))),
+        },
     }
-
-    Err(anyhow::anyhow!("Failed to parse Pyth price data"))
 }
 
 async fn process_and_save(
-    pool: &PgPool,
-    http_client: &reqwest::Client,
-    price_cache: &Arc<RwLock<Option<PriceCache>>>,
-    tx: TransactionResult,
-) -> Result<()> {
+    ctx: &WorkerContext,
+    tx: &TransactionResult,
+    program_id: Option<&str>,
+    commitment: Commitment,
+) -> Result<(), IndexerError> {
+    let pool = &ctx.db_pool;
+
     let sig = tx
         .transaction
         .signatures
@@ -186,246 +2423,786 @@ async fn process_and_save(
         .cloned()
         .unwrap_or_default();
 
-    println!("📊 Processing transaction: {}", sig);
+    info!("Processing transaction: {}", sig);
+
+    let enrich = ctx.catch_up.is_none() || processing_mode() == ProcessingMode::RealTime;
+
+    // Bundled launches create several tokens
+    let created: Vec<Token> = parse_token_creation(tx, &ctx.parse_options.curve)?
+        .into_iter()
+        .filter(|token| ctx.watchlist.includes_creation(&token.mint_address))
+        .collect();
+    if created.is_empty() && !ctx.watchlist.touches(tx) {
+        debug!("No watched mint in {}", sig);
+        return Ok(());
+    }
+
+    // Audit record for every processed transaction, trade or not. Without a
+    // block time the row's hypertable position would be a guess, so skip it.
+    if let Some(block_time) = tx.block_time.and_then(|ts| DateTime::from_timestamp(ts, 0)) {
+        let message = &tx.transaction.message;
+        let audit = Transaction {
+            signature: sig.clone(),
+            slot: tx.slot as i64,
+            block_time,
+            signer: message
+                .account_keys
+                .iter()
+                .find(|k| k.signer)
+                .map(|k| k.pubkey.clone())
+                .unwrap_or_default(),
+            success: tx.meta.as_ref().is_none_or(|meta| meta.err.is_none()),
+            instruction_count: i32::try_from(message.instructions.len()).ok(),
+            commitment,
+            created_at: None,
+        };
+        insert_transaction(pool, &audit).await?;
+    }
 
     // Get real-time SOL price with caching
-    let current_sol_price = get_sol_price(http_client, price_cache).await?;
+    let sol_price = get_sol_price(&ctx.price_providers, &ctx.price_cache).await?;
+    let current_sol_price = sol_price.price;
 
-    // 1. Check if this is a token creation event
-    if let Ok(Some(mut token)) = parse_token_creation(&tx) {
-        println!("🪙 New token created: {}", token.mint_address);
+    // 1. Token creation events
+    for token in created {
+        save_created_token(ctx, token, current_sol_price, enrich).await?;
+    }
+
+    // Graduation: liquidity moved from the curve to an AMM pool
+    if let Some(migration) = parse_migration(tx).filter(|m| ctx.watchlist.includes(&m.mint_address))
+    {
+        insert_migration(pool, &migration).await?;
+        let mint = &migration.mint_address;
 
-        // Calculate initial market cap
-        if !token.virtual_token_reserves.is_zero() {
-            let token_price_sol = token.virtual_sol_reserves / token.virtual_token_reserves;
-            let sol_price_dec = Decimal::from_f64(current_sol_price).unwrap_or(Decimal::ZERO);
-            token.market_cap_usd = token_price_sol * token.token_total_supply * sol_price_dec;
+        if let Some(amm_pool) = &migration.pool_address {
+            if set_amm_pool_address(pool, mint, amm_pool).await? {
+                info!("{} migrated to AMM pool {}", mint, amm_pool);
+            } else {
+                warn!("Migration for unknown token {} (pool {})", mint, amm_pool);
+            }
+        } else {
+            info!("{} withdrawn for Raydium migration", mint);
         }
 
-        upsert_token(pool, &token).await?;
-        println!(
-            "✅ Token saved to DB (Market Cap: ${:.2})",
-            token.market_cap_usd
-        );
+        // Normally recorded by the completing trade; this covers a missed one.
+        // Also marks the token complete.
+        if record_graduation(pool, mint, &sig, migration.slot, migration.migrated_at).await? {
+            info!(
+                "{} graduated at slot {} (via migration)",
+                mint, migration.slot
+            );
+        }
     }
 
     // 2. Parse the transaction for trades
-    match parse_pump_fun_transaction(&tx, current_sol_price) {
-        Ok(Some(trade)) => {
-            println!(
-                "💰 Trade detected: {} {} tokens for {} SOL (${:.2})",
-                if trade.is_buy { "BUY" } else { "SELL" },
-                trade.token_amount,
-                trade.sol_amount,
-                trade.price_usd.unwrap_or(Decimal::ZERO)
+    match parse_transaction(tx, program_id, current_sol_price, ctx.parse_options) {
+        Ok(ParseOutcome::Trades(mut trades)) => {
+            trades.retain(|trade| ctx.watchlist.includes(&trade.token_mint));
+            if let Some(batch) = &ctx.trade_batch {
+                for trade in &mut trades {
+                    prepare_trade(trade, &sol_price);
+                }
+                batch
+                    .push(PendingTransaction {
+                        tx: tx.clone(),
+                        program_id: program_id.map(str::to_string),
+                        commitment,
+                        trades,
+                        sol_price,
+                        enrich,
+                        span: tracing::Span::current(),
+                    })
+                    .await;
+                return Ok(());
+            }
+            // Several trades per transaction (bundles, routers) are applied in
+            // order, and each token's card is rewritten once after them
+            let mut cards = CardRefreshes::default();
+            for mut trade in trades {
+                save_trade(ctx, Some(tx), &mut trade, &sol_price, enrich, &mut cards).await?;
+            }
+            cards.write(ctx).await;
+        }
+        Ok(ParseOutcome::Creation(tokens)) => {
+            debug!(
+                "Creation-only transaction for {} token(s) in {}",
+                tokens.len(),
+                sig
             );
+        }
+        Ok(ParseOutcome::NonTrade(ix)) => {
+            debug!(
+                "Skipping non-trade pump.fun instruction {} in {}",
+                ix.name(),
+                sig
+            );
+        }
+        Ok(ParseOutcome::NotPumpFun) => {
+            debug!("No pump.fun instruction in {}", sig);
+        }
+        Ok(ParseOutcome::UnsupportedProgram(program_id)) => {
+            debug!("No parser for program {} yet, skipping {}", program_id, sig);
+        }
+        Ok(ParseOutcome::MissingMeta) => {
+            warn!("Transaction {} has no meta, cannot diff balances", sig);
+        }
+        Ok(ParseOutcome::NoBalanceChange) => {
+            info!("No trade data found in transaction");
+            if enrich {
+                ctx.skip_archive
+                    .archive(pool, tx, &sig, "no_balance_change")
+                    .await;
+            }
+        }
+        Ok(ParseOutcome::UnknownInstruction) => {
+            info!("Unknown pump.fun instruction in {}", sig);
+            if enrich {
+                ctx.skip_archive
+                    .archive(pool, tx, &sig, "unknown_instruction")
+                    .await;
+            }
+        }
+        Err(e) => {
+            metrics::PARSE_ERRORS.inc();
+            return Err(e);
+        }
+    }
 
-            // Insert the trade
-            insert_trade(pool, &trade).await?;
-            println!("✅ Trade saved to DB: {}", sig);
-
-            // 3. Update token with latest reserves and market cap (or create if not exists)
-            let mut token = match get_token(pool, &trade.token_mint).await {
-                Ok(Some(t)) => t,
-                _ => {
-                    // Token doesn't exist, try to extract metadata from this transaction
-                    let (name, symbol, uri) = extract_token_metadata_from_tx(&tx);
-                    let bonding_curve = find_bonding_curve_from_tx(&tx, &trade.token_mint);
-
-                    Token {
-                        mint_address: trade.token_mint.clone(),
-                        name,
-                        symbol,
-                        uri,
-                        bonding_curve_address: bonding_curve,
-                        creator_wallet: Some(trade.user_wallet.clone()),
-                        virtual_token_reserves: trade.virtual_token_reserves,
-                        virtual_sol_reserves: trade.virtual_sol_reserves,
-                        real_token_reserves: Decimal::ZERO,
-                        token_total_supply: Decimal::from_u64(1_000_000_000_000_000)
-                            .unwrap_or(Decimal::ZERO), // 1B tokens standard
-                        market_cap_usd: Decimal::ZERO,
-                        bonding_curve_progress: Decimal::ZERO,
-                        complete: false,
-                        created_at: trade.timestamp,
-                        updated_at: None,
-                    }
-                }
-            };
+    Ok(())
+}
 
-            // If token metadata is missing, try to extract from current transaction
-            if token.name.is_none() || token.symbol.is_none() || token.uri.is_none() {
-                let (name, symbol, uri) = extract_token_metadata_from_tx(&tx);
-                if token.name.is_none() && name.is_some() {
-                    token.name = name;
-                }
-                if token.symbol.is_none() && symbol.is_some() {
-                    token.symbol = symbol;
-                }
-                if token.uri.is_none() && uri.is_some() {
-                    token.uri = uri;
-                }
-            }
+/// Store a newly created token with its initial market cap
+async fn save_created_token(
+    ctx: &WorkerContext,
+    mut token: Token,
+    current_sol_price: f64,
+    enrich: bool,
+) -> Result<(), IndexerError> {
+    let pool = &ctx.db_pool;
+    info!("New token created: {}", token.mint_address);
 
-            // If bonding curve is missing, try to find it
-            if token.bonding_curve_address.is_none() {
-                token.bonding_curve_address = find_bonding_curve_from_tx(&tx, &trade.token_mint);
-            }
+    // Calculate initial market cap
+    match market_cap_from_reserves(
+        token.virtual_sol_reserves,
+        token.virtual_token_reserves,
+        token.token_total_supply,
+        current_sol_price,
+    ) {
+        Ok(market_cap) => {
+            token.market_cap_usd = market_cap;
+            token.market_cap_usd_raw = market_cap;
+        }
+        Err(e) => {
+            metrics::PRICING_FAILURES.inc();
+            warn!("No initial market cap for {}: {}", token.mint_address, e);
+        }
+    }
+
+    upsert_token(pool, &token).await?;
+    metrics::TOKENS_CREATED.inc();
+    info!(
+        "Token saved to DB (Market Cap: ${:.2})",
+        token.market_cap_usd
+    );
+    // Buys from the creation slot, or sells by the creator, may have been
+    // indexed first
+    log_snipers(pool, &token.mint_address).await;
+    log_dev_sold(pool, &token.mint_address).await;
+
+    if enrich {
+        spawn_metadata_fetch(ctx, &token.mint_address);
+    }
+    Ok(())
+}
+
+/// Recount a token's snipers. Best effort: a failure only leaves the count
+/// stale until the next buy in its creation slot.
+async fn log_snipers(pool: &PgPool, mint_address: &str) {
+    match refresh_snipers(pool, mint_address).await {
+        Ok(Some(count)) if count > 0 => {
+            info!(
+                "{} sniped by {} wallet(s) in its creation slot",
+                mint_address, count
+            );
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Sniper count failed for {}: {}", mint_address, e),
+    }
+}
+
+/// Recheck whether a token's creator has sold. Best effort, like
+/// `log_snipers`: a failure leaves the flag stale until their next sell.
+async fn log_dev_sold(pool: &PgPool, mint_address: &str) {
+    match update_dev_sold(pool, mint_address).await {
+        Ok(true) => info!("Creator of {} has sold", mint_address),
+        Ok(false) => {}
+        Err(e) => warn!("Dev sold check failed for {}: {}", mint_address, e),
+    }
+}
+
+/// Store one parsed trade and apply it to its token and the trader's holding.
+/// `enrich = false` (catch-up mode) skips sandwich detection and metadata
+/// resolution. `tx` is the raw transaction, when there is one, for what the
+/// trade itself doesn't carry (metadata, creator fee terms, post-balances).
+async fn save_trade(
+    ctx: &WorkerContext,
+    tx: Option<&TransactionResult>,
+    trade: &mut Trade,
+    sol_price: &PriceCache,
+    enrich: bool,
+    cards: &mut CardRefreshes,
+) -> Result<(), IndexerError> {
+    prepare_trade(trade, sol_price);
+
+    // Insert the trade
+    // Replays and backfills see stored trades again; applying them
+    // to the token and holders twice would double-count
+    if !insert_trade(&ctx.db_pool, trade).await? {
+        info!("Trade already indexed: {}", trade.signature);
+        return Ok(());
+    }
+    apply_trade(ctx, tx, trade, sol_price, enrich, cards).await
+}
+
+/// Stamp a parsed trade with the SOL price it was valued at
+fn prepare_trade(trade: &mut Trade, sol_price: &PriceCache) {
+    stamp_sol_price(trade, sol_price.price, sol_price.fetched_at);
+
+    let price_usd = trade
+        .price_usd
+        .map_or_else(|| "unpriced".to_string(), |price| format!("${:.2}", price));
+    info!(
+        "Trade detected: {} {} tokens for {} SOL ({})",
+        trade.ix_name.to_uppercase(),
+        trade.token_amount,
+        trade.sol_amount,
+        price_usd
+    );
+}
+
+/// Copy the creator fee rate `trade`'s TradeEvent reports onto its token.
+/// Events from before creator fees existed leave the stored rate alone.
+pub fn record_creator_fee_terms(token: &mut Token, meta: &TransactionMeta, trade: &Trade) {
+    if let Some(fees) =
+        find_trade_event(meta, &trade.token_mint, &trade.user_wallet).and_then(|event| event.fees)
+    {
+        token.creator_fee_bps = i32::try_from(fees.creator_fee_basis_points).ok();
+    }
+}
+
+/// Keep the exact SOL/USD rate behind a priced trade's `price_usd`, and when
+/// it was fetched, so its USD figures can be audited or recomputed. Unpriced
+/// trades (transfers) are left alone.
+pub fn stamp_sol_price(trade: &mut Trade, sol_price_usd: f64, fetched_at: DateTime<Utc>) {
+    if trade.price_usd.is_none() {
+        return;
+    }
+    trade.sol_price_usd = Decimal::from_f64(sol_price_usd);
+    trade.sol_price_at = Some(fetched_at);
+    trade.usd_amount = trade
+        .sol_price_usd
+        .and_then(|rate| (trade.sol_amount / Decimal::from(LAMPORTS_PER_SOL)).checked_mul(rate));
+}
 
-            // Update reserves from trade
-            token.virtual_sol_reserves = trade.virtual_sol_reserves;
-            token.virtual_token_reserves = trade.virtual_token_reserves;
+/// Apply a just-inserted trade to its token, holder and token card
+async fn apply_trade(
+    ctx: &WorkerContext,
+    tx: Option<&TransactionResult>,
+    trade: &Trade,
+    sol_price: &PriceCache,
+    enrich: bool,
+    cards: &mut CardRefreshes,
+) -> Result<(), IndexerError> {
+    let pool = &ctx.db_pool;
+    let progress_mode = ctx.progress_mode;
+    let current_sol_price = sol_price.price;
 
-            // Calculate market cap: (price_per_token) * total_supply
-            if !trade.virtual_token_reserves.is_zero() {
-                let token_price_sol = trade.virtual_sol_reserves / trade.virtual_token_reserves;
-                let sol_price_dec = Decimal::from_f64(current_sol_price).unwrap_or(Decimal::ZERO);
-                token.market_cap_usd = token_price_sol * token.token_total_supply * sol_price_dec;
+    metrics::TRADES_PROCESSED.inc();
+    info!("Trade saved to DB: {}", trade.signature);
 
-                // Calculate bonding curve progress (typically completes at 85 SOL)
-                let bonding_complete_sol =
-                    Decimal::from_u64(85_000_000_000).unwrap_or(Decimal::ZERO); // 85 SOL in lamports
-                token.bonding_curve_progress =
-                    (trade.virtual_sol_reserves / bonding_complete_sol) * Decimal::from(100);
-                if token.bonding_curve_progress >= Decimal::from(100) {
-                    token.complete = true;
+    // Best effort: the trade is stored either way
+    if let Some(redis) = &ctx.trade_feed
+        && let Err(e) = publish_trade(&mut redis.clone(), trade).await
+    {
+        warn!("Failed to publish trade {}: {}", trade.signature, e);
+    }
+
+    // A new trade may complete a sandwich with others already in its slot
+    if enrich && trade.track_volume {
+        match detect_sandwiches_in_slot(pool, &trade.token_mint, trade.slot).await {
+            Ok(sandwiches) => {
+                // Later trades in the slot re-detect it, so log only ones involving this trade
+                for s in sandwiches.into_iter().filter(|s| {
+                    [
+                        &s.victim.signature,
+                        &s.front_run_signature,
+                        &s.back_run_signature,
+                    ]
+                    .contains(&&trade.signature)
+                }) {
+                    info!(
+                        "Sandwich on {} by {} (profit: {} lamports)",
+                        s.victim.signature, s.attacker_wallet, s.profit
+                    );
                 }
             }
+            Err(e) => warn!("Sandwich detection failed: {}", e),
+        }
+    }
 
-            upsert_token(pool, &token).await?;
-            println!(
-                "✅ Token saved/updated (Market Cap: ${:.2}, Progress: {:.1}%)",
-                token.market_cap_usd, token.bonding_curve_progress
-            );
+    // 3. Update token with latest reserves and market cap (or create if not exists)
+    let mut token = match get_token(pool, &trade.token_mint).await {
+        Ok(Some(t)) => t,
+        _ => {
+            // Token doesn't exist; metadata and curve are filled in below
+            Token {
+                mint_address: trade.token_mint.clone(),
+                name: None,
+                symbol: None,
+                uri: None,
+                bonding_curve_address: None,
+                creator_wallet: Some(trade.user_wallet.clone()),
+                virtual_token_reserves: trade.virtual_token_reserves,
+                virtual_sol_reserves: trade.virtual_sol_reserves,
+                real_token_reserves: Decimal::ZERO,
+                token_total_supply: Decimal::from(ctx.parse_options.curve.token_total_supply),
+                market_cap_usd: Decimal::ZERO,
+                market_cap_usd_raw: Decimal::ZERO,
+                bonding_curve_progress: Decimal::ZERO,
+                bonding_curve_progress_raw: Decimal::ZERO,
+                complete: false,
+                amm_pool_address: None,
+                creator_fee_bps: None,
+                last_trade_slot: None,
+                first_trade_at: None,
+                last_trade_at: None,
+                creation_slot: None,
+                sniper_count: 0,
+                bundled: false,
+                dev_sold: false,
+                graduated_at: None,
+                graduation_slot: None,
+                graduation_signature: None,
+                graduation_market_cap_usd: None,
+                image: None,
+                description: None,
+                twitter: None,
+                telegram: None,
+                website: None,
+                metadata_fetched_at: None,
+                created_at: trade.timestamp,
+                updated_at: None,
+            }
+        }
+    };
+    // An older trade than the token has seen still updates holders, but its
+    // curve state is stale (the upsert keeps the newer one)
+    let is_latest = token.last_trade_slot.is_none_or(|slot| trade.slot >= slot);
 
-            // 4. Update token holder balance
-            // Get current balance if exists
-            let current_balance = if let Ok(Some(holder)) =
-                get_token_holder(pool, &trade.token_mint, &trade.user_wallet).await
-            {
-                holder.balance
-            } else {
-                Decimal::ZERO
-            };
+    // If token metadata is missing, try to extract from current transaction
+    if enrich
+        && let Some(tx) = tx
+        && (token.name.is_none() || token.symbol.is_none() || token.uri.is_none())
+    {
+        let (name, symbol, uri) = extract_token_metadata_from_tx(tx, &trade.token_mint);
+        if token.name.is_none() && name.is_some() {
+            token.name = name;
+        }
+        if token.symbol.is_none() && symbol.is_some() {
+            token.symbol = symbol;
+        }
+        if token.uri.is_none() && uri.is_some() {
+            token.uri = uri;
+        }
+    }
 
-            // Calculate new balance
-            let new_balance = if trade.is_buy {
-                current_balance + trade.token_amount
-            } else {
-                // For sells, ensure we don't go negative
-                if current_balance >= trade.token_amount {
-                    current_balance - trade.token_amount
-                } else {
-                    Decimal::ZERO
-                }
-            };
+    // If bonding curve is missing, derive it (guessing only for a bad mint)
+    if token.bonding_curve_address.is_none() {
+        token.bonding_curve_address = derive_bonding_curve(&trade.token_mint)
+            .or_else(|| find_bonding_curve_from_tx(tx?, &trade.token_mint));
+    }
+
+    // Creator fee terms are only reported per trade, not at creation
+    if let Some(meta) = tx.and_then(|tx| tx.meta.as_ref()) {
+        record_creator_fee_terms(&mut token, meta, trade);
+    }
+
+    // Update reserves from trade (transfers don't touch the curve)
+    if trade.track_volume {
+        token.virtual_sol_reserves = trade.virtual_sol_reserves;
+        token.virtual_token_reserves = trade.virtual_token_reserves;
+    }
 
-            // Update holder only if balance > 0 or it's a buy
-            if new_balance > Decimal::ZERO || trade.is_buy {
-                let holder = TokenHolder {
-                    token_mint: trade.token_mint.clone(),
-                    user_wallet: trade.user_wallet.clone(),
-                    balance: new_balance,
-                    last_updated_slot: trade.slot,
-                    updated_at: None,
-                };
-
-                upsert_token_holder(pool, &holder).await?;
-                println!(
-                    "✅ Token holder updated: {} (balance: {})",
-                    trade.user_wallet, new_balance
+    // Market cap and curve progress from the post-trade reserves. Progress
+    // doesn't depend on the SOL price, so an unpriced trade still moves it.
+    // A figure that can't be computed keeps its stored value
+    if trade.track_volume {
+        match market_cap_from_reserves(
+            trade.virtual_sol_reserves,
+            trade.virtual_token_reserves,
+            token.token_total_supply,
+            current_sol_price,
+        ) {
+            Ok(raw_market_cap) => {
+                if ctx
+                    .market_cap_clamp
+                    .apply(&mut token, raw_market_cap, trade.slot)
+                {
+                    warn!(
+                        "Clamped market cap for {} at slot {}: ${:.2} kept over ${:.2}",
+                        token.mint_address, trade.slot, token.market_cap_usd, raw_market_cap
+                    );
+                }
+            }
+            Err(e) => {
+                metrics::PRICING_FAILURES.inc();
+                warn!(
+                    "Market cap of {} not updated at slot {}: {}",
+                    token.mint_address, trade.slot, e
                 );
-            } else {
-                println!("ℹ️  Holder {} sold all tokens", trade.user_wallet);
             }
         }
-        Ok(None) => {
-            println!("ℹ️  No trade data found in transaction");
+        match progress_from_reserves(trade.virtual_sol_reserves, &ctx.parse_options.curve) {
+            Ok(raw_progress) => progress_mode.apply(&mut token, raw_progress),
+            Err(e) => {
+                metrics::PRICING_FAILURES.inc();
+                warn!(
+                    "Curve progress of {} not updated at slot {}: {}",
+                    token.mint_address, trade.slot, e
+                );
+            }
         }
-        Err(e) => {
-            eprintln!("⚠️  Parse error for {}: {}", sig, e);
+    }
+    // The trade's own slot, which the upsert compares against the stored one
+    token.last_trade_slot = Some(trade.slot);
+    // Transfers aren't trading activity
+    if trade.track_volume {
+        token.last_trade_at = Some(trade.timestamp);
+    }
+
+    upsert_token(pool, &token).await?;
+    info!(
+        "Token saved/updated (Market Cap: ${:.2}, Progress: {:.1}%)",
+        token.market_cap_usd, token.bonding_curve_progress
+    );
+
+    // A buy landing with the create is a sniper (or part of a bundle)
+    if trade.is_buy && trade.track_volume && token.creation_slot == Some(trade.slot) {
+        log_snipers(pool, &token.mint_address).await;
+    }
+
+    // The creator selling is a rug signal. Only trusted once the creation is
+    // indexed: before that the creator is a guess from the first trade.
+    if !trade.is_buy
+        && !token.dev_sold
+        && token.creation_slot.is_some()
+        && token.creator_wallet.as_deref() == Some(trade.user_wallet.as_str())
+    {
+        log_dev_sold(pool, &token.mint_address).await;
+    }
+
+    // Covers tokens whose creation we missed or skipped while catching up
+    if enrich && token.metadata_fetched_at.is_none() && token.uri.is_some() {
+        spawn_metadata_fetch(ctx, &token.mint_address);
+    }
+
+    if token.complete
+        && record_graduation(
+            pool,
+            &token.mint_address,
+            &trade.signature,
+            trade.slot,
+            trade.timestamp,
+        )
+        .await?
+    {
+        info!(
+            "{} graduated at slot {} (Market Cap: ${:.2})",
+            token.mint_address, trade.slot, token.market_cap_usd
+        );
+    }
+
+    // 4. Update token holder balance
+    // Get current balance if exists
+    let existing = get_token_holder(pool, &trade.token_mint, &trade.user_wallet)
+        .await
+        .ok()
+        .flatten();
+    let current_balance = existing
+        .as_ref()
+        .map_or(Decimal::ZERO, |holder| holder.balance);
+
+    // A sell by an unknown wallet may be an evicted dust holder's
+    if existing.is_none() && !trade.is_buy && ctx.holder_cap.enabled() {
+        debit_holder_tail(pool, &trade.token_mint, trade.token_amount).await?;
+    }
+
+    // The transaction's own post-balance is authoritative, so a missed or
+    // reordered trade can't leave the balance off. Only without one (the
+    // wallet's account isn't in the balance lists) is the delta applied.
+    let post_balance = tx
+        .and_then(|tx| tx.meta.as_ref())
+        .and_then(|meta| post_token_balance(meta, &trade.token_mint, &trade.user_wallet));
+    let new_balance = match post_balance {
+        Some(balance) => Decimal::from(balance),
+        None if trade.is_buy => current_balance + trade.token_amount,
+        // For sells, ensure we don't go negative
+        None => (current_balance - trade.token_amount).max(Decimal::ZERO),
+    };
+
+    // Record new holders with a balance, and every change to known ones
+    // (including selling out, so the stored balance drops to zero)
+    if new_balance > Decimal::ZERO || existing.is_some() {
+        let holder = TokenHolder {
+            token_mint: trade.token_mint.clone(),
+            user_wallet: trade.user_wallet.clone(),
+            balance: new_balance,
+            last_updated_slot: trade.slot,
+            updated_at: None,
+        };
+
+        upsert_token_holder(
+            pool,
+            &holder,
+            trade.instruction_index,
+            trade.inner_instruction_index,
+        )
+        .await?;
+        if new_balance.is_zero() {
+            info!("Holder {} sold all tokens", trade.user_wallet);
+        } else {
+            info!(
+                "Token holder updated: {} (balance: {})",
+                trade.user_wallet, new_balance
+            );
+        }
+
+        // Only a new row can push the token over the cap
+        if existing.is_none() {
+            ctx.holder_cap.enforce(pool, &trade.token_mint).await?;
         }
+    } else {
+        info!(
+            "Wallet {} holds none of {}",
+            trade.user_wallet, trade.token_mint
+        );
     }
 
+    // 5. Queue the token's Redis card for a rewrite (skipped while catching up)
+    if enrich && trade.track_volume && is_latest && ctx.token_cards.is_some() {
+        cards.record(&token, trade, current_sol_price);
+    }
     Ok(())
 }
 
+/// Token cards due a rewrite, one per mint, from the state the newest trade
+/// applied left. Filled while a transaction's (or a flushed batch's) trades
+/// are applied and written once after, so the card's 24h volume and holder
+/// count queries run once per mint instead of once per trade.
+#[derive(Default)]
+pub struct CardRefreshes {
+    cards: HashMap<String, CardRefresh>,
+}
+
+struct CardRefresh {
+    token: Token,
+    last_trade_slot: i64,
+    last_trade_at: DateTime<Utc>,
+    sol_price: f64,
+}
+
+impl CardRefreshes {
+    /// Queue `token`'s card as `trade` left it, unless a later trade of the
+    /// same mint already is
+    pub fn record(&mut self, token: &Token, trade: &Trade, sol_price: f64) {
+        if self
+            .cards
+            .get(&token.mint_address)
+            .is_some_and(|queued| queued.last_trade_slot > trade.slot)
+        {
+            return;
+        }
+        self.cards.insert(
+            token.mint_address.clone(),
+            CardRefresh {
+                token: token.clone(),
+                last_trade_slot: trade.slot,
+                last_trade_at: trade.timestamp,
+                sol_price,
+            },
+        );
+    }
+
+    /// Mints with a card queued
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// Slot of the trade `mint`'s queued card reflects
+    pub fn last_trade_slot(&self, mint: &str) -> Option<i64> {
+        self.cards.get(mint).map(|queued| queued.last_trade_slot)
+    }
+
+    /// Rewrite every queued card. Best effort, like the rest of the card
+    /// bookkeeping: a failure leaves that card stale until the next trade.
+    async fn write(self, ctx: &WorkerContext) {
+        let Some(redis) = &ctx.token_cards else {
+            return;
+        };
+        for (mint, queued) in self.cards {
+            if let Err(e) = refresh_token_card(&ctx.db_pool, redis.clone(), &queued).await {
+                error!("Token card update failed for {}: {}", mint, e);
+            }
+        }
+    }
+}
+
+/// Rewrite a token's card from its just-saved state
+async fn refresh_token_card(
+    pool: &PgPool,
+    mut redis: RedisClient,
+    queued: &CardRefresh,
+) -> Result<()> {
+    let token = &queued.token;
+    let price_sol = price_from_reserves(token.virtual_sol_reserves, token.virtual_token_reserves)?;
+    let volume = get_24h_volume_stats(pool, &token.mint_address).await?;
+    let card = TokenCard {
+        mint_address: token.mint_address.clone(),
+        price_sol,
+        price_usd: sol_to_usd(price_sol, queued.sol_price)?,
+        market_cap_usd: token.market_cap_usd,
+        bonding_curve_progress: token.bonding_curve_progress,
+        volume_24h_sol: volume.volume_sol,
+        volume_24h_usd: volume.volume_usd,
+        holder_count: get_holder_count(pool, &token.mint_address).await?,
+        last_trade_slot: queued.last_trade_slot,
+        last_trade_at: queued.last_trade_at,
+    };
+    write_token_card(&mut redis, &card).await
+}
+
+/// Resolve a token's off-chain metadata in the background so slow gateways
+/// never hold up trades. The claim is recorded before fetching, so each mint
+/// is attempted once whether or not the fetch succeeds. At most
+/// `MAX_METADATA_FETCHES` run at once; when all are busy the mint isn't
+/// claimed, so a later trade retries it.
+fn spawn_metadata_fetch(ctx: &WorkerContext, mint: &str) {
+    let Ok(permit) = METADATA_FETCHES.try_acquire() else {
+        debug!("Metadata fetches busy, deferring {}", mint);
+        return;
+    };
+    let (pool, client, mint) = (
+        ctx.db_pool.clone(),
+        ctx.http_client.clone(),
+        mint.to_string(),
+    );
+    ctx.background.spawn(async move {
+        let _permit = permit;
+        let uri = match claim_token_metadata_fetch(&pool, &mint).await {
+            Ok(Some(uri)) => uri,
+            Ok(None) => return, // No URI yet, or already claimed
+            Err(e) => {
+                error!("Metadata claim failed for {}: {}", mint, e);
+                return;
+            }
+        };
+
+        if let Some(metadata) = fetch_token_metadata(&client, &uri).await {
+            match set_token_metadata(&pool, &mint, &metadata).await {
+                Ok(()) => info!("Metadata stored for {}", mint),
+                Err(e) => error!("Failed to store metadata for {}: {}", mint, e),
+            }
+        }
+    });
+}
+
 /// Extract token metadata from transaction logs
 fn extract_token_metadata_from_tx(
     tx: &TransactionResult,
+    mint: &str,
 ) -> (Option<String>, Option<String>, Option<String>) {
-    if let Some(meta) = &tx.meta {
-        if let Some(logs) = &meta.log_messages {
-            let mut name = None;
-            let mut symbol = None;
-            let mut uri = None;
-
-            for log in logs {
-                // Extract name
-                if name.is_none() && (log.contains("name:") || log.contains("Name:")) {
-                    if let Some(pos) = log.find("name:").or_else(|| log.find("Name:")) {
-                        let start = pos + 5;
-                        let value = log[start..]
-                            .trim()
-                            .split(&[',', '"', '}'][..])
-                            .next()
-                            .unwrap_or("")
-                            .trim();
-                        if !value.is_empty() && value.len() < 100 {
-                            name = Some(value.to_string());
-                        }
-                    }
+    // Reliable when the transaction created the token
+    if let Some(args) = find_create_args(tx, mint) {
+        return (Some(args.name), Some(args.symbol), Some(args.uri));
+    }
+
+    // Fallback: best-effort log scraping
+    if let Some(meta) = &tx.meta
+        && let Some(logs) = &meta.log_messages
+    {
+        let mut name = None;
+        let mut symbol = None;
+        let mut uri = None;
+
+        for log in logs {
+            // Extract name
+            if name.is_none()
+                && (log.contains("name:") || log.contains("Name:"))
+                && let Some(pos) = log.find("name:").or_else(|| log.find("Name:"))
+            {
+                let start = pos + 5;
+                let value = log[start..]
+                    .trim()
+                    .split(&[',', '"', '}'][..])
+                    .next()
+                    .unwrap_or("")
+                    .trim();
+                if !value.is_empty() && value.len() < 100 {
+                    name = Some(value.to_string());
                 }
+            }
 
-                // Extract symbol
-                if symbol.is_none() && (log.contains("symbol:") || log.contains("Symbol:")) {
-                    if let Some(pos) = log.find("symbol:").or_else(|| log.find("Symbol:")) {
-                        let start = pos + 7;
-                        let value = log[start..]
-                            .trim()
-                            .split(&[',', '"', '}'][..])
-                            .next()
-                            .unwrap_or("")
-                            .trim();
-                        if !value.is_empty() && value.len() < 20 {
-                            symbol = Some(value.to_string());
-                        }
-                    }
+            // Extract symbol
+            if symbol.is_none()
+                && (log.contains("symbol:") || log.contains("Symbol:"))
+                && let Some(pos) = log.find("symbol:").or_else(|| log.find("Symbol:"))
+            {
+                let start = pos + 7;
+                let value = log[start..]
+                    .trim()
+                    .split(&[',', '"', '}'][..])
+                    .next()
+                    .unwrap_or("")
+                    .trim();
+                if !value.is_empty() && value.len() < 20 {
+                    symbol = Some(value.to_string());
                 }
+            }
 
-                // Extract URI
-                if uri.is_none()
-                    && (log.contains("uri:") || log.contains("Uri:") || log.contains("metadata:"))
+            // Extract URI
+            if uri.is_none()
+                && (log.contains("uri:") || log.contains("Uri:") || log.contains("metadata:"))
+                && let Some(pos) = log
+                    .find("uri:")
+                    .or_else(|| log.find("Uri:"))
+                    .or_else(|| log.find("metadata:"))
+            {
+                let start = pos
+                    + if log[pos..].starts_with("metadata:") {
+                        9
+                    } else {
+                        4
+                    };
+                let value = log[start..]
+                    .trim()
+                    .split(&[' ', ',', '"', '}'][..])
+                    .next()
+                    .unwrap_or("")
+                    .trim();
+                if value.starts_with("http")
+                    || value.starts_with("ipfs")
+                    || value.starts_with("ar://")
                 {
-                    if let Some(pos) = log
-                        .find("uri:")
-                        .or_else(|| log.find("Uri:"))
-                        .or_else(|| log.find("metadata:"))
-                    {
-                        let start = pos
-                            + if log[pos..].starts_with("metadata:") {
-                                9
-                            } else {
-                                4
-                            };
-                        let value = log[start..]
-                            .trim()
-                            .split(&[' ', ',', '"', '}'][..])
-                            .next()
-                            .unwrap_or("")
-                            .trim();
-                        if value.starts_with("http")
-                            || value.starts_with("ipfs")
-                            || value.starts_with("ar://")
-                        {
-                            uri = Some(value.to_string());
-                        }
-                    }
+                    uri = Some(value.to_string());
                 }
             }
-
-            return (name, symbol, uri);
         }
+
+        return (name, symbol, uri);
     }
     (None, None, None)
 }
@@ -466,7 +3243,7 @@ fn find_bonding_curve_from_tx(tx: &TransactionResult, mint_address: &str) -> Opt
         }
 
         // Bonding curve typically at position 4-6
-        if idx >= 3 && idx <= 7 && account.pubkey.len() == 44 {
+        if (3..=7).contains(&idx) && is_valid_pubkey(&account.pubkey) {
             candidates.push((idx, account.pubkey.clone()));
         }
     }
@@ -0,0 +1,126 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use borsh::BorshDeserialize;
+use serde_json::{Value, json};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use tokio::sync::OnceCell;
+
+use crate::error::{IndexerError, Result};
+use crate::helius::fetcher::RpcPool;
+use crate::pricing::CurveParams;
+
+const PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+/// `sha256("account:Global")[..8]`
+const GLOBAL_ACCOUNT_DISCRIMINATOR: [u8; 8] = [167, 232, 232, 177, 200, 108, 114, 127];
+
+/// Read once per process: the program changes it rarely, and a restart picks
+/// up a change
+static GLOBAL_CONFIG: OnceCell<GlobalConfig> = OnceCell::const_new();
+
+/// Leading fields of pump.fun's `Global` account, present in every program
+/// version; later versions append more
+#[derive(BorshDeserialize)]
+struct GlobalAccount {
+    _initialized: bool,
+    _authority: [u8; 32],
+    fee_recipient: [u8; 32],
+    initial_virtual_token_reserves: u64,
+    initial_virtual_sol_reserves: u64,
+    initial_real_token_reserves: u64,
+    token_total_supply: u64,
+    fee_basis_points: u64,
+}
+
+/// Pump.fun's on-chain global config: the protocol fee and the reserves new
+/// curves start with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalConfig {
+    pub fee_recipient: String,
+    pub fee_basis_points: u64,
+    pub initial_virtual_token_reserves: u64,
+    pub initial_virtual_sol_reserves: u64,
+    pub initial_real_token_reserves: u64,
+    pub token_total_supply: u64,
+}
+
+impl GlobalConfig {
+    /// The built-in curve parameters with the starting reserves and supply
+    /// replaced by these. A curve starts with no real SOL, so its initial
+    /// virtual SOL is the offset every curve carries.
+    pub fn curve_params(&self) -> CurveParams {
+        CurveParams {
+            virtual_sol_offset: self.initial_virtual_sol_reserves,
+            initial_virtual_token_reserves: self.initial_virtual_token_reserves,
+            initial_real_token_reserves: self.initial_real_token_reserves,
+            token_total_supply: self.token_total_supply,
+            ..CurveParams::default()
+        }
+    }
+}
+
+/// The program's `global` PDA
+pub fn global_config_address() -> Option<String> {
+    let program_id = Pubkey::from_str(PUMP_FUN_PROGRAM_ID).ok()?;
+    let (address, _bump) = Pubkey::find_program_address(&[b"global"], &program_id);
+    Some(address.to_string())
+}
+
+/// Decode the `Global` account's data, if it is one
+pub fn decode_global_config(data: &[u8]) -> Option<GlobalConfig> {
+    let mut payload = data.strip_prefix(GLOBAL_ACCOUNT_DISCRIMINATOR.as_slice())?;
+    let account = GlobalAccount::deserialize(&mut payload).ok()?;
+
+    Some(GlobalConfig {
+        fee_recipient: bs58::encode(account.fee_recipient).into_string(),
+        fee_basis_points: account.fee_basis_points,
+        initial_virtual_token_reserves: account.initial_virtual_token_reserves,
+        initial_virtual_sol_reserves: account.initial_virtual_sol_reserves,
+        initial_real_token_reserves: account.initial_real_token_reserves,
+        token_total_supply: account.token_total_supply,
+    })
+}
+
+/// Read and decode the global config account with `getAccountInfo`. The
+/// first successful read is cached for the life of the process.
+pub async fn fetch_global_config(client: &reqwest::Client, rpc: &RpcPool) -> Result<GlobalConfig> {
+    GLOBAL_CONFIG
+        .get_or_try_init(|| fetch_uncached(client, rpc))
+        .await
+        .cloned()
+}
+
+async fn fetch_uncached(client: &reqwest::Client, rpc: &RpcPool) -> Result<GlobalConfig> {
+    let address = global_config_address()
+        .ok_or_else(|| IndexerError::ParseError("invalid program id".to_string()))?;
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getAccountInfo",
+        "params": [address, { "encoding": "base64" }]
+    });
+    let body: Value = client
+        .post(rpc.url()?)
+        .json(&request)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    if let Some(error) = body.get("error") {
+        return Err(IndexerError::HeliusError(format!("RPC error: {}", error)));
+    }
+    let value = body
+        .pointer("/result/value")
+        .filter(|value| !value.is_null())
+        .ok_or_else(|| IndexerError::HeliusError(format!("no account at {}", address)))?;
+    let data = value
+        .pointer("/data/0")
+        .and_then(|data| data.as_str())
+        .and_then(|data| BASE64.decode(data).ok())
+        .ok_or_else(|| IndexerError::ParseError(format!("{} has no base64 data", address)))?;
+
+    decode_global_config(&data)
+        .ok_or_else(|| IndexerError::ParseError(format!("{} is not a Global account", address)))
+}
@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+/// Settings for the outbound HTTP client shared by a worker (Helius RPC,
+/// Pyth). Without explicit timeouts a stalled connection would wedge the
+/// task awaiting it forever.
+#[derive(Debug, Clone, Copy)]
+pub struct HttpConfig {
+    pub connect_timeout: Duration,
+    /// Ceiling for a whole request; RPC calls also apply their own,
+    /// latency-based timeout below it
+    pub request_timeout: Duration,
+    pub pool_max_idle_per_host: usize,
+    /// Permit plain `http://` URLs, for an RPC node on a private network or
+    /// a local `solana-test-validator`
+    pub allow_http: bool,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(30),
+            pool_max_idle_per_host: 32,
+            allow_http: false,
+        }
+    }
+}
+
+impl HttpConfig {
+    /// Read from `HTTP_CONNECT_TIMEOUT_SECS`, `HTTP_REQUEST_TIMEOUT_SECS`,
+    /// `HTTP_POOL_MAX_IDLE` and `HTTP_ALLOW_PLAINTEXT`, keeping the defaults
+    /// for unset or invalid values
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok()?.parse().ok()
+        }
+
+        let defaults = Self::default();
+        Self {
+            connect_timeout: var("HTTP_CONNECT_TIMEOUT_SECS")
+                .map_or(defaults.connect_timeout, Duration::from_secs),
+            request_timeout: var("HTTP_REQUEST_TIMEOUT_SECS")
+                .map_or(defaults.request_timeout, Duration::from_secs),
+            pool_max_idle_per_host: var("HTTP_POOL_MAX_IDLE")
+                .unwrap_or(defaults.pool_max_idle_per_host),
+            allow_http: var("HTTP_ALLOW_PLAINTEXT").unwrap_or(defaults.allow_http),
+        }
+    }
+
+    /// Build the client: HTTPS only (unless `allow_http`), TLS 1.2+ with
+    /// certificate verification
+    pub fn build(self) -> Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .https_only(!self.allow_http)
+            .min_tls_version(reqwest::tls::Version::TLS_1_2)
+            .build()
+            .context("Failed to build HTTP client")
+    }
+}
@@ -1,14 +1,34 @@
+use crate::health::{Component, Health};
+use crate::helius::fetcher::{RpcPool, endpoint_from_env};
+use crate::helius::http::HttpConfig;
+use crate::metrics;
+use crate::models::helius_model::TransactionResult;
 use crate::redis::redis_cleint::RedisClient;
 use anyhow::Result;
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::{debug, error, info, warn};
 
 const PING_INTERVAL: u64 = 30_000;
+/// Silence (not even a pong) for this long means the connection is dead
+const STALE_CONNECTION: Duration = Duration::from_secs(90);
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(10);
 const PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
-const REDIS_CHANNEL: &str = "solana:transactions";
+const REDIS_STREAM: &str = "solana:transactions:stream";
+const REDIS_STREAM_MAX_LEN: usize = 100_000;
+const RECONNECT_BACKOFF_INITIAL: Duration = Duration::from_secs(1);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(60);
+/// A connection that lasted this long counts as healthy and resets the backoff
+const STABLE_CONNECTION: Duration = Duration::from_secs(30);
+const SEEN_KEY_PREFIX: &str = "ingester:seen:";
+const DEFAULT_DEDUP_WINDOW_SECS: u64 = 300;
 
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
@@ -20,6 +40,7 @@ enum LogMessage {
 #[derive(Debug, Deserialize)]
 struct UpdateParams {
     result: UpdateResult,
+    subscription: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -32,43 +53,117 @@ struct UpdateResult {
 pub struct TransactionInfo {
     pub signature: String,
     pub err: Option<serde_json::Value>,
+    /// Program whose subscription reported it; absent means pump.fun
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_id: Option<String>,
+    /// The full transaction, when the source already had it (webhooks);
+    /// the worker then skips its RPC fetch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<Box<TransactionResult>>,
+}
+
+/// Programs to watch, from `PROGRAM_IDS` (comma-separated), pump.fun alone
+/// by default
+pub fn program_ids_from_env() -> Vec<String> {
+    let ids: Vec<String> = std::env::var("PROGRAM_IDS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect();
+    if ids.is_empty() {
+        vec![PUMP_FUN_PROGRAM_ID.to_string()]
+    } else {
+        ids
+    }
+}
+
+/// How long a published signature is remembered so repeat notifications
+/// (e.g. across commitment levels) are dropped: `INGEST_DEDUP_WINDOW_SECS`,
+/// default 300, `0` to publish every notification
+pub fn dedup_window_from_env() -> u64 {
+    std::env::var("INGEST_DEDUP_WINDOW_SECS")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(DEFAULT_DEDUP_WINDOW_SECS)
+}
+
+fn seen_key(program_id: Option<&str>, signature: &str) -> String {
+    format!(
+        "{}{}:{}",
+        SEEN_KEY_PREFIX,
+        program_id.unwrap_or_default(),
+        signature
+    )
 }
 
 pub struct WebSocketClient {
-    api_key: String,
+    ws_url: String,
     redis_client: RedisClient,
+    program_ids: Vec<String>,
+    dedup_window_secs: u64,
 }
 
 impl WebSocketClient {
-    pub fn new(api_key: String, redis_client: RedisClient) -> Self {
+    pub fn new(ws_url: String, redis_client: RedisClient, program_ids: Vec<String>) -> Self {
         Self {
-            api_key,
+            ws_url,
             redis_client,
+            program_ids,
+            dedup_window_secs: dedup_window_from_env(),
+        }
+    }
+
+    /// Whether `signature` was already published for `program_id` within the
+    /// dedup window (`SET NX` with a TTL, so the window spans reconnects and
+    /// ingester restarts). Redis errors count as unseen: a duplicate only
+    /// costs an RPC call, a dropped signature costs a trade.
+    async fn seen_recently(&mut self, program_id: Option<&str>, signature: &str) -> bool {
+        if self.dedup_window_secs == 0 {
+            return false;
+        }
+        let key = seen_key(program_id, signature);
+        match self.redis_client.set_nx(&key, self.dedup_window_secs).await {
+            Ok(first) => !first,
+            Err(e) => {
+                warn!("Dedup check failed for {}: {}", signature, e);
+                false
+            }
         }
     }
 
-    pub async fn connect(&mut self) -> Result<()> {
-        let url = format!("wss://mainnet.helius-rpc.com/?api-key={}", self.api_key);
-        println!("🔌 Connecting to Helius WebSocket...");
+    /// Stream log notifications into Redis until the connection drops (an
+    /// error) or `shutdown` is cancelled (`Ok`).
+    ///
+    /// `logsSubscribe` takes a single address per subscription, so each
+    /// program gets its own; a transaction touching several of them is
+    /// published once per program (the worker's inserts are idempotent).
+    pub async fn connect(&mut self, shutdown: &CancellationToken) -> Result<()> {
+        info!("Connecting to RPC WebSocket...");
 
-        let (ws_stream, _) = connect_async(&url).await?;
-        println!("✅ Connected to WebSocket!");
+        let (ws_stream, _) = connect_async(&self.ws_url).await?;
+        info!("Connected to WebSocket!");
 
         let (mut write, mut read) = ws_stream.split();
 
-        // 1. Send subscription
-        let request = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "logsSubscribe",
-            "params": [
-                { "mentions": [PUMP_FUN_PROGRAM_ID] },
-                { "commitment": "confirmed" }
-            ]
-        });
-        write
-            .send(Message::Text(request.to_string().into()))
-            .await?;
+        // 1. Send one subscription per program; request id N is program N-1
+        for (index, program_id) in self.program_ids.iter().enumerate() {
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": index + 1,
+                "method": "logsSubscribe",
+                "params": [
+                    { "mentions": [program_id] },
+                    { "commitment": "confirmed" }
+                ]
+            });
+            write
+                .send(Message::Text(request.to_string().into()))
+                .await?;
+        }
+        // Subscription id -> program, filled in as confirmations arrive
+        let mut subscriptions: HashMap<u64, String> = HashMap::new();
 
         // 2. Background Ping Task
         let ping_task = tokio::spawn(async move {
@@ -81,30 +176,80 @@ impl WebSocketClient {
             }
         });
 
-        // 3. Process Messages
-        while let Some(message) = read.next().await {
+        // 3. Process Messages. Any frame, pongs included, proves the connection
+        // is alive; a half-open one would otherwise go quiet forever.
+        let mut last_message = tokio::time::Instant::now();
+        let mut watchdog = tokio::time::interval(WATCHDOG_INTERVAL);
+        loop {
+            let message = tokio::select! {
+                _ = shutdown.cancelled() => {
+                    ping_task.abort();
+                    return Ok(());
+                }
+                _ = watchdog.tick() => {
+                    if last_message.elapsed() >= STALE_CONNECTION {
+                        ping_task.abort();
+                        return Err(anyhow::anyhow!(
+                            "No message for {:?}, connection is stale",
+                            last_message.elapsed()
+                        ));
+                    }
+                    continue;
+                }
+                message = read.next() => message,
+            };
+            let Some(message) = message else {
+                break;
+            };
+            last_message = tokio::time::Instant::now();
+
             match message {
-                Ok(Message::Text(text)) => {
-                    if let Ok(LogMessage::Update { params }) =
-                        serde_json::from_str::<LogMessage>(&text)
-                    {
-                        let tx_info = params.result.value;
+                Ok(Message::Text(text)) => match serde_json::from_str::<LogMessage>(&text) {
+                    Ok(LogMessage::Update { params }) => {
+                        let mut tx_info = params.result.value;
+                        tx_info.program_id = subscriptions.get(&params.subscription).cloned();
 
                         // Ignore failed transactions
                         if tx_info.err.is_some() {
                             continue;
                         }
 
-                        println!("📥 Detected: {}", tx_info.signature);
+                        if self
+                            .seen_recently(tx_info.program_id.as_deref(), &tx_info.signature)
+                            .await
+                        {
+                            debug!("Duplicate notification for {}", tx_info.signature);
+                            continue;
+                        }
+
+                        info!("Detected: {}", tx_info.signature);
 
-                        // PUBLISH to Redis
-                        if let Err(e) = self.redis_client.publish(REDIS_CHANNEL, &tx_info).await {
-                            eprintln!("❌ Publish failed: {}", e);
+                        // Queue it durably for the worker group
+                        if let Err(e) = self
+                            .redis_client
+                            .xadd_signature(REDIS_STREAM, &tx_info, REDIS_STREAM_MAX_LEN)
+                            .await
+                        {
+                            error!("Publish failed: {}", e);
+                            // Forget it so a repeat notification isn't dropped
+                            let key = seen_key(tx_info.program_id.as_deref(), &tx_info.signature);
+                            let _ = self.redis_client.delete(&key).await;
                         } else {
-                            println!("Published to channel: {}", REDIS_CHANNEL);
+                            info!("Published to stream: {}", REDIS_STREAM);
                         }
                     }
-                }
+                    Ok(LogMessage::Confirmation { result, id }) => {
+                        let program_id = (id as usize)
+                            .checked_sub(1)
+                            .and_then(|index| self.program_ids.get(index));
+                        if let Some(program_id) = program_id {
+                            info!("Subscribed to {} (subscription {})", program_id, result);
+                            subscriptions.insert(result, program_id.clone());
+                        }
+                    }
+                    Err(_) => {}
+                },
+                Ok(Message::Pong(_)) => debug!("Pong received"),
                 Ok(Message::Close(_)) => break,
                 Err(_) => break,
                 _ => {}
@@ -116,18 +261,67 @@ impl WebSocketClient {
     }
 }
 
-pub async fn run_ingester() -> Result<()> {
-    let api_key = std::env::var("HELIUS_API_KEY").expect("HELIUS_API_KEY missing");
+/// Log subscription endpoint: `WS_URL`, else Helius mainnet for
+/// `HELIUS_API_KEY`. `None` when neither is set.
+pub fn ws_url() -> Option<String> {
+    endpoint_from_env("WS_URL", "wss")
+}
+
+pub async fn run_ingester(shutdown: CancellationToken) -> Result<()> {
+    let ws_url = ws_url().expect("WS_URL or HELIUS_API_KEY missing");
+    let rpc = RpcPool::from_env().expect("RPC_URL or HELIUS_API_KEY missing");
     let redis_url =
         std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
 
-    let redis_client = RedisClient::new(&redis_url).await?;
-    let mut client = WebSocketClient::new(api_key, redis_client);
+    // Up before any connection, so the readiness probe fails until they are
+    let health = Health::new(&[Component::Redis, Component::Rpc]);
+    let background = TaskTracker::new();
+    metrics::spawn_metrics_server(&background, shutdown.clone(), health.clone()).await?;
+
+    let result = ingest(ws_url, rpc, &redis_url, &health, &shutdown).await;
+    // The metrics server goes down with the ingester, however it stopped
+    shutdown.cancel();
+    background.close();
+    background.wait().await;
+    result
+}
+
+/// Stream logs into Redis, reconnecting until `shutdown`
+async fn ingest(
+    ws_url: String,
+    rpc: RpcPool,
+    redis_url: &str,
+    health: &Health,
+    shutdown: &CancellationToken,
+) -> Result<()> {
+    let redis_client = RedisClient::new(redis_url).await?;
+    health.set_redis(redis_client.clone());
+    health.set_rpc(HttpConfig::from_env().build()?, Arc::new(rpc));
+    let mut client = WebSocketClient::new(ws_url, redis_client, program_ids_from_env());
 
+    // Quick retries after a blip, backing off to once a minute in an outage
+    let mut backoff = RECONNECT_BACKOFF_INITIAL;
+    let mut failures: u32 = 0;
     loop {
-        if let Err(e) = client.connect().await {
-            eprintln!("⚠️ Disconnected: {}. Retrying in 5s...", e);
-            tokio::time::sleep(Duration::from_secs(5)).await;
+        let connected_at = tokio::time::Instant::now();
+        match client.connect(shutdown).await {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                if connected_at.elapsed() >= STABLE_CONNECTION {
+                    backoff = RECONNECT_BACKOFF_INITIAL;
+                    failures = 0;
+                }
+                failures += 1;
+                warn!(
+                    "Disconnected: {}. Retrying in {:?} (failure {} in a row)...",
+                    e, backoff, failures
+                );
+                tokio::select! {
+                    _ = shutdown.cancelled() => return Ok(()),
+                    _ = tokio::time::sleep(backoff) => {}
+                }
+                backoff = (backoff * 2).min(RECONNECT_BACKOFF_MAX);
+            }
         }
     }
 }
@@ -0,0 +1,86 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Samples kept for percentile estimates
+const WINDOW: usize = 1_000;
+/// Samples needed before estimates replace the defaults
+const MIN_SAMPLES: usize = 20;
+
+/// Rolling window of RPC response times, used to derive timeouts and retry
+/// delays from the endpoint's observed latency instead of fixed constants
+#[derive(Debug)]
+pub struct LatencyTracker {
+    samples: Mutex<VecDeque<Duration>>,
+    /// Timeout = p99 x this factor
+    timeout_factor: f64,
+    min_timeout: Duration,
+    max_timeout: Duration,
+    default_timeout: Duration,
+}
+
+/// p50 / p90 / p99 of the current window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyPercentiles {
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub samples: usize,
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new(3.0, Duration::from_millis(500), Duration::from_secs(30))
+    }
+}
+
+impl LatencyTracker {
+    pub fn new(timeout_factor: f64, min_timeout: Duration, max_timeout: Duration) -> Self {
+        Self {
+            samples: Mutex::new(VecDeque::with_capacity(WINDOW)),
+            timeout_factor,
+            min_timeout,
+            max_timeout,
+            default_timeout: Duration::from_secs(10).clamp(min_timeout, max_timeout),
+        }
+    }
+
+    pub fn record(&self, latency: Duration) {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        if samples.len() == WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(latency);
+    }
+
+    /// `None` until enough samples have been recorded
+    pub fn percentiles(&self) -> Option<LatencyPercentiles> {
+        let mut sorted: Vec<Duration> = {
+            let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+            if samples.len() < MIN_SAMPLES {
+                return None;
+            }
+            samples.iter().copied().collect()
+        };
+        sorted.sort_unstable();
+
+        let at = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+        Some(LatencyPercentiles {
+            p50: at(0.50),
+            p90: at(0.90),
+            p99: at(0.99),
+            samples: sorted.len(),
+        })
+    }
+
+    /// Per-request timeout: p99 x factor, within the configured bounds
+    pub fn timeout(&self) -> Duration {
+        match self.percentiles() {
+            Some(p) => p
+                .p99
+                .mul_f64(self.timeout_factor)
+                .clamp(self.min_timeout, self.max_timeout),
+            None => self.default_timeout,
+        }
+    }
+}
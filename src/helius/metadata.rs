@@ -0,0 +1,93 @@
+use crate::models::TokenMetadata;
+use serde_json::Value;
+use std::time::Duration;
+use tracing::warn;
+
+/// Metadata hosts are slow and flaky; give up early rather than hold a task
+const METADATA_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Metadata JSON is a few hundred bytes; anything much larger isn't metadata
+/// and isn't read into memory
+pub const MAX_METADATA_BYTES: usize = 64 * 1024;
+const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+const ARWEAVE_GATEWAY: &str = "https://arweave.net/";
+
+/// Resolve a token's `uri` and pick out the fields we keep. `None` when the
+/// URI can't be fetched or isn't a JSON object; every field is optional.
+pub async fn fetch_token_metadata(client: &reqwest::Client, uri: &str) -> Option<TokenMetadata> {
+    let url = gateway_url(uri)?;
+
+    let response = client
+        .get(&url)
+        .timeout(METADATA_FETCH_TIMEOUT)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status());
+    let body = match response {
+        Ok(response) => read_capped(response).await,
+        Err(e) => Err(e.to_string()),
+    };
+    let json: Value = match body {
+        Ok(body) => serde_json::from_slice(&body).ok()?,
+        Err(e) => {
+            warn!("Metadata fetch failed for {}: {}", url, e);
+            return None;
+        }
+    };
+
+    let field = |name: &str| {
+        json.get(name)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+
+    json.is_object().then(|| TokenMetadata {
+        image: field("image"),
+        description: field("description"),
+        twitter: field("twitter"),
+        telegram: field("telegram"),
+        website: field("website"),
+    })
+}
+
+/// The response body, or an error once it passes `MAX_METADATA_BYTES`
+/// (checked against the declared length first, then while reading)
+async fn read_capped(mut response: reqwest::Response) -> Result<Vec<u8>, String> {
+    let too_large = || format!("body over {} bytes", MAX_METADATA_BYTES);
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_METADATA_BYTES as u64)
+    {
+        return Err(too_large());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if body.len() + chunk.len() > MAX_METADATA_BYTES {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// An HTTPS URL for `uri`, rewriting `ipfs://` to the gateway in
+/// `IPFS_GATEWAY` and `ar://` to arweave.net
+fn gateway_url(uri: &str) -> Option<String> {
+    let uri = uri.trim();
+    if let Some(cid) = uri.strip_prefix("ipfs://") {
+        let gateway =
+            std::env::var("IPFS_GATEWAY").unwrap_or_else(|_| DEFAULT_IPFS_GATEWAY.to_string());
+        let cid = cid.strip_prefix("ipfs/").unwrap_or(cid);
+        Some(format!("{}/{}", gateway.trim_end_matches('/'), cid))
+    } else if let Some(id) = uri.strip_prefix("ar://") {
+        Some(format!("{}{}", ARWEAVE_GATEWAY, id))
+    } else if uri.starts_with("https://") {
+        Some(uri.to_string())
+    } else {
+        // The shared client is HTTPS-only, and metadata URIs are untrusted
+        // even when it isn't
+        None
+    }
+}
@@ -1,3 +1,11 @@
+pub mod events;
 pub mod fetcher;
+pub mod global_config;
+pub mod http;
 pub mod ingester;
+pub mod latency;
+pub mod metadata;
 pub mod parser;
+pub mod price;
+pub mod retry;
+pub mod webhook;
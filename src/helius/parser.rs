@@ -1,40 +1,235 @@
-use crate::models::{Token, Trade, helius_model::TransactionResult};
-use anyhow::{Context, Result};
+use crate::error::{IndexerError, Result};
+use crate::helius::events::{
+    CreateEvent, InitialCurveState, TradeEvent, decode_create_event_data, decode_create_events,
+    decode_trade_event_data, decode_trade_events, event_cpi_payload, is_event_authority,
+};
+use crate::models::{
+    Migration, Token, Trade, TradeAccounts,
+    helius_model::{
+        EnhancedTransaction, Instruction, TokenBalance, TransactionMeta, TransactionResult,
+    },
+};
+use crate::pricing::{
+    CurveParams, parse_raw_amount, price_from_amounts, safe_decimal, sol_to_usd,
+    virtual_sol_from_real,
+};
+use borsh::BorshDeserialize;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use rust_decimal::prelude::FromPrimitive;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use sqlx::types::Json;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::warn;
 
 const PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
 const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
-const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
 
-// Standard Pump.fun virtual offset (30 SOL)
-const VIRTUAL_SOL_OFFSET: u64 = 30 * LAMPORTS_PER_SOL;
+/// What to do with token balance changes that have no SOL flow behind them
+/// (airdrops, plain transfers). They are not trades and would otherwise show
+/// up with `sol_amount = 0` and a zero price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransferPolicy {
+    /// Drop them entirely
+    #[default]
+    Skip,
+    /// Keep them as `ix_name = "transfer"` rows with `track_volume = false`
+    /// and no price, so balances stay complete but charts and volume ignore them
+    Record,
+}
+
+impl TransferPolicy {
+    /// Read from `ZERO_SOL_TRANSFERS` (`skip` | `record`), defaulting to `skip`
+    pub fn from_env() -> Self {
+        match std::env::var("ZERO_SOL_TRANSFERS").as_deref() {
+            Ok("record") => Self::Record,
+            _ => Self::Skip,
+        }
+    }
+}
+
+/// What to do when a token balance diff disagrees with the RPC's own UI
+/// amounts, which usually means a parsing or decimals bug
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UiAmountCheck {
+    Off,
+    /// Log and count the mismatch but keep the movement
+    #[default]
+    Warn,
+    /// Log, count and drop the movement
+    Reject,
+}
+
+impl UiAmountCheck {
+    /// Read from `UI_AMOUNT_CHECK` (`off` | `warn` | `reject`), defaulting to `warn`
+    pub fn from_env() -> Self {
+        match std::env::var("UI_AMOUNT_CHECK").as_deref() {
+            Ok("off") => Self::Off,
+            Ok("reject") => Self::Reject,
+            _ => Self::Warn,
+        }
+    }
+}
+
+/// Where a trade's SOL amount comes from when no TradeEvent is available
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WsolPolicy {
+    /// Use the trader's wrapped-SOL token balance change when there is one;
+    /// unlike the native delta it excludes fees and rent
+    #[default]
+    Prefer,
+    /// Always use the trader's native SOL delta
+    Ignore,
+}
+
+impl WsolPolicy {
+    /// Read from `WSOL_LEGS` (`prefer` | `ignore`), defaulting to `prefer`
+    pub fn from_env() -> Self {
+        match std::env::var("WSOL_LEGS").as_deref() {
+            Ok("ignore") => Self::Ignore,
+            _ => Self::Prefer,
+        }
+    }
+}
+
+static UI_AMOUNT_MISMATCHES: AtomicU64 = AtomicU64::new(0);
+
+/// Balance diffs seen disagreeing with their UI amounts since startup
+pub fn ui_amount_mismatches() -> u64 {
+    UI_AMOUNT_MISMATCHES.load(Ordering::Relaxed)
+}
+
+/// Parser knobs, read once by the worker
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+    pub transfer_policy: TransferPolicy,
+    pub ui_amount_check: UiAmountCheck,
+    pub wsol_policy: WsolPolicy,
+    /// Keep each trade's accounts on its row (`STORE_TRADE_ACCOUNTS=true`)
+    pub store_accounts: bool,
+    pub curve: CurveParams,
+}
+
+impl ParseOptions {
+    pub fn from_env() -> Self {
+        Self {
+            transfer_policy: TransferPolicy::from_env(),
+            ui_amount_check: UiAmountCheck::from_env(),
+            wsol_policy: WsolPolicy::from_env(),
+            store_accounts: std::env::var("STORE_TRADE_ACCOUNTS").as_deref() == Ok("true"),
+            curve: CurveParams::from_env(),
+        }
+    }
+}
+
+/// Why a transaction did or did not yield a trade, so the worker can account
+/// for every fetched transaction instead of collapsing misses into `None`
+#[derive(Debug)]
+pub enum ParseOutcome {
+    /// Buys, sells or (when recorded) transfers, in execution order
+    Trades(Vec<Trade>),
+    /// A creation-only transaction; the new token(s)
+    Creation(Vec<Token>),
+    /// Pump.fun is not invoked, neither directly nor via CPI
+    NotPumpFun,
+    /// The RPC returned no `meta`, so balances can't be diffed
+    MissingMeta,
+    /// Trade instructions ran but no pump.fun token balance changed
+    NoBalanceChange,
+    /// Only recognised non-trade instructions (setParams, extendAccount, ...)
+    NonTrade(PumpFunInstruction),
+    /// A pump.fun instruction with a discriminator we don't know
+    UnknownInstruction,
+    /// Reported for a program we subscribe to but have no parser for yet
+    UnsupportedProgram(String),
+}
+
+/// Parse a transaction with the parser for the program it was reported for.
+/// `None` (sources that don't say, like webhooks and backfill) means pump.fun.
+pub fn parse_transaction(
+    tx: &TransactionResult,
+    program_id: Option<&str>,
+    current_sol_price: f64,
+    options: ParseOptions,
+) -> Result<ParseOutcome> {
+    match program_id {
+        None | Some(PUMP_FUN_PROGRAM_ID) => {
+            parse_pump_fun_transaction(tx, current_sol_price, options)
+        }
+        Some(other) => Ok(ParseOutcome::UnsupportedProgram(other.to_string())),
+    }
+}
+
+/// Everything the worker would parse out of one transaction, for inspecting
+/// the parser without a database (`replay-file --dry-run`)
+#[derive(Debug, Serialize)]
+pub struct ParsedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub tokens: Vec<Token>,
+    pub migration: Option<Migration>,
+    /// Which `ParseOutcome` `parse_transaction` returned, or its error
+    pub outcome: String,
+    pub trades: Vec<Trade>,
+}
+
+/// Run every parser the worker runs on `tx`, the way `process_and_save` does
+pub fn parse_all(
+    tx: &TransactionResult,
+    program_id: Option<&str>,
+    current_sol_price: f64,
+    options: ParseOptions,
+) -> ParsedTransaction {
+    let (outcome, trades) = match parse_transaction(tx, program_id, current_sol_price, options) {
+        Ok(ParseOutcome::Trades(trades)) => ("trades".to_string(), trades),
+        Ok(ParseOutcome::Creation(_)) => ("creation".to_string(), Vec::new()),
+        Ok(ParseOutcome::NotPumpFun) => ("not_pump_fun".to_string(), Vec::new()),
+        Ok(ParseOutcome::MissingMeta) => ("missing_meta".to_string(), Vec::new()),
+        Ok(ParseOutcome::NoBalanceChange) => ("no_balance_change".to_string(), Vec::new()),
+        Ok(ParseOutcome::NonTrade(ix)) => (format!("non_trade:{}", ix.name()), Vec::new()),
+        Ok(ParseOutcome::UnknownInstruction) => ("unknown_instruction".to_string(), Vec::new()),
+        Ok(ParseOutcome::UnsupportedProgram(program_id)) => {
+            (format!("unsupported_program:{}", program_id), Vec::new())
+        }
+        Err(e) => (format!("error: {:#}", e), Vec::new()),
+    };
+
+    ParsedTransaction {
+        signature: tx
+            .transaction
+            .signatures
+            .first()
+            .cloned()
+            .unwrap_or_default(),
+        slot: tx.slot,
+        tokens: parse_token_creation(tx, &options.curve).unwrap_or_default(),
+        migration: parse_migration(tx),
+        outcome,
+        trades,
+    }
+}
 
 /// Parse a Helius transaction and extract trade data
 /// `current_sol_price`: Real-time SOL/USD price from your worker cache
+/// `options`: How to treat transfers, inconsistent balance entries and WSOL legs
 pub fn parse_pump_fun_transaction(
     tx: &TransactionResult,
     current_sol_price: f64,
-) -> Result<Option<Trade>> {
+    options: ParseOptions,
+) -> Result<ParseOutcome> {
     // 1. Basic Validation
     let signature = tx
         .transaction
         .signatures
         .first()
-        .context("No signature found")?
+        .ok_or_else(|| IndexerError::ParseError("No signature found".to_string()))?
         .clone();
 
-    // Check if this transaction interacts with Pump.fun
-    let is_pump_fun = tx
-        .transaction
-        .message
-        .instructions
-        .iter()
-        .any(|ix| ix.program_id == PUMP_FUN_PROGRAM_ID);
-
-    if !is_pump_fun {
-        return Ok(None);
+    // Check if this transaction interacts with Pump.fun (directly or via CPI)
+    if !invokes_pump_fun(tx) {
+        return Ok(ParseOutcome::NotPumpFun);
     }
 
     // 2. Get Timestamp
@@ -43,105 +238,497 @@ pub fn parse_pump_fun_transaction(
         None => Utc::now(),
     };
 
-    // 3. Parse Trade via Balance Changes
-    if let Some(meta) = &tx.meta {
-        // Safe access to Option<Vec<TokenBalance>>
-        let empty_vec = vec![];
-        let post_balances = meta.post_token_balances.as_ref().unwrap_or(&empty_vec);
-        let pre_balances = meta.pre_token_balances.as_ref().unwrap_or(&empty_vec);
-
-        // Iterate over specific balance entries to find the trade
-        for post in post_balances {
-            // Ignore Wrapped SOL (we want the pump.fun token)
-            if post.mint == SOL_MINT {
-                continue;
-            }
+    // 3. Parse Trade via Token Movements
+    let Some(meta) = &tx.meta else {
+        return Ok(ParseOutcome::MissingMeta);
+    };
+
+    // Prefer the curve <-> user SPL transfers themselves; fall back to
+    // diffing token balances when the RPC didn't parse them
+    let mut movements = parsed_trade_movements(tx);
+    if movements.is_empty() {
+        movements = balance_diff_movements(meta, options.ui_amount_check)?;
+        attribute_to_instructions(tx, &mut movements);
+        // The curve's own token account is never the trader; on its own it
+        // moves at creation (the minted supply) and migration (emptied)
+        movements.retain(|m| derive_bonding_curve(&m.mint).is_none_or(|curve| curve != m.owner));
+    }
+    movements.sort_by_key(|m| m.position);
+    let moves_per_mint = |mint: &str| movements.iter().filter(|m| m.mint == mint).count();
+    let single_trade_mints: Vec<String> = movements
+        .iter()
+        .filter(|m| moves_per_mint(&m.mint) == 1)
+        .map(|m| m.mint.clone())
+        .collect();
 
-            // Find the corresponding Pre-Balance
-            let pre = pre_balances
-                .iter()
-                .find(|p| p.account_index == post.account_index && p.mint == post.mint);
+    // Each TradeEvent belongs to one trade: hand them out in execution order
+    let mut events: Vec<Option<TradeEvent>> =
+        decode_trade_events(meta).into_iter().map(Some).collect();
 
-            let pre_amount: i64 = pre
-                .map(|p| p.ui_token_amount.amount.parse().unwrap_or(0))
-                .unwrap_or(0);
-            let post_amount: i64 = post.ui_token_amount.amount.parse().unwrap_or(0);
+    let ordered = execution_order(tx);
+    let compute_budget = parse_compute_budget(tx);
 
-            let diff = post_amount - pre_amount;
+    let mut trades = Vec::new();
+    for movement in movements {
+        // Balance diffs we couldn't tie to an instruction can't be told
+        // apart, so keep only the first like before multi-trade support
+        if movement.position.is_none() && !trades.is_empty() {
+            break;
+        }
+        let position = movement.position.unwrap_or_default();
+        let diff = movement.amount;
 
-            // If balance didn't change, this isn't the trade
-            if diff == 0 {
-                continue;
+        // -------------------------------------------------------
+        // FOUND THE TRADE
+        // -------------------------------------------------------
+        let token_mint = movement.mint;
+        let user_wallet = movement.owner;
+
+        // The program's own TradeEvent, when emitted, is exact: prefer its
+        // amounts and reserves over balance-derived ones
+        let event = events
+            .iter_mut()
+            .find(|e| {
+                e.as_ref()
+                    .is_some_and(|e| e.mint == token_mint && e.user == user_wallet)
+            })
+            .and_then(Option::take);
+
+        let (token_amount_abs, is_buy) = match &event {
+            Some(e) => (e.token_amount, e.is_buy),
+            None => (diff.unsigned_abs(), diff > 0), // Balance went UP = Buy, DOWN = Sell
+        };
+
+        let ix_accounts = movement
+            .position
+            .and_then(|p| ordered.iter().find(|(position, _)| *position == p))
+            .and_then(|(_, ix)| ix.accounts.as_ref());
+        let bonding_curve = ix_accounts
+            .and_then(|a| a.get(TRADE_BONDING_CURVE_INDEX))
+            .cloned();
+
+        // 4. Calculate SOL Amount used in the trade. What entered or left the
+        // curve is exact; the trader's side also carries tips and rent (its
+        // network fee is taken back out). With several trades on one curve
+        // its delta is their net, so then only the trader's side will do.
+        let sol_amount_abs = match &event {
+            Some(e) => e.sol_amount,
+            None => single_trade_mints
+                .contains(&token_mint)
+                .then(|| {
+                    let curve = bonding_curve
+                        .clone()
+                        .or_else(|| derive_bonding_curve(&token_mint))?;
+                    calculate_curve_sol_change(meta, &curve, &tx.transaction.message.account_keys)
+                })
+                .flatten()
+                .unwrap_or_else(|| {
+                    let wsol = match options.wsol_policy {
+                        WsolPolicy::Prefer => calculate_wsol_change(meta, &user_wallet),
+                        WsolPolicy::Ignore => None,
+                    };
+                    wsol.unwrap_or_else(|| {
+                        calculate_sol_change(
+                            meta,
+                            &user_wallet,
+                            &tx.transaction.message.account_keys,
+                        )
+                    })
+                }),
+        };
+
+        // No SOL moved: an airdrop or transfer, not a trade against the curve
+        let is_transfer = sol_amount_abs == 0;
+        if is_transfer && options.transfer_policy == TransferPolicy::Skip {
+            continue;
+        }
+
+        // 5. Find Bonding Curve Reserves (for tracking market cap/bonding progress)
+        let (virtual_sol, virtual_token) = match &event {
+            Some(e) => (e.virtual_sol_reserves, e.virtual_token_reserves),
+            None => {
+                let (real_sol_reserves, real_token_reserves) = find_bonding_curve_reserves(
+                    meta,
+                    &token_mint,
+                    movement.account_index,
+                    &tx.transaction.message.account_keys,
+                )?;
+
+                // Calculate Virtual Reserves (Pump.fun constant product formula)
+                // Real token reserves are usually accurate enough for analytics
+                (
+                    virtual_sol_from_real(real_sol_reserves, &options.curve),
+                    real_token_reserves,
+                )
             }
+        };
 
-            // -------------------------------------------------------
-            // FOUND THE TRADE
-            // -------------------------------------------------------
-            let token_mint = post.mint.clone();
-            let user_wallet = post.owner.clone().unwrap_or_default();
+        let timestamp = event
+            .as_ref()
+            .and_then(|e| DateTime::from_timestamp(e.timestamp, 0))
+            .unwrap_or(timestamp);
 
-            let token_amount_abs = diff.abs() as u64;
-            let is_buy = diff > 0; // Balance went UP = Buy, DOWN = Sell
+        // 6. Convert to Decimals
+        let decimal_token = Decimal::from(token_amount_abs);
+        let decimal_sol = Decimal::from(sol_amount_abs);
 
-            // 4. Calculate SOL Amount used in the trade
-            let sol_amount_abs =
-                calculate_sol_change(meta, &user_wallet, &tx.transaction.message.account_keys);
+        let accounts = options.store_accounts.then(|| {
+            let fee_recipient = ix_accounts
+                .and_then(|a| a.get(TRADE_FEE_RECIPIENT_INDEX))
+                .or(event
+                    .as_ref()
+                    .and_then(|e| Some(&e.fees.as_ref()?.fee_recipient)));
+            Json(TradeAccounts {
+                trader: user_wallet.clone(),
+                mint: token_mint.clone(),
+                bonding_curve: bonding_curve.clone(),
+                fee_recipient: fee_recipient.cloned(),
+            })
+        });
 
-            // 5. Find Bonding Curve Reserves (for tracking market cap/bonding progress)
-            let (real_sol_reserves, real_token_reserves) = find_bonding_curve_reserves(
-                meta,
-                &token_mint,
-                post.account_index as i64,
-                &tx.transaction.message.account_keys,
-            );
+        // Creator's share, when the program's TradeEvent reports one
+        let creator_fee = event
+            .and_then(|event| event.fees)
+            .map_or(0, |fees| fees.creator_fee);
 
-            // Calculate Virtual Reserves (Pump.fun constant product formula)
-            let virtual_sol = real_sol_reserves + VIRTUAL_SOL_OFFSET;
-            let virtual_token = real_token_reserves; // Usually accurate enough for analytics
+        // 7. Calculate Prices per whole token (transfers carry no price, and
+        // one that can't be computed is left unset rather than zero)
+        let price_sol = if is_transfer {
+            None
+        } else {
+            safe_decimal(price_from_amounts(decimal_sol, decimal_token), "price_sol")
+        };
 
-            // 6. Convert to Decimals
-            let decimal_token = Decimal::from_u64(token_amount_abs).unwrap_or(Decimal::ZERO);
-            let decimal_sol = Decimal::from_u64(sol_amount_abs).unwrap_or(Decimal::ZERO);
+        let price_usd =
+            price_sol.and_then(|p| safe_decimal(sol_to_usd(p, current_sol_price), "price_usd"));
 
-            // 7. Calculate Prices
-            let price_sol = if !decimal_token.is_zero() {
-                Some(decimal_sol / decimal_token)
+        trades.push(Trade {
+            signature: signature.clone(),
+            token_mint,
+            sol_amount: decimal_sol,
+            token_amount: decimal_token,
+            is_buy,
+            user_wallet,
+            timestamp,
+            virtual_sol_reserves: Decimal::from(virtual_sol),
+            virtual_token_reserves: Decimal::from(virtual_token),
+            price_sol,
+            price_usd,
+            // Filled in by the worker, which knows where the rate came from
+            sol_price_usd: None,
+            sol_price_at: None,
+            usd_amount: None,
+            creator_fee_sol: Decimal::from(creator_fee),
+            track_volume: !is_transfer,
+            ix_name: if is_transfer {
+                "transfer".to_string()
+            } else if is_buy {
+                "buy".to_string()
             } else {
-                Some(Decimal::ZERO)
-            };
+                "sell".to_string()
+            },
+            slot: tx.slot as i64,
+            instruction_index: position.index,
+            inner_instruction_index: position.inner_index,
+            accounts,
+            fee_lamports: i64::try_from(meta.fee).ok(),
+            compute_units: meta
+                .compute_units_consumed
+                .and_then(|units| i64::try_from(units).ok()),
+            compute_unit_price: compute_budget
+                .unit_price
+                .and_then(|price| i64::try_from(price).ok()),
+        });
+    }
 
-            let price_usd = price_sol.map(|p| {
-                let sol_price_dec = Decimal::from_f64(current_sol_price).unwrap_or(Decimal::ZERO);
-                p * sol_price_dec
-            });
+    if !trades.is_empty() {
+        return Ok(ParseOutcome::Trades(trades));
+    }
+
+    // 4. No token moved: explain why
+    if let Some(ix) = known_non_trade_instruction(tx) {
+        if matches!(
+            ix,
+            PumpFunInstruction::Create | PumpFunInstruction::CreateV2
+        ) {
+            let tokens = parse_token_creation(tx, &options.curve)?;
+            if !tokens.is_empty() {
+                return Ok(ParseOutcome::Creation(tokens));
+            }
+        }
+        return Ok(ParseOutcome::NonTrade(ix));
+    }
+
+    if pump_fun_instructions(tx).any(|ix| classify_instruction(ix).is_none()) {
+        return Ok(ParseOutcome::UnknownInstruction);
+    }
+
+    Ok(ParseOutcome::NoBalanceChange)
+}
+
+/// A signed change in one wallet's balance of one mint.
+/// `account_index` is the wallet's token account in `accountKeys`;
+/// `position` the trade instruction behind it, when known.
+struct TokenMovement {
+    mint: String,
+    owner: String,
+    account_index: i64,
+    amount: i64,
+    position: Option<InstructionPosition>,
+}
+
+/// `inner_index` of top-level instructions
+const TOP_LEVEL: i32 = -1;
+
+/// Where an instruction sits in a transaction: the top-level instruction
+/// index, and its position among that instruction's CPIs (`TOP_LEVEL` for
+/// the top-level instruction itself). Orders by execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct InstructionPosition {
+    index: i32,
+    inner_index: i32,
+}
+
+impl Default for InstructionPosition {
+    fn default() -> Self {
+        Self {
+            index: 0,
+            inner_index: TOP_LEVEL,
+        }
+    }
+}
+
+/// Every instruction, top-level and inner, in execution order
+fn execution_order(tx: &TransactionResult) -> Vec<(InstructionPosition, &Instruction)> {
+    let inner_wrappers: Vec<_> = tx
+        .meta
+        .iter()
+        .flat_map(|meta| meta.inner_instructions.iter().flatten())
+        .collect();
+
+    let mut ordered = Vec::new();
+    for (index, ix) in tx.transaction.message.instructions.iter().enumerate() {
+        let index = index as i32;
+        ordered.push((
+            InstructionPosition {
+                index,
+                inner_index: TOP_LEVEL,
+            },
+            ix,
+        ));
+        for wrapper in inner_wrappers.iter().filter(|w| w.index as i32 == index) {
+            for (inner_index, inner) in wrapper.instructions.iter().enumerate() {
+                ordered.push((
+                    InstructionPosition {
+                        index,
+                        inner_index: inner_index as i32,
+                    },
+                    inner,
+                ));
+            }
+        }
+    }
+    ordered
+}
+
+fn is_trade_instruction(ix: &Instruction) -> bool {
+    ix.program_id == PUMP_FUN_PROGRAM_ID
+        && classify_instruction(ix).is_some_and(|kind| kind.is_trade())
+}
+
+/// Helper: Tie balance-diff movements to the trade instruction for their
+/// mint and wallet. When any can be tied, the rest (the curve's own token
+/// account, unrelated transfers) are dropped.
+fn attribute_to_instructions(tx: &TransactionResult, movements: &mut Vec<TokenMovement>) {
+    let trade_ixs: Vec<_> = execution_order(tx)
+        .into_iter()
+        .filter(|(_, ix)| is_trade_instruction(ix))
+        .collect();
+
+    for movement in movements.iter_mut() {
+        movement.position = trade_ixs
+            .iter()
+            .find(|(_, ix)| {
+                ix.accounts.as_ref().is_some_and(|accounts| {
+                    accounts.get(TRADE_MINT_INDEX) == Some(&movement.mint)
+                        && accounts.get(TRADE_USER_INDEX) == Some(&movement.owner)
+                })
+            })
+            .map(|(position, _)| *position);
+    }
+
+    if movements.iter().any(|m| m.position.is_some()) {
+        movements.retain(|m| m.position.is_some());
+    }
+}
+
+/// Account positions shared by pump.fun's buy, buyExactSolIn and sell
+const TRADE_FEE_RECIPIENT_INDEX: usize = 1;
+const TRADE_MINT_INDEX: usize = 2;
+const TRADE_BONDING_CURVE_INDEX: usize = 3;
+const TRADE_CURVE_TOKEN_ACCOUNT_INDEX: usize = 4;
+const TRADE_USER_TOKEN_ACCOUNT_INDEX: usize = 5;
+const TRADE_USER_INDEX: usize = 6;
+
+/// Helper: Token movements taken from the `jsonParsed` SPL transfers between
+/// each pump.fun trade's bonding curve and user token accounts. A trade's
+/// transfer is looked for among the instructions it invoked, so repeated
+/// trades by one wallet each get their own.
+fn parsed_trade_movements(tx: &TransactionResult) -> Vec<TokenMovement> {
+    let ordered = execution_order(tx);
+    let account_keys = &tx.transaction.message.account_keys;
+
+    let mut movements = Vec::new();
+    for (i, (position, ix)) in ordered.iter().enumerate() {
+        if !is_trade_instruction(ix) {
+            continue;
+        }
+        let Some(accounts) = ix.accounts.as_ref() else {
+            continue;
+        };
+        let (Some(mint), Some(curve_ata), Some(user_ata), Some(user)) = (
+            accounts.get(TRADE_MINT_INDEX),
+            accounts.get(TRADE_CURVE_TOKEN_ACCOUNT_INDEX),
+            accounts.get(TRADE_USER_TOKEN_ACCOUNT_INDEX),
+            accounts.get(TRADE_USER_INDEX),
+        ) else {
+            continue;
+        };
+
+        // Everything this instruction ran: up to the next top-level or trade instruction
+        let invoked = ordered[i + 1..]
+            .iter()
+            .take_while(|(p, ix)| p.inner_index != TOP_LEVEL && !is_trade_instruction(ix));
+
+        let Some(amount) = invoked
+            .filter_map(|(_, ix)| ix.parsed_token_instruction())
+            .find_map(|t| match t.transfer()? {
+                (src, dst, amount) if src == curve_ata && dst == user_ata => Some(amount as i64),
+                (src, dst, amount) if src == user_ata && dst == curve_ata => Some(-(amount as i64)),
+                _ => None,
+            })
+        else {
+            continue;
+        };
+
+        let Some(account_index) = account_keys.iter().position(|k| k.pubkey == *user_ata) else {
+            continue;
+        };
+
+        movements.push(TokenMovement {
+            mint: mint.clone(),
+            owner: user.clone(),
+            account_index: account_index as i64,
+            amount,
+            position: Some(*position),
+        });
+    }
+
+    movements
+}
+
+/// Helper: Token movements from pre/post token balance diffs (excluding WSOL)
+fn balance_diff_movements(
+    meta: &TransactionMeta,
+    check: UiAmountCheck,
+) -> Result<Vec<TokenMovement>> {
+    // Safe access to Option<Vec<TokenBalance>>
+    let empty_vec = vec![];
+    let post_balances = meta.post_token_balances.as_ref().unwrap_or(&empty_vec);
+    let pre_balances = meta.pre_token_balances.as_ref().unwrap_or(&empty_vec);
+
+    let mut movements = Vec::new();
+    for post in post_balances {
+        // Ignore Wrapped SOL (we want the pump.fun token)
+        if post.mint == SOL_MINT {
+            continue;
+        }
+
+        // Find the corresponding Pre-Balance
+        let pre = pre_balances
+            .iter()
+            .find(|p| p.account_index == post.account_index && p.mint == post.mint);
+
+        let pre_amount: i64 = match pre {
+            Some(p) => parse_raw_amount(&p.ui_token_amount.amount, "pre_token_balance")?,
+            None => 0,
+        };
+        let post_amount: i64 =
+            parse_raw_amount(&post.ui_token_amount.amount, "post_token_balance")?;
+
+        // If balance didn't change, this isn't the trade
+        let diff = post_amount - pre_amount;
+        if diff == 0 {
+            continue;
+        }
 
-            return Ok(Some(Trade {
-                signature,
-                token_mint,
-                sol_amount: decimal_sol,
-                token_amount: decimal_token,
-                is_buy,
-                user_wallet,
-                timestamp,
-                virtual_sol_reserves: Decimal::from_u64(virtual_sol).unwrap_or(Decimal::ZERO),
-                virtual_token_reserves: Decimal::from_u64(virtual_token).unwrap_or(Decimal::ZERO),
-                price_sol,
-                price_usd,
-                track_volume: true,
-                ix_name: if is_buy {
-                    "buy".to_string()
-                } else {
-                    "sell".to_string()
-                },
-                slot: tx.slot as i64,
-            }));
+        if check != UiAmountCheck::Off && !ui_amounts_agree(diff, pre, post) {
+            UI_AMOUNT_MISMATCHES.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "Token {} account #{}: raw diff {} disagrees with UI amounts ({:?} -> {:?})",
+                post.mint,
+                post.account_index,
+                diff,
+                pre.and_then(ui_amount),
+                ui_amount(post)
+            );
+            if check == UiAmountCheck::Reject {
+                continue;
+            }
         }
+
+        movements.push(TokenMovement {
+            mint: post.mint.clone(),
+            owner: post.owner.clone().unwrap_or_default(),
+            account_index: post.account_index as i64,
+            amount: diff,
+            position: None,
+        });
     }
 
-    Ok(None)
+    Ok(movements)
 }
 
-/// Helper: Find the User's SOL balance change
+/// A balance's UI amount, preferring the exact string form
+fn ui_amount(balance: &TokenBalance) -> Option<Decimal> {
+    let ui = &balance.ui_token_amount;
+    ui.ui_amount_string
+        .as_deref()
+        .and_then(|s| Decimal::from_str(s).ok())
+        .or_else(|| ui.ui_amount.and_then(Decimal::from_f64))
+}
+
+/// Whether a raw-amount diff matches the UI amounts scaled by `decimals`.
+/// A missing pre-balance is a new account (zero); a balance without any UI
+/// amount, or with amounts or decimals beyond what `Decimal` holds, can't be
+/// checked and passes.
+fn ui_amounts_agree(raw_diff: i64, pre: Option<&TokenBalance>, post: &TokenBalance) -> bool {
+    let pre_ui = match pre {
+        Some(p) => ui_amount(p),
+        None => Some(Decimal::ZERO),
+    };
+    let (Some(pre_ui), Some(post_ui)) = (pre_ui, ui_amount(post)) else {
+        return true;
+    };
+
+    let decimals = u32::from(post.ui_token_amount.decimals);
+    let (Ok(expected), Ok(raw_unit)) = (
+        Decimal::try_from_i128_with_scale(i128::from(raw_diff), decimals),
+        Decimal::try_new(1, decimals),
+    ) else {
+        return true;
+    };
+    // One raw unit, plus float slack for entries that only carry `uiAmount`
+    let tolerance = raw_unit + expected.abs() * Decimal::new(1, 9);
+
+    post_ui
+        .checked_sub(pre_ui)
+        .and_then(|ui_diff| ui_diff.checked_sub(expected))
+        .is_none_or(|error| error.abs() <= tolerance)
+}
+
+/// Helper: Find the User's SOL balance change, less the network fee when
+/// they paid it
 fn calculate_sol_change(
     meta: &crate::models::helius_model::TransactionMeta,
     user_wallet: &str,
@@ -151,18 +738,87 @@ fn calculate_sol_change(
     if let Some(i) = idx {
         let pre = meta.pre_balances.get(i).copied().unwrap_or(0) as i64;
         let post = meta.post_balances.get(i).copied().unwrap_or(0) as i64;
-        return (pre - post).abs() as u64;
+        // The fee payer's network fee isn't part of any trade
+        let fee = if i == 0 { meta.fee as i64 } else { 0 };
+        return (pre - post - fee).unsigned_abs();
     }
     0
 }
 
-/// Helper: Find the Bonding Curve Account and return its (SOL Balance, Token Balance)
+/// Helper: Lamports that entered or left the bonding curve account, `None`
+/// if it isn't in the transaction or didn't change
+fn calculate_curve_sol_change(
+    meta: &TransactionMeta,
+    bonding_curve: &str,
+    account_keys: &[crate::models::helius_model::AccountKey],
+) -> Option<u64> {
+    let i = account_keys
+        .iter()
+        .position(|k| k.pubkey == bonding_curve)?;
+    let pre = *meta.pre_balances.get(i)? as i64;
+    let post = *meta.post_balances.get(i)? as i64;
+    let diff = (post - pre).unsigned_abs();
+    (diff != 0).then_some(diff)
+}
+
+/// Helper: Net wrapped-SOL change across the user's WSOL token accounts, if
+/// any of them moved. Accounts opened and closed within the transaction
+/// leave no balance record, so those routes still fall back to native SOL.
+fn calculate_wsol_change(meta: &TransactionMeta, user_wallet: &str) -> Option<u64> {
+    let empty_vec = vec![];
+    let pre_balances = meta.pre_token_balances.as_ref().unwrap_or(&empty_vec);
+    let post_balances = meta.post_token_balances.as_ref().unwrap_or(&empty_vec);
+
+    let is_user_wsol =
+        |b: &&TokenBalance| b.mint == SOL_MINT && b.owner.as_deref() == Some(user_wallet);
+    // An unreadable balance falls back to the native SOL change
+    let amount =
+        |b: &TokenBalance| parse_raw_amount::<i128>(&b.ui_token_amount.amount, "wsol_balance").ok();
+
+    let pre: i128 = pre_balances
+        .iter()
+        .filter(is_user_wsol)
+        .map(amount)
+        .sum::<Option<_>>()?;
+    let post: i128 = post_balances
+        .iter()
+        .filter(is_user_wsol)
+        .map(amount)
+        .sum::<Option<_>>()?;
+
+    let diff = (post - pre).unsigned_abs();
+    (diff != 0).then(|| u64::try_from(diff).unwrap_or(u64::MAX))
+}
+
+/// `owner`'s total balance of `mint` after the transaction, summed over its
+/// token accounts, in raw units. `Some(0)` when its accounts were emptied and
+/// closed (present before, gone after); `None` when the transaction shows no
+/// account of it at all.
+pub fn post_token_balance(meta: &TransactionMeta, mint: &str, owner: &str) -> Option<u64> {
+    let empty_vec = vec![];
+    let pre_balances = meta.pre_token_balances.as_ref().unwrap_or(&empty_vec);
+    let post_balances = meta.post_token_balances.as_ref().unwrap_or(&empty_vec);
+
+    let is_owned = |b: &&TokenBalance| b.mint == mint && b.owner.as_deref() == Some(owner);
+    let mut post = post_balances.iter().filter(is_owned).peekable();
+    if post.peek().is_none() {
+        return pre_balances.iter().any(|b| is_owned(&b)).then_some(0);
+    }
+
+    post.map(|b| b.ui_token_amount.amount.parse::<u64>().ok())
+        .try_fold(0u64, |total, amount| total.checked_add(amount?))
+}
+
+/// Helper: Find the Bonding Curve Account and return its (SOL Balance, Token
+/// Balance); `(0, 0)` if it isn't in the transaction. A balance that doesn't
+/// parse is an error, so the transaction is dead-lettered rather than stored
+/// with empty reserves.
 fn find_bonding_curve_reserves(
     meta: &crate::models::helius_model::TransactionMeta,
     mint: &str,
     user_account_index: i64,
     account_keys: &[crate::models::helius_model::AccountKey],
-) -> (u64, u64) {
+) -> Result<(u64, u64)> {
     let empty_vec = vec![];
     let post_token_balances = meta.post_token_balances.as_ref().unwrap_or(&empty_vec);
 
@@ -171,103 +827,206 @@ fn find_bonding_curve_reserves(
         .iter()
         .find(|p| p.mint == mint && p.account_index as i64 != user_account_index)
     {
-        let real_token_reserves = curve_token_account
-            .ui_token_amount
-            .amount
-            .parse::<u64>()
-            .unwrap_or(0);
+        let real_token_reserves = parse_raw_amount(
+            &curve_token_account.ui_token_amount.amount,
+            "real_token_reserves",
+        )?;
 
         // Find SOL balance of the curve owner
-        if let Some(owner_address) = &curve_token_account.owner {
-            if let Some(owner_idx) = account_keys.iter().position(|k| k.pubkey == *owner_address) {
-                let real_sol_reserves = meta.post_balances.get(owner_idx).copied().unwrap_or(0);
-                return (real_sol_reserves, real_token_reserves);
-            }
+        if let Some(owner_address) = &curve_token_account.owner
+            && let Some(owner_idx) = account_keys.iter().position(|k| k.pubkey == *owner_address)
+        {
+            let real_sol_reserves = meta.post_balances.get(owner_idx).copied().unwrap_or(0);
+            return Ok((real_sol_reserves, real_token_reserves));
         }
     }
-    (0, 0)
+    Ok((0, 0))
 }
 
-/// Parse token creation from pump.fun
-pub fn parse_token_creation(tx: &TransactionResult) -> Result<Option<Token>> {
+/// Parse token creation(s) from pump.fun. Bundled launches can create
+/// several mints in one transaction, so every one found is returned.
+pub fn parse_token_creation(tx: &TransactionResult, curve: &CurveParams) -> Result<Vec<Token>> {
     // Check if this is a pump.fun program interaction
-    let is_pump_fun = tx
+    if !invokes_pump_fun(tx) {
+        return Ok(Vec::new());
+    }
+
+    let Some(meta) = &tx.meta else {
+        return Ok(Vec::new());
+    };
+
+    // Prefer the program's own CreateEvents: exact mint, curve, creator and reserves
+    let events = decode_create_events(meta);
+    if !events.is_empty() {
+        return Ok(events
+            .into_iter()
+            .map(|event| token_from_create_event(event, tx.slot, tx.block_time, curve))
+            .collect());
+    }
+
+    // Fallback: look for token mint creation in post_token_balances
+    let empty_vec = vec![];
+    let post_balances = meta.post_token_balances.as_ref().unwrap_or(&empty_vec);
+
+    // Find new token mints (accounts that appear in post but not pre)
+    let pre_balances = meta.pre_token_balances.as_ref().unwrap_or(&empty_vec);
+
+    let mut new_mints: Vec<&str> = Vec::new();
+    for post in post_balances {
+        // Check if this is a new token (not in pre_balances)
+        let is_new = !pre_balances.iter().any(|p| p.mint == post.mint);
+
+        // Only mints a pump.fun instruction actually references
+        if is_new
+            && post.mint != SOL_MINT
+            && !new_mints.contains(&post.mint.as_str())
+            && pump_fun_instructions(tx)
+                .any(|ix| ix.accounts.iter().flatten().any(|a| *a == post.mint))
+        {
+            new_mints.push(&post.mint);
+        }
+    }
+
+    let creator_wallet = tx
         .transaction
         .message
-        .instructions
+        .account_keys
         .iter()
-        .any(|ix| ix.program_id == PUMP_FUN_PROGRAM_ID);
+        .find(|k| k.signer)
+        .map(|k| k.pubkey.clone())
+        .unwrap_or_default();
 
-    if !is_pump_fun {
-        return Ok(None);
-    }
+    // Log metadata can't be told apart per mint, so only use it for a single one
+    let single_mint = new_mints.len() == 1;
 
-    // Look for token mint creation in post_token_balances
-    if let Some(meta) = &tx.meta {
-        let empty_vec = vec![];
-        let post_balances = meta.post_token_balances.as_ref().unwrap_or(&empty_vec);
+    new_mints
+        .into_iter()
+        .map(|mint_address| {
+            // The create instruction's own arguments, else best-effort log scraping
+            let (name, symbol, uri) = match find_create_args(tx, mint_address) {
+                Some(args) => (Some(args.name), Some(args.symbol), Some(args.uri)),
+                None if single_mint => extract_metadata_from_logs(meta),
+                None => (None, None, None),
+            };
 
-        // Find new token mints (accounts that appear in post but not pre)
-        let pre_balances = meta.pre_token_balances.as_ref().unwrap_or(&empty_vec);
+            // The curve's PDA; guess from the writable accounts only if the
+            // mint isn't a valid key
+            let bonding_curve_address = derive_bonding_curve(mint_address).or_else(|| {
+                find_bonding_curve_address(&tx.transaction.message.account_keys, mint_address)
+            });
+
+            // Find bonding curve reserves
+            let (real_sol_reserves, real_token_reserves) = find_bonding_curve_reserves(
+                meta,
+                mint_address,
+                -1, // Not user account
+                &tx.transaction.message.account_keys,
+            )?;
 
-        for post in post_balances {
-            // Check if this is a new token (not in pre_balances)
-            let is_new = !pre_balances.iter().any(|p| p.mint == post.mint);
+            let virtual_sol = virtual_sol_from_real(real_sol_reserves, curve);
+            let virtual_token = real_token_reserves;
 
-            if is_new && post.mint != SOL_MINT {
-                let mint_address = post.mint.clone();
-                let creator_wallet = tx
-                    .transaction
-                    .message
-                    .account_keys
-                    .iter()
-                    .find(|k| k.signer)
-                    .map(|k| k.pubkey.clone())
-                    .unwrap_or_default();
+            Ok(Token {
+                mint_address: mint_address.to_string(),
+                name,
+                symbol,
+                uri,
+                bonding_curve_address,
+                creator_wallet: Some(creator_wallet.clone()),
+                virtual_token_reserves: Decimal::from(virtual_token),
+                virtual_sol_reserves: Decimal::from(virtual_sol),
+                real_token_reserves: Decimal::from(real_token_reserves),
+                token_total_supply: Decimal::from(curve.token_total_supply),
+                market_cap_usd: Decimal::ZERO,
+                market_cap_usd_raw: Decimal::ZERO,
+                bonding_curve_progress: Decimal::ZERO,
+                bonding_curve_progress_raw: Decimal::ZERO,
+                complete: false,
+                amm_pool_address: None,
+                creator_fee_bps: None,
+                last_trade_slot: None,
+                first_trade_at: None,
+                last_trade_at: None,
+                creation_slot: Some(tx.slot as i64),
+                sniper_count: 0,
+                bundled: false,
+                dev_sold: false,
+                graduated_at: None,
+                graduation_slot: None,
+                graduation_signature: None,
+                graduation_market_cap_usd: None,
+                image: None,
+                description: None,
+                twitter: None,
+                telegram: None,
+                website: None,
+                metadata_fetched_at: None,
+                created_at: chrono::Utc::now(),
+                updated_at: None,
+            })
+        })
+        .collect()
+}
 
-                // Extract metadata from logs
-                let (name, symbol, uri) = extract_metadata_from_logs(meta);
+/// Build a `Token` entirely from a decoded `CreateEvent`
+fn token_from_create_event(
+    event: CreateEvent,
+    slot: u64,
+    block_time: Option<i64>,
+    curve: &CurveParams,
+) -> Token {
+    // Older events carry no reserves: every curve starts from the same state
+    let initial = event.initial_state.unwrap_or(InitialCurveState {
+        virtual_token_reserves: curve.initial_virtual_token_reserves,
+        virtual_sol_reserves: curve.virtual_sol_offset,
+        real_token_reserves: curve.initial_real_token_reserves,
+        token_total_supply: curve.token_total_supply,
+    });
 
-                // Find bonding curve address (typically one of the writable accounts)
-                let bonding_curve_address =
-                    find_bonding_curve_address(&tx.transaction.message.account_keys, &mint_address);
+    let created_at = event
+        .timestamp
+        .or(block_time)
+        .and_then(|ts| DateTime::from_timestamp(ts, 0))
+        .unwrap_or(Utc::now());
 
-                // Find bonding curve reserves
-                let (real_sol_reserves, real_token_reserves) = find_bonding_curve_reserves(
-                    meta,
-                    &mint_address,
-                    -1, // Not user account
-                    &tx.transaction.message.account_keys,
-                );
-
-                let virtual_sol = real_sol_reserves + VIRTUAL_SOL_OFFSET;
-                let virtual_token = real_token_reserves;
-
-                return Ok(Some(Token {
-                    mint_address,
-                    name,
-                    symbol,
-                    uri,
-                    bonding_curve_address,
-                    creator_wallet: Some(creator_wallet),
-                    virtual_token_reserves: Decimal::from_u64(virtual_token)
-                        .unwrap_or(Decimal::ZERO),
-                    virtual_sol_reserves: Decimal::from_u64(virtual_sol).unwrap_or(Decimal::ZERO),
-                    real_token_reserves: Decimal::from_u64(real_token_reserves)
-                        .unwrap_or(Decimal::ZERO),
-                    token_total_supply: Decimal::from_u64(1_000_000_000_000_000)
-                        .unwrap_or(Decimal::ZERO), // Standard pump.fun supply
-                    market_cap_usd: Decimal::ZERO,
-                    bonding_curve_progress: Decimal::ZERO,
-                    complete: false,
-                    created_at: chrono::Utc::now(),
-                    updated_at: None,
-                }));
-            }
-        }
+    Token {
+        mint_address: event.mint,
+        name: Some(event.name),
+        symbol: Some(event.symbol),
+        uri: Some(event.uri),
+        bonding_curve_address: Some(event.bonding_curve),
+        creator_wallet: Some(event.creator.unwrap_or(event.user)),
+        virtual_token_reserves: Decimal::from(initial.virtual_token_reserves),
+        virtual_sol_reserves: Decimal::from(initial.virtual_sol_reserves),
+        real_token_reserves: Decimal::from(initial.real_token_reserves),
+        token_total_supply: Decimal::from(initial.token_total_supply),
+        market_cap_usd: Decimal::ZERO,
+        market_cap_usd_raw: Decimal::ZERO,
+        bonding_curve_progress: Decimal::ZERO,
+        bonding_curve_progress_raw: Decimal::ZERO,
+        complete: false,
+        amm_pool_address: None,
+        creator_fee_bps: None,
+        last_trade_slot: None,
+        first_trade_at: None,
+        last_trade_at: None,
+        creation_slot: Some(slot as i64),
+        sniper_count: 0,
+        bundled: false,
+        dev_sold: false,
+        graduated_at: None,
+        graduation_slot: None,
+        graduation_signature: None,
+        graduation_market_cap_usd: None,
+        image: None,
+        description: None,
+        twitter: None,
+        telegram: None,
+        website: None,
+        metadata_fetched_at: None,
+        created_at,
+        updated_at: None,
     }
-
-    Ok(None)
 }
 
 /// Extract token metadata (name, symbol, uri) from transaction logs
@@ -286,50 +1045,44 @@ fn extract_metadata_from_logs(
             // "symbol: TKN"
             // "uri: https://..."
 
-            if log.contains("name:") || log.contains("Name:") {
-                if let Some(pos) = log.find("name:").or_else(|| log.find("Name:")) {
-                    let start = pos + 5;
-                    let value = log[start..].trim().split(',').next().unwrap_or("").trim();
-                    if !value.is_empty() && value.len() < 100 {
-                        name = Some(value.to_string());
-                    }
+            if (log.contains("name:") || log.contains("Name:"))
+                && let Some(pos) = log.find("name:").or_else(|| log.find("Name:"))
+            {
+                let start = pos + 5;
+                let value = log[start..].trim().split(',').next().unwrap_or("").trim();
+                if !value.is_empty() && value.len() < 100 {
+                    name = Some(value.to_string());
                 }
             }
 
-            if log.contains("symbol:") || log.contains("Symbol:") {
-                if let Some(pos) = log.find("symbol:").or_else(|| log.find("Symbol:")) {
-                    let start = pos + 7;
-                    let value = log[start..].trim().split(',').next().unwrap_or("").trim();
-                    if !value.is_empty() && value.len() < 20 {
-                        symbol = Some(value.to_string());
-                    }
+            if (log.contains("symbol:") || log.contains("Symbol:"))
+                && let Some(pos) = log.find("symbol:").or_else(|| log.find("Symbol:"))
+            {
+                let start = pos + 7;
+                let value = log[start..].trim().split(',').next().unwrap_or("").trim();
+                if !value.is_empty() && value.len() < 20 {
+                    symbol = Some(value.to_string());
                 }
             }
 
-            if log.contains("uri:") || log.contains("Uri:") || log.contains("metadata:") {
-                if let Some(pos) = log
+            if (log.contains("uri:") || log.contains("Uri:") || log.contains("metadata:"))
+                && let Some(pos) = log
                     .find("uri:")
                     .or_else(|| log.find("Uri:"))
                     .or_else(|| log.find("metadata:"))
+            {
+                let start = pos
+                    + if log[pos..].starts_with("metadata:") {
+                        9
+                    } else {
+                        4
+                    };
+                let value = log[start..].split_whitespace().next().unwrap_or("").trim();
+                if value.starts_with("http")
+                    || value.starts_with("ipfs")
+                    || value.starts_with("ar://")
                 {
-                    let start = pos
-                        + if log[pos..].starts_with("metadata:") {
-                            9
-                        } else {
-                            4
-                        };
-                    let value = log[start..]
-                        .trim()
-                        .split_whitespace()
-                        .next()
-                        .unwrap_or("")
-                        .trim();
-                    if value.starts_with("http")
-                        || value.starts_with("ipfs")
-                        || value.starts_with("ar://")
-                    {
-                        uri = Some(value.to_string());
-                    }
+                    uri = Some(value.to_string());
                 }
             }
         }
@@ -340,6 +1093,24 @@ fn extract_metadata_from_logs(
     (None, None, None)
 }
 
+/// Whether `key` is base58 for exactly 32 bytes. Length alone won't do: keys
+/// with leading zero bytes encode to 43 characters (or fewer), not 44.
+pub fn is_valid_pubkey(key: &str) -> bool {
+    bs58::decode(key)
+        .into_vec()
+        .is_ok_and(|bytes| bytes.len() == 32)
+}
+
+/// Pump.fun's bonding curve account for `mint`: the program address derived
+/// from `["bonding-curve", mint]`. `None` if `mint` isn't a valid key.
+pub fn derive_bonding_curve(mint: &str) -> Option<String> {
+    let mint = Pubkey::from_str(mint).ok()?;
+    let program_id = Pubkey::from_str(PUMP_FUN_PROGRAM_ID).ok()?;
+    let (address, _bump) =
+        Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &program_id);
+    Some(address.to_string())
+}
+
 /// Find bonding curve address from account keys
 /// Pump.fun bonding curve is typically:
 /// - A writable PDA (Program Derived Address)
@@ -379,8 +1150,7 @@ fn find_bonding_curve_address(
         }
 
         // Bonding curve is typically at position 4-6 in Pump.fun transactions
-        // and is a base58 address (44 chars for standard Solana addresses)
-        if idx >= 3 && idx <= 7 && account.pubkey.len() == 44 {
+        if (3..=7).contains(&idx) && is_valid_pubkey(&account.pubkey) {
             candidates.push((idx, account.pubkey.clone()));
         }
     }
@@ -388,3 +1158,426 @@ fn find_bonding_curve_address(
     // Return the first valid candidate (typically position 4 or 5)
     candidates.first().map(|(_, addr)| addr.clone())
 }
+
+/// All pump.fun instructions in the transaction: top-level ones first, then
+/// those invoked via CPI (routers and aggregators only show up in `inner_instructions`).
+/// Event self-CPIs are left out: they only carry an event.
+fn pump_fun_instructions(tx: &TransactionResult) -> impl Iterator<Item = &Instruction> {
+    let inner = tx
+        .meta
+        .iter()
+        .flat_map(|meta| meta.inner_instructions.iter().flatten())
+        .flat_map(|wrapper| wrapper.instructions.iter());
+
+    tx.transaction
+        .message
+        .instructions
+        .iter()
+        .chain(inner)
+        .filter(|ix| ix.program_id == PUMP_FUN_PROGRAM_ID && !is_event_cpi(ix))
+}
+
+/// Whether `ix` is one of pump.fun's `emit_cpi!` event self-invocations
+fn is_event_cpi(ix: &Instruction) -> bool {
+    ix.data
+        .as_deref()
+        .and_then(|data| bs58::decode(data).into_vec().ok())
+        .is_some_and(|data| event_cpi_payload(&data).is_some())
+}
+
+/// Account positions in pump.fun's `migrate` (PumpSwap) and legacy
+/// `withdraw` (Raydium) instructions; only `migrate` names the new pool
+const MIGRATE_MINT_INDEX: usize = 2;
+const MIGRATE_POOL_INDEX: usize = 9;
+
+/// If the transaction migrates a completed curve off pump.fun, describe it.
+/// Legacy `withdraw` migrations create the Raydium pool in a later
+/// transaction, so they carry no pool address.
+pub fn parse_migration(tx: &TransactionResult) -> Option<Migration> {
+    let (mint_address, pool_address) = pump_fun_instructions(tx).find_map(|ix| {
+        let accounts = ix.accounts.as_ref()?;
+        let pool = match classify_instruction(ix)? {
+            PumpFunInstruction::Migrate => Some(accounts.get(MIGRATE_POOL_INDEX)?.clone()),
+            PumpFunInstruction::Withdraw => None,
+            _ => return None,
+        };
+        Some((accounts.get(MIGRATE_MINT_INDEX)?.clone(), pool))
+    })?;
+
+    let migrated_at = tx
+        .block_time
+        .and_then(|ts| DateTime::from_timestamp(ts, 0))
+        .unwrap_or_else(Utc::now);
+
+    Some(Migration {
+        signature: tx.transaction.signatures.first()?.clone(),
+        mint_address,
+        pool_address,
+        slot: tx.slot as i64,
+        migrated_at,
+        created_at: None,
+    })
+}
+
+/// Whether the transaction invokes pump.fun at all, directly or through a CPI
+pub fn invokes_pump_fun(tx: &TransactionResult) -> bool {
+    pump_fun_instructions(tx).next().is_some()
+}
+
+/// Pump.fun program instructions, identified by their 8-byte Anchor discriminator
+/// (`sha256("global:<name>")[..8]`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PumpFunInstruction {
+    Initialize,
+    SetParams,
+    Create,
+    CreateV2,
+    Buy,
+    BuyExactSolIn,
+    Sell,
+    Withdraw,
+    Migrate,
+    ExtendAccount,
+    CollectCreatorFee,
+    SetCreator,
+    UpdateGlobalAuthority,
+}
+
+impl PumpFunInstruction {
+    const DISCRIMINATORS: [([u8; 8], PumpFunInstruction); 13] = [
+        ([175, 175, 109, 31, 13, 152, 155, 237], Self::Initialize),
+        ([27, 234, 178, 52, 147, 2, 187, 141], Self::SetParams),
+        ([24, 30, 200, 40, 5, 28, 7, 119], Self::Create),
+        ([214, 144, 76, 236, 95, 139, 49, 180], Self::CreateV2),
+        ([102, 6, 61, 18, 1, 218, 235, 234], Self::Buy),
+        ([56, 252, 116, 8, 158, 223, 205, 95], Self::BuyExactSolIn),
+        ([51, 230, 133, 164, 1, 127, 131, 173], Self::Sell),
+        ([183, 18, 70, 156, 148, 109, 161, 34], Self::Withdraw),
+        ([155, 234, 231, 146, 236, 158, 162, 30], Self::Migrate),
+        ([234, 102, 194, 203, 150, 72, 62, 229], Self::ExtendAccount),
+        (
+            [20, 22, 86, 123, 198, 28, 219, 132],
+            Self::CollectCreatorFee,
+        ),
+        ([254, 148, 255, 112, 207, 142, 170, 165], Self::SetCreator),
+        (
+            [227, 181, 74, 196, 208, 21, 97, 213],
+            Self::UpdateGlobalAuthority,
+        ),
+    ];
+
+    /// Look up an instruction by the first 8 bytes of its decoded data
+    pub fn from_data(data: &[u8]) -> Option<Self> {
+        let discriminator = data.get(..8)?;
+        Self::DISCRIMINATORS
+            .iter()
+            .find(|(d, _)| d.as_slice() == discriminator)
+            .map(|(_, ix)| *ix)
+    }
+
+    /// IDL name of the instruction, used in logs
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Initialize => "initialize",
+            Self::SetParams => "setParams",
+            Self::Create => "create",
+            Self::CreateV2 => "createV2",
+            Self::Buy => "buy",
+            Self::BuyExactSolIn => "buyExactSolIn",
+            Self::Sell => "sell",
+            Self::Withdraw => "withdraw",
+            Self::Migrate => "migrate",
+            Self::ExtendAccount => "extendAccount",
+            Self::CollectCreatorFee => "collectCreatorFee",
+            Self::SetCreator => "setCreator",
+            Self::UpdateGlobalAuthority => "updateGlobalAuthority",
+        }
+    }
+
+    /// Whether the instruction moves tokens against the bonding curve
+    pub fn is_trade(&self) -> bool {
+        matches!(self, Self::Buy | Self::BuyExactSolIn | Self::Sell)
+    }
+}
+
+/// String arguments every version of `create` / `createV2` starts with
+#[derive(BorshDeserialize)]
+struct CreateArgsHeader {
+    name: String,
+    symbol: String,
+    uri: String,
+}
+
+/// Arguments of a pump.fun `create` / `createV2` instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    /// Present since the program took the creator as an argument
+    pub creator: Option<String>,
+}
+
+/// Position of the new mint in `create` / `createV2` accounts
+const CREATE_MINT_INDEX: usize = 0;
+
+/// Borsh-decode a pump.fun `create` / `createV2` instruction's arguments.
+/// `None` for any other instruction or malformed data.
+pub fn decode_create_instruction(ix: &Instruction) -> Option<CreateArgs> {
+    if !matches!(
+        classify_instruction(ix)?,
+        PumpFunInstruction::Create | PumpFunInstruction::CreateV2
+    ) {
+        return None;
+    }
+
+    let data = bs58::decode(ix.data.as_ref()?).into_vec().ok()?;
+    let mut payload = data.get(8..)?;
+    let header = CreateArgsHeader::deserialize(&mut payload).ok()?;
+    // Older program versions stop after `uri`
+    let creator = <[u8; 32]>::deserialize(&mut payload)
+        .ok()
+        .map(|key| bs58::encode(key).into_string());
+
+    Some(CreateArgs {
+        name: header.name,
+        symbol: header.symbol,
+        uri: header.uri,
+        creator,
+    })
+}
+
+/// Arguments of the `create` instruction (top-level or CPI) that made `mint`
+pub fn find_create_args(tx: &TransactionResult, mint: &str) -> Option<CreateArgs> {
+    pump_fun_instructions(tx)
+        .filter(|ix| {
+            ix.accounts
+                .as_ref()
+                .and_then(|a| a.get(CREATE_MINT_INDEX))
+                .is_some_and(|m| m == mint)
+        })
+        .find_map(decode_create_instruction)
+}
+
+/// Classify a single pump.fun instruction from its base58 `data` field.
+/// Returns `None` for other programs, missing data, or unknown discriminators.
+pub fn classify_instruction(ix: &Instruction) -> Option<PumpFunInstruction> {
+    if ix.program_id != PUMP_FUN_PROGRAM_ID {
+        return None;
+    }
+
+    let data = bs58::decode(ix.data.as_ref()?).into_vec().ok()?;
+    PumpFunInstruction::from_data(&data)
+}
+
+/// If every pump.fun instruction in the transaction (top-level and CPI) is a
+/// recognised non-trade instruction, return the first one.
+///
+/// Lets the worker tell "known admin/maintenance instruction" apart from a
+/// transaction it genuinely failed to extract a trade from.
+pub fn known_non_trade_instruction(tx: &TransactionResult) -> Option<PumpFunInstruction> {
+    let mut first = None;
+    for ix in pump_fun_instructions(tx) {
+        match classify_instruction(ix) {
+            Some(kind) if !kind.is_trade() => {
+                first.get_or_insert(kind);
+            }
+            // A trade or an instruction we can't identify: not a known non-trade
+            _ => return None,
+        }
+    }
+
+    first
+}
+
+const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+
+/// `ComputeBudgetInstruction` variant tags (first data byte)
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// What a transaction's compute budget instructions asked for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComputeBudget {
+    /// `SetComputeUnitLimit`: compute units requested
+    pub unit_limit: Option<u32>,
+    /// `SetComputeUnitPrice`: priority fee in micro-lamports per compute unit
+    pub unit_price: Option<u64>,
+}
+
+/// Decode the transaction's `SetComputeUnitLimit` / `SetComputeUnitPrice`
+/// instructions. The runtime only honours them at the top level and rejects
+/// duplicates, so the first of each is taken.
+pub fn parse_compute_budget(tx: &TransactionResult) -> ComputeBudget {
+    decode_compute_budget(
+        tx.transaction
+            .message
+            .instructions
+            .iter()
+            .filter(|ix| ix.program_id == COMPUTE_BUDGET_PROGRAM_ID)
+            .filter_map(|ix| ix.data.as_deref()),
+    )
+}
+
+/// `parse_compute_budget` over the base58 data of the compute budget
+/// program's top-level instructions
+pub fn decode_compute_budget<'a>(data: impl IntoIterator<Item = &'a str>) -> ComputeBudget {
+    let mut budget = ComputeBudget::default();
+    let payloads = data
+        .into_iter()
+        .filter_map(|data| bs58::decode(data).into_vec().ok());
+
+    for data in payloads {
+        match data.split_first() {
+            Some((&SET_COMPUTE_UNIT_LIMIT, rest)) => {
+                if let Ok(bytes) = <[u8; 4]>::try_from(rest) {
+                    budget.unit_limit.get_or_insert(u32::from_le_bytes(bytes));
+                }
+            }
+            Some((&SET_COMPUTE_UNIT_PRICE, rest)) => {
+                if let Ok(bytes) = <[u8; 8]>::try_from(rest) {
+                    budget.unit_price.get_or_insert(u64::from_le_bytes(bytes));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    budget
+}
+
+/// Created tokens and trades of a Helius enhanced transaction, read from the
+/// events pump.fun emits by self-CPI. The payload has no logs or token
+/// balances, so a trade that emitted no event can't be recovered from it.
+pub fn parse_enhanced_transaction(
+    tx: &EnhancedTransaction,
+    current_sol_price: f64,
+    options: ParseOptions,
+) -> (Vec<Token>, Vec<Trade>) {
+    let compute_budget = decode_compute_budget(
+        tx.instructions
+            .iter()
+            .filter(|ix| ix.program_id == COMPUTE_BUDGET_PROGRAM_ID)
+            .map(|ix| ix.data.as_str()),
+    );
+
+    let mut tokens = Vec::new();
+    let mut trades = Vec::new();
+    for (index, ix) in tx.instructions.iter().enumerate() {
+        let index = index as i32;
+        // An event belongs to the last pump.fun trade instruction before it:
+        // this one, or a CPI when a router called the program
+        let mut position = InstructionPosition {
+            index,
+            inner_index: TOP_LEVEL,
+        };
+        let mut trade_accounts: &[String] = &ix.accounts;
+
+        let inner = ix
+            .inner_instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, inner)| inner.program_id == PUMP_FUN_PROGRAM_ID);
+        for (inner_index, inner) in inner {
+            let Ok(data) = bs58::decode(&inner.data).into_vec() else {
+                continue;
+            };
+            let Some(payload) =
+                event_cpi_payload(&data).filter(|_| is_event_authority(&inner.accounts))
+            else {
+                if PumpFunInstruction::from_data(&data).is_some_and(|kind| kind.is_trade()) {
+                    position = InstructionPosition {
+                        index,
+                        inner_index: inner_index as i32,
+                    };
+                    trade_accounts = &inner.accounts;
+                }
+                continue;
+            };
+
+            if let Some(event) = decode_create_event_data(payload) {
+                tokens.push(token_from_create_event(
+                    event,
+                    tx.slot,
+                    tx.timestamp,
+                    &options.curve,
+                ));
+            } else if let Some(event) = decode_trade_event_data(payload) {
+                trades.push(trade_from_event(
+                    tx,
+                    event,
+                    position,
+                    trade_accounts,
+                    compute_budget,
+                    current_sol_price,
+                    options,
+                ));
+            }
+        }
+    }
+
+    (tokens, trades)
+}
+
+/// Build a `Trade` entirely from a decoded `TradeEvent` of an enhanced
+/// transaction; `ix_accounts` are those of the trade instruction behind it
+fn trade_from_event(
+    tx: &EnhancedTransaction,
+    event: TradeEvent,
+    position: InstructionPosition,
+    ix_accounts: &[String],
+    compute_budget: ComputeBudget,
+    current_sol_price: f64,
+    options: ParseOptions,
+) -> Trade {
+    let decimal_token = Decimal::from(event.token_amount);
+    let decimal_sol = Decimal::from(event.sol_amount);
+    let price_sol = safe_decimal(price_from_amounts(decimal_sol, decimal_token), "price_sol");
+    let price_usd =
+        price_sol.and_then(|p| safe_decimal(sol_to_usd(p, current_sol_price), "price_usd"));
+
+    let accounts = options.store_accounts.then(|| {
+        Json(TradeAccounts {
+            trader: event.user.clone(),
+            mint: event.mint.clone(),
+            bonding_curve: ix_accounts.get(TRADE_BONDING_CURVE_INDEX).cloned(),
+            fee_recipient: ix_accounts
+                .get(TRADE_FEE_RECIPIENT_INDEX)
+                .or(event.fees.as_ref().map(|fees| &fees.fee_recipient))
+                .cloned(),
+        })
+    });
+
+    Trade {
+        signature: tx.signature.clone(),
+        token_mint: event.mint,
+        sol_amount: decimal_sol,
+        token_amount: decimal_token,
+        is_buy: event.is_buy,
+        user_wallet: event.user,
+        timestamp: DateTime::from_timestamp(event.timestamp, 0)
+            .or_else(|| DateTime::from_timestamp(tx.timestamp?, 0))
+            .unwrap_or(Utc::now()),
+        virtual_sol_reserves: Decimal::from(event.virtual_sol_reserves),
+        virtual_token_reserves: Decimal::from(event.virtual_token_reserves),
+        price_sol,
+        price_usd,
+        // Filled in by the worker, which knows where the rate came from
+        sol_price_usd: None,
+        sol_price_at: None,
+        usd_amount: None,
+        creator_fee_sol: Decimal::from(event.fees.map_or(0, |fees| fees.creator_fee)),
+        track_volume: true,
+        ix_name: if event.is_buy { "buy" } else { "sell" }.to_string(),
+        slot: tx.slot as i64,
+        instruction_index: position.index,
+        inner_instruction_index: position.inner_index,
+        accounts,
+        fee_lamports: i64::try_from(tx.fee).ok(),
+        // Not part of the enhanced payload
+        compute_units: None,
+        compute_unit_price: compute_budget
+            .unit_price
+            .and_then(|price| i64::try_from(price).ok()),
+    }
+}
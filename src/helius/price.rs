@@ -0,0 +1,313 @@
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde_json::{Value, json};
+use std::future::Future;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use crate::pricing::{PricingError, parse_raw_amount};
+
+const SOL_USD_FEED_ID: &str = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+const DEFAULT_PYTH_HERMES_URL: &str = "https://hermes.pyth.network";
+const DEFAULT_PYTH_WS_URL: &str = "wss://hermes.pyth.network/ws";
+/// SOL/USD publishes several times a second; this much silence means the
+/// stream is dead
+const PYTH_STREAM_STALE: Duration = Duration::from_secs(30);
+/// Exponents a USD price feed can sensibly have; anything else is a
+/// malformed update
+const PYTH_EXPO_RANGE: RangeInclusive<i64> = -18..=0;
+
+/// A source of the current SOL/USD price
+pub trait PriceProvider {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+
+    fn get_sol_usd(&self) -> impl Future<Output = Result<f64>> + Send;
+}
+
+/// Pyth Hermes latest price update, from `PYTH_HERMES_URL` (default the
+/// public Hermes instance)
+pub struct Pyth {
+    client: reqwest::Client,
+    hermes_url: String,
+}
+
+impl Pyth {
+    pub fn from_env(client: reqwest::Client) -> Self {
+        let hermes_url = std::env::var("PYTH_HERMES_URL")
+            .unwrap_or_else(|_| DEFAULT_PYTH_HERMES_URL.to_string());
+        Self { client, hermes_url }
+    }
+}
+
+impl PriceProvider for Pyth {
+    fn name(&self) -> &'static str {
+        "pyth"
+    }
+
+    async fn get_sol_usd(&self) -> Result<f64> {
+        let url = format!(
+            "{}/v2/updates/price/latest?ids[]={}",
+            self.hermes_url.trim_end_matches('/'),
+            SOL_USD_FEED_ID
+        );
+        let data: Value = get_json(&self.client, &url).await?;
+        pyth_latest_price(&data)
+    }
+}
+
+/// The SOL/USD price in a Hermes `/v2/updates/price/latest` response
+pub fn pyth_latest_price(data: &Value) -> Result<f64> {
+    let price_data = data
+        .pointer("/parsed/0/price")
+        .context("Pyth response has no price")?;
+    pyth_price(price_data).context("Failed to parse Pyth price data")
+}
+
+/// A Pyth `price` object (`{"price": "...", "expo": -8, ...}`) as a float:
+/// `price * 10^expo`, scaled exactly as a decimal before converting.
+/// Exponents outside [`PYTH_EXPO_RANGE`] and non-positive prices are
+/// rejected.
+fn pyth_price(price_data: &Value) -> Result<f64, PricingError> {
+    let price = price_data
+        .get("price")
+        .and_then(Value::as_str)
+        .ok_or(PricingError::InvalidAmount("pyth price"))?;
+    let price: i64 = parse_raw_amount(price, "pyth price")?;
+    let expo = price_data
+        .get("expo")
+        .and_then(Value::as_i64)
+        .ok_or(PricingError::InvalidAmount("pyth expo"))?;
+    if !PYTH_EXPO_RANGE.contains(&expo) {
+        return Err(PricingError::InvalidExponent(expo));
+    }
+    if price <= 0 {
+        return Err(PricingError::InvalidSolPrice);
+    }
+
+    // The range check keeps the scale within Decimal's 28 digits
+    Decimal::new(price, expo.unsigned_abs() as u32)
+        .to_f64()
+        .ok_or(PricingError::Overflow)
+}
+
+/// Subscribe to SOL/USD on Pyth's Hermes WebSocket (`PYTH_WS_URL`) and call `on_price` with
+/// every update. Returns `Ok` on shutdown and an error once the connection
+/// drops or goes silent; reconnecting is up to the caller.
+pub async fn stream_pyth_sol_usd(
+    mut on_price: impl FnMut(f64),
+    shutdown: &CancellationToken,
+) -> Result<()> {
+    let url = std::env::var("PYTH_WS_URL").unwrap_or_else(|_| DEFAULT_PYTH_WS_URL.to_string());
+    let (ws_stream, _) = connect_async(&url).await?;
+    let (mut write, mut read) = ws_stream.split();
+
+    let request = json!({ "type": "subscribe", "ids": [SOL_USD_FEED_ID] });
+    write
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+
+    loop {
+        let message = tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            message = tokio::time::timeout(PYTH_STREAM_STALE, read.next()) => message,
+        };
+        let message = match message {
+            Ok(Some(Ok(message))) => message,
+            Ok(Some(Err(e))) => return Err(e.into()),
+            Ok(None) => return Err(anyhow::anyhow!("Pyth stream closed")),
+            Err(_) => {
+                return Err(anyhow::anyhow!(
+                    "No Pyth update for {:?}, stream is stale",
+                    PYTH_STREAM_STALE
+                ));
+            }
+        };
+
+        match message {
+            Message::Text(text) => {
+                let Ok(data) = serde_json::from_str::<Value>(&text) else {
+                    continue;
+                };
+                match data.get("type").and_then(|t| t.as_str()) {
+                    Some("price_update") => {
+                        if let Some(price) = data.pointer("/price_feed/price") {
+                            match pyth_price(price) {
+                                Ok(price) => on_price(price),
+                                Err(e) => warn!("Ignoring Pyth update: {}", e),
+                            }
+                        }
+                    }
+                    Some("response")
+                        if data.get("status").and_then(|s| s.as_str()) != Some("success") =>
+                    {
+                        return Err(anyhow::anyhow!("Pyth subscription rejected: {}", text));
+                    }
+                    _ => {}
+                }
+            }
+            Message::Ping(payload) => write.send(Message::Pong(payload)).await?,
+            Message::Close(_) => return Err(anyhow::anyhow!("Pyth stream closed")),
+            _ => {}
+        }
+    }
+}
+
+/// CoinGecko simple price API
+pub struct CoinGecko {
+    client: reqwest::Client,
+}
+
+impl PriceProvider for CoinGecko {
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    async fn get_sol_usd(&self) -> Result<f64> {
+        let url = "https://api.coingecko.com/api/v3/simple/price?ids=solana&vs_currencies=usd";
+        let data: Value = get_json(&self.client, url).await?;
+
+        data.pointer("/solana/usd")
+            .and_then(|p| p.as_f64())
+            .context("Failed to parse CoinGecko price")
+    }
+}
+
+/// Binance SOL/USDT ticker (USDT taken as USD)
+pub struct Binance {
+    client: reqwest::Client,
+}
+
+impl PriceProvider for Binance {
+    fn name(&self) -> &'static str {
+        "binance"
+    }
+
+    async fn get_sol_usd(&self) -> Result<f64> {
+        let url = "https://api.binance.com/api/v3/ticker/price?symbol=SOLUSDT";
+        let data: Value = get_json(&self.client, url).await?;
+
+        data.get("price")
+            .and_then(|p| p.as_str())
+            .and_then(|p| p.parse().ok())
+            .context("Failed to parse Binance price")
+    }
+}
+
+/// A constant price, for reproducible offline runs (`replay-file --sol-price`)
+pub struct Fixed(pub f64);
+
+impl PriceProvider for Fixed {
+    fn name(&self) -> &'static str {
+        "fixed"
+    }
+
+    async fn get_sol_usd(&self) -> Result<f64> {
+        Ok(self.0)
+    }
+}
+
+/// One of the built-in providers; lets a configured list of them be tried in
+/// order without boxing futures
+pub enum AnyPriceProvider {
+    Pyth(Pyth),
+    CoinGecko(CoinGecko),
+    Binance(Binance),
+    Fixed(Fixed),
+}
+
+impl AnyPriceProvider {
+    fn from_name(name: &str, client: &reqwest::Client) -> Option<Self> {
+        let client = client.clone();
+        match name {
+            "pyth" => Some(Self::Pyth(Pyth::from_env(client))),
+            "coingecko" => Some(Self::CoinGecko(CoinGecko { client })),
+            "binance" => Some(Self::Binance(Binance { client })),
+            _ => None,
+        }
+    }
+}
+
+impl PriceProvider for AnyPriceProvider {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Pyth(p) => p.name(),
+            Self::CoinGecko(p) => p.name(),
+            Self::Binance(p) => p.name(),
+            Self::Fixed(p) => p.name(),
+        }
+    }
+
+    async fn get_sol_usd(&self) -> Result<f64> {
+        match self {
+            Self::Pyth(p) => p.get_sol_usd().await,
+            Self::CoinGecko(p) => p.get_sol_usd().await,
+            Self::Binance(p) => p.get_sol_usd().await,
+            Self::Fixed(p) => p.get_sol_usd().await,
+        }
+    }
+}
+
+/// Providers in priority order
+pub struct PriceProviders(Vec<AnyPriceProvider>);
+
+impl PriceProviders {
+    /// Read from `PRICE_SOURCES` (comma-separated, default
+    /// `pyth,coingecko,binance`); unknown names are skipped
+    pub fn from_env(client: &reqwest::Client) -> Self {
+        let sources =
+            std::env::var("PRICE_SOURCES").unwrap_or_else(|_| "pyth,coingecko,binance".to_string());
+        let mut providers: Vec<AnyPriceProvider> = sources
+            .split(',')
+            .filter_map(|name| {
+                let provider = AnyPriceProvider::from_name(name.trim(), client);
+                if provider.is_none() {
+                    warn!("Unknown price source '{}', skipping", name.trim());
+                }
+                provider
+            })
+            .collect();
+        if providers.is_empty() {
+            providers.push(AnyPriceProvider::Pyth(Pyth::from_env(client.clone())));
+        }
+        Self(providers)
+    }
+
+    /// Always `price`, never touching the network
+    pub fn fixed(price: f64) -> Self {
+        Self(vec![AnyPriceProvider::Fixed(Fixed(price))])
+    }
+
+    /// The first price any provider returns, with its name
+    pub async fn get_sol_usd(&self) -> Result<(f64, &'static str)> {
+        let mut last_error = None;
+        for provider in &self.0 {
+            match provider.get_sol_usd().await {
+                Ok(price) if price.is_finite() && price > 0.0 => {
+                    return Ok((price, provider.name()));
+                }
+                Ok(price) => {
+                    last_error = Some(anyhow::anyhow!("{} returned {}", provider.name(), price))
+                }
+                Err(e) => {
+                    warn!("Price source {} failed: {}", provider.name(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No price sources configured")))
+    }
+}
+
+async fn get_json(client: &reqwest::Client, url: &str) -> Result<Value> {
+    let response = client.get(url).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("price API error: {}", response.status()));
+    }
+    Ok(response.json().await?)
+}
@@ -0,0 +1,55 @@
+use rand::Rng;
+use std::time::Duration;
+
+/// Exponential backoff with jitter for RPC retries: the delay before retry
+/// `n` is `base * 2^n`, capped at `max_delay`, plus up to half of that again
+/// at random so workers that failed together don't retry together
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total tries, including the first
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Read from `RPC_MAX_ATTEMPTS` and `RPC_BACKOFF_BASE_MS`, keeping the
+    /// defaults (4 attempts, 250ms) for unset or invalid values
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let max_attempts = std::env::var("RPC_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(defaults.max_attempts);
+        let base_delay = std::env::var("RPC_BACKOFF_BASE_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .map_or(defaults.base_delay, Duration::from_millis);
+        Self {
+            max_attempts,
+            base_delay,
+            ..defaults
+        }
+    }
+
+    /// Delay before retry `retry` (0-based: 0 is the wait after the first failure)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let jitter = rand::rng().random_range(0..=exp.as_millis() as u64 / 2);
+        exp + Duration::from_millis(jitter)
+    }
+}
@@ -0,0 +1,200 @@
+use crate::helius::fetcher::EnhancedProcessor;
+use crate::helius::ingester::TransactionInfo;
+use crate::models::helius_model::{EnhancedTransaction, TransactionResult};
+use crate::redis::redis_cleint::RedisClient;
+use anyhow::{Context, Result};
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+    routing::post,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+const REDIS_STREAM: &str = "solana:transactions:stream";
+const REDIS_STREAM_MAX_LEN: usize = 100_000;
+/// How long a delivered signature is remembered, covering Helius' retries
+const SEEN_TTL_SECS: u64 = 3600;
+const SEEN_KEY_PREFIX: &str = "webhook:seen:";
+
+#[derive(Clone)]
+struct WebhookState {
+    auth_token: String,
+    redis_client: RedisClient,
+    enhanced: Arc<EnhancedProcessor>,
+}
+
+/// Receive Helius webhooks.
+///
+/// Raw-transaction webhooks (`/helius/raw`) are handed to the worker over the
+/// same Redis stream the WebSocket ingester uses. Entries in the RPC's
+/// `jsonParsed` shape are forwarded whole, so the worker skips its
+/// `getTransaction` call; anything else is forwarded by signature and
+/// fetched as usual.
+///
+/// Enhanced-transaction webhooks (`/helius`) are already parsed, so they are
+/// stored here directly, with no worker or RPC round trip.
+///
+/// Helius retries deliveries that don't get a 2xx, so signatures are
+/// deduplicated in Redis for an hour (the DB inserts are idempotent as well).
+pub async fn run_webhook(shutdown: CancellationToken) -> Result<()> {
+    let auth_token =
+        std::env::var("WEBHOOK_AUTH_TOKEN").context("WEBHOOK_AUTH_TOKEN must be set")?;
+    let addr = std::env::var("WEBHOOK_ADDR").unwrap_or_else(|_| "0.0.0.0:8080".to_string());
+    let redis_url =
+        std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+
+    let redis_client = RedisClient::new(&redis_url).await?;
+    let enhanced = Arc::new(EnhancedProcessor::init(redis_client.clone(), shutdown.clone()).await?);
+    let state = WebhookState {
+        auth_token,
+        redis_client,
+        enhanced: enhanced.clone(),
+    };
+
+    let app = Router::new()
+        .route("/helius", post(receive_enhanced))
+        .route("/helius/raw", post(receive_raw))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("Failed to bind {}", addr))?;
+    info!("Webhook receiver listening on {}", addr);
+
+    let served = axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+        .await
+        .context("Webhook server failed");
+    shutdown.cancel();
+    enhanced.finish().await;
+    served
+}
+
+/// `POST /helius/raw`: an array of transactions
+async fn receive_raw(
+    State(mut state): State<WebhookState>,
+    headers: HeaderMap,
+    Json(entries): Json<Vec<Value>>,
+) -> StatusCode {
+    if !authorized(&headers, &state.auth_token) {
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    for entry in entries {
+        let Some(info) = transaction_info(entry) else {
+            continue;
+        };
+
+        // Ignore failed transactions, like the WebSocket ingester does
+        if info.err.is_some() {
+            continue;
+        }
+
+        let seen_key = format!("{}{}", SEEN_KEY_PREFIX, info.signature);
+        match state.redis_client.set_nx(&seen_key, SEEN_TTL_SECS).await {
+            Ok(true) => {}
+            Ok(false) => continue, // Retried or duplicate delivery
+            // Ask Helius to retry rather than risk dropping it
+            Err(e) => {
+                error!("Dedup check failed: {}", e);
+                return StatusCode::SERVICE_UNAVAILABLE;
+            }
+        }
+
+        info!("Webhook: {}", info.signature);
+
+        if let Err(e) = state
+            .redis_client
+            .xadd_signature(REDIS_STREAM, &info, REDIS_STREAM_MAX_LEN)
+            .await
+        {
+            error!("Publish failed: {}", e);
+            // Forget it so the retry isn't dropped as a duplicate
+            let _ = state.redis_client.delete(&seen_key).await;
+            return StatusCode::SERVICE_UNAVAILABLE;
+        }
+    }
+
+    StatusCode::OK
+}
+
+/// `POST /helius`: an array of enhanced transactions, stored as they come
+async fn receive_enhanced(
+    State(mut state): State<WebhookState>,
+    headers: HeaderMap,
+    Json(entries): Json<Vec<EnhancedTransaction>>,
+) -> StatusCode {
+    if !authorized(&headers, &state.auth_token) {
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    for tx in entries {
+        // Ignore failed transactions, like the WebSocket ingester does
+        if tx.transaction_error.is_some() {
+            continue;
+        }
+
+        let seen_key = format!("{}{}", SEEN_KEY_PREFIX, tx.signature);
+        match state.redis_client.set_nx(&seen_key, SEEN_TTL_SECS).await {
+            Ok(true) => {}
+            Ok(false) => continue, // Retried or duplicate delivery
+            Err(e) => {
+                error!("Dedup check failed: {}", e);
+                return StatusCode::SERVICE_UNAVAILABLE;
+            }
+        }
+
+        info!("Enhanced webhook: {}", tx.signature);
+
+        match state.enhanced.process(&tx).await {
+            Ok(()) => {}
+            Err(e) if e.is_transient() => {
+                error!("Failed to store {}: {:#}", tx.signature, e);
+                // Forget it so the retry isn't dropped as a duplicate
+                let _ = state.redis_client.delete(&seen_key).await;
+                return StatusCode::SERVICE_UNAVAILABLE;
+            }
+            // Helius retrying it would fail the same way
+            Err(e) => warn!("Skipping {}: {:#}", tx.signature, e),
+        }
+    }
+
+    StatusCode::OK
+}
+
+/// Whether the request carries the shared secret as its `Authorization`
+/// header. Compared in constant time, so response timing doesn't reveal how
+/// much of a guess matched.
+pub fn authorized(headers: &HeaderMap, auth_token: &str) -> bool {
+    headers
+        .get(AUTHORIZATION)
+        .is_some_and(|value| bool::from(value.as_bytes().ct_eq(auth_token.as_bytes())))
+}
+
+/// The stream payload for one webhook entry: the full transaction when it
+/// parses as one, otherwise just its signature
+fn transaction_info(entry: Value) -> Option<TransactionInfo> {
+    let signature = entry
+        .pointer("/transaction/signatures/0")
+        .and_then(|s| s.as_str())?
+        .to_string();
+    let err = entry
+        .pointer("/meta/err")
+        .filter(|err| !err.is_null())
+        .cloned();
+    let transaction = serde_json::from_value::<TransactionResult>(entry)
+        .ok()
+        .map(Box::new);
+
+    Some(TransactionInfo {
+        signature,
+        err,
+        program_id: None,
+        transaction,
+    })
+}
@@ -1,4 +1,13 @@
+pub mod analytics;
+pub mod api;
 pub mod db;
+pub mod error;
+pub mod gateway;
+pub mod health;
 pub mod helius;
+pub mod logging;
+pub mod metrics;
 pub mod models;
+pub mod pricing;
 pub mod redis;
+pub mod tasks;
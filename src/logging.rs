@@ -0,0 +1,15 @@
+use tracing_subscriber::EnvFilter;
+
+/// Install the global `tracing` subscriber. What gets logged comes from
+/// `RUST_LOG` (default `info`); `LOG_FORMAT=json` writes one JSON object per
+/// line, span fields such as a transaction's `signature` included, for log
+/// shippers.
+pub fn init() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    match std::env::var("LOG_FORMAT").as_deref() {
+        Ok("json") => builder.json().init(),
+        _ => builder.init(),
+    }
+}
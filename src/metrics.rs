@@ -0,0 +1,295 @@
+use anyhow::{Context, Result};
+use axum::{Router, http::header::CONTENT_TYPE, response::IntoResponse, routing::get};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::{info, warn};
+
+use crate::health::{Health, health_handler};
+use crate::helius::fetcher::{ProcessingMode, processing_lag, processing_mode, rpc_circuit_state};
+use crate::helius::latency::LatencyTracker;
+use crate::helius::parser::ui_amount_mismatches;
+
+const DEFAULT_METRICS_PORT: u16 = 9464;
+/// Longest a Pushgateway push may take before the job gives up on it
+const PUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Upper bounds (seconds) of the RPC latency histogram buckets
+const LATENCY_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0];
+
+/// A monotonically increasing count
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    pub fn inc(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Fixed-bucket latency histogram, cumulative as Prometheus expects
+pub struct Histogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    const fn new() -> Self {
+        Self {
+            buckets: [const { AtomicU64::new(0) }; LATENCY_BUCKETS.len()],
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, latency: Duration) {
+        let seconds = latency.as_secs_f64();
+        for (bound, bucket) in LATENCY_BUCKETS.iter().zip(&self.buckets) {
+            if seconds <= *bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Trades written to the database (duplicates not counted)
+pub static TRADES_PROCESSED: Counter = Counter::new();
+/// Tokens saved from creation instructions
+pub static TOKENS_CREATED: Counter = Counter::new();
+/// `getTransaction` calls that failed for good (after retries)
+pub static RPC_ERRORS: Counter = Counter::new();
+/// RPC endpoints taken out of rotation after repeated failures
+pub static RPC_ENDPOINTS_BENCHED: Counter = Counter::new();
+/// Times the RPC circuit breaker opened
+pub static RPC_CIRCUIT_OPENED: Counter = Counter::new();
+/// Transactions the parser returned an error for
+pub static PARSE_ERRORS: Counter = Counter::new();
+/// On-chain amounts that failed to convert or parse (see `parse_raw_amount`)
+pub static AMOUNT_CONVERSION_FAILURES: Counter = Counter::new();
+/// Market caps and curve progress that couldn't be computed, leaving the
+/// token's stored value as it was
+pub static PRICING_FAILURES: Counter = Counter::new();
+/// Duration of each `getTransaction` request
+pub static FETCH_LATENCY: Histogram = Histogram::new();
+/// The RPC latency window timeouts are derived from, for its percentiles
+static RPC_LATENCY: OnceLock<Arc<LatencyTracker>> = OnceLock::new();
+
+/// Report `tracker`'s percentiles as `rpc_latency_seconds`. The first
+/// tracker registered in the process is the one reported; `tracker` is
+/// handed back either way.
+pub fn register_rpc_latency(tracker: Arc<LatencyTracker>) -> Arc<LatencyTracker> {
+    RPC_LATENCY.get_or_init(|| tracker.clone());
+    tracker
+}
+
+/// Everything in the Prometheus text exposition format
+pub fn render() -> String {
+    let mut out = String::new();
+
+    let counters = [
+        (
+            "trades_processed_total",
+            "Trades written to the database",
+            &TRADES_PROCESSED,
+        ),
+        (
+            "tokens_created_total",
+            "Tokens saved from creation instructions",
+            &TOKENS_CREATED,
+        ),
+        (
+            "rpc_errors_total",
+            "getTransaction calls that failed after retries",
+            &RPC_ERRORS,
+        ),
+        (
+            "rpc_endpoints_benched_total",
+            "RPC endpoints benched after repeated failures",
+            &RPC_ENDPOINTS_BENCHED,
+        ),
+        (
+            "rpc_circuit_opened_total",
+            "Times the RPC circuit breaker opened",
+            &RPC_CIRCUIT_OPENED,
+        ),
+        (
+            "parse_errors_total",
+            "Transactions the parser failed on",
+            &PARSE_ERRORS,
+        ),
+        (
+            "amount_conversion_failures_total",
+            "On-chain amounts that failed to convert or parse",
+            &AMOUNT_CONVERSION_FAILURES,
+        ),
+        (
+            "pricing_failures_total",
+            "Market caps and curve progress that couldn't be computed",
+            &PRICING_FAILURES,
+        ),
+    ];
+    for (name, help, counter) in counters {
+        let _ = writeln!(
+            out,
+            "# HELP {name} {help}\n# TYPE {name} counter\n{name} {}",
+            counter.get()
+        );
+    }
+    let _ = writeln!(
+        out,
+        "# HELP ui_amount_mismatches_total Balance entries disagreeing with their UI amount\n\
+         # TYPE ui_amount_mismatches_total counter\n\
+         ui_amount_mismatches_total {}",
+        ui_amount_mismatches()
+    );
+
+    let name = "fetch_transaction_duration_seconds";
+    let _ = writeln!(out, "# HELP {name} getTransaction request latency");
+    let _ = writeln!(out, "# TYPE {name} histogram");
+    for (bound, bucket) in LATENCY_BUCKETS.iter().zip(&FETCH_LATENCY.buckets) {
+        let _ = writeln!(
+            out,
+            "{name}_bucket{{le=\"{bound}\"}} {}",
+            bucket.load(Ordering::Relaxed)
+        );
+    }
+    let count = FETCH_LATENCY.count.load(Ordering::Relaxed);
+    let sum = FETCH_LATENCY.sum_micros.load(Ordering::Relaxed) as f64 / 1e6;
+    let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {count}");
+    let _ = writeln!(out, "{name}_sum {sum}\n{name}_count {count}");
+
+    if let Some(p) = RPC_LATENCY.get().and_then(|tracker| tracker.percentiles()) {
+        let name = "rpc_latency_seconds";
+        let _ = writeln!(
+            out,
+            "# HELP {name} Rolling RPC latency percentiles request timeouts are derived from"
+        );
+        let _ = writeln!(out, "# TYPE {name} gauge");
+        for (quantile, latency) in [("0.5", p.p50), ("0.9", p.p90), ("0.99", p.p99)] {
+            let _ = writeln!(
+                out,
+                "{name}{{quantile=\"{quantile}\"}} {}",
+                latency.as_secs_f64()
+            );
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP processing_lag_seconds How long the last stream entry read waited in the stream\n\
+         # TYPE processing_lag_seconds gauge\n\
+         processing_lag_seconds {}",
+        processing_lag().as_secs_f64()
+    );
+    let _ = writeln!(
+        out,
+        "# HELP catching_up Whether the worker is in catch-up mode\n\
+         # TYPE catching_up gauge\n\
+         catching_up {}",
+        u8::from(processing_mode() == ProcessingMode::CatchUp)
+    );
+    let _ = writeln!(
+        out,
+        "# HELP rpc_circuit_state RPC circuit breaker: 0 closed, 1 open, 2 half-open\n\
+         # TYPE rpc_circuit_state gauge\n\
+         rpc_circuit_state {}",
+        rpc_circuit_state() as u8
+    );
+
+    out
+}
+
+/// Push everything in `render` to the Pushgateway at `PUSHGATEWAY_URL`, as
+/// job `job`, replacing what the job pushed last. For one-shot commands that
+/// exit before a scrape; does nothing when `PUSHGATEWAY_URL` is unset.
+pub async fn push_metrics(job: &str) -> Result<()> {
+    let Ok(url) = std::env::var("PUSHGATEWAY_URL") else {
+        return Ok(());
+    };
+    let client = reqwest::Client::builder()
+        .timeout(PUSH_TIMEOUT)
+        .build()
+        .context("Failed to build Pushgateway client")?;
+    push_metrics_to(&client, &url, job).await
+}
+
+/// `push_metrics` to the Pushgateway at `base_url`
+pub async fn push_metrics_to(client: &reqwest::Client, base_url: &str, job: &str) -> Result<()> {
+    let url = format!("{}/metrics/job/{}", base_url.trim_end_matches('/'), job);
+    client
+        .put(&url)
+        .header(CONTENT_TYPE, "text/plain; version=0.0.4")
+        .body(render())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to push metrics to {}", url))?;
+    Ok(())
+}
+
+/// Bind the metrics endpoint and serve it on `tasks` until `shutdown`. A
+/// port that can't be bound is returned as an error; a server that fails
+/// after starting is logged.
+pub async fn spawn_metrics_server(
+    tasks: &TaskTracker,
+    shutdown: CancellationToken,
+    health: Arc<Health>,
+) -> Result<()> {
+    let listener = bind_metrics().await?;
+    tasks.spawn(async move {
+        if let Err(e) = serve_metrics(listener, shutdown, health).await {
+            warn!("Metrics endpoint stopped: {:#}", e);
+        }
+    });
+    Ok(())
+}
+
+async fn metrics_handler() -> impl IntoResponse {
+    ([(CONTENT_TYPE, "text/plain; version=0.0.4")], render())
+}
+
+/// Listen on `METRICS_PORT` (default 9464)
+async fn bind_metrics() -> Result<tokio::net::TcpListener> {
+    let port = std::env::var("METRICS_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(DEFAULT_METRICS_PORT);
+    let addr = format!("0.0.0.0:{}", port);
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("Failed to bind {}", addr))?;
+    info!("Metrics on http://{}/metrics", addr);
+    Ok(listener)
+}
+
+/// Serve `GET /metrics` and the `GET /health` probe on `listener` until
+/// `shutdown`
+async fn serve_metrics(
+    listener: tokio::net::TcpListener,
+    shutdown: CancellationToken,
+    health: Arc<Health>,
+) -> Result<()> {
+    let app = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .route("/health", get(health_handler))
+        .with_state(health);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { shutdown.cancelled().await })
+        .await
+        .context("Metrics server failed")
+}
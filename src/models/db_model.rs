@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
+use sqlx::types::Json;
 
 // ==========================================
 // DATABASE MODELS (Match SQL Schema Exactly)
@@ -23,14 +24,59 @@ pub struct Token {
     pub token_total_supply: Decimal,
 
     pub market_cap_usd: Decimal,
+    /// Unclamped market cap from the latest trade
+    pub market_cap_usd_raw: Decimal,
     pub bonding_curve_progress: Decimal,
+    pub bonding_curve_progress_raw: Decimal,
     pub complete: bool,
+    pub amm_pool_address: Option<String>,
+    /// Creator's cut of each trade; `None` until a trade reports it
+    pub creator_fee_bps: Option<i32>,
+    pub last_trade_slot: Option<i64>,
+    /// Earliest and latest trade timestamps (transfers excluded). Only
+    /// `last_trade_at` is written: the upsert folds it into both.
+    pub first_trade_at: Option<DateTime<Utc>>,
+    pub last_trade_at: Option<DateTime<Utc>>,
+
+    /// Slot of the create instruction; `None` until the creation is indexed
+    pub creation_slot: Option<i64>,
+    /// Wallets other than the creator that bought in the creation slot, and
+    /// whether there were enough of them to call the launch bundled
+    pub sniper_count: i32,
+    pub bundled: bool,
+    /// Whether the creator has sold (or transferred out) any of their tokens
+    pub dev_sold: bool,
+
+    /// Set once, by the transaction that first saw the curve complete
+    pub graduated_at: Option<DateTime<Utc>>,
+    pub graduation_slot: Option<i64>,
+    pub graduation_signature: Option<String>,
+    pub graduation_market_cap_usd: Option<Decimal>,
+
+    /// Off-chain metadata from `uri`, filled in once by the worker
+    pub image: Option<String>,
+    pub description: Option<String>,
+    pub twitter: Option<String>,
+    pub telegram: Option<String>,
+    pub website: Option<String>,
+    pub metadata_fetched_at: Option<DateTime<Utc>>,
 
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
 }
 
-/// Trades Hypertable - PRIMARY KEY (timestamp, signature)
+/// The fields we keep from a token's off-chain metadata JSON
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    pub image: Option<String>,
+    pub description: Option<String>,
+    pub twitter: Option<String>,
+    pub telegram: Option<String>,
+    pub website: Option<String>,
+}
+
+/// Trades Hypertable - PRIMARY KEY (timestamp, signature, instruction_index,
+/// inner_instruction_index)
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Trade {
     pub signature: String,
@@ -43,12 +89,204 @@ pub struct Trade {
 
     pub virtual_sol_reserves: Decimal,
     pub virtual_token_reserves: Decimal,
+    /// Execution price per whole token
     pub price_sol: Option<Decimal>,
     pub price_usd: Option<Decimal>,
+    /// SOL/USD rate `price_usd` was converted with, and when it was fetched
+    pub sol_price_usd: Option<Decimal>,
+    pub sol_price_at: Option<DateTime<Utc>>,
+    /// `sol_amount` in USD at `sol_price_usd`; `None` when the trade wasn't
+    /// priced
+    pub usd_amount: Option<Decimal>,
+    /// Lamports of `sol_amount` paid to the token creator (0 if none)
+    pub creator_fee_sol: Decimal,
 
     pub track_volume: bool,
     pub ix_name: String, // 'buy' or 'sell'
     pub slot: i64,
+    /// The trade's instruction within the transaction: top-level index, and
+    /// position among that instruction's CPIs (-1 if it is top-level itself)
+    pub instruction_index: i32,
+    pub inner_instruction_index: i32,
+    /// Accounts the trade touched, when `STORE_TRADE_ACCOUNTS` is on
+    pub accounts: Option<Json<TradeAccounts>>,
+    /// Network fee and compute units of the whole transaction, shared by
+    /// every trade in it; `None` on rows stored before they were recorded
+    pub fee_lamports: Option<i64>,
+    pub compute_units: Option<i64>,
+    /// Priority fee bid by the transaction, micro-lamports per compute unit;
+    /// `None` when it set no `SetComputeUnitPrice`
+    pub compute_unit_price: Option<i64>,
+}
+
+/// The accounts of a trade worth keeping for later re-derivation
+/// (stored as JSONB on `trades.accounts`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradeAccounts {
+    pub trader: String,
+    pub mint: String,
+    pub bonding_curve: Option<String>,
+    pub fee_recipient: Option<String>,
+}
+
+/// Position in a mint's trade history, newest first: the primary key of the
+/// last trade on a page. Serialized as an opaque string (see `Display`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TradeCursor {
+    pub timestamp: DateTime<Utc>,
+    pub signature: String,
+    pub instruction_index: i32,
+    pub inner_instruction_index: i32,
+}
+
+impl From<&Trade> for TradeCursor {
+    fn from(trade: &Trade) -> Self {
+        Self {
+            timestamp: trade.timestamp,
+            signature: trade.signature.clone(),
+            instruction_index: trade.instruction_index,
+            inner_instruction_index: trade.inner_instruction_index,
+        }
+    }
+}
+
+impl std::fmt::Display for TradeCursor {
+    /// `{timestamp}_{signature}_{instruction}_{inner}`; the timestamp keeps
+    /// full precision so the cursor round-trips exactly
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}_{}_{}_{}",
+            self.timestamp
+                .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+            self.signature,
+            self.instruction_index,
+            self.inner_instruction_index
+        )
+    }
+}
+
+impl std::str::FromStr for TradeCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut parts = s.split('_');
+        let (Some(timestamp), Some(signature), Some(ix), Some(inner), None) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            anyhow::bail!("malformed trade cursor");
+        };
+        Ok(Self {
+            timestamp: DateTime::parse_from_rfc3339(timestamp)?.with_timezone(&Utc),
+            signature: signature.to_string(),
+            instruction_index: ix.parse()?,
+            inner_instruction_index: inner.parse()?,
+        })
+    }
+}
+
+impl Serialize for TradeCursor {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TradeCursor {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// One page of a mint's trades, newest first, and where the next page starts
+/// (`None` on the last page)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradePage {
+    pub trades: Vec<Trade>,
+    pub next_cursor: Option<TradeCursor>,
+}
+
+/// How concentrated a token's supply is (not a table)
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct HolderStats {
+    pub token_mint: String,
+    /// Wallets with a non-zero balance, dust holders in the tail included
+    pub holder_count: i64,
+    /// Share of `token_total_supply` held by the 10 largest holders, percent
+    pub top10_percent: Decimal,
+    /// Gini coefficient of the tracked balances: 0 when everyone holds the
+    /// same, approaching 1 when one wallet holds everything
+    pub gini: Decimal,
+}
+
+/// A wallet that bought a token in its creation slot (not a table). SOL
+/// amounts are lamports, token amounts raw units.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Sniper {
+    pub user_wallet: String,
+    pub buy_count: i64,
+    pub sol_amount: Decimal,
+    pub token_amount: Decimal,
+}
+
+/// A wallet's profit and loss on one token, from its stored trades (not a
+/// table). SOL amounts are lamports, balance is raw token units.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WalletPnl {
+    pub token_mint: String,
+    pub user_wallet: String,
+    /// Sell proceeds minus the average cost of the tokens sold
+    pub realized_usd: Decimal,
+    /// Current value of `balance` minus its average cost
+    pub unrealized_usd: Decimal,
+    pub total_bought_sol: Decimal,
+    pub total_sold_sol: Decimal,
+    pub balance: Decimal,
+}
+
+/// One OHLCV candle aggregated from `trades` (not a table)
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Candle {
+    pub bucket_start: DateTime<Utc>,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume_sol: Decimal,
+    pub trade_count: i64,
+}
+
+/// A token's SOL volume over some window (not a table)
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TokenVolume {
+    pub token_mint: String,
+    pub volume_sol: Decimal,
+}
+
+/// A token's trading volume over some window (not a table). USD volume sums
+/// each trade at the SOL price it was stored with; trades without one are
+/// left out and counted in `unpriced_trades`.
+#[derive(Debug, Clone, Default, FromRow, Serialize, Deserialize)]
+pub struct VolumeStats {
+    pub volume_sol: Decimal,
+    pub volume_usd: Decimal,
+    pub unpriced_trades: i64,
+}
+
+/// Trade activity for one (day-of-week, hour-of-day) cell (not a table)
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct HeatmapCell {
+    /// ISO day of week: 1 = Monday ... 7 = Sunday
+    pub day_of_week: i32,
+    /// 0-23
+    pub hour_of_day: i32,
+    pub trade_count: i64,
+    pub volume_sol: Decimal,
 }
 
 /// Token Holders - PRIMARY KEY (token_mint, user_wallet)
@@ -61,6 +299,26 @@ pub struct TokenHolder {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
+/// Token holder tails - "other holders" evicted past the holder cap
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TokenHolderTail {
+    pub token_mint: String,
+    pub holder_count: i64,
+    pub balance: Decimal,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Migrations table - one row per curve migration transaction
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Migration {
+    pub signature: String,
+    pub mint_address: String,
+    pub pool_address: Option<String>,
+    pub slot: i64,
+    pub migrated_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
 /// Transactions table (optional audit log) - PRIMARY KEY (block_time, signature)
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Transaction {
@@ -70,5 +328,39 @@ pub struct Transaction {
     pub signer: String,
     pub success: bool,
     pub instruction_count: Option<i32>,
+    /// Commitment the row was fetched at; a higher one replaces it
+    pub commitment: Commitment,
     pub created_at: Option<DateTime<Utc>>,
 }
+
+/// A stored `getTransaction` result (`raw_transactions`, `STORE_RAW_TX=true`)
+#[derive(Debug, Clone, FromRow)]
+pub struct RawTransaction {
+    pub signature: String,
+    pub slot: i64,
+    pub commitment: Commitment,
+    /// The `TransactionResult` JSON, gzip-compressed
+    pub data: Vec<u8>,
+    pub stored_at: DateTime<Utc>,
+}
+
+/// How settled a transaction was when we fetched it, lowest first
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, sqlx::Type, Serialize, Deserialize,
+)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    /// The RPC's name for it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Confirmed => "confirmed",
+            Self::Finalized => "finalized",
+        }
+    }
+}
@@ -4,7 +4,7 @@ use serde_json::Value;
 // ------------------------------------------
 // 1. TOP LEVEL RESPONSE
 // ------------------------------------------
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TransactionResult {
     pub slot: u64,
     #[serde(rename = "blockTime")]
@@ -16,13 +16,13 @@ pub struct TransactionResult {
 // ------------------------------------------
 // 2. TRANSACTION DATA
 // ------------------------------------------
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TransactionData {
     pub signatures: Vec<String>,
     pub message: TransactionMessage,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TransactionMessage {
     #[serde(rename = "accountKeys")]
     pub account_keys: Vec<AccountKey>,
@@ -31,7 +31,7 @@ pub struct TransactionMessage {
     pub recent_blockhash: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AccountKey {
     pub pubkey: String,
     pub signer: bool,
@@ -41,7 +41,7 @@ pub struct AccountKey {
 // ------------------------------------------
 // 3. INSTRUCTIONS (The Hard Part)
 // ------------------------------------------
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Instruction {
     #[serde(rename = "programId")]
     pub program_id: String,
@@ -51,10 +51,87 @@ pub struct Instruction {
     pub program: Option<String>,
 }
 
+impl Instruction {
+    /// Typed view of `parsed` for the SPL Token instructions we care about.
+    /// `None` for other programs and instruction types.
+    pub fn parsed_token_instruction(&self) -> Option<ParsedTokenInstruction> {
+        if !matches!(
+            self.program.as_deref(),
+            Some("spl-token") | Some("spl-token-2022")
+        ) {
+            return None;
+        }
+        serde_json::from_value(self.parsed.clone()?).ok()
+    }
+}
+
+/// SPL Token instructions as decoded by the RPC's `jsonParsed` encoding
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", content = "info", rename_all = "camelCase")]
+pub enum ParsedTokenInstruction {
+    Transfer(TransferInfo),
+    TransferChecked(TransferCheckedInfo),
+    MintTo(MintToInfo),
+    Burn(BurnInfo),
+}
+
+impl ParsedTokenInstruction {
+    /// `(source, destination, raw amount)` for transfers
+    pub fn transfer(&self) -> Option<(&str, &str, u64)> {
+        match self {
+            Self::Transfer(t) => Some((&t.source, &t.destination, t.amount.parse().ok()?)),
+            Self::TransferChecked(t) => Some((
+                &t.source,
+                &t.destination,
+                t.token_amount.amount.parse().ok()?,
+            )),
+            _ => None,
+        }
+    }
+}
+
+// Multisig-owned accounts report `multisigAuthority` instead of `authority`,
+// so authorities are optional throughout.
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TransferInfo {
+    pub source: String,
+    pub destination: String,
+    pub amount: String,
+    pub authority: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferCheckedInfo {
+    pub source: String,
+    pub destination: String,
+    pub mint: String,
+    pub token_amount: UiTokenAmount,
+    pub authority: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintToInfo {
+    pub mint: String,
+    pub account: String,
+    pub amount: String,
+    pub mint_authority: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BurnInfo {
+    pub account: String,
+    pub mint: String,
+    pub amount: String,
+    pub authority: Option<String>,
+}
+
 // ------------------------------------------
 // 4. METADATA (Logs & Balances)
 // ------------------------------------------
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TransactionMeta {
     pub err: Option<Value>,
     pub fee: u64,
@@ -78,7 +155,7 @@ pub struct TransactionMeta {
 // ------------------------------------------
 // 5. HELPER STRUCTS
 // ------------------------------------------
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TokenBalance {
     #[serde(rename = "accountIndex")]
     pub account_index: u32,
@@ -88,7 +165,7 @@ pub struct TokenBalance {
     pub owner: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct UiTokenAmount {
     #[serde(rename = "uiAmount")]
     pub ui_amount: Option<f64>,
@@ -98,8 +175,48 @@ pub struct UiTokenAmount {
     pub ui_amount_string: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct InnerInstructionWrapper {
     pub index: u32,
     pub instructions: Vec<Instruction>,
 }
+
+// ------------------------------------------
+// 6. ENHANCED TRANSACTIONS (Helius webhooks)
+// ------------------------------------------
+// Only the fields we map onto trades and tokens; Helius' own `type`,
+// `description`, `events` and transfer summaries are ignored.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnhancedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    /// Block time, Unix seconds
+    pub timestamp: Option<i64>,
+    pub fee: u64,
+    pub fee_payer: String,
+    pub transaction_error: Option<Value>,
+    #[serde(default)]
+    pub instructions: Vec<EnhancedInstruction>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnhancedInstruction {
+    pub program_id: String,
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    /// Base58, as in the raw transaction
+    pub data: String,
+    #[serde(default)]
+    pub inner_instructions: Vec<EnhancedInnerInstruction>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnhancedInnerInstruction {
+    pub program_id: String,
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    pub data: String,
+}
@@ -1,54 +1,120 @@
-use super::{Token, TokenHolder, Trade, Transaction};
-use anyhow::{Context, Result};
+use super::{
+    Candle, Commitment, HeatmapCell, HolderStats, Migration, RawTransaction, Sniper, Token,
+    TokenHolder, TokenHolderTail, TokenMetadata, TokenVolume, Trade, TradeCursor, TradePage,
+    Transaction, VolumeStats, WalletPnl,
+};
+use crate::db::{capabilities, time_bucket_sql};
+use crate::error::{DbContext, Result};
+use crate::pricing::{LAMPORTS_PER_SOL, price_from_reserves, raw_to_tokens};
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use sqlx::{PgPool, Row};
+use std::collections::{HashMap, HashSet};
 
 // ==========================================
 // TOKEN OPERATIONS
 // ==========================================
 
-/// Insert or update token metadata
+/// Whether an upsert carries curve state at least as new as the stored one.
+/// Creation upserts (no trade slot) only count as newer before any trade.
+const TRADE_STATE_IS_NEWER: &str =
+    "(tokens.last_trade_slot IS NULL OR EXCLUDED.last_trade_slot >= tokens.last_trade_slot)";
+
+/// Insert or update token metadata. Reserves, market cap and progress are
+/// only replaced by state from the same or a later trade slot.
 pub async fn upsert_token(pool: &PgPool, token: &Token) -> Result<()> {
-    sqlx::query(
+    let sql = format!(
         r#"
         INSERT INTO tokens (
             mint_address, name, symbol, uri, bonding_curve_address, creator_wallet,
             virtual_token_reserves, virtual_sol_reserves, real_token_reserves,
-            token_total_supply, market_cap_usd, bonding_curve_progress, complete
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            token_total_supply, market_cap_usd, bonding_curve_progress,
+            bonding_curve_progress_raw, complete, amm_pool_address, creator_fee_bps,
+            market_cap_usd_raw, last_trade_slot, first_trade_at, last_trade_at, creation_slot
+        ) VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $19,
+            $20
+        )
         ON CONFLICT (mint_address) 
         DO UPDATE SET
             name = EXCLUDED.name,
             symbol = EXCLUDED.symbol,
             uri = EXCLUDED.uri,
-            virtual_token_reserves = EXCLUDED.virtual_token_reserves,
-            virtual_sol_reserves = EXCLUDED.virtual_sol_reserves,
-            real_token_reserves = EXCLUDED.real_token_reserves,
+            -- Curve state only moves forward: a trade from an older slot
+            -- (a slow or concurrent worker) must not roll it back
+            virtual_token_reserves = CASE WHEN {NEWER} THEN EXCLUDED.virtual_token_reserves ELSE tokens.virtual_token_reserves END,
+            virtual_sol_reserves = CASE WHEN {NEWER} THEN EXCLUDED.virtual_sol_reserves ELSE tokens.virtual_sol_reserves END,
+            real_token_reserves = CASE WHEN {NEWER} THEN EXCLUDED.real_token_reserves ELSE tokens.real_token_reserves END,
             token_total_supply = EXCLUDED.token_total_supply,
-            market_cap_usd = EXCLUDED.market_cap_usd,
-            bonding_curve_progress = EXCLUDED.bonding_curve_progress,
-            complete = EXCLUDED.complete,
+            market_cap_usd = CASE WHEN {NEWER} THEN EXCLUDED.market_cap_usd ELSE tokens.market_cap_usd END,
+            bonding_curve_progress = CASE WHEN {NEWER} THEN EXCLUDED.bonding_curve_progress ELSE tokens.bonding_curve_progress END,
+            bonding_curve_progress_raw = CASE WHEN {NEWER} THEN EXCLUDED.bonding_curve_progress_raw ELSE tokens.bonding_curve_progress_raw END,
+            -- Once a curve completes it stays complete
+            complete = tokens.complete OR EXCLUDED.complete,
+            amm_pool_address = COALESCE(EXCLUDED.amm_pool_address, tokens.amm_pool_address),
+            creator_fee_bps = COALESCE(EXCLUDED.creator_fee_bps, tokens.creator_fee_bps),
+            market_cap_usd_raw = CASE WHEN {NEWER} THEN EXCLUDED.market_cap_usd_raw ELSE tokens.market_cap_usd_raw END,
+            last_trade_slot = GREATEST(EXCLUDED.last_trade_slot, tokens.last_trade_slot),
+            -- LEAST/GREATEST skip NULLs, so trades arriving out of order
+            -- (backfill) still widen the range and creations leave it alone
+            first_trade_at = LEAST(EXCLUDED.first_trade_at, tokens.first_trade_at),
+            last_trade_at = GREATEST(EXCLUDED.last_trade_at, tokens.last_trade_at),
+            -- A token first stored from a trade has its creator guessed; the
+            -- creation event's is authoritative
+            creator_wallet = CASE WHEN tokens.creation_slot IS NULL AND EXCLUDED.creation_slot IS NOT NULL
+                THEN EXCLUDED.creator_wallet ELSE tokens.creator_wallet END,
+            creation_slot = COALESCE(tokens.creation_slot, EXCLUDED.creation_slot),
             updated_at = NOW()
         "#,
+        NEWER = TRADE_STATE_IS_NEWER,
+    );
+
+    sqlx::query(&sql)
+        .bind(&token.mint_address)
+        .bind(&token.name)
+        .bind(&token.symbol)
+        .bind(&token.uri)
+        .bind(&token.bonding_curve_address)
+        .bind(&token.creator_wallet)
+        .bind(token.virtual_token_reserves)
+        .bind(token.virtual_sol_reserves)
+        .bind(token.real_token_reserves)
+        .bind(token.token_total_supply)
+        .bind(token.market_cap_usd)
+        .bind(token.bonding_curve_progress)
+        .bind(token.bonding_curve_progress_raw)
+        .bind(token.complete)
+        .bind(&token.amm_pool_address)
+        .bind(token.creator_fee_bps)
+        .bind(token.market_cap_usd_raw)
+        .bind(token.last_trade_slot)
+        .bind(token.last_trade_at)
+        .bind(token.creation_slot)
+        .execute(pool)
+        .await
+        .context("Failed to upsert token")?;
+
+    Ok(())
+}
+
+/// When anything `GET /tokens/{mint}` shows last changed: the token row, or
+/// its creator's balance. `None` if the token isn't stored.
+pub async fn get_token_version(pool: &PgPool, mint_address: &str) -> Result<Option<DateTime<Utc>>> {
+    let version = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+        r#"
+        SELECT GREATEST(COALESCE(t.updated_at, t.created_at), h.updated_at)
+        FROM tokens t
+        LEFT JOIN token_holders h
+            ON h.token_mint = t.mint_address AND h.user_wallet = t.creator_wallet
+        WHERE t.mint_address = $1
+        "#,
     )
-    .bind(&token.mint_address)
-    .bind(&token.name)
-    .bind(&token.symbol)
-    .bind(&token.uri)
-    .bind(&token.bonding_curve_address)
-    .bind(&token.creator_wallet)
-    .bind(&token.virtual_token_reserves)
-    .bind(&token.virtual_sol_reserves)
-    .bind(&token.real_token_reserves)
-    .bind(&token.token_total_supply)
-    .bind(&token.market_cap_usd)
-    .bind(&token.bonding_curve_progress)
-    .bind(token.complete)
-    .execute(pool)
+    .bind(mint_address)
+    .fetch_optional(pool)
     .await
-    .context("Failed to upsert token")?;
+    .context("Failed to get token version")?;
 
-    Ok(())
+    Ok(version.flatten())
 }
 
 /// Get token by mint address
@@ -67,8 +133,29 @@ pub async fn get_token(pool: &PgPool, mint_address: &str) -> Result<Option<Token
             real_token_reserves,
             token_total_supply,
             market_cap_usd,
+            market_cap_usd_raw,
             bonding_curve_progress,
+            bonding_curve_progress_raw,
             complete,
+            amm_pool_address,
+            creator_fee_bps,
+            last_trade_slot,
+            first_trade_at,
+            last_trade_at,
+            creation_slot,
+            sniper_count,
+            bundled,
+            dev_sold,
+            graduated_at,
+            graduation_slot,
+            graduation_signature,
+            graduation_market_cap_usd,
+            image,
+            description,
+            twitter,
+            telegram,
+            website,
+            metadata_fetched_at,
             created_at,
             updated_at
         FROM tokens 
@@ -83,81 +170,556 @@ pub async fn get_token(pool: &PgPool, mint_address: &str) -> Result<Option<Token
     Ok(token)
 }
 
+/// Tokens whose name or symbol contains `query` (case-insensitive), highest
+/// 24h volume first. `%` and `_` in `query` match literally.
+pub async fn search_tokens(pool: &PgPool, query: &str, limit: i64) -> Result<Vec<Token>> {
+    let pattern = format!("%{}%", escape_like(query));
+    let tokens = sqlx::query_as::<_, Token>(
+        r#"
+        SELECT 
+            t.mint_address,
+            t.name,
+            t.symbol,
+            t.uri,
+            t.bonding_curve_address,
+            t.creator_wallet,
+            t.virtual_token_reserves,
+            t.virtual_sol_reserves,
+            t.real_token_reserves,
+            t.token_total_supply,
+            t.market_cap_usd,
+            t.market_cap_usd_raw,
+            t.bonding_curve_progress,
+            t.bonding_curve_progress_raw,
+            t.complete,
+            t.amm_pool_address,
+            t.creator_fee_bps,
+            t.last_trade_slot,
+            t.first_trade_at,
+            t.last_trade_at,
+            t.creation_slot,
+            t.sniper_count,
+            t.bundled,
+            t.dev_sold,
+            t.graduated_at,
+            t.graduation_slot,
+            t.graduation_signature,
+            t.graduation_market_cap_usd,
+            t.image,
+            t.description,
+            t.twitter,
+            t.telegram,
+            t.website,
+            t.metadata_fetched_at,
+            t.created_at,
+            t.updated_at
+        FROM tokens t
+        LEFT JOIN LATERAL (
+            SELECT SUM(sol_amount) AS volume_sol
+            FROM trades
+            WHERE token_mint = t.mint_address
+            AND track_volume
+            AND timestamp > NOW() - INTERVAL '24 hours'
+        ) v ON TRUE
+        WHERE t.name ILIKE $1 ESCAPE '\' OR t.symbol ILIKE $1 ESCAPE '\'
+        ORDER BY COALESCE(v.volume_sol, 0) DESC, t.created_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(pattern)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to search tokens")?;
+
+    Ok(tokens)
+}
+
+/// Escape `LIKE` wildcards (and the escape character itself) in user input
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Get many tokens in one round-trip, in the order of `mint_addresses`.
+/// Unknown mints are left out, and a repeated mint is returned once, where
+/// it first appears.
+pub async fn get_tokens_by_mints(pool: &PgPool, mint_addresses: &[String]) -> Result<Vec<Token>> {
+    let tokens = sqlx::query_as::<_, Token>(
+        r#"
+        SELECT 
+            mint_address,
+            name,
+            symbol,
+            uri,
+            bonding_curve_address,
+            creator_wallet,
+            virtual_token_reserves,
+            virtual_sol_reserves,
+            real_token_reserves,
+            token_total_supply,
+            market_cap_usd,
+            market_cap_usd_raw,
+            bonding_curve_progress,
+            bonding_curve_progress_raw,
+            complete,
+            amm_pool_address,
+            creator_fee_bps,
+            last_trade_slot,
+            first_trade_at,
+            last_trade_at,
+            creation_slot,
+            sniper_count,
+            bundled,
+            dev_sold,
+            graduated_at,
+            graduation_slot,
+            graduation_signature,
+            graduation_market_cap_usd,
+            image,
+            description,
+            twitter,
+            telegram,
+            website,
+            metadata_fetched_at,
+            created_at,
+            updated_at
+        FROM tokens 
+        WHERE mint_address = ANY($1)
+        "#,
+    )
+    .bind(mint_addresses)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch tokens")?;
+
+    let mut by_mint: HashMap<String, Token> = tokens
+        .into_iter()
+        .map(|t| (t.mint_address.clone(), t))
+        .collect();
+
+    Ok(mint_addresses
+        .iter()
+        .filter_map(|mint| by_mint.remove(mint))
+        .collect())
+}
+
+/// Whether token `tk` is dead to the feeds: no trade since `$2` (when set) and
+/// a market cap under `$3` USD
+const DEAD_TOKEN_SQL: &str = r#"
+    ($2::TIMESTAMPTZ IS NOT NULL
+     AND (tk.last_trade_at IS NULL OR tk.last_trade_at < $2)
+     AND COALESCE(tk.market_cap_usd, 0) < $3)
+"#;
+
+/// Tokens with the most recent trades, latest first. Dead tokens (no trade
+/// since `dead_before` and a market cap under `min_market_cap_usd`) are left
+/// out; `None` keeps every token.
+pub async fn get_recently_active_tokens(
+    pool: &PgPool,
+    limit: i64,
+    dead_before: Option<DateTime<Utc>>,
+    min_market_cap_usd: Decimal,
+) -> Result<Vec<Token>> {
+    let sql = format!(
+        r#"
+        SELECT 
+            mint_address,
+            name,
+            symbol,
+            uri,
+            bonding_curve_address,
+            creator_wallet,
+            virtual_token_reserves,
+            virtual_sol_reserves,
+            real_token_reserves,
+            token_total_supply,
+            market_cap_usd,
+            market_cap_usd_raw,
+            bonding_curve_progress,
+            bonding_curve_progress_raw,
+            complete,
+            amm_pool_address,
+            creator_fee_bps,
+            last_trade_slot,
+            first_trade_at,
+            last_trade_at,
+            creation_slot,
+            sniper_count,
+            bundled,
+            dev_sold,
+            graduated_at,
+            graduation_slot,
+            graduation_signature,
+            graduation_market_cap_usd,
+            image,
+            description,
+            twitter,
+            telegram,
+            website,
+            metadata_fetched_at,
+            created_at,
+            updated_at
+        FROM tokens tk
+        WHERE last_trade_at IS NOT NULL
+        AND NOT {DEAD_TOKEN_SQL}
+        ORDER BY last_trade_at DESC
+        LIMIT $1
+        "#
+    );
+    let tokens = sqlx::query_as::<_, Token>(&sql)
+        .bind(limit)
+        .bind(dead_before)
+        .bind(min_market_cap_usd)
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch recently active tokens")?;
+
+    Ok(tokens)
+}
+
+/// Record the AMM pool a graduated token migrated to; this also marks it complete
+pub async fn set_amm_pool_address(
+    pool: &PgPool,
+    mint_address: &str,
+    amm_pool_address: &str,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE tokens
+        SET amm_pool_address = $2, complete = TRUE, updated_at = NOW()
+        WHERE mint_address = $1
+        "#,
+    )
+    .bind(mint_address)
+    .bind(amm_pool_address)
+    .execute(pool)
+    .await
+    .context("Failed to set AMM pool address")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Claim the one-time metadata fetch for a token. Returns its URI if this
+/// call won the claim, `None` if it has no URI or was already claimed.
+pub async fn claim_token_metadata_fetch(
+    pool: &PgPool,
+    mint_address: &str,
+) -> Result<Option<String>> {
+    let uri = sqlx::query_scalar::<_, String>(
+        r#"
+        UPDATE tokens
+        SET metadata_fetched_at = NOW(), updated_at = NOW()
+        WHERE mint_address = $1 AND uri IS NOT NULL AND metadata_fetched_at IS NULL
+        RETURNING uri
+        "#,
+    )
+    .bind(mint_address)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to claim token metadata fetch")?;
+
+    Ok(uri)
+}
+
+/// Store a token's off-chain metadata
+pub async fn set_token_metadata(
+    pool: &PgPool,
+    mint_address: &str,
+    metadata: &TokenMetadata,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE tokens
+        SET image = $2, description = $3, twitter = $4, telegram = $5, website = $6,
+            updated_at = NOW()
+        WHERE mint_address = $1
+        "#,
+    )
+    .bind(mint_address)
+    .bind(&metadata.image)
+    .bind(&metadata.description)
+    .bind(&metadata.twitter)
+    .bind(&metadata.telegram)
+    .bind(&metadata.website)
+    .execute(pool)
+    .await
+    .context("Failed to set token metadata")?;
+
+    Ok(())
+}
+
+/// Record the transaction at which a token graduated, using its stored
+/// market cap as the final one. Only the first call per token has any effect;
+/// returns whether this call was it.
+pub async fn record_graduation(
+    pool: &PgPool,
+    mint_address: &str,
+    signature: &str,
+    slot: i64,
+    graduated_at: DateTime<Utc>,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE tokens
+        SET graduated_at = $4,
+            graduation_slot = $3,
+            graduation_signature = $2,
+            graduation_market_cap_usd = market_cap_usd,
+            complete = TRUE,
+            updated_at = NOW()
+        WHERE mint_address = $1 AND graduated_at IS NULL
+        "#,
+    )
+    .bind(mint_address)
+    .bind(signature)
+    .bind(slot)
+    .bind(graduated_at)
+    .execute(pool)
+    .await
+    .context("Failed to record graduation")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 // ==========================================
 // TRADE OPERATIONS
 // ==========================================
 
-/// Insert a new trade (no upsert, trades are immutable)
-pub async fn insert_trade(pool: &PgPool, trade: &Trade) -> Result<()> {
-    sqlx::query(
+/// Insert a new trade (no upsert, trades are immutable).
+/// Returns `false` if it was already stored.
+pub async fn insert_trade(pool: &PgPool, trade: &Trade) -> Result<bool> {
+    let result = sqlx::query(
         r#"
         INSERT INTO trades (
             signature, token_mint, sol_amount, token_amount, is_buy,
             user_wallet, timestamp, virtual_sol_reserves, virtual_token_reserves,
-            price_sol, price_usd, track_volume, ix_name, slot
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
-        ON CONFLICT (timestamp, signature) DO NOTHING
+            price_sol, price_usd, sol_price_usd, sol_price_at, creator_fee_sol,
+            track_volume, ix_name, slot, instruction_index, inner_instruction_index,
+            accounts, fee_lamports, compute_units, compute_unit_price, usd_amount
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24)
+        ON CONFLICT (timestamp, signature, instruction_index, inner_instruction_index) DO NOTHING
         "#,
     )
     .bind(&trade.signature)
     .bind(&trade.token_mint)
-    .bind(&trade.sol_amount)
-    .bind(&trade.token_amount)
+    .bind(trade.sol_amount)
+    .bind(trade.token_amount)
     .bind(trade.is_buy)
     .bind(&trade.user_wallet)
     .bind(trade.timestamp)
-    .bind(&trade.virtual_sol_reserves)
-    .bind(&trade.virtual_token_reserves)
-    .bind(&trade.price_sol)
-    .bind(&trade.price_usd)
+    .bind(trade.virtual_sol_reserves)
+    .bind(trade.virtual_token_reserves)
+    .bind(trade.price_sol)
+    .bind(trade.price_usd)
+    .bind(trade.sol_price_usd)
+    .bind(trade.sol_price_at)
+    .bind(trade.creator_fee_sol)
     .bind(trade.track_volume)
     .bind(&trade.ix_name)
     .bind(trade.slot)
+    .bind(trade.instruction_index)
+    .bind(trade.inner_instruction_index)
+    .bind(&trade.accounts)
+    .bind(trade.fee_lamports)
+    .bind(trade.compute_units)
+    .bind(trade.compute_unit_price)
+    .bind(trade.usd_amount)
     .execute(pool)
     .await
     .context("Failed to insert trade")?;
 
-    Ok(())
+    Ok(result.rows_affected() > 0)
 }
 
-/// Batch insert trades for efficiency
-pub async fn batch_insert_trades(pool: &PgPool, trades: &[Trade]) -> Result<()> {
+/// A trade row's primary key without its timestamp, which a transaction's
+/// signature already fixes
+type TradeKey = (String, i32, i32);
+
+/// Insert trades in a single statement: one array per column, zipped back
+/// into rows by `UNNEST`. Returns, per trade, whether it was new (`false`
+/// when already stored, or repeated earlier in `trades`), as with
+/// `insert_trade`.
+pub async fn batch_insert_trades(pool: &PgPool, trades: &[Trade]) -> Result<Vec<bool>> {
     if trades.is_empty() {
-        return Ok(());
+        return Ok(Vec::new());
+    }
+
+    fn column<'a, T>(trades: &'a [Trade], field: impl Fn(&'a Trade) -> T) -> Vec<T> {
+        trades.iter().map(field).collect()
     }
 
+    let stored = sqlx::query_as::<_, TradeKey>(
+        r#"
+        INSERT INTO trades (
+            signature, token_mint, sol_amount, token_amount, is_buy,
+            user_wallet, timestamp, virtual_sol_reserves, virtual_token_reserves,
+            price_sol, price_usd, sol_price_usd, sol_price_at, creator_fee_sol,
+            track_volume, ix_name, slot, instruction_index, inner_instruction_index,
+            accounts, fee_lamports, compute_units, compute_unit_price, usd_amount
+        )
+        SELECT * FROM UNNEST(
+            $1::text[], $2::text[], $3::numeric[], $4::numeric[], $5::bool[],
+            $6::text[], $7::timestamptz[], $8::numeric[], $9::numeric[],
+            $10::numeric[], $11::numeric[], $12::numeric[], $13::timestamptz[], $14::numeric[],
+            $15::bool[], $16::text[], $17::int8[], $18::int4[], $19::int4[],
+            $20::jsonb[], $21::int8[], $22::int8[], $23::int8[], $24::numeric[]
+        )
+        ON CONFLICT (timestamp, signature, instruction_index, inner_instruction_index) DO NOTHING
+        RETURNING signature, instruction_index, inner_instruction_index
+        "#,
+    )
+    .bind(column(trades, |t| t.signature.as_str()))
+    .bind(column(trades, |t| t.token_mint.as_str()))
+    .bind(column(trades, |t| t.sol_amount))
+    .bind(column(trades, |t| t.token_amount))
+    .bind(column(trades, |t| t.is_buy))
+    .bind(column(trades, |t| t.user_wallet.as_str()))
+    .bind(column(trades, |t| t.timestamp))
+    .bind(column(trades, |t| t.virtual_sol_reserves))
+    .bind(column(trades, |t| t.virtual_token_reserves))
+    .bind(column(trades, |t| t.price_sol))
+    .bind(column(trades, |t| t.price_usd))
+    .bind(column(trades, |t| t.sol_price_usd))
+    .bind(column(trades, |t| t.sol_price_at))
+    .bind(column(trades, |t| t.creator_fee_sol))
+    .bind(column(trades, |t| t.track_volume))
+    .bind(column(trades, |t| t.ix_name.as_str()))
+    .bind(column(trades, |t| t.slot))
+    .bind(column(trades, |t| t.instruction_index))
+    .bind(column(trades, |t| t.inner_instruction_index))
+    .bind(column(trades, |t| t.accounts.as_ref()))
+    .bind(column(trades, |t| t.fee_lamports))
+    .bind(column(trades, |t| t.compute_units))
+    .bind(column(trades, |t| t.compute_unit_price))
+    .bind(column(trades, |t| t.usd_amount))
+    .fetch_all(pool)
+    .await
+    .context("Failed to insert trade batch")?;
+
+    // Each stored key counts as new once, for its first trade
+    let mut stored: HashSet<TradeKey> = stored.into_iter().collect();
+    Ok(trades
+        .iter()
+        .map(|t| {
+            stored.remove(&(
+                t.signature.clone(),
+                t.instruction_index,
+                t.inner_instruction_index,
+            ))
+        })
+        .collect())
+}
+
+/// `(signature, timestamp, slot)` of every transaction with trades at or
+/// after `min_slot`
+pub async fn get_trade_signatures_since_slot(
+    pool: &PgPool,
+    min_slot: i64,
+) -> Result<Vec<(String, DateTime<Utc>, i64)>> {
+    let rows = sqlx::query_as::<_, (String, DateTime<Utc>, i64)>(
+        r#"
+        SELECT DISTINCT signature, timestamp, slot
+        FROM trades
+        WHERE slot >= $1
+        "#,
+    )
+    .bind(min_slot)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch recent trade signatures")?;
+
+    Ok(rows)
+}
+
+/// Delete every trade of `mint`, leaving holders alone (for synthetic rows,
+/// e.g. `bench-inserts`). Returns the number deleted.
+pub async fn delete_trades_for_mint(pool: &PgPool, mint: &str) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM trades WHERE token_mint = $1")
+        .bind(mint)
+        .execute(pool)
+        .await
+        .context("Failed to delete trades")?;
+    Ok(result.rows_affected())
+}
+
+/// Delete every trade of a transaction that didn't make it onto the chain,
+/// undoing what each did to its wallet's holder balance, and drop its audit
+/// row. Token curve state is left for the next trade to overwrite.
+/// Returns the number of trades deleted.
+pub async fn delete_trade(
+    pool: &PgPool,
+    signature: &str,
+    timestamp: DateTime<Utc>,
+) -> Result<usize> {
     let mut tx = pool.begin().await?;
 
-    for trade in trades {
-        sqlx::query(
-            r#"
-            INSERT INTO trades (
-                signature, token_mint, sol_amount, token_amount, is_buy,
-                user_wallet, timestamp, virtual_sol_reserves, virtual_token_reserves,
-                price_sol, price_usd, track_volume, ix_name, slot
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
-            ON CONFLICT (timestamp, signature) DO NOTHING
-            "#,
+    let deleted = sqlx::query(
+        r#"
+        DELETE FROM trades
+        WHERE signature = $1 AND timestamp = $2
+        RETURNING token_mint, user_wallet, token_amount, is_buy
+        "#,
+    )
+    .bind(signature)
+    .bind(timestamp)
+    .fetch_all(&mut *tx)
+    .await
+    .context("Failed to delete trade")?;
+
+    for row in &deleted {
+        reverse_holder_delta(
+            &mut tx,
+            row.try_get("token_mint")?,
+            row.try_get("user_wallet")?,
+            row.try_get("token_amount")?,
+            row.try_get("is_buy")?,
         )
-        .bind(&trade.signature)
-        .bind(&trade.token_mint)
-        .bind(&trade.sol_amount)
-        .bind(&trade.token_amount)
-        .bind(trade.is_buy)
-        .bind(&trade.user_wallet)
-        .bind(trade.timestamp)
-        .bind(&trade.virtual_sol_reserves)
-        .bind(&trade.virtual_token_reserves)
-        .bind(&trade.price_sol)
-        .bind(&trade.price_usd)
-        .bind(trade.track_volume)
-        .bind(&trade.ix_name)
-        .bind(trade.slot)
-        .execute(&mut *tx)
         .await?;
     }
 
-    tx.commit().await.context("Failed to commit trade batch")?;
+    sqlx::query("DELETE FROM transactions WHERE signature = $1")
+        .bind(signature)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to delete audit row")?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit trade deletion")?;
+    Ok(deleted.len())
+}
+
+/// Undo one trade's effect on a holder balance (a buy's tokens come back
+/// off, a sell's go back on), floored at zero like the worker does
+async fn reverse_holder_delta(
+    conn: &mut sqlx::PgConnection,
+    token_mint: &str,
+    user_wallet: &str,
+    token_amount: Decimal,
+    is_buy: bool,
+) -> Result<()> {
+    let delta = if is_buy { -token_amount } else { token_amount };
+    sqlx::query(
+        r#"
+        UPDATE token_holders
+        SET balance = GREATEST(balance + $3, 0), updated_at = NOW()
+        WHERE token_mint = $1 AND user_wallet = $2
+        "#,
+    )
+    .bind(token_mint)
+    .bind(user_wallet)
+    .bind(delta)
+    .execute(conn)
+    .await
+    .context("Failed to reverse holder balance")?;
+
     Ok(())
 }
 
@@ -181,9 +743,19 @@ pub async fn get_recent_trades(
             virtual_token_reserves,
             price_sol,
             price_usd,
+            sol_price_usd,
+            sol_price_at,
+            usd_amount,
+            creator_fee_sol,
             track_volume,
             ix_name,
-            slot
+            slot,
+            instruction_index,
+            inner_instruction_index,
+            accounts,
+            fee_lamports,
+            compute_units,
+            compute_unit_price
         FROM trades 
         WHERE token_mint = $1 
         ORDER BY timestamp DESC 
@@ -199,29 +771,127 @@ pub async fn get_recent_trades(
     Ok(trades)
 }
 
+/// One page of `mint_address`'s trades, newest first, starting after
+/// `before` (from the previous page's `next_cursor`) and optionally bounded
+/// to `from <= timestamp < to`. Keyset pagination on the primary key, so
+/// pages stay stable while new trades arrive.
+pub async fn get_trades_paginated(
+    pool: &PgPool,
+    mint_address: &str,
+    before: Option<&TradeCursor>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    limit: i64,
+) -> Result<TradePage> {
+    // One extra row tells whether there is a next page
+    let mut trades = sqlx::query_as::<_, Trade>(
+        r#"
+        SELECT 
+            signature,
+            token_mint,
+            sol_amount,
+            token_amount,
+            is_buy,
+            user_wallet,
+            timestamp,
+            virtual_sol_reserves,
+            virtual_token_reserves,
+            price_sol,
+            price_usd,
+            sol_price_usd,
+            sol_price_at,
+            usd_amount,
+            creator_fee_sol,
+            track_volume,
+            ix_name,
+            slot,
+            instruction_index,
+            inner_instruction_index,
+            accounts,
+            fee_lamports,
+            compute_units,
+            compute_unit_price
+        FROM trades 
+        WHERE token_mint = $1 
+          AND ($2::timestamptz IS NULL
+               OR (timestamp, signature, instruction_index, inner_instruction_index)
+                  < ($2, $3, $4, $5))
+          AND ($6::timestamptz IS NULL OR timestamp >= $6)
+          AND ($7::timestamptz IS NULL OR timestamp < $7)
+        ORDER BY timestamp DESC, signature DESC, instruction_index DESC, inner_instruction_index DESC
+        LIMIT $8
+        "#,
+    )
+    .bind(mint_address)
+    .bind(before.map(|c| c.timestamp))
+    .bind(before.map(|c| c.signature.as_str()))
+    .bind(before.map(|c| c.instruction_index))
+    .bind(before.map(|c| c.inner_instruction_index))
+    .bind(from)
+    .bind(to)
+    .bind(limit + 1)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch trades page")?;
+
+    let next_cursor = if trades.len() as i64 > limit {
+        trades.truncate(limit.max(0) as usize);
+        trades.last().map(TradeCursor::from)
+    } else {
+        None
+    };
+
+    Ok(TradePage {
+        trades,
+        next_cursor,
+    })
+}
+
 // ==========================================
 // TOKEN HOLDER OPERATIONS
 // ==========================================
 
-/// Upsert token holder balance
-pub async fn upsert_token_holder(pool: &PgPool, holder: &TokenHolder) -> Result<()> {
+/// Upsert token holder balance, as of the trade at `instruction_index` /
+/// `inner_instruction_index` in `holder.last_updated_slot`. Skipped when the
+/// stored balance is from a later trade: a later slot, or later in the same
+/// slot (by instruction position; transactions aren't ordered within a slot).
+/// A replay of the same trade writes again.
+pub async fn upsert_token_holder(
+    pool: &PgPool,
+    holder: &TokenHolder,
+    instruction_index: i32,
+    inner_instruction_index: i32,
+) -> Result<()> {
     sqlx::query(
         r#"
         INSERT INTO token_holders (
-            token_mint, user_wallet, balance, last_updated_slot
-        ) VALUES ($1, $2, $3, $4)
+            token_mint, user_wallet, balance, last_updated_slot,
+            last_updated_instruction_index, last_updated_inner_instruction_index
+        ) VALUES ($1, $2, $3, $4, $5, $6)
         ON CONFLICT (token_mint, user_wallet)
         DO UPDATE SET
             balance = EXCLUDED.balance,
             last_updated_slot = EXCLUDED.last_updated_slot,
+            last_updated_instruction_index = EXCLUDED.last_updated_instruction_index,
+            last_updated_inner_instruction_index = EXCLUDED.last_updated_inner_instruction_index,
             updated_at = NOW()
-        WHERE token_holders.last_updated_slot < EXCLUDED.last_updated_slot
+        WHERE (
+            token_holders.last_updated_slot,
+            token_holders.last_updated_instruction_index,
+            token_holders.last_updated_inner_instruction_index
+        ) <= (
+            EXCLUDED.last_updated_slot,
+            EXCLUDED.last_updated_instruction_index,
+            EXCLUDED.last_updated_inner_instruction_index
+        )
         "#,
     )
     .bind(&holder.token_mint)
     .bind(&holder.user_wallet)
-    .bind(&holder.balance)
+    .bind(holder.balance)
     .bind(holder.last_updated_slot)
+    .bind(instruction_index)
+    .bind(inner_instruction_index)
     .execute(pool)
     .await
     .context("Failed to upsert token holder")?;
@@ -244,7 +914,7 @@ pub async fn get_top_holders(
             last_updated_slot,
             updated_at
         FROM token_holders 
-        WHERE token_mint = $1 
+        WHERE token_mint = $1 AND balance > 0
         ORDER BY balance DESC 
         LIMIT $2
         "#,
@@ -285,68 +955,1102 @@ pub async fn get_token_holder(
     Ok(holder)
 }
 
-// ==========================================
-// TRANSACTION OPERATIONS (Audit Log)
-// ==========================================
+/// Evict the smallest holders of a token once it has more than `evict_above`
+/// rows, down to `keep`, folding them into its `token_holder_tails` aggregate.
+/// Holders with more than `min_balance` are never evicted.
+/// Returns how many holders were evicted.
+pub async fn evict_dust_holders(
+    pool: &PgPool,
+    mint_address: &str,
+    keep: i64,
+    evict_above: i64,
+    min_balance: Decimal,
+) -> Result<i64> {
+    let evicted: i64 = sqlx::query_scalar(
+        r#"
+        WITH ranked AS (
+            SELECT user_wallet,
+                   balance,
+                   ROW_NUMBER() OVER (ORDER BY balance DESC, user_wallet) as rank,
+                   COUNT(*) OVER () as total
+            FROM token_holders
+            WHERE token_mint = $1
+        ),
+        evicted AS (
+            DELETE FROM token_holders h
+            USING ranked r
+            WHERE h.token_mint = $1
+            AND h.user_wallet = r.user_wallet
+            AND r.total > $3
+            AND r.rank > $2
+            AND r.balance <= $4
+            RETURNING h.balance
+        ),
+        summary AS (
+            -- Sold-out (zero) rows are simply dropped
+            SELECT COUNT(*) FILTER (WHERE balance > 0) as holder_count,
+                   COALESCE(SUM(balance), 0) as balance
+            FROM evicted
+        ),
+        tail AS (
+            INSERT INTO token_holder_tails (token_mint, holder_count, balance)
+            SELECT $1, holder_count, balance FROM summary WHERE holder_count > 0
+            ON CONFLICT (token_mint) DO UPDATE SET
+                holder_count = token_holder_tails.holder_count + EXCLUDED.holder_count,
+                balance = token_holder_tails.balance + EXCLUDED.balance,
+                updated_at = NOW()
+        )
+        SELECT holder_count FROM summary
+        "#,
+    )
+    .bind(mint_address)
+    .bind(keep)
+    .bind(evict_above)
+    .bind(min_balance)
+    .fetch_one(pool)
+    .await
+    .context("Failed to evict dust holders")?;
 
-/// Insert transaction record
-pub async fn insert_transaction(pool: &PgPool, transaction: &Transaction) -> Result<()> {
+    Ok(evicted)
+}
+
+/// Take a sell by a wallet with no holder row out of the token's tail balance
+/// (floored at zero). Evicted holders are only tracked in aggregate, so this
+/// is the best attribution available; a no-op for tokens without a tail.
+pub async fn debit_holder_tail(pool: &PgPool, mint_address: &str, amount: Decimal) -> Result<()> {
     sqlx::query(
         r#"
-        INSERT INTO transactions (
-            signature, slot, block_time, signer, success, instruction_count
-        ) VALUES ($1, $2, $3, $4, $5, $6)
-        ON CONFLICT (block_time, signature) DO NOTHING
+        UPDATE token_holder_tails
+        SET balance = GREATEST(balance - $2, 0), updated_at = NOW()
+        WHERE token_mint = $1
         "#,
     )
-    .bind(&transaction.signature)
-    .bind(transaction.slot)
-    .bind(transaction.block_time)
-    .bind(&transaction.signer)
-    .bind(transaction.success)
-    .bind(transaction.instruction_count)
+    .bind(mint_address)
+    .bind(amount)
     .execute(pool)
     .await
-    .context("Failed to insert transaction")?;
+    .context("Failed to debit holder tail")?;
 
     Ok(())
 }
 
-// ==========================================
-// ANALYTICS / STATS QUERIES
-// ==========================================
-
-/// Get total trades count for a token
-pub async fn get_token_trade_count(pool: &PgPool, mint_address: &str) -> Result<i64> {
-    let row = sqlx::query(
+/// Get the aggregate of a token's evicted holders, if any were evicted
+pub async fn get_holder_tail(pool: &PgPool, mint_address: &str) -> Result<Option<TokenHolderTail>> {
+    let tail = sqlx::query_as::<_, TokenHolderTail>(
         r#"
-        SELECT COUNT(*) as count FROM trades WHERE token_mint = $1
+        SELECT token_mint, holder_count, balance, updated_at
+        FROM token_holder_tails
+        WHERE token_mint = $1
         "#,
     )
     .bind(mint_address)
-    .fetch_one(pool)
+    .fetch_optional(pool)
     .await
-    .context("Failed to get trade count")?;
+    .context("Failed to fetch holder tail")?;
 
-    let count: i64 = row.try_get("count")?;
-    Ok(count)
+    Ok(tail)
 }
 
-/// Get 24h volume for a token
-pub async fn get_24h_volume(pool: &PgPool, mint_address: &str) -> Result<Decimal> {
-    let row = sqlx::query(
+/// Rebuild `token_holders` for one mint by replaying its stored trades in
+/// slot order, and by instruction position within a slot.
+///
+/// Runs in a single transaction: the mint's holder rows are deleted and replaced
+/// with the replayed balances, so readers never see a half-rebuilt state.
+///
+/// Caveat: balances only reflect what is in `trades`. Plain SPL transfers between
+/// wallets are invisible unless the worker recorded them (`ZERO_SOL_TRANSFERS=record`),
+/// and sells exceeding the replayed balance are floored at zero like the worker does.
+/// Returns the number of holders written.
+pub async fn rebuild_token_holders(pool: &PgPool, mint_address: &str) -> Result<usize> {
+    let mut tx = pool.begin().await?;
+
+    let rows = sqlx::query(
         r#"
-        SELECT COALESCE(SUM(sol_amount), 0) as volume
-        FROM trades 
-        WHERE token_mint = $1 
-        AND timestamp > NOW() - INTERVAL '24 hours'
+        SELECT user_wallet, token_amount, is_buy, slot, instruction_index, inner_instruction_index
+        FROM trades
+        WHERE token_mint = $1
+        ORDER BY slot ASC, instruction_index ASC, inner_instruction_index ASC
+        "#,
+    )
+    .bind(mint_address)
+    .fetch_all(&mut *tx)
+    .await
+    .context("Failed to load trades for holder rebuild")?;
+
+    // Balance and the (slot, instruction, inner instruction) that last set it
+    let mut balances: HashMap<String, (Decimal, (i64, i32, i32))> = HashMap::new();
+    for row in rows {
+        let wallet: String = row.try_get("user_wallet")?;
+        let amount: Decimal = row.try_get("token_amount")?;
+        let is_buy: bool = row.try_get("is_buy")?;
+        let position = (
+            row.try_get("slot")?,
+            row.try_get("instruction_index")?,
+            row.try_get("inner_instruction_index")?,
+        );
+
+        let entry = balances.entry(wallet).or_insert((Decimal::ZERO, position));
+        entry.0 = if is_buy {
+            entry.0 + amount
+        } else {
+            (entry.0 - amount).max(Decimal::ZERO)
+        };
+        entry.1 = position;
+    }
+
+    sqlx::query("DELETE FROM token_holders WHERE token_mint = $1")
+        .bind(mint_address)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to clear token holders")?;
+
+    // Every holder is back in full; the next eviction re-applies any cap
+    sqlx::query("DELETE FROM token_holder_tails WHERE token_mint = $1")
+        .bind(mint_address)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to clear holder tail")?;
+
+    let mut written = 0;
+    for (wallet, (balance, (slot, instruction_index, inner_instruction_index))) in balances {
+        if balance.is_zero() {
+            continue;
+        }
+
+        // The replayed position, so an earlier trade of the same slot that the
+        // worker applies later can't roll the balance back
+        sqlx::query(
+            r#"
+            INSERT INTO token_holders (
+                token_mint, user_wallet, balance, last_updated_slot,
+                last_updated_instruction_index, last_updated_inner_instruction_index
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(mint_address)
+        .bind(&wallet)
+        .bind(balance)
+        .bind(slot)
+        .bind(instruction_index)
+        .bind(inner_instruction_index)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to insert rebuilt holder")?;
+
+        written += 1;
+    }
+
+    tx.commit()
+        .await
+        .context("Failed to commit holder rebuild")?;
+    Ok(written)
+}
+
+// ==========================================
+// TRANSACTION OPERATIONS (Audit Log)
+// ==========================================
+
+/// Insert transaction record
+///
+/// A copy fetched at a higher commitment replaces a stored lower-commitment
+/// one (whose slot and block time may have drifted, which can also move it
+/// to another hypertable key); otherwise the stored row is kept. Writers of
+/// the same signature are serialized on an advisory lock, since the hypertable
+/// can't have a unique index on the signature alone.
+pub async fn insert_transaction(pool: &PgPool, transaction: &Transaction) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(&transaction.signature)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to lock transaction signature")?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM transactions
+        WHERE signature = $1 AND commitment_rank(commitment) < commitment_rank($2)
+        "#,
+    )
+    .bind(&transaction.signature)
+    .bind(transaction.commitment)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to replace transaction")?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO transactions (
+            signature, slot, block_time, signer, success, instruction_count, commitment
+        )
+        SELECT $1, $2, $3, $4, $5, $6, $7
+        WHERE NOT EXISTS (SELECT 1 FROM transactions WHERE signature = $1)
+        ON CONFLICT (block_time, signature) DO NOTHING
+        "#,
+    )
+    .bind(&transaction.signature)
+    .bind(transaction.slot)
+    .bind(transaction.block_time)
+    .bind(&transaction.signer)
+    .bind(transaction.success)
+    .bind(transaction.instruction_count)
+    .bind(transaction.commitment)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to insert transaction")?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+// ==========================================
+// MIGRATION OPERATIONS
+// ==========================================
+
+/// Record a curve migration; replays of the same transaction are ignored
+pub async fn insert_migration(pool: &PgPool, migration: &Migration) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO migrations (signature, mint_address, pool_address, slot, migrated_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (signature) DO NOTHING
+        "#,
+    )
+    .bind(&migration.signature)
+    .bind(&migration.mint_address)
+    .bind(&migration.pool_address)
+    .bind(migration.slot)
+    .bind(migration.migrated_at)
+    .execute(pool)
+    .await
+    .context("Failed to insert migration")?;
+
+    Ok(())
+}
+
+// ==========================================
+// SKIPPED TRANSACTION OPERATIONS
+// ==========================================
+
+/// Archive a transaction the parser produced nothing from, with the reason
+pub async fn insert_skipped_transaction(
+    pool: &PgPool,
+    signature: &str,
+    slot: i64,
+    reason: &str,
+    raw: &serde_json::Value,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO skipped_transactions (signature, slot, reason, raw)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (signature) DO NOTHING
+        "#,
+    )
+    .bind(signature)
+    .bind(slot)
+    .bind(reason)
+    .bind(raw)
+    .execute(pool)
+    .await
+    .context("Failed to archive skipped transaction")?;
+
+    Ok(())
+}
+
+// ==========================================
+// RAW TRANSACTION OPERATIONS
+// ==========================================
+
+/// Store a fetched transaction's compressed JSON. One already stored is only
+/// replaced by a higher commitment.
+pub async fn insert_raw_transaction(
+    pool: &PgPool,
+    signature: &str,
+    slot: i64,
+    commitment: Commitment,
+    data: &[u8],
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO raw_transactions (signature, slot, commitment, data)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (signature) DO UPDATE SET
+            slot = EXCLUDED.slot,
+            commitment = EXCLUDED.commitment,
+            data = EXCLUDED.data,
+            stored_at = NOW()
+        WHERE commitment_rank(raw_transactions.commitment) < commitment_rank(EXCLUDED.commitment)
+        "#,
+    )
+    .bind(signature)
+    .bind(slot)
+    .bind(commitment)
+    .bind(data)
+    .execute(pool)
+    .await
+    .context("Failed to store raw transaction")?;
+
+    Ok(())
+}
+
+/// Stored transactions in `[from_slot, to_slot]`, in slot order, `limit` at
+/// a time. Pass the last row's `(slot, signature)` as `after` for the next
+/// page.
+pub async fn get_raw_transactions(
+    pool: &PgPool,
+    from_slot: i64,
+    to_slot: i64,
+    after: Option<(i64, &str)>,
+    limit: i64,
+) -> Result<Vec<RawTransaction>> {
+    let (after_slot, after_signature) = after.unwrap_or((i64::MIN, ""));
+    sqlx::query_as::<_, RawTransaction>(
+        r#"
+        SELECT signature, slot, commitment, data, stored_at
+        FROM raw_transactions
+        WHERE slot BETWEEN $1 AND $2 AND (slot, signature) > ($3, $4)
+        ORDER BY slot, signature
+        LIMIT $5
+        "#,
+    )
+    .bind(from_slot)
+    .bind(to_slot)
+    .bind(after_slot)
+    .bind(after_signature)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch raw transactions")
+}
+
+/// Delete up to `limit` stored transactions kept since before `cutoff`.
+/// Returns the number deleted.
+pub async fn prune_raw_transactions(
+    pool: &PgPool,
+    cutoff: DateTime<Utc>,
+    limit: i64,
+) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM raw_transactions
+        WHERE signature IN (
+            SELECT signature FROM raw_transactions WHERE stored_at < $1 LIMIT $2
+        )
+        "#,
+    )
+    .bind(cutoff)
+    .bind(limit)
+    .execute(pool)
+    .await
+    .context("Failed to prune raw transactions")?;
+
+    Ok(result.rows_affected())
+}
+
+// ==========================================
+// ANALYTICS / STATS QUERIES
+// ==========================================
+
+/// Get total trades count for a token
+pub async fn get_token_trade_count(pool: &PgPool, mint_address: &str) -> Result<i64> {
+    let row = sqlx::query(
+        r#"
+        SELECT COUNT(*) as count FROM trades WHERE token_mint = $1
+        "#,
+    )
+    .bind(mint_address)
+    .fetch_one(pool)
+    .await
+    .context("Failed to get trade count")?;
+
+    let count: i64 = row.try_get("count")?;
+    Ok(count)
+}
+
+/// Number of wallets holding a token, including dust holders folded into its
+/// tail by the holder cap
+pub async fn get_holder_count(pool: &PgPool, mint_address: &str) -> Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM token_holders WHERE token_mint = $1 AND balance > 0)
+            + COALESCE((SELECT holder_count FROM token_holder_tails WHERE token_mint = $1), 0)
+        "#,
+    )
+    .bind(mint_address)
+    .fetch_one(pool)
+    .await
+    .context("Failed to get holder count")?;
+
+    Ok(count)
+}
+
+/// Realized and unrealized PnL of `wallet` on `mint_address`, walking its
+/// buys and sells in order with a weighted-average cost basis. Each trade is
+/// valued at the SOL price it was recorded with; the open position at the
+/// token's current curve price and the latest recorded SOL price.
+///
+/// Sells beyond the tracked position (trades from before indexing, tokens
+/// received by transfer) have no known cost: the position floors at zero and
+/// the excess is realized at zero cost.
+pub async fn get_wallet_pnl(pool: &PgPool, mint_address: &str, wallet: &str) -> Result<WalletPnl> {
+    let trades = sqlx::query(
+        r#"
+        SELECT is_buy, sol_amount, token_amount, sol_price_usd
+        FROM trades
+        WHERE token_mint = $1 AND user_wallet = $2 AND track_volume
+        ORDER BY slot, timestamp, instruction_index, inner_instruction_index
+        "#,
+    )
+    .bind(mint_address)
+    .bind(wallet)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch wallet trades")?;
+
+    let current = sqlx::query(
+        r#"
+        SELECT
+            t.virtual_sol_reserves,
+            t.virtual_token_reserves,
+            (SELECT sol_price_usd FROM trades
+             WHERE token_mint = $1 AND sol_price_usd IS NOT NULL
+             ORDER BY timestamp DESC LIMIT 1) AS sol_price_usd,
+            (SELECT balance FROM token_holders
+             WHERE token_mint = $1 AND user_wallet = $2) AS balance
+        FROM tokens t
+        WHERE t.mint_address = $1
+        "#,
+    )
+    .bind(mint_address)
+    .bind(wallet)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch token for PnL")?;
+
+    let current_sol_price: Option<Decimal> =
+        current.as_ref().and_then(|row| row.get("sol_price_usd"));
+    let lamports_per_sol = Decimal::from(LAMPORTS_PER_SOL);
+    // USD value of `lamports` at `sol_price` (the current price when unknown)
+    let to_usd = |lamports: Decimal, sol_price: Option<Decimal>| {
+        sol_price
+            .or(current_sol_price)
+            .map_or(Decimal::ZERO, |price| lamports / lamports_per_sol * price)
+    };
+
+    let mut pnl = WalletPnl {
+        token_mint: mint_address.to_string(),
+        user_wallet: wallet.to_string(),
+        ..Default::default()
+    };
+    let mut position = Decimal::ZERO;
+    let mut position_cost_usd = Decimal::ZERO;
+
+    for row in &trades {
+        let sol_amount: Decimal = row.get("sol_amount");
+        let token_amount: Decimal = row.get("token_amount");
+        let value_usd = to_usd(sol_amount, row.get("sol_price_usd"));
+
+        if row.get("is_buy") {
+            pnl.total_bought_sol += sol_amount;
+            position += token_amount;
+            position_cost_usd += value_usd;
+        } else {
+            pnl.total_sold_sol += sol_amount;
+            let matched = token_amount.min(position);
+            let cost_usd = if position.is_zero() {
+                Decimal::ZERO
+            } else {
+                position_cost_usd * matched / position
+            };
+            pnl.realized_usd += value_usd - cost_usd;
+            position -= matched;
+            position_cost_usd = (position_cost_usd - cost_usd).max(Decimal::ZERO);
+        }
+    }
+
+    // The holder row follows on-chain balances, transfers included; the
+    // tracked position is the fallback
+    pnl.balance = current
+        .as_ref()
+        .and_then(|row| row.get::<Option<Decimal>, _>("balance"))
+        .unwrap_or(position);
+
+    if let Some(row) = &current
+        && let Ok(price) = price_from_reserves(
+            row.get("virtual_sol_reserves"),
+            row.get("virtual_token_reserves"),
+        )
+    {
+        let average_cost_usd = if position.is_zero() {
+            Decimal::ZERO
+        } else {
+            position_cost_usd / position
+        };
+        let value_usd = to_usd(raw_to_tokens(pnl.balance) * price * lamports_per_sol, None);
+        pnl.unrealized_usd = value_usd - average_cost_usd * pnl.balance;
+    }
+
+    Ok(pnl)
+}
+
+/// Average network fee of the transactions that traded `mint_address`, in
+/// lamports; each transaction counts once however many trades it holds.
+/// `None` when none of its trades were stored with a fee.
+pub async fn get_avg_fee(pool: &PgPool, mint_address: &str) -> Result<Option<Decimal>> {
+    let avg_fee = sqlx::query_scalar::<_, Option<Decimal>>(
+        r#"
+        SELECT AVG(fee_lamports)
+        FROM (
+            SELECT DISTINCT ON (signature) fee_lamports
+            FROM trades
+            WHERE token_mint = $1 AND fee_lamports IS NOT NULL
+        ) per_transaction
         "#,
     )
     .bind(mint_address)
     .fetch_one(pool)
     .await
-    .context("Failed to get 24h volume")?;
+    .context("Failed to get average fee")?;
+
+    Ok(avg_fee)
+}
+
+/// Holder count and concentration of a token, `None` if it isn't indexed.
+/// Zero balances don't count. The Gini coefficient only sees holders with a
+/// row; dust folded into the tail is in the count but not the distribution.
+pub async fn get_holder_stats(pool: &PgPool, mint_address: &str) -> Result<Option<HolderStats>> {
+    let stats = sqlx::query_as::<_, HolderStats>(
+        r#"
+        WITH holders AS (
+            SELECT balance FROM token_holders WHERE token_mint = $1 AND balance > 0
+        ),
+        ranked AS (
+            SELECT
+                balance,
+                ROW_NUMBER() OVER (ORDER BY balance) AS rank,
+                COUNT(*) OVER () AS n,
+                SUM(balance) OVER () AS total
+            FROM holders
+        )
+        SELECT
+            t.mint_address AS token_mint,
+            (SELECT COUNT(*) FROM holders) + COALESCE(tail.holder_count, 0) AS holder_count,
+            ROUND(COALESCE(
+                (SELECT SUM(balance) FROM (
+                    SELECT balance FROM holders ORDER BY balance DESC LIMIT 10
+                ) top) * 100 / NULLIF(t.token_total_supply, 0),
+                0
+            ), 4) AS top10_percent,
+            -- G = 2 * sum(rank * balance) / (n * total) - (n + 1) / n,
+            -- balances ranked ascending
+            ROUND(COALESCE(
+                (SELECT 2 * SUM(rank * balance) / (MAX(n) * MAX(total))
+                        - (MAX(n) + 1)::NUMERIC / MAX(n)
+                 FROM ranked),
+                0
+            ), 4) AS gini
+        FROM tokens t
+        LEFT JOIN token_holder_tails tail ON tail.token_mint = t.mint_address
+        WHERE t.mint_address = $1
+        "#,
+    )
+    .bind(mint_address)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to get holder stats")?;
+
+    Ok(stats)
+}
+
+/// Get a token's trades in one slot, in intra-slot order (input for sandwich
+/// detection)
+pub async fn get_trades_in_slot(
+    pool: &PgPool,
+    mint_address: &str,
+    slot: i64,
+) -> Result<Vec<Trade>> {
+    let trades = sqlx::query_as::<_, Trade>(
+        r#"
+        SELECT 
+            signature,
+            token_mint,
+            sol_amount,
+            token_amount,
+            is_buy,
+            user_wallet,
+            timestamp,
+            virtual_sol_reserves,
+            virtual_token_reserves,
+            price_sol,
+            price_usd,
+            sol_price_usd,
+            sol_price_at,
+            usd_amount,
+            creator_fee_sol,
+            track_volume,
+            ix_name,
+            slot,
+            instruction_index,
+            inner_instruction_index,
+            accounts,
+            fee_lamports,
+            compute_units,
+            compute_unit_price
+        FROM trades 
+        WHERE token_mint = $1 AND slot = $2
+        ORDER BY instruction_index, inner_instruction_index
+        "#,
+    )
+    .bind(mint_address)
+    .bind(slot)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch trades in slot")?;
+
+    Ok(trades)
+}
+
+/// Flag a victim trade as sandwiched, recording the attacker and their profit.
+/// Only the trade at `victim`'s key is flagged, not others in its transaction.
+pub async fn mark_sandwiched(
+    pool: &PgPool,
+    victim: &TradeCursor,
+    attacker_wallet: &str,
+    profit: Decimal,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE trades
+        SET sandwiched = TRUE, sandwich_attacker = $5, sandwich_profit = $6
+        WHERE timestamp = $1 AND signature = $2
+          AND instruction_index = $3 AND inner_instruction_index = $4
+        "#,
+    )
+    .bind(victim.timestamp)
+    .bind(&victim.signature)
+    .bind(victim.instruction_index)
+    .bind(victim.inner_instruction_index)
+    .bind(attacker_wallet)
+    .bind(profit)
+    .execute(pool)
+    .await
+    .context("Failed to mark sandwiched trade")?;
+
+    Ok(())
+}
+
+/// Count the wallets, creator excluded, that bought `mint_address` in its
+/// creation slot and store the count, with `bundled` set from `bundled_min`.
+/// `None` if the token or its creation slot isn't stored yet.
+pub async fn update_sniper_count(
+    pool: &PgPool,
+    mint_address: &str,
+    bundled_min: i32,
+) -> Result<Option<i32>> {
+    let count = sqlx::query_scalar::<_, i32>(
+        r#"
+        UPDATE tokens t
+        SET (sniper_count, bundled) = (
+            SELECT COUNT(DISTINCT tr.user_wallet)::INT,
+                   COUNT(DISTINCT tr.user_wallet) >= $2
+            FROM trades tr
+            WHERE tr.token_mint = t.mint_address
+            AND tr.slot = t.creation_slot
+            AND tr.is_buy
+            AND tr.track_volume
+            AND tr.user_wallet IS DISTINCT FROM t.creator_wallet
+        ),
+        updated_at = NOW()
+        WHERE t.mint_address = $1 AND t.creation_slot IS NOT NULL
+        RETURNING t.sniper_count
+        "#,
+    )
+    .bind(mint_address)
+    .bind(bundled_min)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to update sniper count")?;
+
+    Ok(count)
+}
+
+/// Recompute whether the creator of `mint_address` has sold or transferred
+/// out any of their tokens, from the stored trades. `false` if the token or
+/// its creator isn't known.
+pub async fn update_dev_sold(pool: &PgPool, mint_address: &str) -> Result<bool> {
+    let dev_sold = sqlx::query_scalar::<_, bool>(
+        r#"
+        UPDATE tokens t
+        SET dev_sold = EXISTS (
+            SELECT 1 FROM trades tr
+            WHERE tr.token_mint = t.mint_address
+            AND tr.user_wallet = t.creator_wallet
+            AND NOT tr.is_buy
+        ),
+        updated_at = NOW()
+        WHERE t.mint_address = $1 AND t.creator_wallet IS NOT NULL
+        RETURNING t.dev_sold
+        "#,
+    )
+    .bind(mint_address)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to update dev sold flag")?;
+
+    Ok(dev_sold.unwrap_or(false))
+}
+
+/// The creator's current balance of `mint_address` as a percentage of its
+/// total supply (0 once they hold none). `None` if the token or its creator
+/// isn't known.
+pub async fn get_dev_holdings(pool: &PgPool, mint_address: &str) -> Result<Option<Decimal>> {
+    let holdings = sqlx::query_scalar::<_, Option<Decimal>>(
+        r#"
+        SELECT COALESCE(h.balance, 0) * 100 / NULLIF(t.token_total_supply, 0)
+        FROM tokens t
+        LEFT JOIN token_holders h
+            ON h.token_mint = t.mint_address AND h.user_wallet = t.creator_wallet
+        WHERE t.mint_address = $1 AND t.creator_wallet IS NOT NULL
+        "#,
+    )
+    .bind(mint_address)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to get dev holdings")?;
+
+    Ok(holdings.flatten())
+}
+
+/// Get the wallets, creator excluded, that bought a token in its creation
+/// slot, biggest spenders first
+pub async fn get_snipers(pool: &PgPool, mint_address: &str) -> Result<Vec<Sniper>> {
+    let snipers = sqlx::query_as::<_, Sniper>(
+        r#"
+        SELECT
+            tr.user_wallet,
+            COUNT(*) as buy_count,
+            SUM(tr.sol_amount) as sol_amount,
+            SUM(tr.token_amount) as token_amount
+        FROM trades tr
+        JOIN tokens t ON t.mint_address = tr.token_mint
+        WHERE tr.token_mint = $1
+        AND tr.slot = t.creation_slot
+        AND tr.is_buy
+        AND tr.track_volume
+        AND tr.user_wallet IS DISTINCT FROM t.creator_wallet
+        GROUP BY tr.user_wallet
+        ORDER BY sol_amount DESC, tr.user_wallet
+        "#,
+    )
+    .bind(mint_address)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch snipers")?;
+
+    Ok(snipers)
+}
+
+/// Get how many of a token's trades were sandwiched
+pub async fn get_sandwich_count(pool: &PgPool, mint_address: &str) -> Result<i64> {
+    let row = sqlx::query(
+        r#"
+        SELECT COUNT(*) as count FROM trades WHERE token_mint = $1 AND sandwiched
+        "#,
+    )
+    .bind(mint_address)
+    .fetch_one(pool)
+    .await
+    .context("Failed to get sandwich count")?;
+
+    let count: i64 = row.try_get("count")?;
+    Ok(count)
+}
+
+/// Window bounds shared by the `token_volume_24h` readers: buckets wholly
+/// inside the last 24h come from the aggregate up to its watermark (the end of
+/// its newest bucket); the partial first hour and everything past the
+/// watermark come from `trades`
+const VOLUME_24H_BOUNDS: &str = r#"
+    bounds AS (
+        SELECT
+            NOW() - INTERVAL '24 hours' as since,
+            time_bucket(INTERVAL '1 hour', NOW() - INTERVAL '24 hours') + INTERVAL '1 hour' as first_bucket,
+            (SELECT MAX(bucket) + INTERVAL '1 hour' FROM token_volume_24h) as watermark
+    )
+"#;
+
+/// SOL and USD volume of a token over the last 24h.
+///
+/// With TimescaleDB this reads the `token_volume_24h` continuous aggregate and
+/// only scans `trades` for the window it doesn't cover yet (roughly the last
+/// hour, see the migration for the refresh policy).
+pub async fn get_24h_volume_stats(pool: &PgPool, mint_address: &str) -> Result<VolumeStats> {
+    let sql = if capabilities().timescaledb {
+        format!(
+            r#"
+            WITH {VOLUME_24H_BOUNDS},
+            aggregated AS (
+                SELECT
+                    COALESCE(SUM(v.volume_sol), 0) as volume_sol,
+                    COALESCE(SUM(v.volume_usd), 0) as volume_usd,
+                    COALESCE(SUM(v.trade_count - v.priced_count), 0) as unpriced
+                FROM token_volume_24h v, bounds b
+                WHERE v.token_mint = $1
+                AND v.bucket >= b.first_bucket
+                AND v.bucket < b.watermark
+            ),
+            live AS (
+                SELECT
+                    COALESCE(SUM(t.sol_amount), 0) as volume_sol,
+                    COALESCE(SUM(t.usd_amount), 0) as volume_usd,
+                    COUNT(*) FILTER (WHERE t.usd_amount IS NULL) as unpriced
+                FROM trades t, bounds b
+                WHERE t.token_mint = $1
+                AND t.track_volume
+                AND t.timestamp > b.since
+                AND (b.watermark IS NULL OR t.timestamp < b.first_bucket OR t.timestamp >= b.watermark)
+            )
+            SELECT
+                a.volume_sol + l.volume_sol as volume_sol,
+                a.volume_usd + l.volume_usd as volume_usd,
+                (a.unpriced + l.unpriced)::int8 as unpriced_trades
+            FROM aggregated a, live l
+            "#
+        )
+    } else {
+        r#"
+        SELECT
+            COALESCE(SUM(sol_amount), 0) as volume_sol,
+            COALESCE(SUM(usd_amount), 0) as volume_usd,
+            COUNT(*) FILTER (WHERE usd_amount IS NULL) as unpriced_trades
+        FROM trades
+        WHERE token_mint = $1
+        AND track_volume
+        AND timestamp > NOW() - INTERVAL '24 hours'
+        "#
+        .to_string()
+    };
+
+    let stats = sqlx::query_as::<_, VolumeStats>(&sql)
+        .bind(mint_address)
+        .fetch_one(pool)
+        .await
+        .context("Failed to get 24h volume")?;
+
+    Ok(stats)
+}
+
+/// Get 24h SOL volume for a token (see `get_24h_volume_stats`)
+pub async fn get_24h_volume(pool: &PgPool, mint_address: &str) -> Result<Decimal> {
+    Ok(get_24h_volume_stats(pool, mint_address).await?.volume_sol)
+}
+
+/// Get 24h USD volume for a token, leaving out unpriced trades (see
+/// `get_24h_volume_stats`)
+pub async fn get_24h_volume_usd(pool: &PgPool, mint_address: &str) -> Result<Decimal> {
+    Ok(get_24h_volume_stats(pool, mint_address).await?.volume_usd)
+}
+
+/// Tokens with the highest SOL volume over the last 24h, built on the same
+/// aggregate (and fallback) as `get_24h_volume`. Dead tokens are left out as
+/// in `get_recently_active_tokens`.
+pub async fn get_top_tokens_by_volume(
+    pool: &PgPool,
+    limit: i64,
+    dead_before: Option<DateTime<Utc>>,
+    min_market_cap_usd: Decimal,
+) -> Result<Vec<TokenVolume>> {
+    let sql = if capabilities().timescaledb {
+        format!(
+            r#"
+            WITH {VOLUME_24H_BOUNDS},
+            volumes AS (
+                SELECT v.token_mint, v.volume_sol
+                FROM token_volume_24h v, bounds b
+                WHERE v.bucket >= b.first_bucket
+                AND v.bucket < b.watermark
+                UNION ALL
+                SELECT t.token_mint, t.sol_amount
+                FROM trades t, bounds b
+                WHERE t.track_volume
+                AND t.timestamp > b.since
+                AND (b.watermark IS NULL OR t.timestamp < b.first_bucket OR t.timestamp >= b.watermark)
+            )
+            SELECT token_mint, SUM(volume_sol) as volume_sol
+            FROM volumes
+            WHERE NOT EXISTS (
+                SELECT 1 FROM tokens tk
+                WHERE tk.mint_address = token_mint AND {DEAD_TOKEN_SQL}
+            )
+            GROUP BY token_mint
+            ORDER BY volume_sol DESC
+            LIMIT $1
+            "#
+        )
+    } else {
+        format!(
+            r#"
+            SELECT token_mint, SUM(sol_amount) as volume_sol
+            FROM trades
+            WHERE track_volume
+            AND timestamp > NOW() - INTERVAL '24 hours'
+            AND NOT EXISTS (
+                SELECT 1 FROM tokens tk
+                WHERE tk.mint_address = token_mint AND {DEAD_TOKEN_SQL}
+            )
+            GROUP BY token_mint
+            ORDER BY volume_sol DESC
+            LIMIT $1
+            "#
+        )
+    };
+
+    let tokens = sqlx::query_as::<_, TokenVolume>(&sql)
+        .bind(limit)
+        .bind(dead_before)
+        .bind(min_market_cap_usd)
+        .fetch_all(pool)
+        .await
+        .context("Failed to get top tokens by volume")?;
+
+    Ok(tokens)
+}
+
+/// USD price candles for a token over `[from, to)`, ascending by time.
+/// Buckets without priced trades are omitted rather than emitted as gaps.
+///
+/// With TimescaleDB and an interval of whole minutes this rolls up the
+/// `token_candles_1m` continuous aggregate instead of scanning `trades`; a
+/// minute then counts if it starts within `[from, to)`.
+pub async fn get_ohlcv(
+    pool: &PgPool,
+    mint_address: &str,
+    interval_secs: i64,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Candle>> {
+    let sql = if capabilities().timescaledb && interval_secs % 60 == 0 {
+        r#"
+        SELECT
+            time_bucket(make_interval(secs => $2), bucket) as bucket_start,
+            first(open, bucket) as open,
+            MAX(high) as high,
+            MIN(low) as low,
+            last(close, bucket) as close,
+            SUM(volume_sol) as volume_sol,
+            SUM(trade_count)::int8 as trade_count
+        FROM token_candles_1m
+        WHERE token_mint = $1
+        AND bucket >= $3
+        AND bucket < $4
+        GROUP BY bucket_start
+        ORDER BY bucket_start ASC
+        "#
+        .to_string()
+    } else {
+        format!(
+            r#"
+            SELECT
+                {bucket} as bucket_start,
+                (array_agg(price_usd ORDER BY timestamp, slot, instruction_index, inner_instruction_index))[1] as open,
+                MAX(price_usd) as high,
+                MIN(price_usd) as low,
+                (array_agg(price_usd ORDER BY timestamp DESC, slot DESC, instruction_index DESC, inner_instruction_index DESC))[1] as close,
+                SUM(sol_amount) as volume_sol,
+                COUNT(*) as trade_count
+            FROM trades
+            WHERE token_mint = $1
+            AND track_volume
+            AND price_usd IS NOT NULL
+            AND timestamp >= $3
+            AND timestamp < $4
+            GROUP BY bucket_start
+            ORDER BY bucket_start ASC
+            "#,
+            bucket = time_bucket_sql("timestamp", "$2")
+        )
+    };
+
+    let candles = sqlx::query_as::<_, Candle>(&sql)
+        .bind(mint_address)
+        .bind(interval_secs)
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await
+        .context("Failed to get OHLCV candles")?;
+
+    Ok(candles)
+}
+
+/// Rematerialize the one-minute candles covering `[from, to]`, e.g. after a
+/// backfill wrote trades older than the refresh policy looks back. No-op
+/// without TimescaleDB.
+pub async fn refresh_candles(pool: &PgPool, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<()> {
+    if !capabilities().timescaledb {
+        return Ok(());
+    }
+
+    // Only whole buckets inside the window are refreshed, so widen it to the
+    // minutes `from` and `to` fall in. Sent as a simple query: the refresh
+    // refuses to run inside a transaction block, which a prepared statement
+    // can count as.
+    let sql = format!(
+        r#"
+        CALL refresh_continuous_aggregate(
+            'token_candles_1m',
+            time_bucket(INTERVAL '1 minute', '{from}'::timestamptz),
+            time_bucket(INTERVAL '1 minute', '{to}'::timestamptz) + INTERVAL '1 minute'
+        )
+        "#,
+        from = from.to_rfc3339(),
+        to = to.to_rfc3339()
+    );
+    sqlx::raw_sql(&sql)
+        .execute(pool)
+        .await
+        .context("Failed to refresh candles")?;
+
+    Ok(())
+}
+
+/// Trade counts and volume over `[from, to)` by day of week and hour of day in
+/// `time_zone` (an IANA name such as `"UTC"` or `"America/New_York"`), for one
+/// token or, with `mint_address = None`, the whole platform. Cells without
+/// trades are omitted.
+pub async fn get_activity_heatmap(
+    pool: &PgPool,
+    mint_address: Option<&str>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    time_zone: &str,
+) -> Result<Vec<HeatmapCell>> {
+    let cells = sqlx::query_as::<_, HeatmapCell>(
+        r#"
+        SELECT
+            extract(isodow FROM timestamp AT TIME ZONE $4)::INT as day_of_week,
+            extract(hour FROM timestamp AT TIME ZONE $4)::INT as hour_of_day,
+            COUNT(*) as trade_count,
+            COALESCE(SUM(sol_amount), 0) as volume_sol
+        FROM trades
+        WHERE ($1::TEXT IS NULL OR token_mint = $1)
+        AND track_volume
+        AND timestamp >= $2
+        AND timestamp < $3
+        GROUP BY day_of_week, hour_of_day
+        ORDER BY day_of_week, hour_of_day
+        "#,
+    )
+    .bind(mint_address)
+    .bind(from)
+    .bind(to)
+    .bind(time_zone)
+    .fetch_all(pool)
+    .await
+    .context("Failed to get activity heatmap")?;
 
-    let volume: Decimal = row.try_get("volume")?;
-    Ok(volume)
+    Ok(cells)
 }
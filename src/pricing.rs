@@ -0,0 +1,185 @@
+//! Bonding-curve economics shared by the parser and the worker. Inputs are
+//! raw units as they appear on chain: lamports, and token base units. Prices
+//! and market caps come out per whole token, in SOL or USD.
+
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use std::fmt::Display;
+use std::str::FromStr;
+use thiserror::Error;
+use tracing::warn;
+
+use crate::metrics;
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+/// Decimals of every pump.fun mint
+pub const TOKEN_DECIMALS: u32 = 6;
+
+/// A launchpad's curve economics. Pump.fun has changed these over time and
+/// devnet differs, so they are configurable rather than baked in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurveParams {
+    /// Virtual SOL every curve holds on top of its real reserves (lamports)
+    pub virtual_sol_offset: u64,
+    /// Virtual SOL reserves at which a curve is complete (lamports)
+    pub complete_virtual_sol: u64,
+    /// Starting reserves and supply (raw token units), for events that
+    /// don't report them
+    pub initial_virtual_token_reserves: u64,
+    pub initial_real_token_reserves: u64,
+    pub token_total_supply: u64,
+}
+
+impl Default for CurveParams {
+    /// Pump.fun mainnet: 30 SOL offset, complete at 85 SOL, 1B tokens
+    fn default() -> Self {
+        Self {
+            virtual_sol_offset: 30 * LAMPORTS_PER_SOL,
+            complete_virtual_sol: 85 * LAMPORTS_PER_SOL,
+            initial_virtual_token_reserves: 1_073_000_000_000_000,
+            initial_real_token_reserves: 793_100_000_000_000,
+            token_total_supply: 1_000_000_000_000_000,
+        }
+    }
+}
+
+impl CurveParams {
+    /// Read from `CURVE_VIRTUAL_SOL_OFFSET`, `CURVE_COMPLETE_SOL` (lamports),
+    /// `CURVE_INITIAL_VIRTUAL_TOKENS`, `CURVE_INITIAL_REAL_TOKENS` and
+    /// `CURVE_TOKEN_SUPPLY` (raw units), keeping the defaults for unset or
+    /// invalid values
+    pub fn from_env() -> Self {
+        Self::from_env_over(Self::default())
+    }
+
+    /// `from_env`, with `defaults` (e.g. read from chain) in place of the
+    /// built-in ones
+    pub fn from_env_over(defaults: Self) -> Self {
+        fn var(name: &str) -> Option<u64> {
+            std::env::var(name).ok()?.parse().ok()
+        }
+
+        Self {
+            virtual_sol_offset: var("CURVE_VIRTUAL_SOL_OFFSET")
+                .unwrap_or(defaults.virtual_sol_offset),
+            complete_virtual_sol: var("CURVE_COMPLETE_SOL")
+                .filter(|sol| *sol > 0)
+                .unwrap_or(defaults.complete_virtual_sol),
+            initial_virtual_token_reserves: var("CURVE_INITIAL_VIRTUAL_TOKENS")
+                .unwrap_or(defaults.initial_virtual_token_reserves),
+            initial_real_token_reserves: var("CURVE_INITIAL_REAL_TOKENS")
+                .unwrap_or(defaults.initial_real_token_reserves),
+            token_total_supply: var("CURVE_TOKEN_SUPPLY").unwrap_or(defaults.token_total_supply),
+        }
+    }
+}
+
+/// Why a curve figure couldn't be computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum PricingError {
+    #[error("token reserves are empty")]
+    EmptyReserves,
+    #[error("SOL price is not a finite positive number")]
+    InvalidSolPrice,
+    #[error("arithmetic overflow")]
+    Overflow,
+    #[error("{0} is not a valid amount")]
+    InvalidAmount(&'static str),
+    #[error("price exponent {0} is out of range")]
+    InvalidExponent(i64),
+}
+
+/// Parse a raw on-chain amount string (token balances are strings in RPC
+/// responses). A failure is logged against `field` and counted instead of
+/// quietly becoming zero; callers propagate it so the transaction is
+/// dead-lettered.
+pub fn parse_raw_amount<T: FromStr>(raw: &str, field: &'static str) -> Result<T, PricingError> {
+    raw.parse().map_err(|_| conversion_failed(field, raw))
+}
+
+/// A computed figure a row can be stored without (a trade's prices), or
+/// `None` when it couldn't be computed. The failure is logged against
+/// `field` and counted rather than stored as zero.
+pub fn safe_decimal(result: Result<Decimal, PricingError>, field: &'static str) -> Option<Decimal> {
+    result
+        .inspect_err(|e| {
+            metrics::AMOUNT_CONVERSION_FAILURES.inc();
+            warn!("Could not compute {}: {}", field, e);
+        })
+        .ok()
+}
+
+fn conversion_failed(field: &'static str, value: impl Display) -> PricingError {
+    metrics::AMOUNT_CONVERSION_FAILURES.inc();
+    warn!("Could not convert {} = {}", field, value);
+    PricingError::InvalidAmount(field)
+}
+
+/// Virtual SOL reserves for a curve holding `real_sol` lamports
+pub fn virtual_sol_from_real(real_sol: u64, curve: &CurveParams) -> u64 {
+    real_sol.saturating_add(curve.virtual_sol_offset)
+}
+
+/// `raw` token base units in whole tokens
+pub fn raw_to_tokens(raw: Decimal) -> Decimal {
+    raw / Decimal::from(10u64.pow(TOKEN_DECIMALS))
+}
+
+/// Price in SOL per whole token of `lamports` against `raw_tokens` base
+/// units: a trade's execution price, or the curve's spot price from its
+/// reserves
+pub fn price_from_amounts(lamports: Decimal, raw_tokens: Decimal) -> Result<Decimal, PricingError> {
+    if raw_tokens <= Decimal::ZERO || lamports < Decimal::ZERO {
+        return Err(PricingError::EmptyReserves);
+    }
+    // Scaled up before dividing, so tiny prices keep their precision
+    lamports
+        .checked_mul(Decimal::from(10u64.pow(TOKEN_DECIMALS)))
+        .and_then(|scaled| scaled.checked_div(raw_tokens))
+        .and_then(|price| price.checked_div(Decimal::from(LAMPORTS_PER_SOL)))
+        .ok_or(PricingError::Overflow)
+}
+
+/// Spot price of the constant-product curve, in SOL per whole token
+pub fn price_from_reserves(
+    virtual_sol: Decimal,
+    virtual_token: Decimal,
+) -> Result<Decimal, PricingError> {
+    price_from_amounts(virtual_sol, virtual_token)
+}
+
+/// Convert a SOL-denominated value to USD at `sol_price_usd`
+pub fn sol_to_usd(value: Decimal, sol_price_usd: f64) -> Result<Decimal, PricingError> {
+    let sol_price = Decimal::from_f64(sol_price_usd)
+        .filter(|price| *price > Decimal::ZERO)
+        .ok_or(PricingError::InvalidSolPrice)?;
+    value.checked_mul(sol_price).ok_or(PricingError::Overflow)
+}
+
+/// Market cap at the curve's spot price: price x total supply (raw units),
+/// in USD
+pub fn market_cap_from_reserves(
+    virtual_sol: Decimal,
+    virtual_token: Decimal,
+    total_supply: Decimal,
+    sol_price_usd: f64,
+) -> Result<Decimal, PricingError> {
+    let fdv_sol = price_from_reserves(virtual_sol, virtual_token)?
+        .checked_mul(raw_to_tokens(total_supply))
+        .ok_or(PricingError::Overflow)?;
+    sol_to_usd(fdv_sol, sol_price_usd)
+}
+
+/// Bonding curve progress in percent of `curve.complete_virtual_sol`. Not
+/// capped: 100 or more means the curve is complete.
+pub fn progress_from_reserves(
+    virtual_sol: Decimal,
+    curve: &CurveParams,
+) -> Result<Decimal, PricingError> {
+    if virtual_sol < Decimal::ZERO {
+        return Err(PricingError::EmptyReserves);
+    }
+    (virtual_sol / Decimal::from(curve.complete_virtual_sol.max(1)))
+        .checked_mul(Decimal::ONE_HUNDRED)
+        .ok_or(PricingError::Overflow)
+}
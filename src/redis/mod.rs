@@ -1 +1,3 @@
 pub mod redis_cleint;
+pub mod token_card;
+pub mod trade_feed;
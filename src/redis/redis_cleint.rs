@@ -3,9 +3,19 @@ use futures_util::StreamExt;
 use redis::aio::ConnectionManager;
 use redis::{AsyncCommands, Client, RedisResult};
 use serde_json;
+use std::collections::HashMap;
 use std::fmt;
 use tracing::{info, warn};
 
+/// Entries fetched per `XREADGROUP`, and how long it blocks waiting for any
+const STREAM_READ_COUNT: usize = 100;
+const STREAM_BLOCK_MS: u64 = 5_000;
+
+/// A stream entry as Redis returns it: id and flattened field/value list
+type RawStreamEntry = (String, Vec<String>);
+/// One stream's part of an `XREADGROUP` reply
+type StreamReply = (String, Vec<RawStreamEntry>);
+
 #[derive(Clone)]
 pub struct RedisClient {
     pub connection: ConnectionManager,
@@ -50,6 +60,321 @@ impl RedisClient {
         Ok(())
     }
 
+    /// Appends `message` to the durable `stream` for the worker consumer
+    /// group, trimming it approximately to `max_len` entries to bound memory.
+    /// Returns the entry id.
+    pub async fn xadd_signature<T: serde::Serialize>(
+        &mut self,
+        stream: &str,
+        message: &T,
+        max_len: usize,
+    ) -> Result<String> {
+        let json = serde_json::to_string(message).context("Failed to serialize message")?;
+
+        redis::cmd("XADD")
+            .arg(stream)
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(max_len)
+            .arg("*")
+            .arg("payload")
+            .arg(&json)
+            .query_async(&mut self.connection)
+            .await
+            .map_err(|e| {
+                warn!("Redis stream append error: {}", e);
+                anyhow::Error::from(e)
+            })
+    }
+
+    /// Appends `message` to the durable `stream` and, when `notify_channel` is set,
+    /// publishes the same payload on it as a low-latency nudge for idle consumers.
+    ///
+    /// Both commands run inside one `MULTI`/`EXEC` block, so a notification is never
+    /// sent for an entry that did not make it into the stream (and vice versa).
+    /// The stream is trimmed approximately to `max_len` entries to bound memory.
+    pub async fn publish_durable<T: serde::Serialize>(
+        &mut self,
+        stream: &str,
+        notify_channel: Option<&str>,
+        message: &T,
+        max_len: usize,
+    ) -> Result<String> {
+        let json = serde_json::to_string(message).context("Failed to serialize message")?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .cmd("XADD")
+            .arg(stream)
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(max_len)
+            .arg("*")
+            .arg("payload")
+            .arg(&json);
+
+        if let Some(channel) = notify_channel {
+            pipe.cmd("PUBLISH").arg(channel).arg(&json).ignore();
+        }
+
+        let (entry_id,): (String,) = pipe.query_async(&mut self.connection).await.map_err(|e| {
+            warn!("Redis durable publish error: {}", e);
+            anyhow::Error::from(e)
+        })?;
+
+        Ok(entry_id)
+    }
+
+    /// Reads `stream` as `consumer` of consumer `group` (created at the
+    /// stream's end if missing) on a dedicated connection, yielding
+    /// `(entry_id, payload)` pairs. Entries this consumer was handed before
+    /// but never acked come first, so a restarted worker picks up where it
+    /// died. Each entry must be `ack`ed once handled.
+    pub async fn consume_stream(
+        &self,
+        stream: &str,
+        group: &str,
+        consumer: &str,
+    ) -> Result<impl futures_util::Stream<Item = Result<(String, String)>>> {
+        let mut connection = ConnectionManager::new(self.client.clone())
+            .await
+            .context("Failed to open stream connection")?;
+
+        let created: RedisResult<()> = redis::cmd("XGROUP")
+            .arg("CREATE")
+            .arg(stream)
+            .arg(group)
+            .arg("$")
+            .arg("MKSTREAM")
+            .query_async(&mut connection)
+            .await;
+        match created {
+            Ok(()) => info!("Created consumer group {} on {}", group, stream),
+            Err(e) if e.code() == Some("BUSYGROUP") => {}
+            Err(e) => return Err(e).context("Failed to create consumer group"),
+        }
+
+        struct State {
+            connection: ConnectionManager,
+            stream: String,
+            group: String,
+            consumer: String,
+            /// `0` while replaying our own pending entries, then `>`
+            next_id: String,
+            buffered: std::collections::VecDeque<(String, String)>,
+        }
+
+        let state = State {
+            connection,
+            stream: stream.to_string(),
+            group: group.to_string(),
+            consumer: consumer.to_string(),
+            next_id: "0".to_string(),
+            buffered: Default::default(),
+        };
+
+        Ok(futures_util::stream::unfold(
+            state,
+            |mut state| async move {
+                loop {
+                    if let Some(entry) = state.buffered.pop_front() {
+                        return Some((Ok(entry), state));
+                    }
+
+                    // Nil when BLOCK times out, else [[stream, [entry, ...]]]
+                    let reply: RedisResult<Option<Vec<StreamReply>>> = redis::cmd("XREADGROUP")
+                        .arg("GROUP")
+                        .arg(&state.group)
+                        .arg(&state.consumer)
+                        .arg("COUNT")
+                        .arg(STREAM_READ_COUNT)
+                        .arg("BLOCK")
+                        .arg(STREAM_BLOCK_MS)
+                        .arg("STREAMS")
+                        .arg(&state.stream)
+                        .arg(&state.next_id)
+                        .query_async(&mut state.connection)
+                        .await;
+
+                    let entries = match reply {
+                        Ok(reply) => reply
+                            .into_iter()
+                            .flatten()
+                            .flat_map(|(_, entries)| entries)
+                            .collect::<Vec<_>>(),
+                        Err(e) => {
+                            let e = anyhow::Error::from(e).context("Failed to read consumer group");
+                            return Some((Err(e), state));
+                        }
+                    };
+
+                    if entries.is_empty() && state.next_id != ">" {
+                        // Pending entries all replayed; switch to new ones
+                        state.next_id = ">".to_string();
+                        continue;
+                    }
+                    if state.next_id != ">"
+                        && let Some((last_id, _)) = entries.last()
+                    {
+                        state.next_id = last_id.clone();
+                    }
+                    state
+                        .buffered
+                        .extend(entries.into_iter().filter_map(|(id, fields)| {
+                            let payload = stream_payload(&fields)?;
+                            Some((id, payload))
+                        }));
+                }
+            },
+        ))
+    }
+
+    /// Acknowledges a `consume_stream` entry, dropping it from the group's
+    /// pending list
+    pub async fn ack(&mut self, stream: &str, group: &str, id: &str) -> Result<()> {
+        self.connection
+            .xack::<_, _, _, ()>(stream, group, &[id])
+            .await
+            .context("Failed to ack stream entry")
+    }
+
+    /// Takes over up to `count` entries of `group` left unacked for
+    /// `min_idle_ms` by any consumer (e.g. a worker that died), returning
+    /// them as `(entry_id, payload)` pairs now pending on `consumer`
+    pub async fn claim_stale(
+        &mut self,
+        stream: &str,
+        group: &str,
+        consumer: &str,
+        min_idle_ms: u64,
+        count: usize,
+    ) -> Result<Vec<(String, String)>> {
+        let (_, entries, _): (String, Vec<RawStreamEntry>, Vec<String>) = redis::cmd("XAUTOCLAIM")
+            .arg(stream)
+            .arg(group)
+            .arg(consumer)
+            .arg(min_idle_ms)
+            .arg("0")
+            .arg("COUNT")
+            .arg(count)
+            .query_async(&mut self.connection)
+            .await
+            .context("Failed to claim stale entries")?;
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|(id, fields)| Some((id, stream_payload(&fields)?)))
+            .collect())
+    }
+
+    /// Reads up to `count` entries of `stream` between `start` and `end` with
+    /// `XRANGE` (ids inclusive; prefix with `(` for exclusive, `-`/`+` for open
+    /// ends). Returns `(entry_id, payload)` pairs in stream order; the payload
+    /// is `None` for entries without a `payload` field, which are still
+    /// returned so callers can page past them.
+    pub async fn read_range(
+        &mut self,
+        stream: &str,
+        start: &str,
+        end: &str,
+        count: usize,
+    ) -> Result<Vec<(String, Option<String>)>> {
+        let entries: Vec<RawStreamEntry> = redis::cmd("XRANGE")
+            .arg(stream)
+            .arg(start)
+            .arg(end)
+            .arg("COUNT")
+            .arg(count)
+            .query_async(&mut self.connection)
+            .await
+            .context("Failed to read stream range")?;
+
+        Ok(entries
+            .into_iter()
+            .map(|(id, fields)| (id, stream_payload(&fields)))
+            .collect())
+    }
+
+    /// Adds `message` to the sorted set `key`, scored by when it is due
+    /// (`due_at_ms`, Unix milliseconds), for later pickup with `take_due`.
+    pub async fn schedule<T: serde::Serialize>(
+        &mut self,
+        key: &str,
+        message: &T,
+        due_at_ms: i64,
+    ) -> Result<()> {
+        let json = serde_json::to_string(message).context("Failed to serialize message")?;
+
+        self.connection
+            .zadd::<_, _, _, ()>(key, json, due_at_ms)
+            .await
+            .context("Failed to schedule message")?;
+
+        Ok(())
+    }
+
+    /// Claims up to `count` members of `key` due by `now_ms`. Each member is
+    /// removed as it is claimed, so concurrent consumers never both get it.
+    pub async fn take_due(&mut self, key: &str, now_ms: i64, count: usize) -> Result<Vec<String>> {
+        let due: Vec<String> = self
+            .connection
+            .zrangebyscore_limit(key, "-inf", now_ms, 0, count as isize)
+            .await
+            .context("Failed to read due messages")?;
+
+        let mut claimed = Vec::with_capacity(due.len());
+        for member in due {
+            let removed: i64 = self
+                .connection
+                .zrem(key, &member)
+                .await
+                .context("Failed to claim due message")?;
+            if removed > 0 {
+                claimed.push(member);
+            }
+        }
+
+        Ok(claimed)
+    }
+
+    /// Pushes `message` onto the head of the list `key`, trimming it to the
+    /// newest `max_len` entries in the same `MULTI`/`EXEC` block.
+    pub async fn push_capped<T: serde::Serialize>(
+        &mut self,
+        key: &str,
+        message: &T,
+        max_len: usize,
+    ) -> Result<()> {
+        let json = serde_json::to_string(message).context("Failed to serialize message")?;
+
+        redis::pipe()
+            .atomic()
+            .lpush(key, json)
+            .ignore()
+            .ltrim(key, 0, max_len as isize - 1)
+            .ignore()
+            .query_async::<()>(&mut self.connection)
+            .await
+            .context("Failed to push message")?;
+
+        Ok(())
+    }
+
+    /// Removes and returns the oldest entry of a list filled by `push_capped`
+    pub async fn pop_oldest(&mut self, key: &str) -> Result<Option<String>> {
+        self.connection
+            .rpop(key, None)
+            .await
+            .context("Failed to pop message")
+    }
+
+    pub async fn list_len(&mut self, key: &str) -> Result<usize> {
+        self.connection
+            .llen(key)
+            .await
+            .context("Failed to read list length")
+    }
+
     // --- NEW: Subscribe Method for the Worker ---
     pub async fn subscribe(
         &self,
@@ -70,6 +395,29 @@ impl RedisClient {
         Ok(stream)
     }
 
+    /// Subscribe to every channel matching `pattern`; yields
+    /// `(channel, payload)` pairs
+    pub async fn psubscribe(
+        &self,
+        pattern: &str,
+    ) -> Result<impl futures_util::Stream<Item = (String, String)>> {
+        let mut pubsub_conn = self
+            .client
+            .get_async_pubsub()
+            .await
+            .context("Failed to get PubSub")?;
+        pubsub_conn.psubscribe(pattern).await?;
+
+        let stream = pubsub_conn.into_on_message().map(|msg| {
+            (
+                msg.get_channel_name().to_string(),
+                msg.get_payload::<String>().unwrap_or_default(),
+            )
+        });
+
+        Ok(stream)
+    }
+
     pub async fn set<T: serde::Serialize>(
         &mut self,
         key: &str,
@@ -106,6 +454,74 @@ impl RedisClient {
         }
     }
 
+    /// Raise the integer at `key` to `value`, leaving it alone if it is already
+    /// higher; atomic, so concurrent writers never move it backwards
+    pub async fn set_max(&mut self, key: &str, value: i64) -> Result<()> {
+        redis::cmd("EVAL")
+            .arg(
+                "local current = tonumber(redis.call('GET', KEYS[1]))
+                 if current == nil or tonumber(ARGV[1]) > current then
+                     redis.call('SET', KEYS[1], ARGV[1])
+                 end",
+            )
+            .arg(1)
+            .arg(key)
+            .arg(value)
+            .query_async::<()>(&mut self.connection)
+            .await
+            .context("Failed to raise value")?;
+
+        Ok(())
+    }
+
+    /// Set `key` with a TTL unless it already exists (`SET NX EX`).
+    /// Returns whether this call created it.
+    pub async fn set_nx(&mut self, key: &str, expiry_seconds: u64) -> Result<bool> {
+        let created: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(expiry_seconds)
+            .query_async(&mut self.connection)
+            .await
+            .context("Failed to set key if absent")?;
+
+        Ok(created.is_some())
+    }
+
+    /// Set several fields of the hash `key` at once (`HSET`), optionally
+    /// (re)setting its TTL in the same round-trip
+    pub async fn hset_multiple(
+        &mut self,
+        key: &str,
+        fields: &[(&str, String)],
+        expiry_seconds: Option<u64>,
+    ) -> Result<()> {
+        let mut pipe = redis::pipe();
+        pipe.atomic().hset_multiple(key, fields).ignore();
+        if let Some(seconds) = expiry_seconds {
+            pipe.expire(key, seconds as i64).ignore();
+        }
+
+        pipe.query_async::<()>(&mut self.connection)
+            .await
+            .context("Failed to set hash fields")?;
+
+        Ok(())
+    }
+
+    /// Every field of the hash `key`; empty when it doesn't exist
+    pub async fn hgetall(&mut self, key: &str) -> Result<HashMap<String, String>> {
+        let fields = self
+            .connection
+            .hgetall(key)
+            .await
+            .context("Failed to read hash")?;
+
+        Ok(fields)
+    }
+
     pub async fn delete(&mut self, key: &str) -> Result<()> {
         self.connection
             .del::<_, ()>(key)
@@ -135,3 +551,11 @@ impl RedisClient {
         Ok(())
     }
 }
+
+/// The `payload` field of a stream entry's flattened field/value list
+fn stream_payload(fields: &[String]) -> Option<String> {
+    fields
+        .chunks_exact(2)
+        .find(|pair| pair[0] == "payload")
+        .map(|pair| pair[1].clone())
+}
@@ -0,0 +1,84 @@
+use crate::redis::redis_cleint::RedisClient;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Cards of tokens that stop trading expire after a week
+const CARD_TTL_SECS: u64 = 7 * 24 * 3600;
+
+/// Latest at-a-glance state of a token, kept in the Redis hash
+/// `token:{mint}:card` so token cards render without a database query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenCard {
+    pub mint_address: String,
+    /// Curve spot price after the latest trade, per whole token
+    pub price_sol: Decimal,
+    pub price_usd: Decimal,
+    pub market_cap_usd: Decimal,
+    pub bonding_curve_progress: Decimal,
+    pub volume_24h_sol: Decimal,
+    /// Unpriced trades left out
+    pub volume_24h_usd: Decimal,
+    pub holder_count: i64,
+    pub last_trade_slot: i64,
+    pub last_trade_at: DateTime<Utc>,
+}
+
+pub fn card_key(mint_address: &str) -> String {
+    format!("token:{}:card", mint_address)
+}
+
+/// Replace a token's card with `card`
+pub async fn write_token_card(redis: &mut RedisClient, card: &TokenCard) -> Result<()> {
+    let fields = [
+        ("mint_address", card.mint_address.clone()),
+        ("price_sol", card.price_sol.to_string()),
+        ("price_usd", card.price_usd.to_string()),
+        ("market_cap_usd", card.market_cap_usd.to_string()),
+        (
+            "bonding_curve_progress",
+            card.bonding_curve_progress.to_string(),
+        ),
+        ("volume_24h_sol", card.volume_24h_sol.to_string()),
+        ("volume_24h_usd", card.volume_24h_usd.to_string()),
+        ("holder_count", card.holder_count.to_string()),
+        ("last_trade_slot", card.last_trade_slot.to_string()),
+        ("last_trade_at", card.last_trade_at.to_rfc3339()),
+    ];
+
+    redis
+        .hset_multiple(&card_key(&card.mint_address), &fields, Some(CARD_TTL_SECS))
+        .await
+}
+
+/// A token's card, `None` when there is none (or it is incomplete, e.g.
+/// written by an older version); callers then fall back to the database
+pub async fn read_token_card(
+    redis: &mut RedisClient,
+    mint_address: &str,
+) -> Result<Option<TokenCard>> {
+    let fields = redis.hgetall(&card_key(mint_address)).await?;
+    Ok(card_from_fields(&fields))
+}
+
+fn card_from_fields(fields: &HashMap<String, String>) -> Option<TokenCard> {
+    fn field<T: FromStr>(fields: &HashMap<String, String>, name: &str) -> Option<T> {
+        fields.get(name)?.parse().ok()
+    }
+
+    Some(TokenCard {
+        mint_address: fields.get("mint_address")?.clone(),
+        price_sol: field(fields, "price_sol")?,
+        price_usd: field(fields, "price_usd")?,
+        market_cap_usd: field(fields, "market_cap_usd")?,
+        bonding_curve_progress: field(fields, "bonding_curve_progress")?,
+        volume_24h_sol: field(fields, "volume_24h_sol")?,
+        volume_24h_usd: field(fields, "volume_24h_usd")?,
+        holder_count: field(fields, "holder_count")?,
+        last_trade_slot: field(fields, "last_trade_slot")?,
+        last_trade_at: field(fields, "last_trade_at")?,
+    })
+}
@@ -0,0 +1,29 @@
+use crate::models::Trade;
+use crate::redis::redis_cleint::RedisClient;
+use anyhow::Result;
+
+/// Pub/sub channel every newly indexed trade is published to
+pub const ALL_TRADES_CHANNEL: &str = "trades:all";
+
+/// Pub/sub channel of one token's newly indexed trades
+pub fn token_trades_channel(mint_address: &str) -> String {
+    format!("token:{}:trades", mint_address)
+}
+
+/// Pattern matching every token's trade channel
+pub const TOKEN_TRADES_PATTERN: &str = "token:*:trades";
+
+/// The mint of a [`token_trades_channel`] name
+pub fn mint_from_channel(channel: &str) -> Option<&str> {
+    channel.strip_prefix("token:")?.strip_suffix(":trades")
+}
+
+/// Publish a just-stored trade, as JSON, to its token's channel and the
+/// global one. Pub/sub is fire-and-forget: subscribers that aren't connected
+/// miss it and should backfill from the API.
+pub async fn publish_trade(redis: &mut RedisClient, trade: &Trade) -> Result<()> {
+    redis
+        .publish(&token_trades_channel(&trade.token_mint), trade)
+        .await?;
+    redis.publish(ALL_TRADES_CHANNEL, trade).await
+}
@@ -0,0 +1,146 @@
+use anyhow::Result;
+use std::future::Future;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::AbortOnDropHandle;
+use tracing::{error, warn};
+
+const RESTART_BACKOFF_INITIAL: Duration = Duration::from_secs(1);
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// Resolves on Ctrl-C (SIGINT) or, on Unix, SIGTERM (what `docker stop` and
+/// Kubernetes send), so either triggers the same clean shutdown
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Owns every long-running background task of a binary.
+///
+/// Tasks share one `CancellationToken` and are expected to return once it is
+/// cancelled. A task that panics is restarted with exponential backoff; one
+/// that returns (`Ok` or `Err`) is considered finished and is not restarted.
+pub struct TaskManager {
+    shutdown: CancellationToken,
+    tasks: Vec<(&'static str, JoinHandle<()>)>,
+}
+
+impl Default for TaskManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self {
+            shutdown: CancellationToken::new(),
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Token cancelled on shutdown, for tasks and loops not spawned here
+    pub fn token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Spawn a supervised task. `task` is called again for every restart,
+    /// each time with a clone of the shared shutdown token.
+    pub fn spawn<F, Fut>(&mut self, name: &'static str, task: F)
+    where
+        F: Fn(CancellationToken) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let shutdown = self.shutdown.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = RESTART_BACKOFF_INITIAL;
+
+            loop {
+                // Aborting the supervisor (shutdown timeout) also aborts the attempt
+                match AbortOnDropHandle::new(tokio::spawn(task(shutdown.clone()))).await {
+                    Ok(Ok(())) => return,
+                    Ok(Err(e)) => {
+                        error!("Task {} failed: {}", name, e);
+                        return;
+                    }
+                    Err(e) if e.is_panic() => {
+                        if shutdown.is_cancelled() {
+                            return;
+                        }
+                        error!("Task {} panicked, restarting in {:?}", name, backoff);
+                    }
+                    // Cancelled from outside (runtime shutting down)
+                    Err(_) => return,
+                }
+
+                tokio::select! {
+                    _ = shutdown.cancelled() => return,
+                    _ = tokio::time::sleep(backoff) => {}
+                }
+                backoff = (backoff * 2).min(RESTART_BACKOFF_MAX);
+            }
+        });
+
+        self.tasks.push((name, handle));
+    }
+
+    /// Resolves once any one task has stopped for good (or shutdown began)
+    pub async fn any_finished(&mut self) {
+        if self.tasks.is_empty() {
+            return self.shutdown.cancelled().await;
+        }
+        let polls = self.tasks.iter_mut().map(|(_, handle)| handle);
+        let finished = tokio::select! {
+            (_, index, _) = futures_util::future::select_all(polls) => Some(index),
+            _ = self.shutdown.cancelled() => None,
+        };
+
+        // A completed JoinHandle must not be polled again by `shutdown`
+        if let Some(index) = finished {
+            self.tasks.swap_remove(index);
+        }
+    }
+
+    /// Cancel every task and wait up to `timeout` for them to return.
+    /// Tasks still running after that are aborted.
+    pub async fn shutdown(self, timeout: Duration) {
+        self.shutdown.cancel();
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        for (name, mut handle) in self.tasks {
+            if tokio::time::timeout_at(deadline, &mut handle)
+                .await
+                .is_err()
+            {
+                warn!("Task {} did not stop in time, aborting", name);
+                handle.abort();
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Trades are bucketed by day of week and hour of day in the requested zone.
+
+mod common;
+
+use chrono::{DateTime, Duration};
+use common::{cleanup, test_pool, trade, unique};
+use indexer::models::HeatmapCell;
+use indexer::models::queries::{get_activity_heatmap, insert_trade};
+use rust_decimal::Decimal;
+
+#[tokio::test]
+async fn trades_land_in_their_local_weekday_and_hour() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let mint = unique("heatmap");
+    // 2024-01-01T00:00:00Z, a Monday
+    let start = DateTime::from_timestamp(1_704_067_200, 0).unwrap();
+    // (minutes after start, lamports)
+    let fills = [
+        (10, 100),
+        (50, 200),
+        (13 * 60 + 30, 400),
+        // Sunday 23:30
+        (6 * 24 * 60 + 23 * 60 + 30, 800),
+    ];
+    for (i, (offset, lamports)) in fills.into_iter().enumerate() {
+        let mut t = trade(&mint, &format!("{mint}-{i}"), "wallet", true);
+        t.timestamp = start + Duration::minutes(offset);
+        t.sol_amount = Decimal::from(lamports);
+        insert_trade(&pool, &t).await.unwrap();
+    }
+    // Transfers and trades outside the window don't count
+    let mut transfer = trade(&mint, &format!("{mint}-transfer"), "wallet", true);
+    transfer.timestamp = start + Duration::minutes(20);
+    transfer.track_volume = false;
+    insert_trade(&pool, &transfer).await.unwrap();
+    let mut late = trade(&mint, &format!("{mint}-late"), "wallet", true);
+    late.timestamp = start + Duration::days(7);
+    insert_trade(&pool, &late).await.unwrap();
+
+    let end = start + Duration::days(7);
+    let utc = get_activity_heatmap(&pool, Some(&mint), start, end, "UTC")
+        .await
+        .unwrap();
+    let new_york = get_activity_heatmap(&pool, Some(&mint), start, end, "America/New_York")
+        .await
+        .unwrap();
+    cleanup(&pool, &mint).await;
+
+    let cells = |cells: &[HeatmapCell]| -> Vec<(i32, i32, i64, Decimal)> {
+        cells
+            .iter()
+            .map(|c| (c.day_of_week, c.hour_of_day, c.trade_count, c.volume_sol))
+            .collect()
+    };
+    assert_eq!(
+        cells(&utc),
+        [
+            (1, 0, 2, Decimal::from(300)),
+            (1, 13, 1, Decimal::from(400)),
+            (7, 23, 1, Decimal::from(800)),
+        ]
+    );
+    // Five hours behind UTC in January: Monday's first hour is Sunday evening
+    assert_eq!(
+        cells(&new_york),
+        [
+            (1, 8, 1, Decimal::from(400)),
+            (7, 18, 1, Decimal::from(800)),
+            (7, 19, 2, Decimal::from(300)),
+        ]
+    );
+}
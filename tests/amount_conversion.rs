@@ -0,0 +1,78 @@
+//! An amount that doesn't parse fails the transaction instead of becoming
+//! zero, so the worker dead-letters it; a price that can't be computed is
+//! left unset.
+
+use indexer::helius::parser::{
+    ParseOptions, ParseOutcome, parse_token_creation, parse_transaction,
+};
+use indexer::metrics::AMOUNT_CONVERSION_FAILURES;
+use indexer::models::helius_model::TransactionResult;
+use indexer::pricing::CurveParams;
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// `name` without its parsed transfers (so reserves come from token balance
+/// diffs), with the bonding curve's post balance set to `amount`
+fn fixture_with_curve_amount(name: &str, amount: &str) -> TransactionResult {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!("tests/fixtures/{name}.json"));
+    let mut fixture: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let curve = fixture["transaction"]["message"]["accountKeys"][2]["pubkey"].clone();
+    fixture["meta"]["innerInstructions"] = Value::Array(Vec::new());
+    fixture["meta"]["logMessages"] = Value::Array(Vec::new());
+    for balance in fixture["meta"]["postTokenBalances"].as_array_mut().unwrap() {
+        if balance["owner"] == curve {
+            balance["uiTokenAmount"]["amount"] = amount.into();
+        }
+    }
+    serde_json::from_value(fixture).unwrap()
+}
+
+#[test]
+fn malformed_trade_amount_fails_the_transaction() {
+    let tx = fixture_with_curve_amount("sell", "204957142857143");
+    assert!(parse_transaction(&tx, None, 150.0, ParseOptions::default()).is_ok());
+
+    let tx = fixture_with_curve_amount("sell", "12x");
+    let before = AMOUNT_CONVERSION_FAILURES.get();
+    assert!(parse_transaction(&tx, None, 150.0, ParseOptions::default()).is_err());
+    assert!(AMOUNT_CONVERSION_FAILURES.get() > before);
+}
+
+#[test]
+fn amount_past_u64_fails_the_transaction() {
+    // u64::MAX + 1
+    let tx = fixture_with_curve_amount("sell", "18446744073709551616");
+    let before = AMOUNT_CONVERSION_FAILURES.get();
+    assert!(parse_transaction(&tx, None, 150.0, ParseOptions::default()).is_err());
+    assert!(AMOUNT_CONVERSION_FAILURES.get() > before);
+}
+
+#[test]
+fn unusable_sol_price_leaves_the_usd_price_unset() {
+    let tx = fixture_with_curve_amount("sell", "204957142857143");
+    for sol_price in [0.0, f64::NAN] {
+        let before = AMOUNT_CONVERSION_FAILURES.get();
+        let Ok(ParseOutcome::Trades(trades)) =
+            parse_transaction(&tx, None, sol_price, ParseOptions::default())
+        else {
+            panic!("sell no longer parses to trades");
+        };
+        assert!(
+            trades[0]
+                .price_sol
+                .is_some_and(|price| price > Decimal::ZERO)
+        );
+        assert_eq!(trades[0].price_usd, None);
+        assert!(AMOUNT_CONVERSION_FAILURES.get() > before);
+    }
+}
+
+#[test]
+fn malformed_creation_amount_fails_the_transaction() {
+    let tx = fixture_with_curve_amount("create", "12x");
+    let before = AMOUNT_CONVERSION_FAILURES.get();
+    assert!(parse_token_creation(&tx, &CurveParams::default()).is_err());
+    assert!(AMOUNT_CONVERSION_FAILURES.get() > before);
+}
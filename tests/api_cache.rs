@@ -0,0 +1,68 @@
+//! Conditional requests on the token endpoint.
+
+mod common;
+
+use common::{cleanup, insert_token, test_pool, unique};
+use indexer::api::router;
+use indexer::db::DbPools;
+use reqwest::StatusCode;
+use reqwest::header::{CACHE_CONTROL, ETAG, IF_NONE_MATCH};
+
+#[tokio::test]
+async fn matching_if_none_match_gets_304() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let mint = unique("api-cache");
+    insert_token(&pool, &mint).await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/tokens/{}", listener.local_addr().unwrap(), mint);
+    let app = router(DbPools::single(pool.clone()), None);
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    let client = reqwest::Client::new();
+
+    let first = client.get(&url).send().await.unwrap();
+    assert_eq!(first.status(), StatusCode::OK);
+    assert!(first.headers().contains_key(CACHE_CONTROL));
+    let etag = first.headers()[ETAG].to_str().unwrap().to_string();
+
+    let cached = client
+        .get(&url)
+        .header(IF_NONE_MATCH, &etag)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(cached.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(cached.headers()[ETAG], etag.as_str());
+    assert!(cached.bytes().await.unwrap().is_empty());
+
+    // Any change to the row moves the tag on
+    sqlx::query(
+        "UPDATE tokens SET updated_at = NOW() + INTERVAL '1 second' WHERE mint_address = $1",
+    )
+    .bind(&mint)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let changed = client
+        .get(&url)
+        .header(IF_NONE_MATCH, &etag)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(changed.status(), StatusCode::OK);
+    assert_ne!(changed.headers()[ETAG], etag.as_str());
+
+    // Unknown tokens still 404, untagged
+    let missing = client
+        .get(format!("{}-missing", url))
+        .header(IF_NONE_MATCH, "*")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    assert!(!missing.headers().contains_key(ETAG));
+
+    cleanup(&pool, &mint).await;
+}
@@ -0,0 +1,85 @@
+//! The `transactions` audit log keeps one row per signature, at the highest
+//! commitment it was fetched at.
+
+mod common;
+
+use chrono::{DateTime, Utc};
+use indexer::models::{Commitment, Transaction, insert_transaction};
+use sqlx::PgPool;
+
+fn audit_row(signature: &str, slot: i64, commitment: Commitment) -> Transaction {
+    Transaction {
+        signature: signature.to_string(),
+        slot,
+        block_time: DateTime::from_timestamp(1_700_000_000 + slot, 0).unwrap(),
+        signer: "signer".to_string(),
+        success: true,
+        instruction_count: Some(3),
+        commitment,
+        created_at: None,
+    }
+}
+
+async fn stored(pool: &PgPool, signature: &str) -> Vec<(i64, DateTime<Utc>, String)> {
+    sqlx::query_as("SELECT slot, block_time, commitment FROM transactions WHERE signature = $1")
+        .bind(signature)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+async fn delete(pool: &PgPool, signature: &str) {
+    sqlx::query("DELETE FROM transactions WHERE signature = $1")
+        .bind(signature)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn finalized_refetch_replaces_the_confirmed_row() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let signature = common::unique("audit-finalized");
+
+    insert_transaction(&pool, &audit_row(&signature, 10, Commitment::Confirmed))
+        .await
+        .unwrap();
+    // The finalized copy landed in a later slot
+    let finalized = audit_row(&signature, 12, Commitment::Finalized);
+    insert_transaction(&pool, &finalized).await.unwrap();
+    // A late confirmed copy doesn't roll it back
+    insert_transaction(&pool, &audit_row(&signature, 10, Commitment::Confirmed))
+        .await
+        .unwrap();
+
+    let rows = stored(&pool, &signature).await;
+    delete(&pool, &signature).await;
+    assert_eq!(
+        rows,
+        vec![(12, finalized.block_time, "finalized".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn concurrent_writes_leave_one_row() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let signature = common::unique("audit-concurrent");
+
+    // Different block times, so the primary key alone wouldn't collide
+    let writes = (0..8).map(|slot| {
+        let pool = pool.clone();
+        let row = audit_row(&signature, slot, Commitment::Confirmed);
+        tokio::spawn(async move { insert_transaction(&pool, &row).await })
+    });
+    for write in writes.collect::<Vec<_>>() {
+        write.await.unwrap().unwrap();
+    }
+
+    let rows = stored(&pool, &signature).await;
+    delete(&pool, &signature).await;
+    assert_eq!(rows.len(), 1, "{rows:?}");
+}
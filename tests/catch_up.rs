@@ -0,0 +1,53 @@
+//! The live worker's catch-up switch, driven by how long stream entries waited.
+
+use indexer::helius::fetcher::{
+    CatchUpSwitch, ProcessingMode, processing_lag, processing_mode, stream_entry_lag,
+};
+use std::time::Duration;
+
+const NOW_MS: i64 = 1_700_000_000_000;
+
+/// An entry added `secs_ago` seconds before `NOW_MS`
+fn entry(secs_ago: i64) -> String {
+    format!("{}-0", NOW_MS - secs_ago * 1000)
+}
+
+#[test]
+fn entry_lag_comes_from_the_stream_id() {
+    assert_eq!(
+        stream_entry_lag(&entry(90), NOW_MS),
+        Some(Duration::from_secs(90))
+    );
+    assert_eq!(
+        stream_entry_lag("1699999999500-7", NOW_MS),
+        Some(Duration::from_millis(500))
+    );
+    // Clock skew never reads as negative lag
+    assert_eq!(stream_entry_lag(&entry(-5), NOW_MS), Some(Duration::ZERO));
+    assert_eq!(stream_entry_lag("not-an-id", NOW_MS), None);
+    assert_eq!(stream_entry_lag("1700000000000", NOW_MS), None);
+}
+
+#[test]
+fn switch_toggles_on_simulated_lag() {
+    let switch = CatchUpSwitch::new(Duration::from_secs(60), Duration::from_secs(10));
+    let observe = |secs_ago| switch.observe(stream_entry_lag(&entry(secs_ago), NOW_MS).unwrap());
+
+    assert_eq!(processing_mode(), ProcessingMode::RealTime);
+    assert_eq!(observe(5), ProcessingMode::RealTime);
+    // At the threshold is not over it
+    assert_eq!(observe(60), ProcessingMode::RealTime);
+
+    // A backlog after downtime
+    assert_eq!(observe(600), ProcessingMode::CatchUp);
+    assert_eq!(processing_mode(), ProcessingMode::CatchUp);
+    assert_eq!(processing_lag(), Duration::from_secs(600));
+    // Hysteresis: stays in catch-up between the thresholds
+    assert_eq!(observe(30), ProcessingMode::CatchUp);
+    assert_eq!(observe(10), ProcessingMode::CatchUp);
+
+    // Drained
+    assert_eq!(observe(2), ProcessingMode::RealTime);
+    assert_eq!(observe(30), ProcessingMode::RealTime);
+    assert_eq!(processing_mode(), ProcessingMode::RealTime);
+}
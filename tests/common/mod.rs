@@ -0,0 +1,102 @@
+//! Helpers shared by the database and Redis tests.
+//!
+//! They run against the already-migrated database in `TEST_DATABASE_URL`
+//! (and the Redis in `TEST_REDIS_URL`) and are skipped when it isn't set.
+//! Each test writes rows under its own synthetic mint, or keys under its own
+//! name, and deletes them afterwards.
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use indexer::models::Trade;
+use indexer::redis::redis_cleint::RedisClient;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+
+/// A pool on `TEST_DATABASE_URL`, or `None` (after saying so) when unset
+pub async fn test_pool() -> Option<PgPool> {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL not set, skipping");
+        return None;
+    };
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&url)
+        .await
+        .expect("TEST_DATABASE_URL is not reachable");
+    Some(pool)
+}
+
+/// A client on `TEST_REDIS_URL`, or `None` (after saying so) when unset
+pub async fn test_redis() -> Option<RedisClient> {
+    let Ok(url) = std::env::var("TEST_REDIS_URL") else {
+        eprintln!("TEST_REDIS_URL not set, skipping");
+        return None;
+    };
+    let redis = RedisClient::new(&url)
+        .await
+        .expect("TEST_REDIS_URL is not reachable");
+    Some(redis)
+}
+
+/// A key unique to this test run, so concurrent runs don't collide
+pub fn unique(label: &str) -> String {
+    format!(
+        "test-{}-{}-{}",
+        label,
+        std::process::id(),
+        Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    )
+}
+
+/// Delete everything written under `mint`
+pub async fn cleanup(pool: &PgPool, mint: &str) {
+    for sql in [
+        "DELETE FROM trades WHERE token_mint = $1",
+        "DELETE FROM token_holders WHERE token_mint = $1",
+        "DELETE FROM token_holder_tails WHERE token_mint = $1",
+        "DELETE FROM tokens WHERE mint_address = $1",
+    ] {
+        sqlx::query(sql).bind(mint).execute(pool).await.unwrap();
+    }
+}
+
+/// Store a token row with nothing but its mint set
+pub async fn insert_token(pool: &PgPool, mint: &str) {
+    sqlx::query("INSERT INTO tokens (mint_address) VALUES ($1)")
+        .bind(mint)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+/// A tracked 1 SOL buy or sell on a fresh curve, at the top level of
+/// `signature`; tests override what they care about
+pub fn trade(mint: &str, signature: &str, wallet: &str, is_buy: bool) -> Trade {
+    Trade {
+        signature: signature.to_string(),
+        token_mint: mint.to_string(),
+        sol_amount: Decimal::from(1_000_000_000u64),
+        token_amount: Decimal::from(35_000_000_000_000u64),
+        is_buy,
+        user_wallet: wallet.to_string(),
+        timestamp: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        virtual_sol_reserves: Decimal::from(30_000_000_000u64),
+        virtual_token_reserves: Decimal::from(1_073_000_000_000_000u64),
+        price_sol: None,
+        price_usd: None,
+        sol_price_usd: None,
+        sol_price_at: None,
+        usd_amount: None,
+        creator_fee_sol: Decimal::ZERO,
+        track_volume: true,
+        ix_name: if is_buy { "buy" } else { "sell" }.to_string(),
+        slot: 1,
+        instruction_index: 0,
+        inner_instruction_index: -1,
+        accounts: None,
+        fee_lamports: None,
+        compute_units: None,
+        compute_unit_price: None,
+    }
+}
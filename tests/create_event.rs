@@ -0,0 +1,138 @@
+//! Decoding pump.fun's `CreateEvent` and building new tokens from it.
+
+use indexer::helius::events::{
+    CreateEvent, InitialCurveState, decode_create_event_data, event_cpi_payload,
+};
+use indexer::helius::parser::parse_token_creation;
+use indexer::models::helius_model::TransactionResult;
+use indexer::pricing::CurveParams;
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::path::PathBuf;
+
+const PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+fn create_fixture() -> Value {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/create.json");
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// The `create` fixture's event self-CPI
+fn event_cpi(fixture: &mut Value) -> &mut Value {
+    fixture["meta"]["innerInstructions"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .flat_map(|inner| inner["instructions"].as_array_mut().unwrap())
+        .find(|ix| ix["programId"] == PUMP_FUN_PROGRAM_ID)
+        .unwrap()
+}
+
+/// The `CreateEvent` the `create` fixture's self-CPI carries
+fn captured_event() -> Vec<u8> {
+    let data = event_cpi(&mut create_fixture())["data"]
+        .as_str()
+        .map(|data| bs58::decode(data).into_vec().unwrap())
+        .unwrap();
+    event_cpi_payload(&data).unwrap().to_vec()
+}
+
+fn captured() -> CreateEvent {
+    CreateEvent {
+        name: "Delta Dog".to_string(),
+        symbol: "DDOG".to_string(),
+        uri: "https://ipfs.io/ipfs/QmDeltaDogMetadata".to_string(),
+        mint: "4UzANobV6txbT2MSTBNcBCohcVtDdoWiBvw3Dix841Td".to_string(),
+        bonding_curve: "AV2sEHn3qQHKfKZMv3hSPEcs4N6J8ZaEscFc9CGRzmN6".to_string(),
+        user: "2uK6LvNtS12t8KYr9xzhcpUPH2oYH9egYnf9SWjG1s8B".to_string(),
+        creator: Some("2uK6LvNtS12t8KYr9xzhcpUPH2oYH9egYnf9SWjG1s8B".to_string()),
+        timestamp: Some(1_735_689_600),
+        initial_state: Some(InitialCurveState {
+            virtual_token_reserves: 1_073_000_000_000_000,
+            virtual_sol_reserves: 30_000_000_000,
+            real_token_reserves: 793_100_000_000_000,
+            token_total_supply: 1_000_000_000_000_000,
+        }),
+    }
+}
+
+/// Bytes the current program appends after `user`: creator, timestamp and
+/// four reserve fields
+const TAIL_LEN: usize = 32 + 8 + 4 * 8;
+/// Offset of `virtual_token_reserves` from the end of the payload
+const VIRTUAL_TOKEN_RESERVES_FROM_END: usize = 4 * 8;
+
+#[test]
+fn decodes_a_captured_create_event() {
+    assert_eq!(
+        decode_create_event_data(&captured_event()),
+        Some(captured())
+    );
+}
+
+#[test]
+fn decodes_an_event_from_before_the_tail() {
+    let payload = captured_event();
+    let legacy = &payload[..payload.len() - TAIL_LEN];
+    assert_eq!(
+        decode_create_event_data(legacy),
+        Some(CreateEvent {
+            creator: None,
+            timestamp: None,
+            initial_state: None,
+            ..captured()
+        })
+    );
+}
+
+#[test]
+fn rejects_other_payloads() {
+    let payload = captured_event();
+    // Another event's discriminator, and a header cut short
+    let mut other = payload.clone();
+    other[0] ^= 0xff;
+    assert_eq!(decode_create_event_data(&other), None);
+    assert_eq!(decode_create_event_data(&payload[..40]), None);
+}
+
+#[test]
+fn tokens_come_from_the_event() {
+    // Reserves no heuristic would produce
+    let mut fixture = create_fixture();
+    let ix = event_cpi(&mut fixture);
+    let mut data = bs58::decode(ix["data"].as_str().unwrap())
+        .into_vec()
+        .unwrap();
+    let at = data.len() - VIRTUAL_TOKEN_RESERVES_FROM_END;
+    data[at..at + 8].copy_from_slice(&1_234_567u64.to_le_bytes());
+    ix["data"] = bs58::encode(data).into_string().into();
+    let tx: TransactionResult = serde_json::from_value(fixture).unwrap();
+
+    let tokens = parse_token_creation(&tx, &CurveParams::default()).unwrap();
+    let [token] = tokens.as_slice() else {
+        panic!("expected one token, got {}", tokens.len());
+    };
+    assert_eq!(token.mint_address, captured().mint);
+    assert_eq!(token.creator_wallet, captured().creator);
+    assert_eq!(token.virtual_token_reserves, Decimal::from(1_234_567));
+}
+
+#[test]
+fn tokens_fall_back_to_heuristics_without_the_event() {
+    let mut fixture = create_fixture();
+    fixture["meta"]["innerInstructions"] = Value::Array(Vec::new());
+    let tx: TransactionResult = serde_json::from_value(fixture).unwrap();
+
+    let tokens = parse_token_creation(&tx, &CurveParams::default()).unwrap();
+    let [token] = tokens.as_slice() else {
+        panic!("expected one token, got {}", tokens.len());
+    };
+    // From the create instruction's arguments and accounts instead
+    assert_eq!(token.mint_address, captured().mint);
+    assert_eq!(token.name.as_deref(), Some("Delta Dog"));
+    assert_eq!(token.creator_wallet, captured().creator);
+    assert_eq!(
+        token.bonding_curve_address.as_deref(),
+        Some(captured().bonding_curve.as_str())
+    );
+}
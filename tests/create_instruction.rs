@@ -0,0 +1,95 @@
+//! Decoding a pump.fun `create` instruction's Borsh arguments, and the log
+//! scraping fallback when the data isn't a `create`.
+
+use indexer::helius::parser::{decode_create_instruction, parse_token_creation};
+use indexer::models::helius_model::{Instruction, TransactionResult};
+use indexer::pricing::CurveParams;
+use serde_json::Value;
+use std::path::PathBuf;
+
+const PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+fn fixture(case: &str) -> Value {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{case}.json"));
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// The fixture's top-level pump.fun instruction
+fn pump_fun_instruction(fixture: &mut Value) -> &mut Value {
+    fixture["transaction"]["message"]["instructions"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|ix| ix["programId"] == PUMP_FUN_PROGRAM_ID)
+        .unwrap()
+}
+
+fn instruction(value: &Value) -> Instruction {
+    serde_json::from_value(value.clone()).unwrap()
+}
+
+#[test]
+fn captured_create_decodes() {
+    let mut create = fixture("create");
+    let args = decode_create_instruction(&instruction(pump_fun_instruction(&mut create))).unwrap();
+    assert_eq!(args.name, "Delta Dog");
+    assert_eq!(args.symbol, "DDOG");
+    assert_eq!(args.uri, "https://ipfs.io/ipfs/QmDeltaDogMetadata");
+    assert_eq!(
+        args.creator.as_deref(),
+        Some("2uK6LvNtS12t8KYr9xzhcpUPH2oYH9egYnf9SWjG1s8B")
+    );
+}
+
+#[test]
+fn other_instructions_are_not_creates() {
+    let mut buy = fixture("buy");
+    assert!(decode_create_instruction(&instruction(pump_fun_instruction(&mut buy))).is_none());
+
+    // A create cut off inside its arguments
+    let mut create = fixture("create");
+    let ix = pump_fun_instruction(&mut create);
+    let data = bs58::decode(ix["data"].as_str().unwrap())
+        .into_vec()
+        .unwrap();
+    ix["data"] = bs58::encode(&data[..20]).into_string().into();
+    assert!(decode_create_instruction(&instruction(ix)).is_none());
+}
+
+#[test]
+fn unknown_discriminator_falls_back_to_logs() {
+    let mut create = fixture("create");
+    // No CreateEvent to prefer, and instruction data that isn't a `create`
+    create["meta"]["innerInstructions"] = Value::Array(Vec::new());
+    let ix = pump_fun_instruction(&mut create);
+    let mut data = bs58::decode(ix["data"].as_str().unwrap())
+        .into_vec()
+        .unwrap();
+    data[..8].copy_from_slice(&[0xff; 8]);
+    ix["data"] = bs58::encode(&data).into_string().into();
+    assert!(decode_create_instruction(&instruction(ix)).is_none());
+
+    create["meta"]["logMessages"]
+        .as_array_mut()
+        .unwrap()
+        .extend(
+            [
+                "Program log: name: Logged Dog",
+                "Program log: symbol: LDOG",
+                "Program log: uri: https://ipfs.io/ipfs/QmLoggedDog",
+            ]
+            .map(Value::from),
+        );
+    let tx: TransactionResult = serde_json::from_value(create).unwrap();
+    let tokens = parse_token_creation(&tx, &CurveParams::default()).unwrap();
+
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].name.as_deref(), Some("Logged Dog"));
+    assert_eq!(tokens[0].symbol.as_deref(), Some("LDOG"));
+    assert_eq!(
+        tokens[0].uri.as_deref(),
+        Some("https://ipfs.io/ipfs/QmLoggedDog")
+    );
+}
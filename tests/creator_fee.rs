@@ -0,0 +1,74 @@
+//! The creator's fee is read from each trade's TradeEvent: its lamports go on
+//! the trade and its rate on the token.
+
+mod common;
+
+use common::{cleanup, insert_token, test_pool, unique};
+use indexer::helius::fetcher::record_creator_fee_terms;
+use indexer::helius::parser::{ParseOptions, ParseOutcome, parse_pump_fun_transaction};
+use indexer::models::Trade;
+use indexer::models::helius_model::TransactionResult;
+use indexer::models::queries::{get_token, upsert_token};
+use rust_decimal::Decimal;
+use std::path::PathBuf;
+
+fn fixture(case: &str) -> TransactionResult {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{case}.json"));
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn only_trade(tx: &TransactionResult) -> Trade {
+    match parse_pump_fun_transaction(tx, 150.0, ParseOptions::default()) {
+        Ok(ParseOutcome::Trades(mut trades)) if trades.len() == 1 => trades.remove(0),
+        other => panic!("expected one trade, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn creator_fee_is_extracted_and_applied() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let with_fee = unique("creator-fee");
+    let without_fee = unique("creator-fee-none");
+    insert_token(&pool, &with_fee).await;
+    insert_token(&pool, &without_fee).await;
+
+    // The buy's TradeEvent: 5 bps of its 0.5 SOL to the creator
+    let buy = fixture("buy");
+    let buy_trade = only_trade(&buy);
+    let mut token = get_token(&pool, &with_fee).await.unwrap().unwrap();
+    record_creator_fee_terms(&mut token, buy.meta.as_ref().unwrap(), &buy_trade);
+    upsert_token(&pool, &token).await.unwrap();
+
+    // The sell logs no TradeEvent, so no fee and no rate
+    let sell = fixture("sell");
+    let sell_trade = only_trade(&sell);
+    let mut token = get_token(&pool, &without_fee).await.unwrap().unwrap();
+    record_creator_fee_terms(&mut token, sell.meta.as_ref().unwrap(), &sell_trade);
+    upsert_token(&pool, &token).await.unwrap();
+
+    let with_fee_bps = get_token(&pool, &with_fee)
+        .await
+        .unwrap()
+        .unwrap()
+        .creator_fee_bps;
+    let without_fee_bps = get_token(&pool, &without_fee)
+        .await
+        .unwrap()
+        .unwrap()
+        .creator_fee_bps;
+    cleanup(&pool, &with_fee).await;
+    cleanup(&pool, &without_fee).await;
+
+    assert_eq!(buy_trade.creator_fee_sol, Decimal::from(250_000));
+    assert_eq!(
+        buy_trade.creator_fee_sol * Decimal::from(10_000) / buy_trade.sol_amount,
+        Decimal::from(5)
+    );
+    assert_eq!(with_fee_bps, Some(5));
+    assert_eq!(sell_trade.creator_fee_sol, Decimal::ZERO);
+    assert_eq!(without_fee_bps, None);
+}
@@ -0,0 +1,79 @@
+//! Dead tokens are hidden from the feeds, active and liquid ones are not.
+
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{cleanup, insert_token, test_pool, trade, unique};
+use indexer::models::queries::{
+    get_recently_active_tokens, get_top_tokens_by_volume, insert_trade,
+};
+use rust_decimal::Decimal;
+
+#[tokio::test]
+async fn dead_token_is_left_out_of_the_feeds() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let active = unique("feed-active");
+    let liquid = unique("feed-liquid");
+    let dead = unique("feed-dead");
+
+    // Far in the future, so these lead the feeds whatever else is stored
+    let now = Utc::now() + Duration::days(365);
+    let dead_before = now - Duration::hours(24);
+    for (mint, last_trade_at, market_cap_usd) in [
+        (&active, now, 100),
+        (&liquid, now - Duration::days(3), 1_000_000),
+        (&dead, now - Duration::days(3), 100),
+    ] {
+        insert_token(&pool, mint).await;
+        sqlx::query(
+            "UPDATE tokens SET last_trade_at = $2, market_cap_usd = $3 WHERE mint_address = $1",
+        )
+        .bind(mint)
+        .bind(last_trade_at)
+        .bind(Decimal::from(market_cap_usd))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // A day's volume far above anything real
+        let mut volume = trade(mint, &unique("feed-sig"), "wallet", true);
+        volume.timestamp = Utc::now();
+        volume.sol_amount = Decimal::from(u64::MAX);
+        insert_trade(&pool, &volume).await.unwrap();
+    }
+    let min_market_cap_usd = Decimal::from(5000);
+
+    let feed: Vec<String> =
+        get_recently_active_tokens(&pool, 3, Some(dead_before), min_market_cap_usd)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|token| token.mint_address)
+            .collect();
+    assert_eq!(feed, vec![active.clone(), liquid.clone()]);
+
+    let mut top: Vec<String> =
+        get_top_tokens_by_volume(&pool, 3, Some(dead_before), min_market_cap_usd)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|token| token.token_mint)
+            .filter(|mint| mint.starts_with("test-feed"))
+            .collect();
+    top.sort();
+    let mut expected = vec![active.clone(), liquid.clone()];
+    expected.sort();
+    assert_eq!(top, expected);
+
+    // With the filter off the dead token is back
+    let all = get_recently_active_tokens(&pool, 3, None, min_market_cap_usd)
+        .await
+        .unwrap();
+    assert!(all.iter().any(|token| token.mint_address == dead));
+
+    for mint in [&active, &liquid, &dead] {
+        cleanup(&pool, mint).await;
+    }
+}
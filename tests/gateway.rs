@@ -0,0 +1,57 @@
+//! The gateway's snapshot-to-live handoff.
+
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::trade;
+use indexer::gateway::Subscriptions;
+use indexer::models::{Trade, TradeCursor};
+
+fn at(signature: &str, secs: i64) -> Trade {
+    Trade {
+        timestamp: DateTime::<Utc>::from_timestamp(1_700_000_000 + secs, 0).unwrap(),
+        ..trade("mint", signature, "wallet", true)
+    }
+}
+
+/// The key the gateway reads back from a trade published as JSON
+fn published_key(trade: &Trade) -> TradeCursor {
+    let payload = serde_json::to_value(trade).unwrap();
+    TradeCursor::from(&serde_json::from_value::<Trade>(payload).unwrap())
+}
+
+#[test]
+fn trade_stored_during_the_snapshot_is_sent_once() {
+    let older = at("older", 0);
+    // Stored while the snapshot query ran: in the snapshot and on the feed
+    let concurrent = at("concurrent", 1);
+    // Stored after the snapshot was read
+    let newer = at("newer", 1);
+
+    let mut subscriptions = Subscriptions::default();
+    subscriptions.insert("mint", &[concurrent.clone(), older]);
+
+    assert!(!subscriptions.admits("mint", Some(&published_key(&concurrent))));
+    assert!(subscriptions.admits("mint", Some(&published_key(&newer))));
+    // A payload without a readable key is never taken for a duplicate
+    assert!(subscriptions.admits("mint", None));
+    // Other mints aren't subscribed
+    assert!(!subscriptions.admits("other", Some(&published_key(&newer))));
+}
+
+#[test]
+fn resubscribing_replaces_the_snapshot() {
+    let first = at("first", 0);
+    let second = at("second", 1);
+
+    let mut subscriptions = Subscriptions::default();
+    subscriptions.insert("mint", std::slice::from_ref(&first));
+    subscriptions.insert("mint", std::slice::from_ref(&second));
+    assert_eq!(subscriptions.len(), 1);
+    assert!(subscriptions.admits("mint", Some(&TradeCursor::from(&first))));
+    assert!(!subscriptions.admits("mint", Some(&TradeCursor::from(&second))));
+
+    subscriptions.remove("mint");
+    assert!(subscriptions.is_empty());
+    assert!(!subscriptions.admits("mint", None));
+}
@@ -0,0 +1,65 @@
+//! Decoding of pump.fun's global config account.
+
+use indexer::helius::global_config::{decode_global_config, global_config_address};
+use indexer::pricing::{CurveParams, LAMPORTS_PER_SOL};
+
+const FEE_RECIPIENT: &str = "CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM";
+
+/// A `Global` account as the program lays it out, with `trailing` bytes for
+/// fields later versions append
+fn account(fee_basis_points: u64, initial_virtual_sol: u64, trailing: usize) -> Vec<u8> {
+    let mut data = vec![167, 232, 232, 177, 200, 108, 114, 127];
+    data.push(1); // initialized
+    data.extend([7; 32]); // authority
+    data.extend(bs58::decode(FEE_RECIPIENT).into_vec().unwrap());
+    for value in [
+        1_073_000_000_000_000,
+        initial_virtual_sol,
+        793_100_000_000_000,
+        1_000_000_000_000_000,
+        fee_basis_points,
+    ] {
+        data.extend(u64::to_le_bytes(value));
+    }
+    data.extend(vec![0; trailing]);
+    data
+}
+
+#[test]
+fn derives_the_global_pda() {
+    assert_eq!(
+        global_config_address().as_deref(),
+        Some("4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf")
+    );
+}
+
+#[test]
+fn decodes_fields_and_ignores_later_ones() {
+    let config = decode_global_config(&account(95, 30 * LAMPORTS_PER_SOL, 200)).expect("decodes");
+    assert_eq!(config.fee_recipient, FEE_RECIPIENT);
+    assert_eq!(config.fee_basis_points, 95);
+    assert_eq!(config.initial_real_token_reserves, 793_100_000_000_000);
+    assert_eq!(config.curve_params(), CurveParams::default());
+}
+
+#[test]
+fn initial_virtual_sol_becomes_the_offset() {
+    let config = decode_global_config(&account(100, 42 * LAMPORTS_PER_SOL, 0)).expect("decodes");
+    let curve = config.curve_params();
+    assert_eq!(curve.virtual_sol_offset, 42 * LAMPORTS_PER_SOL);
+    assert_eq!(
+        curve.complete_virtual_sol,
+        CurveParams::default().complete_virtual_sol
+    );
+}
+
+#[test]
+fn rejects_other_accounts() {
+    let data = account(100, 30 * LAMPORTS_PER_SOL, 0);
+    // Cut short
+    assert_eq!(decode_global_config(&data[..data.len() - 1]), None);
+    // Another account type
+    let mut other = data;
+    other[0] ^= 1;
+    assert_eq!(decode_global_config(&other), None);
+}
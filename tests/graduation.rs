@@ -0,0 +1,58 @@
+//! A token's graduation is recorded, and reported, exactly once.
+
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{cleanup, insert_token, test_pool, unique};
+use indexer::models::queries::{get_token, record_graduation};
+use rust_decimal::Decimal;
+
+#[tokio::test]
+async fn reprocessing_a_graduated_token_reports_nothing() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let mint = unique("graduation");
+    insert_token(&pool, &mint).await;
+    sqlx::query("UPDATE tokens SET market_cap_usd = 69000 WHERE mint_address = $1")
+        .bind(&mint)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let graduated_at = Utc.timestamp_opt(1_760_600_000, 0).unwrap();
+    let completing = format!("{mint}-complete");
+    assert!(
+        record_graduation(&pool, &mint, &completing, 100, graduated_at)
+            .await
+            .unwrap()
+    );
+
+    // The market cap moves on, then the completing trade is reprocessed and a
+    // later trade re-observes the complete curve
+    sqlx::query("UPDATE tokens SET market_cap_usd = 80000 WHERE mint_address = $1")
+        .bind(&mint)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(
+        !record_graduation(&pool, &mint, &completing, 100, graduated_at)
+            .await
+            .unwrap()
+    );
+    let later = Utc.timestamp_opt(1_760_600_060, 0).unwrap();
+    assert!(
+        !record_graduation(&pool, &mint, &format!("{mint}-later"), 150, later)
+            .await
+            .unwrap()
+    );
+
+    let token = get_token(&pool, &mint).await.unwrap().unwrap();
+    assert!(token.complete);
+    assert_eq!(token.graduated_at, Some(graduated_at));
+    assert_eq!(token.graduation_slot, Some(100));
+    assert_eq!(token.graduation_signature, Some(completing));
+    assert_eq!(token.graduation_market_cap_usd, Some(Decimal::from(69_000)));
+
+    cleanup(&pool, &mint).await;
+}
@@ -0,0 +1,82 @@
+//! Holder balances come from the transaction's post-balances and only move
+//! forward in (slot, instruction) order.
+
+mod common;
+
+use indexer::helius::parser::post_token_balance;
+use indexer::models::TokenHolder;
+use indexer::models::helius_model::TransactionResult;
+use indexer::models::queries::{get_token_holder, upsert_token_holder};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::path::PathBuf;
+
+const WALLET: &str = "GyA7ZYTLyrBCfFXP97W4gombY8f4PrGi3Tx59cdr5ghk";
+const FIXTURE_MINT: &str = "4UzANobLisx9p8tcyxddZ93n4CjkE9BZoSKkgXG64Tx7";
+
+fn sell_fixture() -> TransactionResult {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sell.json");
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+async fn upsert(pool: &PgPool, mint: &str, balance: u64, slot: i64, ix: i32) {
+    let holder = TokenHolder {
+        token_mint: mint.to_string(),
+        user_wallet: WALLET.to_string(),
+        balance: Decimal::from(balance),
+        last_updated_slot: slot,
+        updated_at: None,
+    };
+    upsert_token_holder(pool, &holder, ix, -1).await.unwrap();
+}
+
+async fn balance(pool: &PgPool, mint: &str) -> Decimal {
+    get_token_holder(pool, mint, WALLET)
+        .await
+        .unwrap()
+        .unwrap()
+        .balance
+}
+
+#[tokio::test]
+async fn sell_after_a_missed_buy_stores_the_on_chain_balance() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let mint = common::unique("holder-missed-buy");
+
+    // 10M stored; a 30M buy was missed, so the wallet really holds 40M when
+    // it sells 25M
+    upsert(&pool, &mint, 10_000_000_000_000, 100, 0).await;
+    let tx = sell_fixture();
+    let post = post_token_balance(tx.meta.as_ref().unwrap(), FIXTURE_MINT, WALLET).unwrap();
+    assert_eq!(post, 15_000_000_000_000);
+    upsert(&pool, &mint, post, tx.slot as i64, 0).await;
+
+    let stored = balance(&pool, &mint).await;
+    common::cleanup(&pool, &mint).await;
+    // Applying the 25M delta to 10M would have floored at zero
+    assert_eq!(stored, Decimal::from(post));
+}
+
+#[tokio::test]
+async fn older_trades_do_not_overwrite_newer_balances() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let mint = common::unique("holder-order");
+
+    upsert(&pool, &mint, 500, 100, 3).await;
+    // Earlier in the same slot, and an earlier slot: both replays, ignored
+    upsert(&pool, &mint, 400, 100, 1).await;
+    upsert(&pool, &mint, 300, 99, 7).await;
+    let after_replays = balance(&pool, &mint).await;
+    // Re-applying the same trade and a later one in the slot both write
+    upsert(&pool, &mint, 500, 100, 3).await;
+    upsert(&pool, &mint, 600, 100, 4).await;
+    let after_later = balance(&pool, &mint).await;
+
+    common::cleanup(&pool, &mint).await;
+    assert_eq!(after_replays, Decimal::from(500));
+    assert_eq!(after_later, Decimal::from(600));
+}
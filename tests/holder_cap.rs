@@ -0,0 +1,70 @@
+//! Past the holder cap the smallest holders are folded into the token's tail.
+
+mod common;
+
+use common::{cleanup, test_pool, unique};
+use indexer::helius::fetcher::HolderCap;
+use indexer::models::TokenHolder;
+use indexer::models::queries::{get_holder_tail, get_top_holders, upsert_token_holder};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+async fn hold(pool: &PgPool, mint: &str, wallet: &str, balance: u64) {
+    let holder = TokenHolder {
+        token_mint: mint.to_string(),
+        user_wallet: wallet.to_string(),
+        balance: Decimal::from(balance),
+        last_updated_slot: 1,
+        updated_at: None,
+    };
+    upsert_token_holder(pool, &holder, 0, -1).await.unwrap();
+}
+
+#[tokio::test]
+async fn exceeding_the_cap_evicts_the_smallest_holders() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    // SAFETY: the only test in this binary, so nothing reads the environment
+    // concurrently
+    unsafe {
+        std::env::set_var("HOLDER_CAP", "2");
+        std::env::set_var("HOLDER_CAP_MIN_BALANCE", "1000");
+    }
+    let cap = HolderCap::from_env();
+
+    let mint = unique("holder-cap");
+    for (wallet, balance) in [
+        ("alice", 10_000),
+        ("bob", 5_000),
+        // Past the cap but significant, so kept
+        ("carol", 3_000),
+        ("dave", 900),
+        ("erin", 50),
+        // Sold out: dropped without counting as a holder
+        ("frank", 0),
+    ] {
+        hold(&pool, &mint, wallet, balance).await;
+    }
+    cap.enforce(&pool, &mint).await.unwrap();
+    let kept = get_top_holders(&pool, &mint, 10).await.unwrap();
+    let tail = get_holder_tail(&pool, &mint).await.unwrap().unwrap();
+
+    // A later dust holder joins the existing tail
+    hold(&pool, &mint, "grace", 10).await;
+    cap.enforce(&pool, &mint).await.unwrap();
+    let grown = get_holder_tail(&pool, &mint).await.unwrap().unwrap();
+    let kept_after = get_top_holders(&pool, &mint, 10).await.unwrap();
+
+    cleanup(&pool, &mint).await;
+    let wallets = |holders: &[TokenHolder]| -> Vec<String> {
+        holders.iter().map(|h| h.user_wallet.clone()).collect()
+    };
+    assert_eq!(wallets(&kept), ["alice", "bob", "carol"]);
+    assert_eq!((tail.holder_count, tail.balance), (2, Decimal::from(950)));
+    assert_eq!(wallets(&kept_after), ["alice", "bob", "carol"]);
+    assert_eq!((grown.holder_count, grown.balance), (3, Decimal::from(960)));
+    // Kept rows plus the tail still add up to every holder's balance
+    let total: Decimal = kept_after.iter().map(|h| h.balance).sum();
+    assert_eq!(total + grown.balance, Decimal::from(18_960));
+}
@@ -0,0 +1,82 @@
+//! Holder count and concentration of a known distribution.
+
+mod common;
+
+use indexer::models::get_holder_stats;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::str::FromStr;
+
+async fn insert_holders(pool: &PgPool, mint: &str, balances: &[u64]) {
+    for (i, balance) in balances.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO token_holders (token_mint, user_wallet, balance, last_updated_slot)
+             VALUES ($1, $2, $3, 1)",
+        )
+        .bind(mint)
+        .bind(format!("wallet-{i}"))
+        .bind(Decimal::from(*balance))
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+}
+
+/// A token with `supply` raw units and holders with `balances`
+async fn token_with_holders(pool: &PgPool, label: &str, supply: u64, balances: &[u64]) -> String {
+    let mint = common::unique(label);
+    common::insert_token(pool, &mint).await;
+    sqlx::query("UPDATE tokens SET token_total_supply = $2 WHERE mint_address = $1")
+        .bind(&mint)
+        .bind(Decimal::from(supply))
+        .execute(pool)
+        .await
+        .unwrap();
+    insert_holders(pool, &mint, balances).await;
+    mint
+}
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+#[tokio::test]
+async fn stats_of_a_known_distribution() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    // 1..=12 plus a wallet that sold out, against a supply of 1000
+    let mut balances: Vec<u64> = (1..=12).collect();
+    balances.push(0);
+    let mint = token_with_holders(&pool, "holder-stats", 1000, &balances).await;
+
+    let stats = get_holder_stats(&pool, &mint).await.unwrap().unwrap();
+    common::cleanup(&pool, &mint).await;
+
+    assert_eq!(stats.holder_count, 12);
+    // The ten largest, 3..=12, hold 75 of 1000
+    assert_eq!(stats.top10_percent, dec("7.5"));
+    // 2 * sum(k^2) / (12 * 78) - 13 / 12 = 0.30556
+    assert_eq!(stats.gini, dec("0.3056"));
+}
+
+#[tokio::test]
+async fn equal_holdings_have_no_concentration() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let mint = token_with_holders(&pool, "holder-stats-equal", 1000, &[50; 4]).await;
+
+    let stats = get_holder_stats(&pool, &mint).await.unwrap().unwrap();
+    common::cleanup(&pool, &mint).await;
+
+    assert_eq!(stats.holder_count, 4);
+    assert_eq!(stats.top10_percent, dec("20"));
+    assert_eq!(stats.gini, Decimal::ZERO);
+    assert!(
+        get_holder_stats(&pool, "not-indexed")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
@@ -0,0 +1,38 @@
+//! The outbound HTTP client gives up on servers that never answer.
+
+use indexer::helius::http::HttpConfig;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn unresponsive_server_times_out() {
+    // Accepts connections and never writes a byte back
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut stalled = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            stalled.push(socket);
+        }
+    });
+
+    let client = HttpConfig {
+        request_timeout: Duration::from_millis(200),
+        allow_http: true,
+        ..HttpConfig::default()
+    }
+    .build()
+    .unwrap();
+
+    let started = Instant::now();
+    let error = client.get(&url).send().await.unwrap_err();
+    assert!(error.is_timeout(), "{error}");
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn plain_http_is_refused_by_default() {
+    let client = HttpConfig::default().build().unwrap();
+    let error = client.get("http://127.0.0.1:9").send().await.unwrap_err();
+    // Rejected before any connection is attempted
+    assert!(error.is_builder(), "{error}");
+}
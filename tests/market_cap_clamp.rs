@@ -0,0 +1,50 @@
+//! A one-slot market cap spike is clamped while its raw value is kept.
+
+mod common;
+
+use common::{cleanup, insert_token, test_pool, unique};
+use indexer::helius::fetcher::MarketCapClamp;
+use indexer::models::queries::{get_token, upsert_token};
+use indexer::pricing::market_cap_from_reserves;
+use rust_decimal::Decimal;
+
+#[tokio::test]
+async fn extreme_ratio_within_a_slot_is_clamped() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    // SAFETY: the only test in this binary, so nothing reads the environment
+    // concurrently
+    unsafe { std::env::set_var("MARKET_CAP_CLAMP_FACTOR", "10") };
+    let clamp = MarketCapClamp::from_env();
+
+    let mint = unique("market-cap-clamp");
+    insert_token(&pool, &mint).await;
+    let mut token = get_token(&pool, &mint).await.unwrap().unwrap();
+    token.market_cap_usd = Decimal::from(4_200);
+    token.last_trade_slot = Some(100);
+    upsert_token(&pool, &token).await.unwrap();
+
+    // A trade in the same slot nearly drains the token side of the curve
+    let spike = market_cap_from_reserves(
+        Decimal::from(30_000_000_000u64),
+        Decimal::from(1_000_000u64),
+        Decimal::from(1_000_000_000_000_000u64),
+        150.0,
+    )
+    .unwrap();
+    assert!(clamp.apply(&mut token, spike, 100));
+    upsert_token(&pool, &token).await.unwrap();
+    let clamped = get_token(&pool, &mint).await.unwrap().unwrap();
+
+    // The next slot is trusted again
+    assert!(!clamp.apply(&mut token, spike, 101));
+    token.last_trade_slot = Some(101);
+    upsert_token(&pool, &token).await.unwrap();
+    let next_slot = get_token(&pool, &mint).await.unwrap().unwrap();
+
+    cleanup(&pool, &mint).await;
+    assert_eq!(clamped.market_cap_usd, Decimal::from(4_200));
+    assert_eq!(clamped.market_cap_usd_raw, spike.round_dp(2));
+    assert_eq!(next_slot.market_cap_usd, spike.round_dp(2));
+}
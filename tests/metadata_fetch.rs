@@ -0,0 +1,53 @@
+//! Metadata bodies are read up to a size cap.
+
+use axum::{Router, body::Body, routing::get};
+use indexer::helius::metadata::{MAX_METADATA_BYTES, fetch_token_metadata};
+
+/// A JSON object padded to `len` bytes
+fn padded_json(len: usize) -> String {
+    let json = r#"{"image":"https://example.com/a.png","pad":""}"#;
+    let padding = "x".repeat(len.saturating_sub(json.len()));
+    json.replace(r#""pad":"""#, &format!(r#""pad":"{padding}""#))
+}
+
+#[tokio::test]
+async fn oversized_bodies_are_not_read() {
+    let app = Router::new()
+        .route("/small", get(|| async { padded_json(1024) }))
+        .route(
+            "/large",
+            get(|| async { padded_json(MAX_METADATA_BYTES + 1) }),
+        )
+        // Chunked, so there's no length to check up front
+        .route(
+            "/streamed",
+            get(|| async {
+                let chunk = || Ok::<_, std::io::Error>(vec![b' '; MAX_METADATA_BYTES / 4]);
+                let body = std::iter::once(Ok(b"{".to_vec()))
+                    .chain(std::iter::repeat_with(chunk).take(8))
+                    .chain(std::iter::once(Ok(b"}".to_vec())));
+                Body::from_stream(tokio_stream::iter(body))
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let gateway = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    // SAFETY: the only test in this binary, so nothing reads the environment
+    // concurrently
+    unsafe { std::env::set_var("IPFS_GATEWAY", &gateway) };
+    let client = reqwest::Client::new();
+
+    let metadata = fetch_token_metadata(&client, "ipfs://small").await.unwrap();
+    assert_eq!(metadata.image.as_deref(), Some("https://example.com/a.png"));
+    assert!(
+        fetch_token_metadata(&client, "ipfs://large")
+            .await
+            .is_none()
+    );
+    assert!(
+        fetch_token_metadata(&client, "ipfs://streamed")
+            .await
+            .is_none()
+    );
+}
@@ -0,0 +1,41 @@
+//! The metrics endpoint reports a port it can't bind and stops on shutdown.
+
+use indexer::health::Health;
+use indexer::metrics::spawn_metrics_server;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+#[tokio::test]
+async fn taken_port_is_an_error_and_server_stops_on_shutdown() {
+    let taken = std::net::TcpListener::bind("0.0.0.0:0").unwrap();
+    let port = taken.local_addr().unwrap().port();
+    // SAFETY: the only test in this binary, so nothing reads the environment
+    // concurrently
+    unsafe { std::env::set_var("METRICS_PORT", port.to_string()) };
+
+    let tasks = TaskTracker::new();
+    let shutdown = CancellationToken::new();
+    let err = spawn_metrics_server(&tasks, shutdown.clone(), Health::new(&[]))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains(&port.to_string()));
+    assert!(tasks.is_empty());
+
+    drop(taken);
+    spawn_metrics_server(&tasks, shutdown.clone(), Health::new(&[]))
+        .await
+        .unwrap();
+    let body = reqwest::get(format!("http://127.0.0.1:{port}/metrics"))
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(body.contains("# TYPE"));
+
+    shutdown.cancel();
+    tasks.close();
+    tokio::time::timeout(std::time::Duration::from_secs(5), tasks.wait())
+        .await
+        .expect("metrics server didn't stop");
+}
@@ -0,0 +1,65 @@
+//! A graduated token's AMM pool is read from its migration and stored on it.
+
+mod common;
+
+use common::{cleanup, insert_token, test_pool, unique};
+use indexer::helius::parser::parse_migration;
+use indexer::models::helius_model::TransactionResult;
+use indexer::models::queries::{get_token, set_amm_pool_address};
+use serde_json::Value;
+use std::path::PathBuf;
+
+const POOL: &str = "AhtA29hCn4u9pQRikwXPQUXgXhSHGkAVgFtnTaF2XeQZ";
+
+fn migration_fixture() -> Value {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/migration.json");
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn pool_address_comes_from_the_migrate_instruction() {
+    let tx: TransactionResult = serde_json::from_value(migration_fixture()).unwrap();
+    let migration = parse_migration(&tx).unwrap();
+    assert_eq!(
+        migration.mint_address,
+        "4UzANobZLW8gyEEXXQ9kBNjy8EAEbsJ2PywxvzbCw9C9"
+    );
+    assert_eq!(migration.pool_address.as_deref(), Some(POOL));
+    assert_eq!(migration.slot, 315_500_000);
+}
+
+#[test]
+fn migrate_without_its_pool_account_has_no_pool() {
+    let mut fixture = migration_fixture();
+    for ix in fixture["transaction"]["message"]["instructions"]
+        .as_array_mut()
+        .unwrap()
+    {
+        if let Some(accounts) = ix["accounts"].as_array_mut() {
+            accounts.truncate(3);
+        }
+    }
+    let tx: TransactionResult = serde_json::from_value(fixture).unwrap();
+    assert!(parse_migration(&tx).is_none());
+}
+
+#[tokio::test]
+async fn stored_pool_address_is_returned_with_the_token() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let mint = unique("migration-pool");
+    insert_token(&pool, &mint).await;
+
+    let updated = set_amm_pool_address(&pool, &mint, POOL).await.unwrap();
+    let token = get_token(&pool, &mint).await.unwrap().unwrap();
+    let unknown = set_amm_pool_address(&pool, &unique("migration-pool-missing"), POOL)
+        .await
+        .unwrap();
+
+    cleanup(&pool, &mint).await;
+    assert!(updated);
+    assert_eq!(token.amm_pool_address.as_deref(), Some(POOL));
+    assert!(token.complete);
+    assert!(!unknown);
+}
@@ -0,0 +1,113 @@
+//! Candles built with the plain-SQL fallback match what `time_bucket` gives.
+
+mod common;
+
+use chrono::{DateTime, Duration};
+use common::{cleanup, test_pool, trade, unique};
+use indexer::db::time_bucket_sql;
+use indexer::models::queries::{get_ohlcv, insert_trade};
+use rust_decimal::Decimal;
+
+#[tokio::test]
+async fn fallback_candles_match_the_trades() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let mint = unique("ohlcv");
+    // 2024-01-01T00:00:00Z, on a five-minute boundary
+    let start = DateTime::from_timestamp(1_704_067_200, 0).unwrap();
+    // (seconds after start, USD price, lamports); inserted out of order
+    let fills = [
+        (400, 7, 1_000),
+        (10, 2, 100),
+        (299, 3, 200),
+        (120, 5, 300),
+        (301, 6, 400),
+        (599, 4, 500),
+    ];
+    for (i, (offset, price, lamports)) in fills.into_iter().enumerate() {
+        let mut t = trade(&mint, &format!("{mint}-{i}"), "wallet", true);
+        t.timestamp = start + Duration::seconds(offset);
+        t.price_usd = Some(Decimal::from(price));
+        t.sol_amount = Decimal::from(lamports);
+        insert_trade(&pool, &t).await.unwrap();
+    }
+    // Unpriced trades and ones outside the window don't count
+    let mut unpriced = trade(&mint, &format!("{mint}-unpriced"), "wallet", true);
+    unpriced.timestamp = start + Duration::seconds(60);
+    insert_trade(&pool, &unpriced).await.unwrap();
+    let mut late = trade(&mint, &format!("{mint}-late"), "wallet", true);
+    late.timestamp = start + Duration::seconds(600);
+    late.price_usd = Some(Decimal::from(100));
+    insert_trade(&pool, &late).await.unwrap();
+
+    let candles = get_ohlcv(&pool, &mint, 300, start, start + Duration::seconds(600))
+        .await
+        .unwrap();
+    cleanup(&pool, &mint).await;
+
+    let summary: Vec<_> = candles
+        .iter()
+        .map(|c| {
+            (
+                (c.bucket_start - start).num_seconds(),
+                c.open,
+                c.high,
+                c.low,
+                c.close,
+                c.volume_sol,
+                c.trade_count,
+            )
+        })
+        .collect();
+    let d = Decimal::from;
+    assert_eq!(
+        summary,
+        vec![
+            (0, d(2), d(5), d(2), d(3), d(600), 3),
+            (300, d(6), d(7), d(4), d(4), d(1_900), 3),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn fallback_buckets_align_like_time_bucket() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let timescaledb: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb')",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    // Never probed, so this is the plain-SQL expression
+    let fallback = time_bucket_sql("ts", "$1");
+    // `time_bucket` aligns to the Unix epoch, i.e. to UTC minutes, hours
+    // and days for those intervals
+    for (interval_secs, unit) in [(60i64, "minute"), (3_600, "hour"), (86_400, "day")] {
+        let reference = if timescaledb {
+            "time_bucket(make_interval(secs => $1), ts)".to_string()
+        } else {
+            format!("date_trunc('{unit}', ts AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'")
+        };
+        let sql = format!(
+            r#"
+            SELECT COUNT(*)
+            FROM generate_series(
+                '2023-12-31 22:58:59.75+00'::timestamptz,
+                '2024-01-02 01:01:01+00'::timestamptz,
+                INTERVAL '7 minutes 13.5 seconds'
+            ) AS ts
+            WHERE {fallback} IS DISTINCT FROM {reference}
+            "#
+        );
+        let mismatched: i64 = sqlx::query_scalar(&sql)
+            .bind(interval_secs)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(mismatched, 0, "{interval_secs}s buckets differ");
+    }
+}
@@ -0,0 +1,100 @@
+//! Every `ParseOutcome` the parser can report, each from a fixture.
+
+use indexer::helius::parser::{ParseOptions, ParseOutcome, PumpFunInstruction, parse_transaction};
+use indexer::models::helius_model::TransactionResult;
+use serde_json::Value;
+use std::path::PathBuf;
+
+const PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+/// `tests/fixtures/<case>.json`, edited by `edit` before it's deserialized
+fn fixture(case: &str, edit: impl FnOnce(&mut Value)) -> TransactionResult {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{case}.json"));
+    let mut json: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    edit(&mut json);
+    serde_json::from_value(json).unwrap()
+}
+
+fn outcome(tx: &TransactionResult, program_id: Option<&str>) -> ParseOutcome {
+    parse_transaction(tx, program_id, 150.0, ParseOptions::default()).unwrap()
+}
+
+fn pump_fun_instruction(json: &mut Value) -> &mut Value {
+    json["transaction"]["message"]["instructions"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|ix| ix["programId"] == PUMP_FUN_PROGRAM_ID)
+        .unwrap()
+}
+
+#[test]
+fn trades() {
+    let tx = fixture("buy", |_| {});
+    assert!(matches!(outcome(&tx, None), ParseOutcome::Trades(t) if t.len() == 1));
+}
+
+#[test]
+fn creation() {
+    let tx = fixture("create", |_| {});
+    assert!(matches!(outcome(&tx, None), ParseOutcome::Creation(t) if t.len() == 1));
+}
+
+#[test]
+fn not_pump_fun() {
+    let tx = fixture("buy", |json| {
+        pump_fun_instruction(json)["programId"] = "11111111111111111111111111111111".into();
+    });
+    assert!(matches!(outcome(&tx, None), ParseOutcome::NotPumpFun));
+}
+
+#[test]
+fn missing_meta() {
+    let tx = fixture("missing_meta", |_| {});
+    assert!(matches!(outcome(&tx, None), ParseOutcome::MissingMeta));
+}
+
+#[test]
+fn no_balance_change() {
+    // The sell ran but moved nothing: no transfer, balances unchanged
+    let tx = fixture("sell", |json| {
+        let meta = &mut json["meta"];
+        meta["innerInstructions"] = Value::Array(Vec::new());
+        meta["postTokenBalances"] = meta["preTokenBalances"].clone();
+        meta["postBalances"] = meta["preBalances"].clone();
+    });
+    assert!(matches!(outcome(&tx, None), ParseOutcome::NoBalanceChange));
+}
+
+#[test]
+fn non_trade() {
+    let tx = fixture("set_params", |_| {});
+    assert!(matches!(
+        outcome(&tx, None),
+        ParseOutcome::NonTrade(PumpFunInstruction::SetParams)
+    ));
+}
+
+#[test]
+fn unknown_instruction() {
+    // `set_params` with a discriminator pump.fun doesn't have
+    let tx = fixture("set_params", |json| {
+        pump_fun_instruction(json)["data"] = bs58::encode([1u8; 16]).into_string().into();
+    });
+    assert!(matches!(
+        outcome(&tx, None),
+        ParseOutcome::UnknownInstruction
+    ));
+}
+
+#[test]
+fn unsupported_program() {
+    let program = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+    let tx = fixture("buy", |_| {});
+    assert!(matches!(
+        outcome(&tx, Some(program)),
+        ParseOutcome::UnsupportedProgram(p) if p == program
+    ));
+}
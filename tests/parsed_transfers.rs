@@ -0,0 +1,149 @@
+//! The RPC's parsed SPL Token instructions are the first source for a
+//! trade's token amount; balance diffs are the fallback.
+
+use indexer::helius::parser::{ParseOptions, ParseOutcome, parse_pump_fun_transaction};
+use indexer::models::Trade;
+use indexer::models::helius_model::{Instruction, ParsedTokenInstruction, TransactionResult};
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+
+const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+fn token_instruction(parsed: Value) -> Instruction {
+    serde_json::from_value(json!({
+        "parsed": parsed,
+        "program": "spl-token",
+        "programId": TOKEN_PROGRAM,
+    }))
+    .unwrap()
+}
+
+fn sell_fixture() -> Value {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sell.json");
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// The sell fixture's curve -> seller transfer
+fn sell_transfer(fixture: &mut Value) -> &mut Value {
+    &mut fixture["meta"]["innerInstructions"][0]["instructions"][0]
+}
+
+fn only_trade(fixture: Value) -> Trade {
+    let tx: TransactionResult = serde_json::from_value(fixture).unwrap();
+    match parse_pump_fun_transaction(&tx, 150.0, ParseOptions::default()) {
+        Ok(ParseOutcome::Trades(mut trades)) if trades.len() == 1 => trades.remove(0),
+        other => panic!("expected one trade, got {other:?}"),
+    }
+}
+
+#[test]
+fn common_shapes_are_typed() {
+    let transfer = token_instruction(json!({
+        "type": "transfer",
+        "info": { "source": "a", "destination": "b", "amount": "25", "authority": "owner" }
+    }));
+    assert_eq!(
+        transfer.parsed_token_instruction().unwrap().transfer(),
+        Some(("a", "b", 25))
+    );
+
+    let checked = token_instruction(json!({
+        "type": "transferChecked",
+        "info": {
+            "source": "a",
+            "destination": "b",
+            "mint": "m",
+            "multisigAuthority": "multisig",
+            "tokenAmount": { "amount": "25", "decimals": 6, "uiAmount": 0.000025, "uiAmountString": "0.000025" }
+        }
+    }));
+    assert_eq!(
+        checked.parsed_token_instruction().unwrap().transfer(),
+        Some(("a", "b", 25))
+    );
+
+    let mint_to = token_instruction(json!({
+        "type": "mintTo",
+        "info": { "mint": "m", "account": "b", "amount": "1000", "mintAuthority": "authority" }
+    }));
+    assert!(matches!(
+        mint_to.parsed_token_instruction(),
+        Some(ParsedTokenInstruction::MintTo(info)) if info.amount == "1000"
+    ));
+
+    let burn = token_instruction(json!({
+        "type": "burn",
+        "info": { "account": "b", "mint": "m", "amount": "7", "authority": "owner" }
+    }));
+    assert!(matches!(
+        burn.parsed_token_instruction(),
+        Some(ParsedTokenInstruction::Burn(info)) if info.amount == "7"
+    ));
+    assert!(
+        burn.parsed_token_instruction()
+            .unwrap()
+            .transfer()
+            .is_none()
+    );
+
+    // Shapes we don't type, and other programs, are skipped rather than errors
+    let approve = token_instruction(json!({
+        "type": "approve",
+        "info": { "source": "a", "delegate": "d", "amount": "1", "owner": "owner" }
+    }));
+    assert!(approve.parsed_token_instruction().is_none());
+    let mut system = transfer.clone();
+    system.program = Some("system".to_string());
+    assert!(system.parsed_token_instruction().is_none());
+}
+
+#[test]
+fn parsed_transfer_beats_balance_diff() {
+    // The seller's token balance ends where it started (say another
+    // instruction topped it back up), but the sell itself moved 25M
+    let mut fixture = sell_fixture();
+    let meta = &mut fixture["meta"];
+    meta["postTokenBalances"][0] = meta["preTokenBalances"][0].clone();
+
+    let trade = only_trade(fixture);
+    assert!(!trade.is_buy);
+    assert_eq!(trade.token_amount, Decimal::from(25_000_000_000_000u64));
+}
+
+#[test]
+fn transfer_checked_reads_the_same_as_transfer() {
+    let mut fixture = sell_fixture();
+    let transfer = sell_transfer(&mut fixture);
+    let info = transfer["parsed"]["info"].clone();
+    transfer["parsed"] = json!({
+        "type": "transferChecked",
+        "info": {
+            "source": info["source"],
+            "destination": info["destination"],
+            "authority": info["authority"],
+            "mint": "4UzANobLisx9p8tcyxddZ93n4CjkE9BZoSKkgXG64Tx7",
+            "tokenAmount": {
+                "amount": info["amount"],
+                "decimals": 6,
+                "uiAmount": 25000000.0,
+                "uiAmountString": "25000000"
+            }
+        }
+    });
+
+    let trade = only_trade(fixture);
+    assert_eq!(trade.token_amount, only_trade(sell_fixture()).token_amount);
+}
+
+#[test]
+fn balance_diff_without_parsed_transfers() {
+    let mut fixture = sell_fixture();
+    let transfer = sell_transfer(&mut fixture);
+    transfer.as_object_mut().unwrap().remove("parsed");
+    transfer["data"] = "3Bxs4ThwQbE4vyj5".into();
+
+    let trade = only_trade(fixture);
+    assert!(!trade.is_buy);
+    assert_eq!(trade.token_amount, Decimal::from(25_000_000_000_000u64));
+}
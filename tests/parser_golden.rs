@@ -0,0 +1,294 @@
+//! Golden-file tests for the pump.fun parser.
+//!
+//! Each `tests/fixtures/<case>.json` is a `getTransaction` result in the
+//! RPC's `jsonParsed` shape, and `tests/golden/<case>.json` is what
+//! `parse_all` makes of it: created tokens, migration, outcome and trades.
+//! After an intended parser change, regenerate the golden files with
+//! `UPDATE_GOLDEN=1 cargo test --test parser_golden` and review the diff.
+
+use indexer::helius::parser::{
+    ParseOptions, ParseOutcome, PumpFunInstruction, TransferPolicy, WsolPolicy, invokes_pump_fun,
+    is_valid_pubkey, known_non_trade_instruction, parse_all, parse_pump_fun_transaction,
+};
+use indexer::models::helius_model::TransactionResult;
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::path::PathBuf;
+
+const PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+/// SOL/USD every case is priced at
+const SOL_PRICE: f64 = 150.0;
+
+fn tests_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests")
+}
+
+/// `tests/fixtures/<case>.json`
+fn fixture(case: &str) -> TransactionResult {
+    let path = tests_dir().join("fixtures").join(format!("{case}.json"));
+    let fixture = std::fs::read_to_string(path).expect("fixture");
+    serde_json::from_str(&fixture).expect("fixture parses")
+}
+
+/// Parse `tests/fixtures/<case>.json` and compare it with its golden file
+fn check(case: &str) -> Value {
+    let dir = tests_dir();
+    let tx = fixture(case);
+
+    let parsed = parse_all(&tx, None, SOL_PRICE, ParseOptions::default());
+    let actual = serde_json::to_value(&parsed).expect("serializes");
+
+    let golden_path = dir.join("golden").join(format!("{case}.json"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let pretty = serde_json::to_string_pretty(&actual).expect("serializes");
+        std::fs::write(&golden_path, pretty + "\n").expect("golden written");
+        return actual;
+    }
+
+    let golden = std::fs::read_to_string(&golden_path).expect("golden file");
+    let expected: Value = serde_json::from_str(&golden).expect("golden parses");
+    assert_eq!(
+        actual,
+        expected,
+        "{case} no longer parses to {}; if intended, rerun with UPDATE_GOLDEN=1",
+        golden_path.display()
+    );
+    actual
+}
+
+#[test]
+fn buy() {
+    let parsed = check("buy");
+    assert_eq!(parsed["outcome"], "trades");
+    let trade = &parsed["trades"][0];
+    assert_eq!(trade["ix_name"], "buy");
+    // From the TradeEvent, not the trader's balance diff
+    assert_eq!(trade["sol_amount"], "500000000");
+    assert_eq!(trade["creator_fee_sol"], "250000");
+    assert_eq!(trade["compute_unit_price"], 100_000);
+}
+
+#[test]
+fn buy_without_event() {
+    // `buy` with its TradeEvent log removed: the amount comes from the
+    // bonding curve's lamport delta, which excludes the trader's fees
+    let parsed = check("buy_without_event");
+    assert_eq!(parsed["outcome"], "trades");
+    let trade = &parsed["trades"][0];
+    assert_eq!(trade["ix_name"], "buy");
+    assert_eq!(trade["sol_amount"], check("buy")["trades"][0]["sol_amount"]);
+    assert_eq!(trade["sol_amount"], "500000000");
+}
+
+#[test]
+fn wsol_buy() {
+    // `buy_without_event` paid from the trader's WSOL account, with the
+    // curve's lamport delta removed: the WSOL balance change is the amount,
+    // not the native delta that only covers the fee and the new account's
+    // rent
+    let parsed = check("wsol_buy");
+    assert_eq!(parsed["outcome"], "trades");
+    assert_eq!(parsed["trades"][0]["sol_amount"], "505000000");
+
+    let options = ParseOptions {
+        wsol_policy: WsolPolicy::Ignore,
+        ..ParseOptions::default()
+    };
+    let Ok(ParseOutcome::Trades(trades)) =
+        parse_pump_fun_transaction(&fixture("wsol_buy"), SOL_PRICE, options)
+    else {
+        panic!("wsol_buy no longer parses to trades");
+    };
+    assert_eq!(trades[0].sol_amount, Decimal::from(2_039_280));
+}
+
+#[test]
+fn routed_buy() {
+    // `buy` routed through an aggregator: pump.fun only shows up as a CPI
+    let tx = fixture("routed_buy");
+    assert!(
+        tx.transaction
+            .message
+            .instructions
+            .iter()
+            .all(|ix| ix.program_id != PUMP_FUN_PROGRAM_ID)
+    );
+    assert!(invokes_pump_fun(&tx));
+
+    let parsed = check("routed_buy");
+    assert_eq!(parsed["outcome"], "trades");
+    let trade = &parsed["trades"][0];
+    let direct = &check("buy")["trades"][0];
+    for field in [
+        "ix_name",
+        "token_mint",
+        "user_wallet",
+        "sol_amount",
+        "token_amount",
+    ] {
+        assert_eq!(trade[field], direct[field], "{field}");
+    }
+    // Positioned at the router's instruction, first CPI under it
+    assert_eq!(trade["instruction_index"], 3);
+    assert_eq!(trade["inner_instruction_index"], 0);
+}
+
+#[test]
+fn airdrop() {
+    // Tokens handed from one wallet to another, no SOL behind them, next to
+    // an unrelated pump.fun instruction: skipped by default
+    let parsed = check("airdrop");
+    assert!(parsed["trades"].as_array().is_some_and(Vec::is_empty));
+    assert_eq!(parsed["outcome"], "non_trade:extendAccount");
+
+    // Recorded on request, as unpriced transfers outside volume
+    let options = ParseOptions {
+        transfer_policy: TransferPolicy::Record,
+        ..ParseOptions::default()
+    };
+    let Ok(ParseOutcome::Trades(trades)) =
+        parse_pump_fun_transaction(&fixture("airdrop"), SOL_PRICE, options)
+    else {
+        panic!("recorded transfers are not trades");
+    };
+    // Movements not tied to a trade instruction can't be told apart, so
+    // only the first is kept: the sender's, who paid the network fee
+    let [transfer] = trades.as_slice() else {
+        panic!("expected one transfer, got {}", trades.len());
+    };
+    assert_eq!(transfer.ix_name, "transfer");
+    assert_eq!(
+        transfer.user_wallet,
+        "GyA7ZYTLyrBCfFXP97W4gombY8f4PrGi3Tx59cdr5ghk"
+    );
+    assert!(!transfer.is_buy);
+    assert_eq!(transfer.token_amount, Decimal::from(10_000_000_000_000u64));
+    assert_eq!(transfer.sol_amount, Decimal::ZERO);
+    assert!(!transfer.track_volume);
+    assert_eq!(transfer.price_sol, None);
+    assert_eq!(transfer.price_usd, None);
+}
+
+#[test]
+fn sell() {
+    let parsed = check("sell");
+    assert_eq!(parsed["outcome"], "trades");
+    let trade = &parsed["trades"][0];
+    assert_eq!(trade["ix_name"], "sell");
+    assert_eq!(trade["is_buy"], false);
+    assert_eq!(trade["token_amount"], "25000000000000");
+}
+
+#[test]
+fn short_curve_key() {
+    // `sell` with a mint whose bonding curve PDA encodes to 43 characters
+    let parsed = check("short_curve_key");
+    let curve = "yYTWmTr916iubMbLif7JHiioo3y94kLiLnybnSELQa8";
+    assert_eq!(curve.len(), 43);
+    assert!(is_valid_pubkey(curve));
+    // The curve's side of the sell is recognized as the curve, not a trade
+    let trades = parsed["trades"].as_array().expect("trades");
+    assert_eq!(trades.len(), 1);
+    assert_eq!(
+        trades[0]["user_wallet"],
+        "GyA7ZYTLyrBCfFXP97W4gombY8f4PrGi3Tx59cdr5ghk"
+    );
+    assert_eq!(trades[0]["token_mint"].as_str().map(str::len), Some(43));
+    assert_eq!(
+        trades[0]["virtual_token_reserves"],
+        check("sell")["trades"][0]["virtual_token_reserves"]
+    );
+}
+
+#[test]
+fn create() {
+    let parsed = check("create");
+    assert_eq!(parsed["outcome"], "creation");
+    assert_eq!(parsed["tokens"].as_array().map(Vec::len), Some(1));
+    assert_eq!(parsed["tokens"][0]["symbol"], "DDOG");
+    assert!(parsed["trades"].as_array().is_some_and(Vec::is_empty));
+}
+
+#[test]
+fn bundled_create() {
+    let parsed = check("bundled_create");
+    assert_eq!(parsed["outcome"], "creation");
+    let symbols: Vec<_> = parsed["tokens"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|token| token["symbol"].as_str().unwrap())
+        .collect();
+    assert_eq!(symbols, ["DDOG", "ECAT"]);
+    assert_ne!(
+        parsed["tokens"][0]["mint_address"],
+        parsed["tokens"][1]["mint_address"]
+    );
+}
+
+#[test]
+fn create_and_buy() {
+    let parsed = check("create_and_buy");
+    assert_eq!(parsed["outcome"], "trades");
+    let (token, trade) = (&parsed["tokens"][0], &parsed["trades"][0]);
+    assert_eq!(token["symbol"], "GCAT");
+    assert_eq!(trade["token_mint"], token["mint_address"]);
+    assert_eq!(trade["user_wallet"], token["creator_wallet"]);
+}
+
+#[test]
+fn migration() {
+    let parsed = check("migration");
+    assert_eq!(parsed["outcome"], "non_trade:migrate");
+    assert!(parsed["migration"]["pool_address"].is_string());
+}
+
+#[test]
+fn set_params() {
+    // An admin instruction: named in the outcome rather than an anonymous miss
+    let parsed = check("set_params");
+    assert_eq!(parsed["outcome"], "non_trade:setParams");
+    assert!(parsed["trades"].as_array().is_some_and(Vec::is_empty));
+
+    let tx = fixture("set_params");
+    assert_eq!(
+        known_non_trade_instruction(&tx),
+        Some(PumpFunInstruction::SetParams)
+    );
+    // A transaction with a trade in it is not a known non-trade
+    assert_eq!(known_non_trade_instruction(&fixture("sell")), None);
+}
+
+#[test]
+fn multiple_token_balances() {
+    let parsed = check("multiple_token_balances");
+    let trades = parsed["trades"].as_array().expect("trades");
+    // One buy per routed mint; the WSOL leg and the untouched holder are not trades
+    assert_eq!(trades.len(), 2);
+    assert_ne!(trades[0]["token_mint"], trades[1]["token_mint"]);
+    assert!(trades.iter().all(|t| t["ix_name"] == "buy"));
+}
+
+#[test]
+fn pubkeys_are_checked_by_decoded_length() {
+    assert!(is_valid_pubkey("11111111111111111111111111111111"));
+    assert!(is_valid_pubkey(
+        "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P"
+    ));
+    // 44 characters that decode to 33 bytes, and 44 that aren't base58
+    assert!(!is_valid_pubkey(
+        "zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz"
+    ));
+    assert!(!is_valid_pubkey(
+        "0EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P0"
+    ));
+}
+
+#[test]
+fn missing_meta() {
+    let parsed = check("missing_meta");
+    assert_eq!(parsed["outcome"], "missing_meta");
+    assert!(parsed["tokens"].as_array().is_some_and(Vec::is_empty));
+    assert!(parsed["trades"].as_array().is_some_and(Vec::is_empty));
+}
@@ -0,0 +1,122 @@
+//! Price impact of hypothetical trades against known curve states.
+
+mod common;
+
+use common::{cleanup, insert_token, test_pool, unique};
+use indexer::analytics::price_impact::{
+    PriceImpact, estimate_price_impact, estimate_token_price_impact,
+};
+use indexer::pricing::LAMPORTS_PER_SOL;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// A fresh pump.fun curve: 30 virtual SOL against 1.073B virtual tokens
+const INITIAL_VIRTUAL_SOL: u64 = 30 * LAMPORTS_PER_SOL;
+const INITIAL_VIRTUAL_TOKENS: u64 = 1_073_000_000_000_000;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn assert_close(actual: Decimal, expected: Decimal, tolerance: Decimal) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{actual} is not within {tolerance} of {expected}"
+    );
+}
+
+fn fresh_curve(sol_in: u64, is_buy: bool) -> Option<PriceImpact> {
+    estimate_price_impact(
+        Decimal::from(INITIAL_VIRTUAL_SOL),
+        Decimal::from(INITIAL_VIRTUAL_TOKENS),
+        Decimal::from(sol_in),
+        is_buy,
+    )
+}
+
+#[test]
+fn one_sol_buy_on_a_fresh_curve() {
+    let impact = fresh_curve(LAMPORTS_PER_SOL, true).unwrap();
+    // 1.073B * (1 - 30/31) tokens, rounded down
+    assert_eq!(impact.token_amount, Decimal::from(34_612_903_225_806u64));
+    assert_close(
+        impact.price_before,
+        dec("0.0000000279589934"),
+        dec("0.0000000000000001"),
+    );
+    assert_close(
+        impact.price_after,
+        dec("0.0000000298539919"),
+        dec("0.0000000000000001"),
+    );
+    // The price scales with the square of the SOL side: (31/30)^2 - 1
+    assert_close(impact.price_change_pct, dec("6.7777777"), dec("0.0000001"));
+}
+
+#[test]
+fn one_sol_sell_on_a_fresh_curve() {
+    let impact = fresh_curve(LAMPORTS_PER_SOL, false).unwrap();
+    // 1.073B * 30/29 = exactly 1.11B virtual tokens afterwards
+    assert_eq!(impact.token_amount, Decimal::from(37_000_000_000_000u64));
+    // (29/30)^2 - 1
+    assert_close(impact.price_change_pct, dec("-6.5555555"), dec("0.0000001"));
+}
+
+#[test]
+fn impossible_trades_have_no_estimate() {
+    // Taking out every lamport of SOL
+    assert_eq!(fresh_curve(INITIAL_VIRTUAL_SOL, false), None);
+    assert_eq!(
+        estimate_price_impact(
+            Decimal::ZERO,
+            Decimal::from(INITIAL_VIRTUAL_TOKENS),
+            Decimal::from(LAMPORTS_PER_SOL),
+            true,
+        ),
+        None
+    );
+    assert_eq!(
+        estimate_price_impact(
+            Decimal::from(INITIAL_VIRTUAL_SOL),
+            Decimal::from(INITIAL_VIRTUAL_TOKENS),
+            Decimal::NEGATIVE_ONE,
+            true,
+        ),
+        None
+    );
+}
+
+#[tokio::test]
+async fn estimates_use_the_stored_reserves() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let mint = unique("price-impact");
+    insert_token(&pool, &mint).await;
+    sqlx::query(
+        "UPDATE tokens SET virtual_sol_reserves = $2, virtual_token_reserves = $3
+         WHERE mint_address = $1",
+    )
+    .bind(&mint)
+    .bind(Decimal::from(INITIAL_VIRTUAL_SOL))
+    .bind(Decimal::from(INITIAL_VIRTUAL_TOKENS))
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let stored = estimate_token_price_impact(&pool, &mint, Decimal::from(LAMPORTS_PER_SOL), true)
+        .await
+        .unwrap();
+    let unknown = estimate_token_price_impact(
+        &pool,
+        &unique("price-impact-unknown"),
+        Decimal::from(LAMPORTS_PER_SOL),
+        true,
+    )
+    .await
+    .unwrap();
+    cleanup(&pool, &mint).await;
+
+    assert_eq!(stored, fresh_curve(LAMPORTS_PER_SOL, true));
+    assert_eq!(unknown, None);
+}
@@ -0,0 +1,170 @@
+//! Constant-product curve math: prices, market cap and progress.
+
+use indexer::pricing::{
+    CurveParams, LAMPORTS_PER_SOL, PricingError, market_cap_from_reserves, price_from_amounts,
+    price_from_reserves, progress_from_reserves, sol_to_usd,
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// A fresh pump.fun curve: 30 virtual SOL against 1.073B virtual tokens
+const INITIAL_VIRTUAL_SOL: u64 = 30 * LAMPORTS_PER_SOL;
+const INITIAL_VIRTUAL_TOKENS: u64 = 1_073_000_000_000_000;
+/// 1B tokens at 6 decimals
+const SUPPLY: u64 = 1_000_000_000_000_000;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn assert_close(actual: Decimal, expected: Decimal, tolerance: Decimal) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{actual} is not within {tolerance} of {expected}"
+    );
+}
+
+#[test]
+fn spot_price_is_sol_per_whole_token() {
+    let price = price_from_reserves(
+        Decimal::from(INITIAL_VIRTUAL_SOL),
+        Decimal::from(INITIAL_VIRTUAL_TOKENS),
+    )
+    .unwrap();
+    // 30 SOL / 1.073B tokens
+    assert_close(price, dec("0.0000000279589934"), dec("0.0000000000000001"));
+}
+
+#[test]
+fn execution_price_matches_amounts() {
+    // 1 SOL for 35M tokens
+    let price = price_from_amounts(
+        Decimal::from(LAMPORTS_PER_SOL),
+        Decimal::from(35_000_000_000_000u64),
+    )
+    .unwrap();
+    assert_close(price, dec("0.0000000285714285"), dec("0.0000000000000001"));
+}
+
+#[test]
+fn market_cap_of_a_fresh_curve() {
+    let market_cap = market_cap_from_reserves(
+        Decimal::from(INITIAL_VIRTUAL_SOL),
+        Decimal::from(INITIAL_VIRTUAL_TOKENS),
+        Decimal::from(SUPPLY),
+        150.0,
+    )
+    .unwrap();
+    // ~27.96 SOL fully diluted at $150
+    assert_close(market_cap, dec("4193.85"), dec("0.01"));
+}
+
+#[test]
+fn market_cap_at_graduation() {
+    // ~115 virtual SOL against ~280M virtual tokens when the curve completes
+    let market_cap = market_cap_from_reserves(
+        Decimal::from(115_005_359_056u64),
+        Decimal::from(279_900_000_000_000u64),
+        Decimal::from(SUPPLY),
+        100.0,
+    )
+    .unwrap();
+    assert_close(market_cap, dec("41088.02"), dec("0.01"));
+}
+
+#[test]
+fn zero_reserves_are_rejected() {
+    assert_eq!(
+        price_from_reserves(Decimal::from(INITIAL_VIRTUAL_SOL), Decimal::ZERO),
+        Err(PricingError::EmptyReserves)
+    );
+    assert_eq!(
+        price_from_reserves(Decimal::from(INITIAL_VIRTUAL_SOL), Decimal::NEGATIVE_ONE),
+        Err(PricingError::EmptyReserves)
+    );
+    assert_eq!(
+        price_from_reserves(Decimal::NEGATIVE_ONE, Decimal::from(INITIAL_VIRTUAL_TOKENS)),
+        Err(PricingError::EmptyReserves)
+    );
+    assert_eq!(
+        market_cap_from_reserves(
+            Decimal::from(INITIAL_VIRTUAL_SOL),
+            Decimal::ZERO,
+            Decimal::from(SUPPLY),
+            150.0,
+        ),
+        Err(PricingError::EmptyReserves)
+    );
+}
+
+#[test]
+fn zero_sol_prices_at_zero() {
+    assert_eq!(
+        price_from_reserves(Decimal::ZERO, Decimal::from(INITIAL_VIRTUAL_TOKENS)),
+        Ok(Decimal::ZERO)
+    );
+}
+
+#[test]
+fn extreme_ratios_keep_precision_or_fail_cleanly() {
+    // One raw unit left on the token side
+    let price = price_from_reserves(Decimal::from(u64::MAX), Decimal::ONE).unwrap();
+    assert_eq!(
+        price,
+        Decimal::from(u64::MAX) * Decimal::from(1_000_000) / Decimal::from(LAMPORTS_PER_SOL)
+    );
+
+    // One lamport against the largest token side: tiny but not zero
+    let price = price_from_reserves(Decimal::ONE, Decimal::from(u64::MAX)).unwrap();
+    assert!(price > Decimal::ZERO);
+
+    // Too large to represent once multiplied by the supply
+    assert_eq!(
+        market_cap_from_reserves(
+            Decimal::from(u64::MAX),
+            Decimal::ONE,
+            Decimal::from(u64::MAX),
+            150.0,
+        ),
+        Err(PricingError::Overflow)
+    );
+}
+
+#[test]
+fn usd_conversion_rejects_bad_sol_prices() {
+    assert_eq!(
+        sol_to_usd(Decimal::ONE, 0.0),
+        Err(PricingError::InvalidSolPrice)
+    );
+    assert_eq!(
+        sol_to_usd(Decimal::ONE, -150.0),
+        Err(PricingError::InvalidSolPrice)
+    );
+    assert_eq!(
+        sol_to_usd(Decimal::ONE, f64::NAN),
+        Err(PricingError::InvalidSolPrice)
+    );
+    assert_eq!(sol_to_usd(dec("2.5"), 150.0), Ok(dec("375")));
+}
+
+#[test]
+fn progress_runs_to_the_completion_threshold() {
+    let curve = CurveParams::default();
+    assert_eq!(
+        progress_from_reserves(Decimal::ZERO, &curve),
+        Ok(Decimal::ZERO)
+    );
+    assert_eq!(
+        progress_from_reserves(Decimal::from(curve.complete_virtual_sol / 2), &curve),
+        Ok(Decimal::from(50))
+    );
+    // Not capped: past the threshold reads over 100
+    assert_eq!(
+        progress_from_reserves(Decimal::from(curve.complete_virtual_sol * 2), &curve),
+        Ok(Decimal::from(200))
+    );
+    assert_eq!(
+        progress_from_reserves(Decimal::NEGATIVE_ONE, &curve),
+        Err(PricingError::EmptyReserves)
+    );
+}
@@ -0,0 +1,58 @@
+//! One-shot jobs push their final metrics to a Pushgateway.
+
+use axum::{Router, body::Bytes, extract::Path, http::HeaderMap, routing::put};
+use indexer::metrics::{TRADES_PROCESSED, push_metrics_to};
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn job_pushes_its_final_metrics() {
+    // A Pushgateway that records what it is sent
+    let (pushed, mut received) = mpsc::unbounded_channel();
+    let app = Router::new().route(
+        "/metrics/job/{job}",
+        put(
+            move |Path(job): Path<String>, headers: HeaderMap, body: Bytes| {
+                let pushed = pushed.clone();
+                async move {
+                    let content_type = headers["content-type"].to_str().unwrap().to_string();
+                    pushed.send((job, content_type, body)).unwrap();
+                }
+            },
+        ),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    TRADES_PROCESSED.inc();
+    push_metrics_to(&reqwest::Client::new(), &url, "backfill")
+        .await
+        .unwrap();
+
+    let (job, content_type, body) = received.recv().await.unwrap();
+    assert_eq!(job, "backfill");
+    assert!(content_type.starts_with("text/plain"));
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.contains("# TYPE trades_processed_total counter"));
+    assert!(body.contains(&format!(
+        "trades_processed_total {}",
+        TRADES_PROCESSED.get()
+    )));
+}
+
+#[tokio::test]
+async fn rejected_push_is_an_error() {
+    let app = Router::new().route(
+        "/metrics/job/{job}",
+        put(|| async { axum::http::StatusCode::BAD_REQUEST }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    assert!(
+        push_metrics_to(&reqwest::Client::new(), &url, "backfill")
+            .await
+            .is_err()
+    );
+}
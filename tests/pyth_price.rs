@@ -0,0 +1,63 @@
+//! Parsing of Pyth Hermes price responses.
+
+use indexer::helius::price::pyth_latest_price;
+use indexer::pricing::PricingError;
+use serde_json::{Value, json};
+
+/// A `/v2/updates/price/latest` response carrying `price` and `expo`
+fn response(price: &str, expo: i64) -> Value {
+    json!({
+        "binary": { "encoding": "hex", "data": [] },
+        "parsed": [{
+            "id": "ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d",
+            "price": { "price": price, "conf": "7563575", "expo": expo, "publish_time": 1760600000 },
+            "ema_price": { "price": price, "conf": "7012345", "expo": expo, "publish_time": 1760600000 }
+        }]
+    })
+}
+
+fn rejection(data: &Value) -> PricingError {
+    let error = pyth_latest_price(data).expect_err("rejected");
+    *error
+        .downcast_ref::<PricingError>()
+        .unwrap_or_else(|| panic!("not a pricing error: {error:#}"))
+}
+
+#[test]
+fn scales_by_negative_exponent() {
+    let price = pyth_latest_price(&response("15012345678", -8)).expect("parses");
+    assert!((price - 150.12345678).abs() < 1e-9, "{price}");
+}
+
+#[test]
+fn accepts_zero_exponent() {
+    assert_eq!(
+        pyth_latest_price(&response("150", 0)).expect("parses"),
+        150.0
+    );
+}
+
+#[test]
+fn rejects_positive_exponent() {
+    assert_eq!(
+        rejection(&response("15012345678", 8)),
+        PricingError::InvalidExponent(8)
+    );
+}
+
+#[test]
+fn rejects_exponent_past_i32() {
+    let expo = i64::from(i32::MIN) - 8;
+    assert_eq!(
+        rejection(&response("15012345678", expo)),
+        PricingError::InvalidExponent(expo)
+    );
+}
+
+#[test]
+fn rejects_non_positive_price() {
+    assert_eq!(
+        rejection(&response("-15012345678", -8)),
+        PricingError::InvalidSolPrice
+    );
+}
@@ -0,0 +1,44 @@
+//! Reads go to the replica pool when one is configured.
+
+use indexer::db::DbPools;
+use sqlx::PgPool;
+
+/// The `application_name` the pool's connections were opened with
+async fn application_name(pool: &PgPool) -> String {
+    sqlx::query_scalar("SELECT current_setting('application_name')")
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+/// `url` with an extra connection parameter
+fn with_param(url: &str, param: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{url}{separator}{param}")
+}
+
+#[tokio::test]
+async fn reads_use_the_read_pool_when_configured() {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL not set, skipping");
+        return;
+    };
+    // The same database under two names stands in for a primary and a replica.
+    // SAFETY: the only test in this binary, so nothing reads the environment
+    // concurrently
+    unsafe {
+        std::env::set_var("DATABASE_URL", with_param(&url, "application_name=primary"));
+        std::env::set_var(
+            "DATABASE_READ_URL",
+            with_param(&url, "application_name=replica"),
+        );
+    }
+    let pools = DbPools::from_env().await.unwrap();
+    assert_eq!(application_name(pools.reader()).await, "replica");
+    assert_eq!(application_name(pools.writer()).await, "primary");
+
+    // SAFETY: as above
+    unsafe { std::env::remove_var("DATABASE_READ_URL") };
+    let pools = DbPools::from_env().await.unwrap();
+    assert_eq!(application_name(pools.reader()).await, "primary");
+}
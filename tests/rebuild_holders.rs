@@ -0,0 +1,80 @@
+//! Holder balances rebuilt from stored trades.
+
+mod common;
+
+use common::{cleanup, test_pool, trade, unique};
+use indexer::models::TokenHolder;
+use indexer::models::queries::{
+    get_token_holder, insert_trade, rebuild_token_holders, upsert_token_holder,
+};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+async fn balance(pool: &PgPool, mint: &str, wallet: &str) -> Option<(Decimal, i64)> {
+    get_token_holder(pool, mint, wallet)
+        .await
+        .unwrap()
+        .map(|h| (h.balance, h.last_updated_slot))
+}
+
+#[tokio::test]
+async fn rebuild_replays_trades_in_order() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let mint = unique("rebuild-holders");
+
+    // (wallet, is_buy, tokens, slot, instruction index); inserted out of order
+    let fills = [
+        ("alice", false, 30, 3, 2),
+        ("alice", true, 100, 1, 0),
+        ("alice", true, 50, 2, 0),
+        ("bob", true, 10, 1, 1),
+        // More than bob holds: floored at zero, and then not a holder
+        ("bob", false, 40, 2, 1),
+        // Both in one slot: the buy runs first
+        ("carol", false, 20, 5, 1),
+        ("carol", true, 20, 5, 0),
+    ];
+    for (i, (wallet, is_buy, tokens, slot, ix)) in fills.into_iter().enumerate() {
+        let mut t = trade(&mint, &format!("{mint}-{i}"), wallet, is_buy);
+        t.token_amount = Decimal::from(tokens);
+        t.slot = slot;
+        t.instruction_index = ix;
+        insert_trade(&pool, &t).await.unwrap();
+    }
+    // A stale row from before the rebuild
+    let stale = TokenHolder {
+        token_mint: mint.clone(),
+        user_wallet: "dave".to_string(),
+        balance: Decimal::from(999),
+        last_updated_slot: 1,
+        updated_at: None,
+    };
+    upsert_token_holder(&pool, &stale, 0, -1).await.unwrap();
+
+    let written = rebuild_token_holders(&pool, &mint).await.unwrap();
+    let alice = balance(&pool, &mint, "alice").await;
+    let bob = balance(&pool, &mint, "bob").await;
+    let carol = balance(&pool, &mint, "carol").await;
+    let dave = balance(&pool, &mint, "dave").await;
+
+    // A trade from earlier in alice's last slot, applied late, is ignored
+    let replayed = TokenHolder {
+        token_mint: mint.clone(),
+        user_wallet: "alice".to_string(),
+        balance: Decimal::from(1),
+        last_updated_slot: 3,
+        updated_at: None,
+    };
+    upsert_token_holder(&pool, &replayed, 1, -1).await.unwrap();
+    let alice_after_replay = balance(&pool, &mint, "alice").await;
+
+    cleanup(&pool, &mint).await;
+    assert_eq!(written, 1);
+    assert_eq!(alice, Some((Decimal::from(120), 3)));
+    assert_eq!(bob, None);
+    assert_eq!(carol, None);
+    assert_eq!(dave, None);
+    assert_eq!(alice_after_replay, alice);
+}
@@ -0,0 +1,60 @@
+//! RPC timeouts follow the observed latency distribution, and its
+//! percentiles are exported.
+
+use indexer::helius::latency::LatencyTracker;
+use indexer::metrics::{register_rpc_latency, render};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn ms(value: u64) -> Duration {
+    Duration::from_millis(value)
+}
+
+#[test]
+fn timeout_tracks_the_p99() {
+    let tracker = LatencyTracker::new(3.0, ms(10), Duration::from_secs(30));
+    // Too few samples: the default
+    tracker.record(ms(100));
+    assert_eq!(tracker.timeout(), Duration::from_secs(10));
+
+    // 1..=100 ms, once each
+    let tracker = LatencyTracker::new(3.0, ms(10), Duration::from_secs(30));
+    for latency in 1..=100 {
+        tracker.record(ms(latency));
+    }
+    let p = tracker.percentiles().unwrap();
+    assert_eq!(
+        (p.p50, p.p90, p.p99, p.samples),
+        (ms(51), ms(90), ms(99), 100)
+    );
+    assert_eq!(tracker.timeout(), ms(297));
+
+    // A slow tail moves the p99, and the timeout with it
+    for _ in 0..10 {
+        tracker.record(ms(2_000));
+    }
+    assert_eq!(tracker.percentiles().unwrap().p99, ms(2_000));
+    assert_eq!(tracker.timeout(), ms(6_000));
+
+    // Within bounds
+    let tracker = LatencyTracker::new(3.0, ms(500), Duration::from_secs(30));
+    for _ in 0..50 {
+        tracker.record(ms(1));
+    }
+    assert_eq!(tracker.timeout(), ms(500));
+}
+
+#[test]
+fn percentiles_are_exported() {
+    let tracker = register_rpc_latency(Arc::new(LatencyTracker::default()));
+    assert!(!render().contains("rpc_latency_seconds"));
+
+    for latency in 1..=100 {
+        tracker.record(ms(latency));
+    }
+    let metrics = render();
+    assert!(metrics.contains("# TYPE rpc_latency_seconds gauge"));
+    assert!(metrics.contains("rpc_latency_seconds{quantile=\"0.5\"} 0.051"));
+    assert!(metrics.contains("rpc_latency_seconds{quantile=\"0.9\"} 0.09"));
+    assert!(metrics.contains("rpc_latency_seconds{quantile=\"0.99\"} 0.099"));
+}
@@ -0,0 +1,61 @@
+//! Sandwich detection over a slot's trades in intra-slot order.
+
+mod common;
+
+use common::trade;
+use indexer::analytics::sandwich::find_sandwiches;
+use indexer::models::{Trade, TradeCursor};
+use rust_decimal::Decimal;
+
+const SLOT: i64 = 42;
+
+fn at(mut trade: Trade, instruction_index: i32, sol_amount: u64) -> Trade {
+    trade.slot = SLOT;
+    trade.instruction_index = instruction_index;
+    trade.sol_amount = Decimal::from(sol_amount);
+    trade
+}
+
+#[test]
+fn buy_victim_sell_is_a_sandwich() {
+    let front = at(trade("mint", "front", "attacker", true), 1, 2_000_000_000);
+    let victim = at(trade("mint", "victim", "victim", true), 2, 1_000_000_000);
+    let back = at(trade("mint", "back", "attacker", false), 3, 2_150_000_000);
+    let bystander = at(trade("mint", "other", "bystander", false), 4, 500_000_000);
+
+    // Given out of order, as rows come back from the database
+    let found = find_sandwiches(&[back, bystander, victim.clone(), front]);
+
+    assert_eq!(found.len(), 1);
+    let sandwich = &found[0];
+    assert_eq!(sandwich.victim, TradeCursor::from(&victim));
+    assert_eq!(sandwich.front_run_signature, "front");
+    assert_eq!(sandwich.back_run_signature, "back");
+    assert_eq!(sandwich.attacker_wallet, "attacker");
+    assert_eq!(sandwich.profit, Decimal::from(150_000_000));
+}
+
+#[test]
+fn sell_before_the_victim_is_not_a_sandwich() {
+    let sell = at(trade("mint", "sell", "attacker", false), 1, 2_150_000_000);
+    let victim = at(trade("mint", "victim", "victim", true), 2, 1_000_000_000);
+    let buy = at(trade("mint", "buy", "attacker", true), 3, 2_000_000_000);
+
+    assert!(find_sandwiches(&[sell, victim, buy]).is_empty());
+}
+
+#[test]
+fn own_trades_and_transfers_are_not_sandwiches() {
+    // The "victim" is the attacker's own wallet
+    let front = at(trade("mint", "front", "attacker", true), 1, 2_000_000_000);
+    let middle = at(trade("mint", "middle", "attacker", true), 2, 1_000_000_000);
+    let back = at(trade("mint", "back", "attacker", false), 3, 2_150_000_000);
+    assert!(find_sandwiches(&[front.clone(), middle, back.clone()]).is_empty());
+
+    // Untracked rows (transfers) don't count as the victim
+    let transfer = Trade {
+        track_volume: false,
+        ..at(trade("mint", "transfer", "victim", true), 2, 1_000_000_000)
+    };
+    assert!(find_sandwiches(&[front, transfer, back]).is_empty());
+}
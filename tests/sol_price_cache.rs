@@ -0,0 +1,58 @@
+//! Concurrent SOL price cache misses share one provider request.
+
+use axum::{Router, extract::State, routing::get};
+use indexer::helius::fetcher::{SharedPriceCache, get_sol_price};
+use indexer::helius::price::PriceProviders;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// A slow Hermes `/v2/updates/price/latest` at $150, counting its requests
+async fn hermes(State(requests): State<Arc<AtomicUsize>>) -> axum::Json<Value> {
+    requests.fetch_add(1, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    axum::Json(json!({
+        "parsed": [{
+            "price": { "price": "15000000000", "conf": "7563575", "expo": -8, "publish_time": 1760600000 }
+        }]
+    }))
+}
+
+#[tokio::test]
+async fn concurrent_misses_make_one_request() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/v2/updates/price/latest", get(hermes))
+        .with_state(requests.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let hermes_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    // SAFETY: the only test in this binary, so nothing reads the environment
+    // concurrently
+    unsafe {
+        std::env::set_var("PRICE_SOURCES", "pyth");
+        std::env::set_var("PYTH_HERMES_URL", &hermes_url);
+    }
+    let providers = Arc::new(PriceProviders::from_env(&reqwest::Client::new()));
+    let cache = Arc::new(SharedPriceCache::default());
+
+    let callers: Vec<_> = (0..32)
+        .map(|_| {
+            let providers = providers.clone();
+            let cache = cache.clone();
+            tokio::spawn(async move { get_sol_price(&providers, &cache).await.unwrap().price })
+        })
+        .collect();
+    let mut prices = Vec::new();
+    for caller in callers {
+        prices.push(caller.await.unwrap());
+    }
+
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    assert!(prices.iter().all(|&price| price == 150.0), "{prices:?}");
+    // Still fresh: no request at all
+    get_sol_price(&providers, &cache).await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
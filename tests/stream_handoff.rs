@@ -0,0 +1,99 @@
+//! Signatures handed to the workers survive a worker that isn't running.
+
+mod common;
+
+use futures_util::StreamExt;
+use std::time::Duration;
+
+const GROUP: &str = "workers";
+const CONSUMER: &str = "worker-1";
+
+#[tokio::test]
+async fn entries_published_while_no_worker_runs_are_delivered() {
+    let Some(mut redis) = common::test_redis().await else {
+        return;
+    };
+    let stream = common::unique("handoff");
+
+    // The group exists from the worker's first start; then it goes down
+    drop(
+        redis
+            .consume_stream(&stream, GROUP, CONSUMER)
+            .await
+            .unwrap(),
+    );
+    let id = redis
+        .xadd_signature(&stream, &"sig-while-down", 1_000)
+        .await
+        .unwrap();
+
+    // The restarted worker is handed the entry it missed
+    let mut entries = Box::pin(
+        redis
+            .consume_stream(&stream, GROUP, CONSUMER)
+            .await
+            .unwrap(),
+    );
+    let delivered = tokio::time::timeout(Duration::from_secs(10), entries.next())
+        .await
+        .expect("entry was not delivered")
+        .unwrap()
+        .unwrap();
+    drop(entries);
+
+    // Unacked, it is handed out again after another restart
+    let mut entries = Box::pin(
+        redis
+            .consume_stream(&stream, GROUP, CONSUMER)
+            .await
+            .unwrap(),
+    );
+    let redelivered = tokio::time::timeout(Duration::from_secs(10), entries.next())
+        .await
+        .expect("unacked entry was not redelivered")
+        .unwrap()
+        .unwrap();
+    drop(entries);
+    redis.ack(&stream, GROUP, &id).await.unwrap();
+
+    redis.delete(&stream).await.unwrap();
+    assert_eq!(delivered, (id.clone(), "\"sig-while-down\"".to_string()));
+    assert_eq!(redelivered, delivered);
+}
+
+#[tokio::test]
+async fn durable_publish_appends_and_notifies() {
+    let Some(mut redis) = common::test_redis().await else {
+        return;
+    };
+    let stream = common::unique("durable");
+    let channel = common::unique("durable-nudge");
+
+    let subscriber = redis.clone();
+    let mut nudges = Box::pin(subscriber.subscribe(&channel).await.unwrap());
+    let id = redis
+        .publish_durable(&stream, Some(&channel), &"sig-durable", 1_000)
+        .await
+        .unwrap();
+
+    let nudge = tokio::time::timeout(Duration::from_secs(10), nudges.next())
+        .await
+        .expect("notification was not published")
+        .unwrap();
+    let entries = redis.read_range(&stream, "-", "+", 10).await.unwrap();
+
+    // Without a channel only the stream entry is written
+    let quiet_id = redis
+        .publish_durable(&stream, None, &"sig-quiet", 1_000)
+        .await
+        .unwrap();
+    let after = redis.read_range(&stream, "-", "+", 10).await.unwrap();
+    let extra = tokio::time::timeout(Duration::from_millis(500), nudges.next()).await;
+
+    redis.delete(&stream).await.unwrap();
+    assert_eq!(nudge, "\"sig-durable\"");
+    assert_eq!(entries, vec![(id, Some("\"sig-durable\"".to_string()))]);
+    assert_eq!(after.len(), 2);
+    assert_eq!(after[1], (quiet_id, Some("\"sig-quiet\"".to_string())));
+    assert!(extra.is_err(), "a notification was sent without a channel");
+}
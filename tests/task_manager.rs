@@ -0,0 +1,43 @@
+//! Cancelling the `TaskManager` stops every task it spawned.
+
+use indexer::tasks::TaskManager;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn cancellation_stops_all_registered_tasks() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let stopped = Arc::new(AtomicUsize::new(0));
+    let mut tasks = TaskManager::new();
+    for name in ["price-stream", "reconciler", "metrics", "watchdog"] {
+        let (started, stopped) = (started.clone(), stopped.clone());
+        tasks.spawn(name, move |shutdown| {
+            let (started, stopped) = (started.clone(), stopped.clone());
+            async move {
+                started.fetch_add(1, Ordering::SeqCst);
+                loop {
+                    tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+                    }
+                }
+                stopped.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+    }
+    // A loop not spawned by the manager watches the same token
+    let token = tasks.token();
+
+    while started.load(Ordering::SeqCst) < 4 {
+        tokio::task::yield_now().await;
+    }
+    let begun = Instant::now();
+    tasks.shutdown(Duration::from_secs(5)).await;
+
+    assert_eq!(stopped.load(Ordering::SeqCst), 4);
+    assert!(token.is_cancelled());
+    // Every task returned on its own rather than waiting out the timeout
+    assert!(begun.elapsed() < Duration::from_secs(1));
+}
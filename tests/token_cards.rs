@@ -0,0 +1,39 @@
+//! Token cards are rewritten once per mint per batch, from the newest trade.
+
+mod common;
+
+use indexer::helius::fetcher::CardRefreshes;
+use indexer::models::queries::get_token;
+
+#[tokio::test]
+async fn one_card_per_mint_from_the_newest_trade() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let first = common::unique("token-cards");
+    let second = common::unique("token-cards");
+    common::insert_token(&pool, &first).await;
+    common::insert_token(&pool, &second).await;
+    let first_token = get_token(&pool, &first).await.unwrap().unwrap();
+    let second_token = get_token(&pool, &second).await.unwrap().unwrap();
+
+    let mut cards = CardRefreshes::default();
+    assert!(cards.is_empty());
+    for (i, slot) in [10, 30, 20].into_iter().enumerate() {
+        let mut trade = common::trade(&first, &format!("{first}-{i}"), "wallet", true);
+        trade.slot = slot;
+        cards.record(&first_token, &trade, 150.0);
+    }
+    let mut trade = common::trade(&second, &format!("{second}-0"), "wallet", false);
+    trade.slot = 5;
+    cards.record(&second_token, &trade, 150.0);
+
+    // Four trades, two mints: two sets of volume and holder queries
+    assert_eq!(cards.len(), 2);
+    assert_eq!(cards.last_trade_slot(&first), Some(30));
+    assert_eq!(cards.last_trade_slot(&second), Some(5));
+    assert_eq!(cards.last_trade_slot("unknown"), None);
+
+    common::cleanup(&pool, &first).await;
+    common::cleanup(&pool, &second).await;
+}
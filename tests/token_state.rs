@@ -0,0 +1,77 @@
+//! A token's curve state only moves forward in trade slot order.
+
+mod common;
+
+use indexer::models::Token;
+use indexer::models::queries::{get_token, upsert_token};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+/// `token` as a trade in `slot` left it
+fn after_trade(token: &Token, slot: i64, virtual_sol: u64, market_cap_usd: u64) -> Token {
+    Token {
+        virtual_sol_reserves: Decimal::from(virtual_sol),
+        market_cap_usd: Decimal::from(market_cap_usd),
+        market_cap_usd_raw: Decimal::from(market_cap_usd),
+        last_trade_slot: Some(slot),
+        ..token.clone()
+    }
+}
+
+async fn stored(pool: &PgPool, mint: &str) -> Token {
+    get_token(pool, mint).await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn older_trade_does_not_roll_back_the_curve() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let mint = common::unique("token-state");
+    common::insert_token(&pool, &mint).await;
+    let token = stored(&pool, &mint).await;
+
+    upsert_token(&pool, &after_trade(&token, 200, 40_000_000_000, 9_000))
+        .await
+        .unwrap();
+    // A slow worker's trade from an earlier slot lands afterwards
+    upsert_token(&pool, &after_trade(&token, 100, 31_000_000_000, 4_500))
+        .await
+        .unwrap();
+    let after_old = stored(&pool, &mint).await;
+    // The same slot again still applies
+    upsert_token(&pool, &after_trade(&token, 200, 41_000_000_000, 9_500))
+        .await
+        .unwrap();
+    let after_same = stored(&pool, &mint).await;
+
+    common::cleanup(&pool, &mint).await;
+    assert_eq!(after_old.market_cap_usd, Decimal::from(9_000));
+    assert_eq!(after_old.market_cap_usd_raw, Decimal::from(9_000));
+    assert_eq!(
+        after_old.virtual_sol_reserves,
+        Decimal::from(40_000_000_000u64)
+    );
+    assert_eq!(after_old.last_trade_slot, Some(200));
+    assert_eq!(after_same.market_cap_usd, Decimal::from(9_500));
+}
+
+#[tokio::test]
+async fn raw_progress_is_always_set() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let mint = common::unique("token-state");
+    // A row written without it reads back as zero progress
+    common::insert_token(&pool, &mint).await;
+    let token = stored(&pool, &mint).await;
+    let cleared =
+        sqlx::query("UPDATE tokens SET bonding_curve_progress_raw = NULL WHERE mint_address = $1")
+            .bind(&mint)
+            .execute(&pool)
+            .await;
+
+    common::cleanup(&pool, &mint).await;
+    assert_eq!(token.bonding_curve_progress_raw, Decimal::ZERO);
+    assert!(cleared.is_err(), "NULL raw progress was accepted");
+}
@@ -0,0 +1,38 @@
+//! Many tokens in one query, in the order asked for.
+
+mod common;
+
+use common::{cleanup, insert_token, test_pool, unique};
+use indexer::models::queries::get_tokens_by_mints;
+
+#[tokio::test]
+async fn mixed_set_keeps_order_and_drops_unknown_and_repeated_mints() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let first = unique("tokens-by-mints-a");
+    let second = unique("tokens-by-mints-b");
+    let third = unique("tokens-by-mints-c");
+    for mint in [&first, &second, &third] {
+        insert_token(&pool, mint).await;
+    }
+    let missing = unique("tokens-by-mints-missing");
+
+    // Not insertion order, an unknown mint in the middle and a repeat
+    let asked = vec![
+        third.clone(),
+        missing.clone(),
+        first.clone(),
+        second.clone(),
+        first.clone(),
+    ];
+    let tokens = get_tokens_by_mints(&pool, &asked).await.unwrap();
+    let empty = get_tokens_by_mints(&pool, &[missing]).await.unwrap();
+
+    for mint in [&first, &second, &third] {
+        cleanup(&pool, mint).await;
+    }
+    let mints: Vec<_> = tokens.iter().map(|t| t.mint_address.as_str()).collect();
+    assert_eq!(mints, [&third, &first, &second]);
+    assert!(empty.is_empty());
+}
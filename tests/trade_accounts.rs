@@ -0,0 +1,63 @@
+//! A trade's accounts are kept on its row when asked for, and read back.
+
+mod common;
+
+use common::{cleanup, test_pool, unique};
+use indexer::helius::parser::{ParseOptions, ParseOutcome, parse_pump_fun_transaction};
+use indexer::models::helius_model::TransactionResult;
+use indexer::models::queries::{get_recent_trades, insert_trade};
+use indexer::models::{Trade, TradeAccounts};
+use std::path::PathBuf;
+
+fn buy_fixture() -> TransactionResult {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/buy.json");
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn parse(store_accounts: bool) -> Trade {
+    let options = ParseOptions {
+        store_accounts,
+        ..ParseOptions::default()
+    };
+    let Ok(ParseOutcome::Trades(mut trades)) =
+        parse_pump_fun_transaction(&buy_fixture(), 150.0, options)
+    else {
+        panic!("buy no longer parses to trades");
+    };
+    trades.remove(0)
+}
+
+#[test]
+fn accounts_are_only_kept_when_enabled() {
+    assert!(parse(false).accounts.is_none());
+}
+
+#[tokio::test]
+async fn stored_accounts_are_retrievable() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let mint = unique("trade-accounts");
+    // Stored under a mint of its own, so reruns and other tests don't collide
+    let mut trade = parse(true);
+    trade.token_mint = mint.clone();
+    trade.signature = format!("{mint}-buy");
+    insert_trade(&pool, &trade).await.unwrap();
+
+    let stored = get_recent_trades(&pool, &mint, 10).await.unwrap();
+    cleanup(&pool, &mint).await;
+
+    let [stored] = stored.as_slice() else {
+        panic!("expected one trade, got {}", stored.len());
+    };
+    let accounts = stored.accounts.as_ref().map(|accounts| &accounts.0);
+    assert_eq!(
+        accounts,
+        Some(&TradeAccounts {
+            trader: "FxNMd5AFGucx8HTDV5RfoCRSkRJ1soMA1zhm4JgqmmBd".to_string(),
+            mint: "4UzANobs7UEddmEUbGbHmjsUomuTN8EF6BFjg4HzFqu9".to_string(),
+            bonding_curve: Some("Hp9TbTEH4jAP4NtSwC2uEqx7iuEp3KHWoVUoJekfj5HG".to_string()),
+            fee_recipient: Some("CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM".to_string()),
+        })
+    );
+}
@@ -0,0 +1,98 @@
+//! Trade cursors round-trip exactly, so pages meet without gaps or repeats.
+
+mod common;
+
+use chrono::{DateTime, Duration};
+use common::{cleanup, test_pool, trade, unique};
+use indexer::models::queries::{get_trades_paginated, insert_trade};
+use indexer::models::{Trade, TradeCursor};
+
+fn cursor(nanos: u32, instruction_index: i32, inner_instruction_index: i32) -> TradeCursor {
+    TradeCursor {
+        timestamp: DateTime::from_timestamp(1_700_000_000, nanos).unwrap(),
+        signature: "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnb".to_string(),
+        instruction_index,
+        inner_instruction_index,
+    }
+}
+
+#[test]
+fn cursor_round_trips_sub_second_timestamps() {
+    for (nanos, ix, inner) in [
+        (0, 0, -1),
+        (1_000, 3, 2),
+        (123_456_000, 0, -1),
+        (999_999_999, 1, 0),
+        (500_000_001, 0, -1),
+    ] {
+        let original = cursor(nanos, ix, inner);
+        let text = original.to_string();
+        assert_eq!(text.parse::<TradeCursor>().unwrap(), original, "{text}");
+
+        let json = serde_json::to_string(&original).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TradeCursor>(&json).unwrap(),
+            original
+        );
+    }
+    assert_eq!(
+        cursor(123_456_000, 0, -1).to_string(),
+        "2023-11-14T22:13:20.123456Z_5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnb_0_-1"
+    );
+}
+
+#[test]
+fn malformed_cursors_are_rejected() {
+    for text in [
+        "",
+        "2023-11-14T22:13:20Z_sig_0",
+        "2023-11-14T22:13:20Z_sig_0_-1_extra",
+        "yesterday_sig_0_-1",
+        "2023-11-14T22:13:20Z_sig_x_-1",
+    ] {
+        assert!(text.parse::<TradeCursor>().is_err(), "{text}");
+    }
+}
+
+#[tokio::test]
+async fn pages_meet_across_sub_second_trades() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let mint = unique("trade-cursor");
+
+    // Five trades 250ms apart, the last two in one transaction
+    let base = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    let mut trades: Vec<Trade> = (0..4)
+        .map(|i| Trade {
+            timestamp: base + Duration::milliseconds(250 * i),
+            ..trade(&mint, &format!("{mint}-{i}"), "wallet", true)
+        })
+        .collect();
+    trades.push(Trade {
+        instruction_index: 1,
+        ..trades[3].clone()
+    });
+    for t in &trades {
+        assert!(insert_trade(&pool, t).await.unwrap());
+    }
+
+    let mut seen = Vec::new();
+    let mut before: Option<TradeCursor> = None;
+    loop {
+        // Through the cursor's text form, as a client would send it back
+        let page = get_trades_paginated(&pool, &mint, before.as_ref(), None, None, 2)
+            .await
+            .unwrap();
+        seen.extend(page.trades.iter().map(TradeCursor::from));
+        match page.next_cursor {
+            Some(next) => before = Some(next.to_string().parse().unwrap()),
+            None => break,
+        }
+    }
+    cleanup(&pool, &mint).await;
+
+    let mut expected: Vec<TradeCursor> = trades.iter().map(TradeCursor::from).collect();
+    expected.reverse();
+    assert_eq!(seen, expected);
+}
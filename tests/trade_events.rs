@@ -0,0 +1,127 @@
+//! Events are read from pump.fun's signed self-CPIs and from `Program data:`
+//! logs pump.fun wrote itself, without counting one event twice.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use indexer::helius::events::decode_trade_events;
+use indexer::models::helius_model::TransactionMeta;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+
+const EVENT_AUTHORITY: &str = "Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1";
+/// `sha256("anchor:event")[..8]`, little-endian
+const EVENT_IX_TAG: [u8; 8] = [228, 69, 165, 46, 81, 203, 154, 29];
+
+/// The `buy` fixture's meta and the `TradeEvent` payload it logs
+fn buy_meta() -> (Value, Vec<u8>) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/buy.json");
+    let fixture: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let meta = fixture["meta"].clone();
+    let payload = meta["logMessages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find_map(|log| log.as_str()?.strip_prefix("Program data: "))
+        .map(|encoded| BASE64.decode(encoded).unwrap())
+        .unwrap();
+    (meta, payload)
+}
+
+/// Add a pump.fun self-CPI carrying `payload`, with `accounts`
+fn add_event_cpi(meta: &mut Value, payload: &[u8], accounts: &[&str]) {
+    let data = bs58::encode([EVENT_IX_TAG.as_slice(), payload].concat()).into_string();
+    meta["innerInstructions"][0]["instructions"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({
+            "accounts": accounts,
+            "data": data,
+            "programId": "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P",
+            "stackHeight": 2
+        }));
+}
+
+fn without_logs(meta: &mut Value) {
+    meta["logMessages"] = Value::Array(Vec::new());
+}
+
+fn event_count(meta: Value) -> usize {
+    let meta: TransactionMeta = serde_json::from_value(meta).unwrap();
+    decode_trade_events(&meta).len()
+}
+
+#[test]
+fn logged_event_is_decoded() {
+    let (meta, _) = buy_meta();
+    assert_eq!(event_count(meta), 1);
+}
+
+#[test]
+fn event_in_both_cpi_and_log_counts_once() {
+    let (mut meta, payload) = buy_meta();
+    add_event_cpi(&mut meta, &payload, &[EVENT_AUTHORITY]);
+    assert_eq!(event_count(meta), 1);
+
+    // Two identical trades, both emitted through the CPI: both count
+    let (mut meta, payload) = buy_meta();
+    add_event_cpi(&mut meta, &payload, &[EVENT_AUTHORITY]);
+    add_event_cpi(&mut meta, &payload, &[EVENT_AUTHORITY]);
+    assert_eq!(event_count(meta), 2);
+}
+
+#[test]
+fn self_cpi_without_the_event_authority_is_ignored() {
+    let (mut meta, payload) = buy_meta();
+    without_logs(&mut meta);
+    add_event_cpi(&mut meta, &payload, &[EVENT_AUTHORITY]);
+    assert_eq!(event_count(meta), 1);
+
+    let (mut meta, payload) = buy_meta();
+    without_logs(&mut meta);
+    add_event_cpi(&mut meta, &payload, &["11111111111111111111111111111111"]);
+    add_event_cpi(&mut meta, &payload, &[]);
+    assert_eq!(event_count(meta), 0);
+}
+
+#[test]
+fn data_logged_outside_pump_fun_frames_is_ignored() {
+    let (mut meta, payload) = buy_meta();
+    let genuine: TransactionMeta = serde_json::from_value(meta.clone()).unwrap();
+    let genuine = decode_trade_events(&genuine);
+    assert_eq!(genuine.len(), 1);
+
+    // The same event with a different SOL amount (the field after the
+    // discriminator and mint)
+    let mut forged = payload.clone();
+    forged[40..48].copy_from_slice(&1u64.to_le_bytes());
+    let forged = format!("Program data: {}", BASE64.encode(&forged));
+    let logs = meta["logMessages"].as_array_mut().unwrap();
+    // Logged by a program of its own, at the top level ...
+    logs.extend(
+        [
+            "Program Fake111111111111111111111111111111111 invoke [1]",
+            &forged,
+            "Program Fake111111111111111111111111111111111 success",
+        ]
+        .map(Value::from),
+    );
+    // ... and by a program pump.fun invoked
+    let pump_success = logs
+        .iter()
+        .position(|log| log == "Program 6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P success")
+        .unwrap();
+    for (offset, log) in [
+        "Program Fake111111111111111111111111111111111 invoke [2]",
+        "Program log: Instruction: Invoke success",
+        &forged,
+        "Program Fake111111111111111111111111111111111 success",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        logs.insert(pump_success + offset, log.into());
+    }
+
+    let meta: TransactionMeta = serde_json::from_value(meta).unwrap();
+    assert_eq!(decode_trade_events(&meta), genuine);
+}
@@ -0,0 +1,56 @@
+//! Trades are keyed per instruction: a transaction can hold several.
+
+mod common;
+
+use common::{cleanup, test_pool, trade, unique};
+use indexer::models::queries::{get_trades_in_slot, insert_trade, mark_sandwiched};
+use indexer::models::{Trade, TradeCursor};
+use rust_decimal::Decimal;
+
+#[tokio::test]
+async fn two_trades_in_one_transaction_are_stored_apart() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let mint = unique("trade-keys");
+    let signature = unique("sig");
+
+    // A router buying, then selling, the same mint in one transaction
+    let buy = trade(&mint, &signature, "wallet-a", true);
+    let sell = Trade {
+        instruction_index: 2,
+        ..trade(&mint, &signature, "wallet-a", false)
+    };
+
+    assert!(insert_trade(&pool, &buy).await.unwrap());
+    assert!(insert_trade(&pool, &sell).await.unwrap());
+    // Re-delivery of either is a duplicate
+    assert!(!insert_trade(&pool, &sell).await.unwrap());
+
+    let mut stored = get_trades_in_slot(&pool, &mint, buy.slot).await.unwrap();
+    stored.sort_by_key(|t| t.instruction_index);
+    assert_eq!(stored.len(), 2);
+    assert_eq!(
+        stored
+            .iter()
+            .map(|t| (t.instruction_index, t.is_buy))
+            .collect::<Vec<_>>(),
+        vec![(0, true), (2, false)]
+    );
+
+    // Flagging one leaves its sibling alone
+    mark_sandwiched(&pool, &TradeCursor::from(&sell), "attacker", Decimal::ONE)
+        .await
+        .unwrap();
+    let flags: Vec<(i32, bool)> = sqlx::query_as(
+        "SELECT instruction_index, sandwiched FROM trades
+         WHERE token_mint = $1 ORDER BY instruction_index",
+    )
+    .bind(&mint)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(flags, vec![(0, false), (2, true)]);
+
+    cleanup(&pool, &mint).await;
+}
@@ -0,0 +1,51 @@
+//! Each priced trade stores the SOL/USD rate behind it and when it was fetched.
+
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::{cleanup, test_pool, trade, unique};
+use indexer::helius::fetcher::stamp_sol_price;
+use indexer::models::queries::insert_trade;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::str::FromStr;
+
+type Stored = (Option<Decimal>, Option<DateTime<Utc>>, Option<Decimal>);
+
+async fn stored(pool: &PgPool, signature: &str) -> Stored {
+    sqlx::query_as(
+        "SELECT sol_price_usd, sol_price_at, usd_amount FROM trades WHERE signature = $1",
+    )
+    .bind(signature)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn sol_price_and_fetch_time_are_stored() {
+    let Some(pool) = test_pool().await else {
+        return;
+    };
+    let mint = unique("trade-sol-price");
+    let fetched_at = DateTime::from_timestamp(1_700_000_000, 250_000_000).unwrap();
+
+    // 1 SOL at $187.25
+    let mut priced = trade(&mint, &format!("{mint}-priced"), "wallet", true);
+    priced.price_usd = Some(Decimal::from_str("0.0000053").unwrap());
+    stamp_sol_price(&mut priced, 187.25, fetched_at);
+    insert_trade(&pool, &priced).await.unwrap();
+
+    // A transfer has no USD figures to explain
+    let mut transfer = trade(&mint, &format!("{mint}-transfer"), "wallet", true);
+    stamp_sol_price(&mut transfer, 187.25, fetched_at);
+    insert_trade(&pool, &transfer).await.unwrap();
+
+    let priced = stored(&pool, &priced.signature).await;
+    let transfer = stored(&pool, &transfer.signature).await;
+    cleanup(&pool, &mint).await;
+
+    let rate = Decimal::from_str("187.25").unwrap();
+    assert_eq!(priced, (Some(rate), Some(fetched_at), Some(rate)));
+    assert_eq!(transfer, (None, None, None));
+}
@@ -0,0 +1,65 @@
+//! Balance diffs are cross-checked against the RPC's UI amounts.
+
+use indexer::helius::parser::{ParseOptions, UiAmountCheck, parse_all, ui_amount_mismatches};
+use indexer::models::helius_model::TransactionResult;
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// The `sell` fixture without its parsed transfers, so the trade comes from
+/// token balance diffs, with the trader's post balance edited by `edit`
+fn sell_from_balances(edit: impl FnOnce(&mut Value)) -> TransactionResult {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sell.json");
+    let mut fixture: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    fixture["meta"]["innerInstructions"] = Value::Array(Vec::new());
+    edit(&mut fixture["meta"]["postTokenBalances"][0]["uiTokenAmount"]);
+    serde_json::from_value(fixture).unwrap()
+}
+
+fn trade_count(tx: &TransactionResult, ui_amount_check: UiAmountCheck) -> usize {
+    let options = ParseOptions {
+        ui_amount_check,
+        ..ParseOptions::default()
+    };
+    parse_all(tx, None, 150.0, options).trades.len()
+}
+
+#[test]
+fn consistent_balances_pass() {
+    let tx = sell_from_balances(|_| {});
+    assert_eq!(trade_count(&tx, UiAmountCheck::Reject), 1);
+}
+
+#[test]
+fn inconsistent_balance_is_counted_and_rejected() {
+    // The raw amount says 15M tokens are left, the UI amount 16M
+    let tx = sell_from_balances(|amount| {
+        amount["uiAmount"] = 16_000_000.0.into();
+        amount["uiAmountString"] = "16000000".into();
+    });
+
+    let before = ui_amount_mismatches();
+    // Warn-only by default: counted, trade kept
+    assert_eq!(trade_count(&tx, UiAmountCheck::default()), 1);
+    assert!(ui_amount_mismatches() > before);
+
+    assert_eq!(trade_count(&tx, UiAmountCheck::Reject), 0);
+    assert_eq!(trade_count(&tx, UiAmountCheck::Off), 1);
+}
+
+#[test]
+fn decimals_out_of_range_are_unverifiable() {
+    // 10^20 overflows a u64 but is still checked: the UI amounts are off by
+    // 14 orders of magnitude for it
+    let tx = sell_from_balances(|amount| amount["decimals"] = 20.into());
+    assert_eq!(trade_count(&tx, UiAmountCheck::Reject), 0);
+
+    // Decimal's scale stops at 28
+    for decimals in [29, 255] {
+        let tx = sell_from_balances(|amount| amount["decimals"] = decimals.into());
+        assert_eq!(
+            trade_count(&tx, UiAmountCheck::Reject),
+            1,
+            "decimals {decimals}"
+        );
+    }
+}
@@ -0,0 +1,28 @@
+//! Webhook deliveries must carry the shared secret.
+
+use axum::http::{HeaderMap, HeaderValue, header::AUTHORIZATION};
+use indexer::helius::webhook::{authorized, run_webhook};
+use tokio_util::sync::CancellationToken;
+
+fn with_authorization(value: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, HeaderValue::from_str(value).unwrap());
+    headers
+}
+
+#[test]
+fn only_the_exact_secret_is_accepted() {
+    assert!(authorized(&with_authorization("s3cret"), "s3cret"));
+    assert!(!authorized(&with_authorization("s3cre"), "s3cret"));
+    assert!(!authorized(&with_authorization("s3cret!"), "s3cret"));
+    assert!(!authorized(&with_authorization("S3cret"), "s3cret"));
+    assert!(!authorized(&HeaderMap::new(), "s3cret"));
+}
+
+#[tokio::test]
+async fn missing_secret_is_a_startup_error() {
+    // SAFETY: the only test in this binary touching the environment
+    unsafe { std::env::remove_var("WEBHOOK_AUTH_TOKEN") };
+    let err = run_webhook(CancellationToken::new()).await.unwrap_err();
+    assert!(format!("{err:#}").contains("WEBHOOK_AUTH_TOKEN"));
+}